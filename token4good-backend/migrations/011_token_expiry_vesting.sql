@@ -0,0 +1,68 @@
+-- Migration 011: Expiration et vesting des crédits T4G
+
+-- ============================================================
+-- 1. Nouveaux action_type sur le ledger
+--    service_refund était déjà utilisé par refund_escrow sans
+--    figurer dans la contrainte d'origine.
+-- ============================================================
+
+ALTER TABLE t4g_token_transactions
+    DROP CONSTRAINT IF EXISTS t4g_token_transactions_action_type_check;
+
+ALTER TABLE t4g_token_transactions
+    ADD CONSTRAINT t4g_token_transactions_action_type_check
+    CHECK (action_type IN (
+        'mentoring', 'code_review', 'documentation', 'support_technique',
+        'parrainage', 'service_payment', 'service_refund', 'weekly_bonus',
+        'welcome_bonus', 'campaign_bonus', 'token_expiry'
+    ));
+
+-- ============================================================
+-- 2. LOTS DE CRÉDITS (grants avec expiration et/ou vesting)
+--    Chaque lot est rattaché à la ligne de crédit du ledger qui l'a créé.
+--    - vesting linéaire entre vesting_start et vesting_end
+--    - vesting_end NULL → lot entièrement acquis à vesting_start (cliff)
+--    - consumed est incrémenté en FIFO par les débits
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS t4g_token_lots (
+    id                 VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    user_id            VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    transaction_id     VARCHAR NOT NULL REFERENCES t4g_token_transactions(id) ON DELETE CASCADE,
+    amount             INT NOT NULL CHECK (amount > 0),
+    consumed           INT NOT NULL DEFAULT 0,
+    vesting_start      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    vesting_end        TIMESTAMPTZ,
+    expires_at         TIMESTAMPTZ,
+    expiry_notified_at TIMESTAMPTZ,
+    expired_at         TIMESTAMPTZ,
+    expired_amount     INT NOT NULL DEFAULT 0,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (consumed >= 0 AND consumed + expired_amount <= amount),
+    CHECK (vesting_end IS NULL OR vesting_end > vesting_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_t4g_token_lots_user_id ON t4g_token_lots(user_id, created_at);
+
+-- Job d'expiration : uniquement les lots encore actifs
+CREATE INDEX IF NOT EXISTS idx_t4g_token_lots_active_expiry
+    ON t4g_token_lots(expires_at)
+    WHERE expired_at IS NULL AND expires_at IS NOT NULL;
+
+-- ============================================================
+-- 3. TRÉSORERIE
+--    Les tokens expirés retournent à la trésorerie de la plateforme.
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS t4g_treasury_movements (
+    id             VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    amount         INT NOT NULL,
+    reason         VARCHAR(50) NOT NULL,
+    user_id        VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    lot_id         VARCHAR REFERENCES t4g_token_lots(id) ON DELETE SET NULL,
+    transaction_id VARCHAR REFERENCES t4g_token_transactions(id) ON DELETE SET NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_t4g_treasury_movements_created_at
+    ON t4g_treasury_movements(created_at);
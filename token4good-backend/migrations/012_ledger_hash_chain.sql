@@ -0,0 +1,46 @@
+-- Migration 012: Chaîne de hachage inviolable sur le ledger T4G
+--
+-- Chaque ligne scellée porte :
+--   - chain_seq      : position dans la chaîne globale (attribuée au scellement)
+--   - prev_hash      : row_hash de la ligne précédente (chaîne globale)
+--   - user_prev_hash : row_hash de la ligne précédente du même utilisateur
+--   - row_hash       : SHA-256(prev_hash | user_prev_hash | contenu canonique)
+--
+-- Le scellement est fait côté backend (services::ledger_chain), dans l'ordre
+-- de commit observé, sous verrou consultatif. Une modification ultérieure
+-- d'une ligne scellée casse la chaîne à partir de cette ligne.
+
+ALTER TABLE t4g_token_transactions
+    ADD COLUMN IF NOT EXISTS chain_seq      BIGINT,
+    ADD COLUMN IF NOT EXISTS prev_hash      VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS user_prev_hash VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS row_hash       VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS sealed_at      TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_t4g_transactions_chain_seq
+    ON t4g_token_transactions(chain_seq);
+
+-- Parcours de la chaîne d'un utilisateur
+CREATE INDEX IF NOT EXISTS idx_t4g_transactions_user_chain
+    ON t4g_token_transactions(user_id, chain_seq);
+
+-- Scellement : uniquement les lignes en attente
+CREATE INDEX IF NOT EXISTS idx_t4g_transactions_unsealed
+    ON t4g_token_transactions(created_at)
+    WHERE chain_seq IS NULL;
+
+-- ============================================================
+-- Têtes de chaîne signées avec la clé émetteur RGB
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS t4g_ledger_chain_heads (
+    id            VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    chain_seq     BIGINT NOT NULL,
+    row_hash      VARCHAR(64) NOT NULL,
+    signature     VARCHAR(128) NOT NULL,
+    issuer_pubkey VARCHAR(66) NOT NULL,
+    signed_at     TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_t4g_ledger_chain_heads_signed_at
+    ON t4g_ledger_chain_heads(signed_at DESC);
@@ -0,0 +1,67 @@
+-- Migration 013: Ajustements manuels de solde par les admins
+--
+-- Remplace les corrections en SQL brut : chaque ajustement porte un code
+-- motif et une justification. Au-delà d'un seuil (ADMIN_ADJUSTMENT_APPROVAL_THRESHOLD),
+-- un second admin doit l'approuver avant qu'il n'atteigne le ledger.
+
+ALTER TABLE t4g_token_transactions
+    DROP CONSTRAINT IF EXISTS t4g_token_transactions_action_type_check;
+
+ALTER TABLE t4g_token_transactions
+    ADD CONSTRAINT t4g_token_transactions_action_type_check
+    CHECK (action_type IN (
+        'mentoring', 'code_review', 'documentation', 'support_technique',
+        'parrainage', 'service_payment', 'service_refund', 'weekly_bonus',
+        'welcome_bonus', 'campaign_bonus', 'token_expiry', 'admin_adjustment'
+    ));
+
+-- ============================================================
+-- 1. AJUSTEMENTS
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS t4g_token_adjustments (
+    id             VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    user_id        VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    amount         INT NOT NULL CHECK (amount <> 0),
+    reason_code    VARCHAR(30) NOT NULL CHECK (reason_code IN (
+                       'error_correction', 'failed_session_refund', 'goodwill_credit',
+                       'fraud_reversal', 'data_migration', 'other'
+                   )),
+    justification  TEXT NOT NULL,
+    status         VARCHAR(20) NOT NULL DEFAULT 'pending_approval'
+                   CHECK (status IN ('pending_approval', 'applied', 'rejected')),
+    requires_approval BOOLEAN NOT NULL DEFAULT FALSE,
+    requested_by   VARCHAR NOT NULL REFERENCES users(id),
+    reviewed_by    VARCHAR REFERENCES users(id),
+    review_comment TEXT,
+    transaction_id VARCHAR REFERENCES t4g_token_transactions(id) ON DELETE SET NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reviewed_at    TIMESTAMPTZ,
+    applied_at     TIMESTAMPTZ,
+    -- Principe des quatre yeux : le relecteur n'est jamais le demandeur
+    CHECK (reviewed_by IS NULL OR reviewed_by <> requested_by)
+);
+
+CREATE INDEX IF NOT EXISTS idx_t4g_token_adjustments_status
+    ON t4g_token_adjustments(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_t4g_token_adjustments_user_id
+    ON t4g_token_adjustments(user_id);
+
+-- ============================================================
+-- 2. PISTE D'AUDIT (append-only)
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS t4g_token_adjustment_events (
+    id            VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    adjustment_id VARCHAR NOT NULL REFERENCES t4g_token_adjustments(id) ON DELETE CASCADE,
+    actor_id      VARCHAR NOT NULL REFERENCES users(id),
+    event         VARCHAR(20) NOT NULL
+                  CHECK (event IN ('requested', 'approved', 'rejected', 'applied')),
+    comment       TEXT,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_t4g_token_adjustment_events_adjustment
+    ON t4g_token_adjustment_events(adjustment_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_t4g_token_adjustment_events_created_at
+    ON t4g_token_adjustment_events(created_at DESC);
@@ -0,0 +1,49 @@
+-- Migration 014: Rapprochement séquestre ↔ ledger
+--
+-- Chaque passe compare mentoring_bookings.tokens_escrowed aux lignes du ledger
+-- typées escrow_debit / escrow_refund / escrow_release et enregistre les écarts.
+
+CREATE TABLE IF NOT EXISTS escrow_reconciliation_runs (
+    id               VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    started_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at      TIMESTAMPTZ,
+    auto_fix         BOOLEAN NOT NULL DEFAULT FALSE,
+    triggered_by     VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    bookings_checked INT NOT NULL DEFAULT 0,
+    issues_found     INT NOT NULL DEFAULT 0,
+    issues_fixed     INT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_escrow_reconciliation_runs_started_at
+    ON escrow_reconciliation_runs(started_at DESC);
+
+CREATE TABLE IF NOT EXISTS escrow_reconciliation_issues (
+    id                 VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    run_id             VARCHAR NOT NULL REFERENCES escrow_reconciliation_runs(id) ON DELETE CASCADE,
+    booking_id         VARCHAR NOT NULL,
+    booking_status     VARCHAR(30),
+    kind               VARCHAR(30) NOT NULL CHECK (kind IN (
+                           'missing_debit', 'debit_amount_mismatch', 'double_debit',
+                           'missing_release', 'double_release', 'missing_refund',
+                           'double_refund', 'orphan_refund', 'release_and_refund',
+                           'orphan_entry'
+                       )),
+    expected_tokens    INT,
+    actual_tokens      INT,
+    transaction_ids    TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    auto_fixable       BOOLEAN NOT NULL DEFAULT FALSE,
+    fixed              BOOLEAN NOT NULL DEFAULT FALSE,
+    fix_transaction_id VARCHAR REFERENCES t4g_token_transactions(id) ON DELETE SET NULL,
+    fix_error          TEXT,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_escrow_reconciliation_issues_run
+    ON escrow_reconciliation_issues(run_id, kind);
+CREATE INDEX IF NOT EXISTS idx_escrow_reconciliation_issues_booking
+    ON escrow_reconciliation_issues(booking_id);
+
+-- Recherche des écritures séquestre par réservation
+CREATE INDEX IF NOT EXISTS idx_t4g_transactions_booking_id
+    ON t4g_token_transactions((metadata->>'booking_id'))
+    WHERE metadata ? 'booking_id';
@@ -0,0 +1,68 @@
+-- Migration 015: Sessions de groupe et offres récurrentes
+--
+-- Une offre peut accueillir plusieurs mentees sur un même créneau (capacity).
+-- Chaque place réservée est une ligne mentoring_bookings avec son propre
+-- séquestre. Une série récurrente génère une offre par occurrence.
+
+-- ============================================================
+-- 1. SÉRIES RÉCURRENTES
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS mentoring_offer_series (
+    id               VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    mentor_id        VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    topic_slug       VARCHAR(100) NOT NULL REFERENCES learning_topics(slug) ON DELETE RESTRICT,
+    target_level     VARCHAR(20) NOT NULL DEFAULT 'beginner'
+                         CHECK (target_level IN ('beginner', 'intermediate', 'advanced')),
+    description      TEXT,
+    duration_minutes INT NOT NULL CHECK (duration_minutes > 0),
+    format           VARCHAR(20) NOT NULL DEFAULT 'video'
+                         CHECK (format IN ('video', 'text', 'async')),
+    token_cost       INT NOT NULL CHECK (token_cost >= 0),
+    capacity         INT NOT NULL DEFAULT 1 CHECK (capacity BETWEEN 1 AND 50),
+    -- Règle : jour + heure locale dans un fuseau IANA, toutes les N semaines
+    weekday          SMALLINT NOT NULL CHECK (weekday BETWEEN 0 AND 6), -- 0 = lundi
+    start_time       TIME NOT NULL,
+    timezone         VARCHAR(64) NOT NULL DEFAULT 'Europe/Paris',
+    interval_weeks   INT NOT NULL DEFAULT 1 CHECK (interval_weeks BETWEEN 1 AND 4),
+    starts_on        DATE NOT NULL,
+    ends_on          DATE,
+    max_occurrences  INT CHECK (max_occurrences > 0),
+    status           VARCHAR(20) NOT NULL DEFAULT 'active'
+                         CHECK (status IN ('active', 'ended', 'cancelled')),
+    generated_until  TIMESTAMPTZ,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_offer_series_mentor ON mentoring_offer_series(mentor_id);
+CREATE INDEX IF NOT EXISTS idx_mentoring_offer_series_active
+    ON mentoring_offer_series(generated_until)
+    WHERE status = 'active';
+
+DROP TRIGGER IF EXISTS trg_mentoring_offer_series_updated_at ON mentoring_offer_series;
+CREATE TRIGGER trg_mentoring_offer_series_updated_at
+    BEFORE UPDATE ON mentoring_offer_series
+    FOR EACH ROW EXECUTE FUNCTION set_updated_at();
+
+-- ============================================================
+-- 2. CAPACITÉ ET OCCURRENCES SUR LES OFFRES
+-- ============================================================
+
+ALTER TABLE mentoring_offers
+    ADD COLUMN IF NOT EXISTS capacity      INT NOT NULL DEFAULT 1 CHECK (capacity BETWEEN 1 AND 50),
+    ADD COLUMN IF NOT EXISTS series_id     VARCHAR REFERENCES mentoring_offer_series(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS occurrence_at TIMESTAMPTZ;
+
+-- Génération idempotente : une seule offre par occurrence de série
+CREATE UNIQUE INDEX IF NOT EXISTS uq_mentoring_offers_series_occurrence
+    ON mentoring_offers(series_id, occurrence_at)
+    WHERE series_id IS NOT NULL;
+
+-- ============================================================
+-- 3. UNE PLACE PAR MENTEE ET PAR CRÉNEAU
+-- ============================================================
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_mentoring_bookings_seat
+    ON mentoring_bookings(offer_id, scheduled_at, mentee_id)
+    WHERE status IN ('pending', 'confirmed', 'pending_completion', 'disputed');
@@ -0,0 +1,43 @@
+-- Migration 016: Flux iCalendar des réservations de mentorat
+--
+-- Chaque utilisateur peut activer une URL de flux secrète (seul le hash
+-- SHA-256 du jeton est stocké). Les événements exportés portent un SEQUENCE
+-- incrémenté à chaque changement de statut ou d'horaire, pour que les
+-- agendas abonnés mettent à jour ou annulent l'événement.
+
+-- ============================================================
+-- 1. JETONS DE FLUX
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS calendar_feed_tokens (
+    user_id          VARCHAR PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    token_hash       VARCHAR(64) NOT NULL UNIQUE,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_accessed_at TIMESTAMPTZ
+);
+
+-- ============================================================
+-- 2. SÉQUENCE iCALENDAR DES RÉSERVATIONS
+-- ============================================================
+
+ALTER TABLE mentoring_bookings
+    ADD COLUMN IF NOT EXISTS calendar_sequence INT NOT NULL DEFAULT 0;
+
+CREATE OR REPLACE FUNCTION bump_booking_calendar_sequence()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.status IS DISTINCT FROM OLD.status
+       OR NEW.scheduled_at IS DISTINCT FROM OLD.scheduled_at THEN
+        NEW.calendar_sequence = OLD.calendar_sequence + 1;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_mentoring_bookings_calendar_sequence ON mentoring_bookings;
+CREATE TRIGGER trg_mentoring_bookings_calendar_sequence
+    BEFORE UPDATE ON mentoring_bookings
+    FOR EACH ROW EXECUTE FUNCTION bump_booking_calendar_sequence();
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_bookings_mentee_scheduled
+    ON mentoring_bookings(mentee_id, scheduled_at);
@@ -0,0 +1,30 @@
+-- Migration 017: Report des réservations confirmées
+--
+-- Le mentor ou le mentee propose un nouvel horaire, l'autre partie accepte,
+-- refuse ou contre-propose. Chaque proposition est conservée : la table sert
+-- d'historique des changements d'horaire de la réservation.
+
+CREATE TABLE IF NOT EXISTS mentoring_booking_reschedules (
+    id                    VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id            VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    proposed_by           VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    previous_scheduled_at TIMESTAMPTZ NOT NULL,
+    proposed_scheduled_at TIMESTAMPTZ NOT NULL,
+    message               TEXT,
+    status                VARCHAR(20) NOT NULL DEFAULT 'pending'
+                              CHECK (status IN ('pending', 'accepted', 'declined',
+                                                'countered', 'withdrawn', 'expired')),
+    -- Proposition à laquelle celle-ci répond (contre-proposition)
+    counter_of            VARCHAR REFERENCES mentoring_booking_reschedules(id) ON DELETE SET NULL,
+    responded_by          VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    responded_at          TIMESTAMPTZ,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_booking_reschedules_booking
+    ON mentoring_booking_reschedules(booking_id, created_at);
+
+-- Une seule proposition en attente par réservation
+CREATE UNIQUE INDEX IF NOT EXISTS uq_booking_reschedules_pending
+    ON mentoring_booking_reschedules(booking_id)
+    WHERE status = 'pending';
@@ -0,0 +1,32 @@
+-- Migration 018: Annulation par le mentee et politiques de remboursement
+--
+-- Chaque offre porte une politique d'annulation par paliers (délai minimal
+-- avant la session → pourcentage remboursé). À l'annulation, le séquestre est
+-- partagé entre remboursement du mentee (escrow_refund) et dédommagement du
+-- mentor (escrow_compensation) dans le ledger.
+
+ALTER TABLE mentoring_offers
+    ADD COLUMN IF NOT EXISTS cancellation_policy JSONB NOT NULL DEFAULT
+        '{"tiers": [{"min_hours_before": 24, "refund_percent": 100},
+                    {"min_hours_before": 2, "refund_percent": 50}]}'::jsonb;
+
+ALTER TABLE mentoring_bookings
+    ADD COLUMN IF NOT EXISTS cancelled_by        VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS cancelled_at        TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS cancellation_reason TEXT,
+    ADD COLUMN IF NOT EXISTS refund_tokens       INT CHECK (refund_tokens >= 0),
+    ADD COLUMN IF NOT EXISTS compensation_tokens INT CHECK (compensation_tokens >= 0);
+
+-- Le rapprochement séquestre signale `compensation_mismatch` quand le
+-- dédommagement versé au mentor ne correspond pas au partage d'annulation
+ALTER TABLE escrow_reconciliation_issues
+    DROP CONSTRAINT IF EXISTS escrow_reconciliation_issues_kind_check;
+
+ALTER TABLE escrow_reconciliation_issues
+    ADD CONSTRAINT escrow_reconciliation_issues_kind_check
+    CHECK (kind IN (
+        'missing_debit', 'debit_amount_mismatch', 'double_debit',
+        'missing_release', 'double_release', 'missing_refund',
+        'double_refund', 'orphan_refund', 'release_and_refund',
+        'compensation_mismatch', 'orphan_entry'
+    ));
@@ -0,0 +1,70 @@
+-- Migration 019: Litiges sur les réservations et arbitrage du séquestre
+--
+-- Un litige ouvert par le mentor ou le mentee bloque le séquestre jusqu'à la
+-- décision d'un admin : remboursement total, versement total au mentor, ou
+-- partage. La chronologie (`mentoring_dispute_events`) est en ajout seul et
+-- regroupe les preuves des parties, les notes internes des admins et la
+-- décision.
+
+CREATE TABLE IF NOT EXISTS mentoring_disputes (
+    id                    VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id            VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    opened_by             VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason                TEXT NOT NULL,
+    status                VARCHAR(20) NOT NULL DEFAULT 'open'
+                              CHECK (status IN ('open', 'under_review', 'resolved')),
+    -- Statut de la réservation avant le litige (confirmed / pending_completion)
+    booking_status_before VARCHAR(30) NOT NULL,
+    assigned_to           VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    outcome               VARCHAR(20)
+                              CHECK (outcome IN ('full_refund', 'full_release', 'split')),
+    refund_tokens         INT CHECK (refund_tokens >= 0),
+    release_tokens        INT CHECK (release_tokens >= 0),
+    proof_action          VARCHAR(10)
+                              CHECK (proof_action IN ('none', 'issue', 'revoke')),
+    resolution_note       TEXT,
+    resolved_by           VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    resolved_at           TIMESTAMPTZ,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at            TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'resolved') = (outcome IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_disputes_status
+    ON mentoring_disputes(status, created_at);
+
+-- Un seul litige non résolu par réservation
+CREATE UNIQUE INDEX IF NOT EXISTS uq_mentoring_disputes_open
+    ON mentoring_disputes(booking_id)
+    WHERE status <> 'resolved';
+
+CREATE TABLE IF NOT EXISTS mentoring_dispute_events (
+    id          VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    dispute_id  VARCHAR NOT NULL REFERENCES mentoring_disputes(id) ON DELETE CASCADE,
+    actor_id    VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    kind        VARCHAR(20) NOT NULL
+                    CHECK (kind IN ('opened', 'evidence', 'assigned', 'note',
+                                    'resolved', 'proof_issued', 'proof_revoked')),
+    message     TEXT,
+    attachments JSONB NOT NULL DEFAULT '[]'::jsonb,
+    -- Notes internes des admins, invisibles des parties
+    internal    BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_dispute_events_dispute
+    ON mentoring_dispute_events(dispute_id, created_at);
+
+-- Révocation d'une preuve RGB suite à un arbitrage
+ALTER TABLE mentoring_proofs
+    ADD COLUMN IF NOT EXISTS revoked_at        TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS revocation_reason TEXT;
+
+-- Réservations déjà en litige : ouverture d'un dossier pour permettre l'arbitrage
+INSERT INTO mentoring_disputes (booking_id, opened_by, reason, booking_status_before, created_at)
+SELECT b.id, b.mentee_id,
+       COALESCE(NULLIF(b.dispute_reason, ''), 'Litige ouvert avant la mise en place de l''arbitrage'),
+       'pending_completion', b.updated_at
+FROM mentoring_bookings b
+WHERE b.status = 'disputed'
+  AND NOT EXISTS (SELECT 1 FROM mentoring_disputes d WHERE d.booking_id = b.id);
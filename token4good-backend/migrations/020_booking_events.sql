@@ -0,0 +1,31 @@
+-- Migration 020: Journal des transitions de statut des réservations
+--
+-- Chaque changement de statut d'une réservation passe par la machine à états
+-- (`services::booking_state`) et y est consigné : statut de départ et
+-- d'arrivée, événement déclencheur et auteur (mentor, mentee, admin ou
+-- système). `from_status` est NULL pour la création.
+
+CREATE TABLE IF NOT EXISTS mentoring_booking_events (
+    id          VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id  VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    from_status VARCHAR(30),
+    to_status   VARCHAR(30) NOT NULL,
+    event       VARCHAR(30) NOT NULL,
+    actor_role  VARCHAR(10) NOT NULL
+                    CHECK (actor_role IN ('mentor', 'mentee', 'admin', 'system')),
+    actor_id    VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    metadata    JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_booking_events_booking
+    ON mentoring_booking_events(booking_id, created_at);
+
+-- Réservations existantes : un événement d'import fixe leur statut de départ
+INSERT INTO mentoring_booking_events
+    (booking_id, from_status, to_status, event, actor_role, created_at)
+SELECT b.id, NULL, b.status, 'imported', 'system', b.updated_at
+FROM mentoring_bookings b
+WHERE NOT EXISTS (
+    SELECT 1 FROM mentoring_booking_events e WHERE e.booking_id = b.id
+);
@@ -0,0 +1,46 @@
+-- Migration 021: Listes d'attente sur les offres de mentoring
+--
+-- Un mentee s'inscrit sur une offre précise ou sur un couple mentor/sujet,
+-- avec un budget T4G optionnel. Quand une offre redevient disponible, le
+-- premier inscrit éligible reçoit une option de réservation exclusive
+-- (`offered`) limitée dans le temps ; il la lève en réservant normalement
+-- (débit du séquestre), sinon elle expire et passe au suivant.
+
+CREATE TABLE IF NOT EXISTS mentoring_waitlist_entries (
+    id              VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    mentee_id       VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Cible : une offre, ou un couple mentor/sujet
+    offer_id        VARCHAR REFERENCES mentoring_offers(id) ON DELETE CASCADE,
+    mentor_id       VARCHAR REFERENCES users(id) ON DELETE CASCADE,
+    topic_slug      VARCHAR(100),
+    max_tokens      INT CHECK (max_tokens >= 0),
+    status          VARCHAR(20) NOT NULL DEFAULT 'waiting'
+                        CHECK (status IN ('waiting', 'offered', 'claimed', 'expired', 'left')),
+    -- Option en cours : offre réservée pour ce mentee jusqu'à `hold_expires_at`
+    hold_offer_id   VARCHAR REFERENCES mentoring_offers(id) ON DELETE SET NULL,
+    hold_expires_at TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (offer_id IS NOT NULL OR (mentor_id IS NOT NULL AND topic_slug IS NOT NULL)),
+    CHECK (status <> 'offered' OR (hold_offer_id IS NOT NULL AND hold_expires_at IS NOT NULL))
+);
+
+-- Une seule inscription active par mentee et par cible
+CREATE UNIQUE INDEX IF NOT EXISTS uq_waitlist_active_target
+    ON mentoring_waitlist_entries(
+        mentee_id, COALESCE(offer_id, ''), COALESCE(mentor_id, ''), COALESCE(topic_slug, '')
+    )
+    WHERE status IN ('waiting', 'offered');
+
+CREATE INDEX IF NOT EXISTS idx_waitlist_offer_waiting
+    ON mentoring_waitlist_entries(offer_id, created_at)
+    WHERE status = 'waiting';
+
+CREATE INDEX IF NOT EXISTS idx_waitlist_pair_waiting
+    ON mentoring_waitlist_entries(mentor_id, topic_slug, created_at)
+    WHERE status = 'waiting';
+
+-- Une seule option en cours par offre
+CREATE UNIQUE INDEX IF NOT EXISTS uq_waitlist_active_hold
+    ON mentoring_waitlist_entries(hold_offer_id)
+    WHERE status = 'offered';
@@ -0,0 +1,44 @@
+-- Migration 022: Avis sur les mentors — réponses et modération
+--
+-- L'avis reste porté par la réservation (`mentee_rating`, `mentee_comment`).
+-- Le mentor peut y répondre une fois ; tout utilisateur peut signaler un avis
+-- abusif, le signalement rejoint la file de modération des admins qui
+-- masquent l'avis (il sort alors des agrégats) ou classent le signalement.
+
+ALTER TABLE mentoring_bookings
+    ADD COLUMN IF NOT EXISTS mentee_rated_at    TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS mentor_reply       TEXT,
+    ADD COLUMN IF NOT EXISTS mentor_replied_at  TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS review_hidden_at   TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS review_hidden_by   VARCHAR REFERENCES users(id) ON DELETE SET NULL;
+
+-- Avis existants : date de l'avis approchée par la dernière mise à jour
+UPDATE mentoring_bookings
+SET mentee_rated_at = updated_at
+WHERE mentee_rating IS NOT NULL AND mentee_rated_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_bookings_reviews
+    ON mentoring_bookings(offer_id, mentee_rated_at DESC)
+    WHERE mentee_rating IS NOT NULL AND review_hidden_at IS NULL;
+
+CREATE TABLE IF NOT EXISTS mentoring_review_reports (
+    id              VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id      VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    reporter_id     VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    reason          TEXT NOT NULL,
+    status          VARCHAR(20) NOT NULL DEFAULT 'pending'
+                        CHECK (status IN ('pending', 'upheld', 'dismissed')),
+    resolved_by     VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    resolved_at     TIMESTAMPTZ,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'pending') = (resolved_at IS NULL))
+);
+
+-- Un seul signalement en attente par utilisateur et par avis
+CREATE UNIQUE INDEX IF NOT EXISTS uq_review_reports_pending
+    ON mentoring_review_reports(booking_id, reporter_id)
+    WHERE status = 'pending';
+
+CREATE INDEX IF NOT EXISTS idx_review_reports_queue
+    ON mentoring_review_reports(status, created_at);
@@ -0,0 +1,26 @@
+-- Migration 023: Fils de discussion par réservation
+--
+-- Mentor et mentee échangent sur la réservation ; l'admin qui traite un
+-- litige ouvert y a aussi accès. Les accusés de lecture sont tenus par
+-- participant (dernier instant lu). Les messages sont purgés après la
+-- période de rétention qui suit la clôture de la réservation
+-- (`services::chat::run_chat_retention`), sauf litige en cours.
+
+CREATE TABLE IF NOT EXISTS mentoring_booking_messages (
+    id          VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id  VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    sender_id   VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    sender_role VARCHAR(10) NOT NULL CHECK (sender_role IN ('mentor', 'mentee', 'admin')),
+    body        TEXT NOT NULL CHECK (char_length(body) BETWEEN 1 AND 4000),
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_booking_messages_thread
+    ON mentoring_booking_messages(booking_id, created_at);
+
+CREATE TABLE IF NOT EXISTS mentoring_booking_thread_reads (
+    booking_id   VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    user_id      VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    last_read_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (booking_id, user_id)
+);
@@ -0,0 +1,34 @@
+-- Migration 024: Salles de visioconférence des sessions en ligne
+--
+-- Une salle est attribuée à la réservation quand elle est confirmée, si
+-- l'offre est au format `video` (`services::meeting::provision`). Le nom de
+-- salle est déterministe : les places d'une même occurrence de groupe
+-- partagent la salle. Les URLs de connexion ne sont pas stockées, elles sont
+-- signées à la demande pour chaque participant.
+--
+-- Les rappels de connexion/déconnexion du fournisseur alimentent
+-- `mentoring_attendance_events` (présence aux sessions).
+
+CREATE TABLE IF NOT EXISTS mentoring_meeting_rooms (
+    booking_id VARCHAR PRIMARY KEY REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    provider   VARCHAR(20) NOT NULL,
+    room_name  VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_meeting_rooms_name ON mentoring_meeting_rooms(room_name);
+
+CREATE TABLE IF NOT EXISTS mentoring_attendance_events (
+    id          VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id  VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    user_id     VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event       VARCHAR(10) NOT NULL CHECK (event IN ('join', 'leave')),
+    occurred_at TIMESTAMPTZ NOT NULL,
+    source      VARCHAR(20) NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Les rappels rejoués par le fournisseur ne créent pas de doublon
+    UNIQUE (booking_id, user_id, event, occurred_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attendance_events_booking
+    ON mentoring_attendance_events(booking_id, occurred_at);
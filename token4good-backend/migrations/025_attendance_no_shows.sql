@@ -0,0 +1,21 @@
+-- Migration 025: Pointage des sessions et absences
+--
+-- Le mentor affiche un code court que le mentee saisit pendant la fenêtre de
+-- connexion ; les connexions remontées par le fournisseur de
+-- visioconférence valent aussi pointage (`mentoring_attendance_events`).
+-- Passé la fenêtre, une session suivie (salle, code ou pointage) reçoit son
+-- constat de présence : absence du mentor → remboursement, absence du
+-- mentee → libération partielle du séquestre
+-- (`services::attendance::run_no_show_detection`).
+
+ALTER TABLE mentoring_bookings
+    ADD COLUMN IF NOT EXISTS checkin_code       VARCHAR(6),
+    ADD COLUMN IF NOT EXISTS checkin_attempts   INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS attendance_outcome VARCHAR(20)
+        CHECK (attendance_outcome IN ('attended', 'mentor_no_show', 'mentee_no_show', 'nobody')),
+    ADD COLUMN IF NOT EXISTS attendance_settled_at TIMESTAMPTZ;
+
+-- Taux d'absence par mentor et par mentee
+CREATE INDEX IF NOT EXISTS idx_mentoring_bookings_attendance
+    ON mentoring_bookings(offer_id, attendance_outcome)
+    WHERE attendance_outcome IS NOT NULL;
@@ -0,0 +1,63 @@
+-- Migration 026: Graphe de compétences et progression d'apprentissage
+--
+-- Chaque session complétée laisse une preuve d'apprentissage pour le sujet
+-- de l'offre et pour chaque `learned_skills` qui correspond à un sujet du
+-- référentiel (slug ou nom). Le niveau d'un utilisateur sur un sujet est
+-- dérivé du nombre de sessions et des recommandations de ses mentors
+-- (`models::skills::skill_level`) ; les preuves RGB sont retrouvées par la
+-- réservation. Les prérequis explicites entre sujets complètent l'ordre des
+-- niveaux au sein d'une catégorie pour suggérer les prochains sujets.
+
+CREATE TABLE IF NOT EXISTS learning_topic_prerequisites (
+    topic_slug        VARCHAR(100) NOT NULL REFERENCES learning_topics(slug) ON DELETE CASCADE,
+    prerequisite_slug VARCHAR(100) NOT NULL REFERENCES learning_topics(slug) ON DELETE CASCADE,
+    PRIMARY KEY (topic_slug, prerequisite_slug),
+    CHECK (topic_slug <> prerequisite_slug)
+);
+
+CREATE TABLE IF NOT EXISTS user_skill_evidence (
+    id          VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    user_id     VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    topic_slug  VARCHAR(100) NOT NULL REFERENCES learning_topics(slug) ON DELETE CASCADE,
+    booking_id  VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    -- Libellé saisi à la confirmation, NULL pour le sujet de l'offre
+    label       TEXT,
+    learned_at  TIMESTAMPTZ NOT NULL,
+    UNIQUE (user_id, topic_slug, booking_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_evidence_user ON user_skill_evidence(user_id, topic_slug);
+
+CREATE TABLE IF NOT EXISTS skill_endorsements (
+    id         VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    user_id    VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    topic_slug VARCHAR(100) NOT NULL REFERENCES learning_topics(slug) ON DELETE CASCADE,
+    mentor_id  VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    booking_id VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    level      VARCHAR(20) NOT NULL CHECK (level IN ('beginner', 'intermediate', 'advanced')),
+    comment    TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Une recommandation par mentor et par sujet, mise à jour au besoin
+    UNIQUE (user_id, topic_slug, mentor_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_skill_endorsements_user ON skill_endorsements(user_id);
+
+-- Reprise des sessions déjà complétées : sujet de l'offre…
+INSERT INTO user_skill_evidence (user_id, topic_slug, booking_id, label, learned_at)
+SELECT b.mentee_id, o.topic_slug, b.id, NULL, b.updated_at
+FROM mentoring_bookings b
+JOIN mentoring_offers o ON o.id = b.offer_id
+WHERE b.status IN ('completed', 'auto_completed')
+ON CONFLICT (user_id, topic_slug, booking_id) DO NOTHING;
+
+-- …et compétences déclarées qui correspondent à un sujet
+INSERT INTO user_skill_evidence (user_id, topic_slug, booking_id, label, learned_at)
+SELECT b.mentee_id, t.slug, b.id, s.label, b.updated_at
+FROM mentoring_bookings b
+CROSS JOIN LATERAL unnest(b.learned_skills) AS s(label)
+JOIN learning_topics t
+  ON t.slug = lower(trim(s.label)) OR lower(t.name) = lower(trim(s.label))
+WHERE b.status IN ('completed', 'auto_completed')
+ON CONFLICT (user_id, topic_slug, booking_id) DO NOTHING;
@@ -0,0 +1,33 @@
+-- Migration 027: Absences des mentors (vacances, examens…)
+--
+-- Une absence occupe tout l'agenda du mentor sur [starts_at, ends_at) :
+-- aucune session n'y est réservable ni reportable
+-- (`services::availability::mentor_occupancy`), et ses offres ouvertes sont
+-- masquées pendant l'absence. Les réservations déjà prises sur la période
+-- sont signalées (`time_off_id`) : le mentor propose un report ou se retire
+-- avec remboursement intégral. `is_mentor_active` suit le calendrier des
+-- absences (`services::time_off::run_schedule_sync`).
+
+CREATE TABLE IF NOT EXISTS mentor_time_off (
+    id         VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    mentor_id  VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    starts_at  TIMESTAMPTZ NOT NULL,
+    ends_at    TIMESTAMPTZ NOT NULL,
+    reason     TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (ends_at > starts_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentor_time_off_mentor ON mentor_time_off(mentor_id, ends_at);
+
+-- Réservation recoupant une absence, en attente de report ou de retrait
+ALTER TABLE mentoring_bookings
+    ADD COLUMN IF NOT EXISTS time_off_id VARCHAR REFERENCES mentor_time_off(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_bookings_time_off
+    ON mentoring_bookings(time_off_id)
+    WHERE time_off_id IS NOT NULL;
+
+-- Mentor désactivé par une absence : réactivé à son terme
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS mentor_paused_by_time_off BOOLEAN NOT NULL DEFAULT false;
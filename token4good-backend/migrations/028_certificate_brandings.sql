@@ -0,0 +1,20 @@
+-- Migration 028: Habillage des certificats par organisation
+--
+-- Les certificats de session et de parcours (PDF) reprennent le nom, les
+-- couleurs et le signataire de l'organisation du mentee : choisie
+-- explicitement (`?branding=`), sinon par le domaine de son e-mail, sinon
+-- l'habillage Token4Good par défaut (`services::certificate`).
+
+CREATE TABLE IF NOT EXISTS certificate_brandings (
+    slug              VARCHAR(100) PRIMARY KEY,
+    organisation_name VARCHAR(200) NOT NULL,
+    -- Domaine e-mail des membres de l'organisation (ex. "ecole.fr")
+    email_domain      VARCHAR(200) UNIQUE,
+    primary_color     VARCHAR(7) NOT NULL DEFAULT '#1E3A8A' CHECK (primary_color ~ '^#[0-9A-Fa-f]{6}$'),
+    accent_color      VARCHAR(7) NOT NULL DEFAULT '#F59E0B' CHECK (accent_color ~ '^#[0-9A-Fa-f]{6}$'),
+    signatory_name    VARCHAR(200),
+    signatory_title   VARCHAR(200),
+    footer            TEXT,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at        TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,131 @@
+-- Migration 029: Demandes de mentoring unifiées avec offres et réservations
+--
+-- Une demande mentee (« j'ai besoin d'aide sur X ») devient une demande
+-- de première classe : les mentors y répondent par une offre privée
+-- (mentoring_offers.request_id), que seul le mentee peut réserver ; la
+-- réservation suit ensuite le circuit habituel (séquestre, complétion,
+-- preuve RGB). Statut de la demande :
+--   open      — en attente de réponses
+--   assigned  — une offre privée a été réservée (mentor_id, booking_id)
+--   completed — la session a été complétée
+--   cancelled — retirée par le mentee
+-- L'annulation de la réservation rouvre la demande (services::booking_state).
+
+-- ============================================================
+-- 1. Ancienne table conservée sous un autre nom
+--    La 010 supprime mentoring_requests, mais une base qui l'a recréée
+--    (schéma 001 : category/message, ou ancien schéma title/description)
+--    garde ses lignes : elles sont reprises en 3.
+-- ============================================================
+
+DO $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.tables
+        WHERE table_schema = current_schema() AND table_name = 'mentoring_requests'
+    ) AND NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema()
+          AND table_name = 'mentoring_requests' AND column_name = 'max_tokens'
+    ) THEN
+        ALTER TABLE mentoring_requests RENAME TO mentoring_requests_legacy;
+    END IF;
+END $$;
+
+-- ============================================================
+-- 2. Demandes
+-- ============================================================
+
+CREATE TABLE IF NOT EXISTS mentoring_requests (
+    id           VARCHAR PRIMARY KEY DEFAULT 'req_' || gen_random_uuid()::text,
+    mentee_id    VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    topic_slug   VARCHAR(100) REFERENCES learning_topics(slug) ON DELETE SET NULL,
+    title        VARCHAR(200) NOT NULL,
+    description  TEXT NOT NULL DEFAULT '',
+    tags         TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    -- Budget maximal en T4G, NULL = pas de limite
+    max_tokens   INT CHECK (max_tokens >= 0),
+    status       VARCHAR(20) NOT NULL DEFAULT 'open'
+                     CHECK (status IN ('open', 'assigned', 'completed', 'cancelled')),
+    mentor_id    VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    booking_id   VARCHAR REFERENCES mentoring_bookings(id) ON DELETE SET NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at   TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_requests_mentee ON mentoring_requests(mentee_id);
+CREATE INDEX IF NOT EXISTS idx_mentoring_requests_open
+    ON mentoring_requests(topic_slug, created_at DESC) WHERE status = 'open';
+CREATE INDEX IF NOT EXISTS idx_mentoring_requests_booking
+    ON mentoring_requests(booking_id) WHERE booking_id IS NOT NULL;
+
+DROP TRIGGER IF EXISTS update_mentoring_requests_updated_at ON mentoring_requests;
+CREATE TRIGGER update_mentoring_requests_updated_at
+    BEFORE UPDATE ON mentoring_requests
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Offre privée : réponse d'un mentor à une demande
+ALTER TABLE mentoring_offers
+    ADD COLUMN IF NOT EXISTS request_id VARCHAR REFERENCES mentoring_requests(id);
+
+CREATE INDEX IF NOT EXISTS idx_mentoring_offers_request
+    ON mentoring_offers(request_id) WHERE request_id IS NOT NULL;
+
+-- ============================================================
+-- 3. Reprise des anciennes demandes
+--    Statuts : pending → open, accepted/assigned → assigned (mentor conservé,
+--    sans réservation), completed → completed, cancelled → cancelled.
+--    Le sujet est repris quand la catégorie correspond à un slug connu.
+-- ============================================================
+
+DO $$
+DECLARE
+    has_category BOOLEAN;
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.tables
+        WHERE table_schema = current_schema() AND table_name = 'mentoring_requests_legacy'
+    ) THEN
+        RETURN;
+    END IF;
+
+    SELECT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_schema = current_schema()
+          AND table_name = 'mentoring_requests_legacy' AND column_name = 'category'
+    ) INTO has_category;
+
+    IF has_category THEN
+        INSERT INTO mentoring_requests
+            (id, mentee_id, topic_slug, title, description, status, mentor_id, created_at)
+        SELECT l.id, l.mentee_id, t.slug, LEFT(l.category, 200), COALESCE(l.message, ''),
+               CASE l.status
+                   WHEN 'pending'  THEN 'open'
+                   WHEN 'accepted' THEN 'assigned'
+                   ELSE l.status
+               END,
+               mu.id, COALESCE(l.created_at, NOW())
+        FROM mentoring_requests_legacy l
+        JOIN users u ON u.id = l.mentee_id
+        LEFT JOIN users mu ON mu.id = l.mentor_id
+        LEFT JOIN learning_topics t ON t.slug = LOWER(l.category)
+        ON CONFLICT (id) DO NOTHING;
+    ELSE
+        EXECUTE $sql$
+            INSERT INTO mentoring_requests
+                (id, mentee_id, title, description, tags, status, mentor_id, created_at)
+            SELECT l.id, l.mentee_id, LEFT(l.title, 200), COALESCE(l.description, ''),
+                   COALESCE(l.tags, ARRAY[]::TEXT[]),
+                   CASE WHEN l.status IN ('open', 'assigned', 'completed', 'cancelled')
+                        THEN l.status ELSE 'open' END,
+                   mu.id, COALESCE(l.created_at, NOW())
+            FROM mentoring_requests_legacy l
+            JOIN users u ON u.id = l.mentee_id
+            LEFT JOIN users mu ON mu.id = l.mentor_id
+            ON CONFLICT (id) DO NOTHING
+        $sql$;
+    END IF;
+END $$;
+
+-- mentoring_requests_legacy est conservée pour contrôle ; à supprimer une
+-- fois la reprise vérifiée.
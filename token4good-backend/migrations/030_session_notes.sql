@@ -0,0 +1,52 @@
+-- Migration 030: Notes de session et ressources partagées par réservation
+--
+-- Chaque participant (mentor, mentee) tient une fiche de notes sur la
+-- réservation : une partie partagée (résumé, actions à mener, liens) visible
+-- des deux participants, et une partie privée visible de son seul auteur.
+-- Les pièces jointes sont des références (URL) vers des documents hébergés
+-- ailleurs : devoirs du mentee, supports du mentor.
+--
+-- Notes et pièces jointes sont modifiables jusqu'à la clôture de la
+-- réservation, puis figées (`frozen_at`, posé par services::booking_state).
+-- À la complétion, l'empreinte des parties partagées est engagée dans la
+-- preuve RGB (`mentoring_proofs.notes_hash`).
+
+CREATE TABLE IF NOT EXISTS mentoring_session_notes (
+    booking_id    VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    author_id     VARCHAR NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    author_role   VARCHAR(10) NOT NULL CHECK (author_role IN ('mentor', 'mentee')),
+    summary       TEXT NOT NULL DEFAULT '',
+    -- ["Relire le chapitre 3", ...]
+    action_items  JSONB NOT NULL DEFAULT '[]',
+    -- [{"title": "...", "url": "https://..."}, ...]
+    links         JSONB NOT NULL DEFAULT '[]',
+    private_notes TEXT NOT NULL DEFAULT '',
+    frozen_at     TIMESTAMPTZ,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (booking_id, author_id)
+);
+
+DROP TRIGGER IF EXISTS update_mentoring_session_notes_updated_at ON mentoring_session_notes;
+CREATE TRIGGER update_mentoring_session_notes_updated_at
+    BEFORE UPDATE ON mentoring_session_notes
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS mentoring_session_attachments (
+    id            VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    booking_id    VARCHAR NOT NULL REFERENCES mentoring_bookings(id) ON DELETE CASCADE,
+    uploader_id   VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    uploader_role VARCHAR(10) NOT NULL CHECK (uploader_role IN ('mentor', 'mentee')),
+    name          VARCHAR(255) NOT NULL,
+    url           TEXT NOT NULL,
+    content_type  VARCHAR(100),
+    size_bytes    BIGINT CHECK (size_bytes >= 0),
+    frozen_at     TIMESTAMPTZ,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_attachments_booking
+    ON mentoring_session_attachments(booking_id, created_at);
+
+-- Empreinte des notes partagées engagée dans la preuve (NULL = sans notes)
+ALTER TABLE mentoring_proofs ADD COLUMN IF NOT EXISTS notes_hash VARCHAR(64);
@@ -0,0 +1,60 @@
+-- Migration 031: Planificateur persistant des tâches de fond
+--
+-- Les tâches (auto-complétion, rappels, scellement du ledger…) sont
+-- déclarées dans le code (`services::jobs`) et synchronisées ici au
+-- démarrage. Une seule instance, élue par verrou consultatif Postgres,
+-- les exécute (`services::scheduler`) : elle tient `scheduler_leader` à jour
+-- et consigne chaque exécution dans `scheduled_job_runs`. Une exécution
+-- restée `running` quand une nouvelle instance prend la main est marquée
+-- `interrupted` et la tâche relancée aussitôt.
+
+CREATE TABLE IF NOT EXISTS scheduled_jobs (
+    name                  VARCHAR(100) PRIMARY KEY,
+    description           TEXT NOT NULL DEFAULT '',
+    -- Expression cron à 6 champs (seconde minute heure jour mois jour_semaine), UTC
+    schedule              VARCHAR(100) NOT NULL,
+    paused                BOOLEAN NOT NULL DEFAULT FALSE,
+    max_retries           INT NOT NULL DEFAULT 0 CHECK (max_retries >= 0),
+    retry_backoff_seconds INT NOT NULL DEFAULT 60 CHECK (retry_backoff_seconds > 0),
+    -- Échecs consécutifs de l'occurrence en cours (0 = pas de nouvelle tentative en attente)
+    attempt               INT NOT NULL DEFAULT 0,
+    next_run_at           TIMESTAMPTZ,
+    -- Exécution manuelle demandée par un admin, prise en charge par l'instance élue
+    trigger_requested_at  TIMESTAMPTZ,
+    triggered_by          VARCHAR REFERENCES users(id) ON DELETE SET NULL,
+    created_at            TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at            TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+DROP TRIGGER IF EXISTS update_scheduled_jobs_updated_at ON scheduled_jobs;
+CREATE TRIGGER update_scheduled_jobs_updated_at
+    BEFORE UPDATE ON scheduled_jobs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS scheduled_job_runs (
+    id           VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    job_name     VARCHAR(100) NOT NULL REFERENCES scheduled_jobs(name) ON DELETE CASCADE,
+    trigger      VARCHAR(10) NOT NULL CHECK (trigger IN ('schedule', 'manual', 'retry')),
+    attempt      INT NOT NULL DEFAULT 0,
+    instance_id  VARCHAR(200) NOT NULL,
+    status       VARCHAR(20) NOT NULL DEFAULT 'running'
+                     CHECK (status IN ('running', 'succeeded', 'failed', 'interrupted')),
+    result       TEXT,
+    error        TEXT,
+    started_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    finished_at  TIMESTAMPTZ,
+    duration_ms  BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_job_runs_job
+    ON scheduled_job_runs(job_name, started_at DESC);
+CREATE INDEX IF NOT EXISTS idx_scheduled_job_runs_running
+    ON scheduled_job_runs(status) WHERE status = 'running';
+
+-- Instance élue (une seule ligne)
+CREATE TABLE IF NOT EXISTS scheduler_leader (
+    id           BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    instance_id  VARCHAR(200) NOT NULL,
+    acquired_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    heartbeat_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,38 @@
+-- Migration 032: Outbox transactionnelle des effets de bord
+--
+-- Les effets de bord d'un changement métier (notification in-app, e-mail,
+-- synchronisation gamification Dazno, émission de la preuve RGB) sont
+-- écrits ici dans la même transaction que le changement, puis livrés par le
+-- répartiteur (`services::outbox`). Chaque instance répartit les entrées
+-- dues (`FOR UPDATE SKIP LOCKED`) ; un échec est retenté avec backoff
+-- jusqu'à `max_attempts`, puis l'entrée passe en `dead` et peut être
+-- rejouée par un admin. Statuts :
+--   pending   — à livrer (à partir de next_attempt_at)
+--   delivered — livrée
+--   skipped   — sans objet (relais e-mail non configuré, preuve déjà émise…)
+--   dead      — abandonnée après max_attempts échecs
+
+CREATE TABLE IF NOT EXISTS outbox_events (
+    id              VARCHAR PRIMARY KEY DEFAULT gen_random_uuid()::text,
+    kind            VARCHAR(20) NOT NULL
+                        CHECK (kind IN ('notification', 'email', 'dazno_sync', 'rgb_proof')),
+    payload         JSONB NOT NULL,
+    -- Évite de doubler un effet de bord (ex. une preuve par réservation)
+    dedup_key       VARCHAR(200) UNIQUE,
+    status          VARCHAR(20) NOT NULL DEFAULT 'pending'
+                        CHECK (status IN ('pending', 'delivered', 'skipped', 'dead')),
+    attempts        INT NOT NULL DEFAULT 0,
+    max_attempts    INT NOT NULL DEFAULT 8 CHECK (max_attempts > 0),
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Réservation de l'entrée par une instance pendant la livraison
+    locked_until    TIMESTAMPTZ,
+    last_error      TEXT,
+    result          TEXT,
+    created_at      TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at    TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbox_events_due
+    ON outbox_events(next_attempt_at) WHERE status = 'pending';
+CREATE INDEX IF NOT EXISTS idx_outbox_events_status
+    ON outbox_events(status, created_at DESC);
@@ -0,0 +1,116 @@
+//! Vérification hors ligne de la chaîne de hachage du ledger T4G.
+//!
+//! Usage :
+//!   DATABASE_URL=postgres://... cargo run --bin verify_ledger -- \
+//!       --pubkey <clé émetteur hex> [--user <user_id>]
+//!
+//! La clé publique de l'émetteur est requise (`--pubkey` ou
+//! `RGB_ISSUER_PUBKEY`) : les têtes signées sont vérifiées avec elle, jamais
+//! avec la clé enregistrée en base.
+//!
+//! Code de sortie : 0 si la chaîne est intègre, 1 si un maillon est cassé,
+//! 2 en cas d'erreur d'exécution.
+
+use sqlx::postgres::PgPoolOptions;
+use token4good_backend::services::ledger_chain;
+
+#[tokio::main]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let usage = || -> ! {
+        eprintln!("usage: verify_ledger --pubkey <issuer_pubkey> [--user <user_id>]");
+        std::process::exit(2);
+    };
+    let mut user_id = None;
+    let mut pubkey = std::env::var("RGB_ISSUER_PUBKEY").ok();
+    let mut args = std::env::args().skip(1);
+    while let Some(flag) = args.next() {
+        match (flag.as_str(), args.next()) {
+            ("--user", Some(id)) => user_id = Some(id),
+            ("--pubkey", Some(key)) => pubkey = Some(key),
+            _ => usage(),
+        }
+    }
+    let Some(pubkey) = pubkey.filter(|k| !k.trim().is_empty()) else {
+        eprintln!("issuer public key required: --pubkey or RGB_ISSUER_PUBKEY");
+        usage();
+    };
+
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL must be set");
+        std::process::exit(2);
+    };
+
+    let pool = match PgPoolOptions::new()
+        .max_connections(1)
+        .connect(&database_url)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Database connection failed: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let report = match ledger_chain::verify_chain(&pool, user_id.as_deref(), pubkey.trim()).await {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Verification failed: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    println!("scope:     {}", report.scope);
+    println!("checked:   {} row(s)", report.checked);
+    println!(
+        "head:      #{} {}",
+        report
+            .head_seq
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".to_string()),
+        report.head_hash
+    );
+    println!("unsealed:  {} row(s)", report.unsealed);
+
+    if let Some(head) = &report.signed_head {
+        println!(
+            "signed:    #{} at {} (signature {}, {}, {})",
+            head.chain_seq,
+            head.signed_at.to_rfc3339(),
+            if head.signature_valid {
+                "valid"
+            } else {
+                "INVALID"
+            },
+            if head.issuer_matches {
+                "issuer key"
+            } else {
+                "UNKNOWN KEY"
+            },
+            if head.matches_chain {
+                "matches chain"
+            } else {
+                "DOES NOT MATCH CHAIN"
+            }
+        );
+    } else {
+        println!("signed:    no signed head yet");
+    }
+
+    match &report.first_break {
+        Some(b) => {
+            println!(
+                "BROKEN at #{} (transaction {}, user {}): {}",
+                b.chain_seq, b.transaction_id, b.user_id, b.reason
+            );
+            std::process::exit(1);
+        }
+        None if !report.valid => {
+            println!("BROKEN: signed head does not verify");
+            std::process::exit(1);
+        }
+        None => println!("OK"),
+    }
+}
@@ -9,13 +9,17 @@ use std::{env, error::Error};
 use tower_http::cors::CorsLayer;
 use tower_http::normalize_path::NormalizePathLayer;
 
-use services::{database::DatabaseService, dazno::DaznoService, rgb::RGBService};
+use services::{
+    chat::ChatHub, database::DatabaseService, dazno::DaznoService, rgb::RGBService,
+};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseService,
     pub rgb: RGBService,
     pub dazno: DaznoService,
+    /// Diffusion temps réel des fils de discussion
+    pub chat: ChatHub,
 }
 
 impl AppState {
@@ -57,7 +61,15 @@ pub async fn build_state() -> Result<AppState, Box<dyn Error>> {
     let rgb = RGBService::new()?;
     let dazno = DaznoService::new()?;
 
-    Ok(AppState { db, rgb, dazno })
+    let chat = ChatHub::new();
+    chat.spawn_listener(db.pool().clone());
+
+    Ok(AppState {
+        db,
+        rgb,
+        dazno,
+        chat,
+    })
 }
 
 fn build_cors_layer() -> CorsLayer {
@@ -115,17 +127,21 @@ pub fn build_router(state: AppState) -> Router {
         )
         .nest(
             "/api/mentoring",
-            routes::mentoring_offers::mentoring_offer_routes().layer(
-                axum::middleware::from_fn_with_state(
+            routes::mentoring_offers::mentoring_offer_routes()
+                .merge(routes::chat::chat_routes())
+                .merge(routes::session_notes::session_notes_routes())
+                .layer(axum::middleware::from_fn_with_state(
                     state.clone(),
                     crate::middleware::auth::auth_middleware,
-                ),
-            ),
+                )),
         )
         .nest(
             "/api/users",
             routes::users::user_routes()
                 .merge(routes::mentoring_offers::mentoring_user_routes())
+                .merge(routes::calendar::calendar_user_routes())
+                .merge(routes::learning::learning_user_routes())
+                .merge(routes::time_off::time_off_user_routes())
                 .layer(axum::middleware::from_fn(
                     crate::middleware::authorization::user_resource_authorization,
                 ))
@@ -148,6 +164,10 @@ pub fn build_router(state: AppState) -> Router {
                 crate::middleware::auth::auth_middleware,
             )),
         )
+        // Vérification des certificats — publique (cible du QR code)
+        .nest("/api/certificates", routes::proofs::certificate_routes())
+        // Flux iCalendar — public, authentifié par le jeton secret de l'URL
+        .nest("/api/calendar", routes::calendar::calendar_feed_routes())
         // Référentiel apprentissages — public, pas d'auth
         .nest("/api/learning", routes::learning::learning_routes())
         .nest(
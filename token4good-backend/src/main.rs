@@ -2,8 +2,8 @@ use axum::{routing::get, Json, Router};
 use serde_json::json;
 use std::net::SocketAddr;
 
+use token4good_backend::services::{jobs, outbox::Dispatcher, scheduler::Scheduler};
 use token4good_backend::{build_router, build_state};
-use token4good_backend::services::mentoring_completion;
 
 #[tokio::main]
 async fn main() {
@@ -29,36 +29,20 @@ async fn main() {
 
     // ── Tâches de fond ────────────────────────────────────────────────────
 
-    // Auto-complétion 48h (toutes les heures)
-    {
-        let pool = state.db.pool().clone();
-        let rgb = state.rgb.clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
-            loop {
-                interval.tick().await;
-                let n = mentoring_completion::run_auto_completion(&pool, &rgb).await;
-                if n > 0 {
-                    tracing::info!("Auto-completion: {} booking(s) processed", n);
-                }
-            }
-        });
-    }
-
-    // Rappels session J-1 et H-1 (toutes les 15 minutes)
-    {
-        let pool = state.db.pool().clone();
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(900));
-            loop {
-                interval.tick().await;
-                let n = mentoring_completion::send_session_reminders(&pool).await;
-                if n > 0 {
-                    tracing::info!("Session reminders: {} notification(s) sent", n);
-                }
-            }
-        });
-    }
+    // Planificateur persistant : une seule instance élue exécute les tâches
+    Scheduler::new(
+        state.db.pool().clone(),
+        jobs::registry(state.db.pool().clone(), state.rgb.clone()),
+    )
+    .spawn();
+
+    // Répartiteur de l'outbox : chaque instance livre les effets de bord dus
+    Dispatcher::new(
+        state.db.pool().clone(),
+        state.rgb.clone(),
+        state.dazno.clone(),
+    )
+    .spawn();
 
     let app = build_router(state);
 
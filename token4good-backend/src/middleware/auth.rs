@@ -24,6 +24,12 @@ pub struct AuthUser {
     pub role: String,
 }
 
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        crate::models::user::is_admin_role(&self.role)
+    }
+}
+
 pub struct JWTService {
     encoding_key: EncodingKey,
     decoding_key: DecodingKey,
@@ -309,7 +315,7 @@ mod tests {
             // Modifier le secret pour créer un service différent
             std::env::set_var("JWT_SECRET", "another-super-secret-key-for-testing-abcde");
             let svc_other = JWTService::new().unwrap();
-            std::env::set_var("JWT_SECRET", &test_secret());
+            std::env::set_var("JWT_SECRET", test_secret());
 
             let result = svc_other.verify_token(&token);
             assert!(
@@ -36,7 +36,7 @@ pub async fn user_resource_authorization(
 
             if (is_write || is_sensitive_path)
                 && auth_user.id != *user_id
-                && auth_user.role != "ADMIN"
+                && !auth_user.is_admin()
             {
                 return Err(StatusCode::FORBIDDEN);
             }
@@ -54,7 +54,7 @@ pub async fn mentor_authorization(request: Request, next: Next) -> Result<Respon
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
     // Check if user has mentor role
-    if auth_user.role != "mentor" && auth_user.role != "ADMIN" {
+    if auth_user.role != "mentor" && !auth_user.is_admin() {
         return Err(StatusCode::FORBIDDEN);
     }
 
@@ -91,7 +91,7 @@ pub async fn admin_authorization(request: Request, next: Next) -> Result<Respons
         .get::<AuthUser>()
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
-    if auth_user.role != "ADMIN" {
+    if !auth_user.is_admin() {
         return Err(StatusCode::FORBIDDEN);
     }
 
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::meeting::AttendanceEvent;
+
+/// Longueur du code de pointage affiché au mentor
+pub const CHECKIN_CODE_LEN: usize = 6;
+/// Saisies erronées tolérées avant blocage du pointage par code
+pub const MAX_CHECKIN_ATTEMPTS: i32 = 5;
+/// Part du séquestre versée au mentor quand le mentee ne se présente pas
+pub const MENTEE_NO_SHOW_RELEASE_PERCENT: i64 = 50;
+/// Sessions fictives sans absence ajoutées au calcul du taux d'absence
+/// utilisé par le classement : une absence isolée pèse peu
+pub const NO_SHOW_PRIOR_SESSIONS: i64 = 3;
+
+// ============================================================
+// Constat de présence
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendanceOutcome {
+    Attended,
+    MentorNoShow,
+    MenteeNoShow,
+    /// Personne ne s'est présenté : traité comme une absence du mentor
+    Nobody,
+}
+
+impl AttendanceOutcome {
+    pub fn from_presence(mentor_present: bool, mentee_present: bool) -> Self {
+        match (mentor_present, mentee_present) {
+            (true, true) => AttendanceOutcome::Attended,
+            (false, true) => AttendanceOutcome::MentorNoShow,
+            (true, false) => AttendanceOutcome::MenteeNoShow,
+            (false, false) => AttendanceOutcome::Nobody,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttendanceOutcome::Attended => "attended",
+            AttendanceOutcome::MentorNoShow => "mentor_no_show",
+            AttendanceOutcome::MenteeNoShow => "mentee_no_show",
+            AttendanceOutcome::Nobody => "nobody",
+        }
+    }
+
+    /// Partage du séquestre : (remboursé au mentee, versé au mentor).
+    /// `None` quand la session a eu lieu (complétion normale).
+    pub fn escrow_split(self, escrow: i64) -> Option<(i64, i64)> {
+        match self {
+            AttendanceOutcome::Attended => None,
+            AttendanceOutcome::MentorNoShow | AttendanceOutcome::Nobody => Some((escrow, 0)),
+            AttendanceOutcome::MenteeNoShow => {
+                let release = escrow * MENTEE_NO_SHOW_RELEASE_PERCENT / 100;
+                Some((escrow - release, release))
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for AttendanceOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "attended" => Ok(AttendanceOutcome::Attended),
+            "mentor_no_show" => Ok(AttendanceOutcome::MentorNoShow),
+            "mentee_no_show" => Ok(AttendanceOutcome::MenteeNoShow),
+            "nobody" => Ok(AttendanceOutcome::Nobody),
+            _ => Err(format!("Invalid attendance outcome: {}", s)),
+        }
+    }
+}
+
+// ============================================================
+// Pointage
+// ============================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CheckInCode {
+    pub booking_id: String,
+    pub code: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckInPayload {
+    pub code: String,
+}
+
+/// Présence d'une réservation vue par un participant
+#[derive(Debug, Serialize)]
+pub struct AttendanceStatus {
+    pub booking_id: String,
+    pub opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+    pub mentor_checked_in_at: Option<DateTime<Utc>>,
+    pub mentee_checked_in_at: Option<DateTime<Utc>>,
+    pub outcome: Option<AttendanceOutcome>,
+    pub events: Vec<AttendanceEvent>,
+}
+
+// ============================================================
+// Taux d'absence
+// ============================================================
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Default)]
+pub struct NoShowStats {
+    /// Sessions dont la présence a été constatée
+    pub sessions: i64,
+    pub no_shows: i64,
+    pub rate: Option<f64>,
+}
+
+impl NoShowStats {
+    pub fn new(sessions: i64, no_shows: i64) -> Self {
+        NoShowStats {
+            sessions,
+            no_shows,
+            rate: (sessions > 0).then(|| no_shows as f64 / sessions as f64),
+        }
+    }
+}
+
+/// Absences d'un utilisateur, comme mentor et comme mentee
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Default)]
+pub struct AttendanceRecord {
+    pub as_mentor: NoShowStats,
+    pub as_mentee: NoShowStats,
+}
+
+/// Taux d'absence lissé, pour le classement des offres
+pub fn smoothed_no_show_rate(no_shows: i64, sessions: i64) -> f64 {
+    no_shows.max(0) as f64 / (sessions.max(0) + NO_SHOW_PRIOR_SESSIONS) as f64
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_and_escrow_split() {
+        assert_eq!(
+            AttendanceOutcome::from_presence(true, true),
+            AttendanceOutcome::Attended
+        );
+        assert_eq!(
+            AttendanceOutcome::from_presence(false, true),
+            AttendanceOutcome::MentorNoShow
+        );
+        assert_eq!(
+            "mentee_no_show".parse::<AttendanceOutcome>(),
+            Ok(AttendanceOutcome::MenteeNoShow)
+        );
+        assert_eq!(AttendanceOutcome::Attended.escrow_split(40), None);
+        assert_eq!(
+            AttendanceOutcome::MentorNoShow.escrow_split(40),
+            Some((40, 0))
+        );
+        assert_eq!(AttendanceOutcome::Nobody.escrow_split(40), Some((40, 0)));
+        assert_eq!(
+            AttendanceOutcome::MenteeNoShow.escrow_split(45),
+            Some((23, 22))
+        );
+    }
+
+    #[test]
+    fn test_no_show_rates() {
+        assert_eq!(NoShowStats::new(0, 0).rate, None);
+        assert_eq!(NoShowStats::new(4, 1).rate, Some(0.25));
+        assert_eq!(smoothed_no_show_rate(0, 0), 0.0);
+        assert_eq!(smoothed_no_show_rate(1, 1), 0.25);
+        assert!(smoothed_no_show_rate(1, 20) < smoothed_no_show_rate(1, 2));
+    }
+}
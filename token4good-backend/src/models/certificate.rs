@@ -0,0 +1,331 @@
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::matching::Level;
+
+// ============================================================
+// Langues
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CertificateLanguage {
+    #[default]
+    Fr,
+    En,
+}
+
+impl std::str::FromStr for CertificateLanguage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "fr" => Ok(CertificateLanguage::Fr),
+            "en" => Ok(CertificateLanguage::En),
+            _ => Err(format!("Invalid certificate language: {}", s)),
+        }
+    }
+}
+
+/// Libellés d'un certificat dans une langue
+#[derive(Debug, Clone, Copy)]
+pub struct CertificateLabels {
+    pub session_title: &'static str,
+    pub path_title: &'static str,
+    pub awarded_to: &'static str,
+    pub session_body: &'static str,
+    pub path_body: &'static str,
+    pub topic: &'static str,
+    pub mentor: &'static str,
+    pub date: &'static str,
+    pub duration: &'static str,
+    pub level: &'static str,
+    pub sessions: &'static str,
+    pub signature: &'static str,
+    pub proof: &'static str,
+    pub verify: &'static str,
+}
+
+impl CertificateLanguage {
+    pub fn labels(self) -> CertificateLabels {
+        match self {
+            CertificateLanguage::Fr => CertificateLabels {
+                session_title: "Certificat de session",
+                path_title: "Certificat de parcours",
+                awarded_to: "Décerné à",
+                session_body: "pour avoir suivi une session de mentoring",
+                path_body: "pour sa progression sur le sujet",
+                topic: "Sujet",
+                mentor: "Mentor",
+                date: "Date",
+                duration: "Durée",
+                level: "Niveau",
+                sessions: "Sessions",
+                signature: "Signature du mentor",
+                proof: "Preuve RGB",
+                verify: "Vérifier",
+            },
+            CertificateLanguage::En => CertificateLabels {
+                session_title: "Session certificate",
+                path_title: "Learning path certificate",
+                awarded_to: "Awarded to",
+                session_body: "for attending a mentoring session",
+                path_body: "for their progress on the topic",
+                topic: "Topic",
+                mentor: "Mentor",
+                date: "Date",
+                duration: "Duration",
+                level: "Level",
+                sessions: "Sessions",
+                signature: "Mentor signature",
+                proof: "RGB proof",
+                verify: "Verify",
+            },
+        }
+    }
+
+    pub fn level_label(self, level: Level) -> &'static str {
+        match (self, level) {
+            (CertificateLanguage::Fr, Level::Beginner) => "Débutant",
+            (CertificateLanguage::Fr, Level::Intermediate) => "Intermédiaire",
+            (CertificateLanguage::Fr, Level::Advanced) => "Avancé",
+            (CertificateLanguage::En, Level::Beginner) => "Beginner",
+            (CertificateLanguage::En, Level::Intermediate) => "Intermediate",
+            (CertificateLanguage::En, Level::Advanced) => "Advanced",
+        }
+    }
+
+    pub fn format_date(self, date: DateTime<Utc>) -> String {
+        const FR_MONTHS: [&str; 12] = [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ];
+        match self {
+            CertificateLanguage::Fr => format!(
+                "{} {} {}",
+                date.day(),
+                FR_MONTHS[date.month0() as usize],
+                date.year()
+            ),
+            CertificateLanguage::En => date.format("%B %-d, %Y").to_string(),
+        }
+    }
+
+    pub fn format_duration(self, minutes: i32) -> String {
+        let (h, m) = (minutes / 60, minutes % 60);
+        match (self, h, m) {
+            (_, 0, m) => format!("{} min", m),
+            (CertificateLanguage::Fr, h, 0) => format!("{} h", h),
+            (CertificateLanguage::Fr, h, m) => format!("{} h {:02}", h, m),
+            (CertificateLanguage::En, h, 0) => format!("{} h", h),
+            (CertificateLanguage::En, h, m) => format!("{} h {} min", h, m),
+        }
+    }
+}
+
+// ============================================================
+// Habillage par organisation
+// ============================================================
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct CertificateBranding {
+    pub slug: String,
+    pub organisation_name: String,
+    pub email_domain: Option<String>,
+    pub primary_color: String,
+    pub accent_color: String,
+    pub signatory_name: Option<String>,
+    pub signatory_title: Option<String>,
+    pub footer: Option<String>,
+}
+
+impl Default for CertificateBranding {
+    fn default() -> Self {
+        CertificateBranding {
+            slug: "token4good".to_string(),
+            organisation_name: "Token4Good".to_string(),
+            email_domain: None,
+            primary_color: "#1E3A8A".to_string(),
+            accent_color: "#F59E0B".to_string(),
+            signatory_name: None,
+            signatory_title: None,
+            footer: None,
+        }
+    }
+}
+
+/// Couleur `#RRGGBB` en composantes 0.0–1.0
+pub fn parse_hex_color(color: &str) -> Option<(f32, f32, f32)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .ok()
+            .map(|v| v as f32 / 255.0)
+    };
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertBrandingPayload {
+    pub organisation_name: String,
+    pub email_domain: Option<String>,
+    pub primary_color: Option<String>,
+    pub accent_color: Option<String>,
+    pub signatory_name: Option<String>,
+    pub signatory_title: Option<String>,
+    pub footer: Option<String>,
+}
+
+impl UpsertBrandingPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.organisation_name.trim().is_empty() {
+            return Err("organisation_name est requis".to_string());
+        }
+        for color in [&self.primary_color, &self.accent_color]
+            .into_iter()
+            .flatten()
+        {
+            if parse_hex_color(color).is_none() {
+                return Err(format!("couleur invalide : {} (attendu #RRGGBB)", color));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct CertificateQuery {
+    pub lang: Option<String>,
+    pub branding: Option<String>,
+}
+
+impl CertificateQuery {
+    pub fn language(&self) -> Result<CertificateLanguage, String> {
+        self.lang
+            .as_deref()
+            .map_or(Ok(CertificateLanguage::default()), str::parse)
+    }
+}
+
+// ============================================================
+// Contenu des certificats
+// ============================================================
+
+/// Certificat d'une session, adossé à sa preuve RGB
+#[derive(Debug, Clone)]
+pub struct SessionCertificate {
+    pub proof_id: String,
+    pub contract_id: String,
+    pub mentee_id: String,
+    pub mentee_name: String,
+    pub mentor_name: String,
+    pub topic_name: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub duration_minutes: i32,
+    pub verify_url: String,
+}
+
+impl SessionCertificate {
+    pub fn file_name(&self) -> String {
+        format!("certificate-{}.pdf", self.proof_id)
+    }
+}
+
+/// Session d'un certificat de parcours
+#[derive(Debug, Clone)]
+pub struct PathSession {
+    pub mentor_name: String,
+    pub learned_at: DateTime<Utc>,
+    pub contract_id: Option<String>,
+}
+
+/// Certificat de parcours sur un sujet
+#[derive(Debug, Clone)]
+pub struct PathCertificate {
+    pub topic_slug: String,
+    pub mentee_name: String,
+    pub topic_name: String,
+    pub level: Level,
+    pub sessions: Vec<PathSession>,
+    /// Preuve la plus récente et son URL de vérification
+    pub contract_id: Option<String>,
+    pub verify_url: Option<String>,
+}
+
+impl PathCertificate {
+    pub fn file_name(&self) -> String {
+        format!("certificate-{}.pdf", self.topic_slug)
+    }
+}
+
+/// Vérification publique d'une preuve (cible du QR code)
+#[derive(Debug, Serialize)]
+pub struct CertificateVerification {
+    pub contract_id: String,
+    pub valid: bool,
+    pub revoked: bool,
+    pub mentee_name: String,
+    pub mentor_name: String,
+    pub topic_name: String,
+    pub session_date: Option<DateTime<Utc>>,
+    pub issued_at: DateTime<Utc>,
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_and_formatting() {
+        assert_eq!("EN".parse(), Ok(CertificateLanguage::En));
+        assert!("de".parse::<CertificateLanguage>().is_err());
+        assert_eq!(
+            CertificateQuery::default().language(),
+            Ok(CertificateLanguage::Fr)
+        );
+
+        let date = DateTime::parse_from_rfc3339("2026-08-03T14:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(CertificateLanguage::Fr.format_date(date), "3 août 2026");
+        assert_eq!(CertificateLanguage::En.format_date(date), "August 3, 2026");
+        assert_eq!(CertificateLanguage::Fr.format_duration(90), "1 h 30");
+        assert_eq!(CertificateLanguage::En.format_duration(90), "1 h 30 min");
+        assert_eq!(CertificateLanguage::Fr.format_duration(45), "45 min");
+        assert_eq!(CertificateLanguage::En.format_duration(120), "2 h");
+    }
+
+    #[test]
+    fn test_branding_colors() {
+        assert_eq!(parse_hex_color("#FF0000"), Some((1.0, 0.0, 0.0)));
+        assert_eq!(parse_hex_color("FF0000"), None);
+        assert_eq!(parse_hex_color("#GG0000"), None);
+        let payload = UpsertBrandingPayload {
+            organisation_name: "École".to_string(),
+            email_domain: None,
+            primary_color: Some("#12ab".to_string()),
+            accent_color: None,
+            signatory_name: None,
+            signatory_title: None,
+            footer: None,
+        };
+        assert!(payload.validate().is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::mentoring_offer::BookingStatus;
+
+/// Longueur maximale d'un message
+pub const MAX_MESSAGE_LEN: usize = 4000;
+/// Le fil passe en lecture seule ce délai après la clôture de la réservation
+pub const THREAD_OPEN_AFTER_CLOSE_DAYS: i64 = 7;
+/// Les messages sont purgés ce délai après la clôture de la réservation
+pub const CHAT_RETENTION_DAYS: i64 = 180;
+
+// ============================================================
+// ChatMessage — message d'un fil de réservation
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChatMessage {
+    pub id: String,
+    pub booking_id: String,
+    pub sender_id: Option<String>,
+    /// mentor, mentee, admin
+    pub sender_role: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ReadReceipt {
+    pub user_id: String,
+    pub last_read_at: DateTime<Utc>,
+}
+
+/// Historique d'un fil, du plus ancien au plus récent
+#[derive(Debug, Serialize)]
+pub struct ChatThread {
+    pub booking_id: String,
+    pub messages: Vec<ChatMessage>,
+    pub read_receipts: Vec<ReadReceipt>,
+    /// Messages des autres participants non lus par l'appelant
+    pub unread_count: i64,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SendMessagePayload {
+    pub body: String,
+}
+
+impl SendMessagePayload {
+    pub fn validate(&self) -> Result<(), String> {
+        let len = self.body.trim().chars().count();
+        if len == 0 {
+            return Err("message vide".to_string());
+        }
+        if len > MAX_MESSAGE_LEN {
+            return Err(format!(
+                "message trop long ({} caractères maximum)",
+                MAX_MESSAGE_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Fil avec des messages non lus, pour `/me/notifications`
+#[derive(Debug, Serialize, Clone)]
+pub struct UnreadThread {
+    pub booking_id: String,
+    pub topic_slug: String,
+    pub unread_count: i64,
+    pub last_message_at: DateTime<Utc>,
+}
+
+/// Événement temps réel diffusé aux abonnés d'un fil
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChatEvent {
+    Message {
+        booking_id: String,
+        message_id: String,
+    },
+    Read {
+        booking_id: String,
+        user_id: String,
+        last_read_at: DateTime<Utc>,
+    },
+}
+
+impl ChatEvent {
+    pub fn booking_id(&self) -> &str {
+        match self {
+            ChatEvent::Message { booking_id, .. } | ChatEvent::Read { booking_id, .. } => {
+                booking_id
+            }
+        }
+    }
+}
+
+/// Un fil accepte de nouveaux messages tant que la réservation est active, et
+/// pendant `THREAD_OPEN_AFTER_CLOSE_DAYS` après sa clôture.
+pub fn is_read_only(status: BookingStatus, closed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    let closed = matches!(
+        status,
+        BookingStatus::Completed | BookingStatus::AutoCompleted | BookingStatus::Cancelled
+    );
+    closed && now - closed_at > Duration::days(THREAD_OPEN_AFTER_CLOSE_DAYS)
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_closes_after_grace_period() {
+        let now = Utc::now();
+        let recent = now - Duration::days(2);
+        let old = now - Duration::days(THREAD_OPEN_AFTER_CLOSE_DAYS + 1);
+
+        assert!(!is_read_only(BookingStatus::Confirmed, old, now));
+        assert!(!is_read_only(BookingStatus::Disputed, old, now));
+        assert!(!is_read_only(BookingStatus::Completed, recent, now));
+        assert!(is_read_only(BookingStatus::Completed, old, now));
+        assert!(is_read_only(BookingStatus::Cancelled, old, now));
+    }
+
+    #[test]
+    fn test_message_validation_and_event_shape() {
+        let msg = |s: &str| SendMessagePayload {
+            body: s.to_string(),
+        };
+        assert!(msg("Bonjour !").validate().is_ok());
+        assert!(msg(" \n ").validate().is_err());
+        assert!(msg(&"é".repeat(MAX_MESSAGE_LEN + 1)).validate().is_err());
+
+        let event = ChatEvent::Message {
+            booking_id: "b1".to_string(),
+            message_id: "m1".to_string(),
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "message");
+        assert_eq!(serde_json::from_value::<ChatEvent>(json).unwrap(), event);
+        assert_eq!(event.booking_id(), "b1");
+    }
+}
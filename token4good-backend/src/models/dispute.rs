@@ -0,0 +1,297 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================
+// Enums
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeStatus {
+    Open,
+    UnderReview,
+    Resolved,
+}
+
+impl std::fmt::Display for DisputeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeStatus::Open => write!(f, "open"),
+            DisputeStatus::UnderReview => write!(f, "under_review"),
+            DisputeStatus::Resolved => write!(f, "resolved"),
+        }
+    }
+}
+
+/// Issue d'un arbitrage sur le séquestre
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DisputeOutcome {
+    /// Séquestre rendu au mentee, réservation annulée
+    FullRefund,
+    /// Séquestre versé au mentor, réservation complétée
+    FullRelease,
+    /// Partage : une part au mentee, le reste au mentor, réservation annulée
+    Split,
+}
+
+impl std::fmt::Display for DisputeOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DisputeOutcome::FullRefund => write!(f, "full_refund"),
+            DisputeOutcome::FullRelease => write!(f, "full_release"),
+            DisputeOutcome::Split => write!(f, "split"),
+        }
+    }
+}
+
+/// Action sur la preuve RGB de la session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofAction {
+    #[default]
+    None,
+    Issue,
+    Revoke,
+}
+
+impl std::fmt::Display for ProofAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProofAction::None => write!(f, "none"),
+            ProofAction::Issue => write!(f, "issue"),
+            ProofAction::Revoke => write!(f, "revoke"),
+        }
+    }
+}
+
+// ============================================================
+// Dispute — litige sur une réservation
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Dispute {
+    pub id: String,
+    pub booking_id: String,
+    pub opened_by: String,
+    pub reason: String,
+    pub status: String,
+    /// Statut de la réservation à l'ouverture du litige
+    pub booking_status_before: String,
+    pub assigned_to: Option<String>,
+    pub outcome: Option<String>,
+    pub refund_tokens: Option<i32>,
+    pub release_tokens: Option<i32>,
+    pub proof_action: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Entrée de la chronologie d'un litige. Les notes admin (`internal`) ne
+/// sont pas visibles des parties.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DisputeEvent {
+    pub id: String,
+    pub dispute_id: String,
+    pub actor_id: Option<String>,
+    /// opened, evidence, assigned, note, resolved, proof_issued, proof_revoked
+    pub kind: String,
+    pub message: Option<String>,
+    pub attachments: Vec<String>,
+    pub internal: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Litige et sa chronologie
+#[derive(Debug, Serialize)]
+pub struct DisputeCase {
+    #[serde(flatten)]
+    pub dispute: Dispute,
+    pub timeline: Vec<DisputeEvent>,
+}
+
+/// Nombre maximal de pièces jointes (URLs) par message de preuve
+pub const MAX_EVIDENCE_ATTACHMENTS: usize = 5;
+
+/// Payload d'ouverture d'un litige (le corps reste optionnel côté route)
+#[derive(Debug, Deserialize, Default)]
+pub struct OpenDisputePayload {
+    pub reason: Option<String>,
+}
+
+/// Message de preuve d'une partie, ou note interne d'un admin
+#[derive(Debug, Deserialize)]
+pub struct DisputeMessagePayload {
+    pub message: String,
+    #[serde(default)]
+    pub attachments: Vec<String>,
+}
+
+impl DisputeMessagePayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.message.trim().is_empty() {
+            return Err("message vide".to_string());
+        }
+        if self.attachments.len() > MAX_EVIDENCE_ATTACHMENTS {
+            return Err(format!(
+                "trop de pièces jointes ({} maximum)",
+                MAX_EVIDENCE_ATTACHMENTS
+            ));
+        }
+        if self.attachments.iter().any(|a| !a.starts_with("https://")) {
+            return Err("les pièces jointes doivent être des URLs https".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Assignation d'un litige (à soi-même par défaut)
+#[derive(Debug, Deserialize, Default)]
+pub struct AssignDisputePayload {
+    pub admin_id: Option<String>,
+}
+
+/// Décision d'arbitrage
+#[derive(Debug, Deserialize)]
+pub struct ResolveDisputePayload {
+    pub outcome: DisputeOutcome,
+    /// Part remboursée au mentee, requise pour `split`
+    pub refund_tokens: Option<i64>,
+    #[serde(default)]
+    pub proof_action: ProofAction,
+    pub note: String,
+}
+
+/// Répartition du séquestre décidée par l'arbitrage
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct EscrowAllocation {
+    pub refund_tokens: i64,
+    pub release_tokens: i64,
+}
+
+impl ResolveDisputePayload {
+    /// Valide la décision et calcule la répartition d'un séquestre `escrow`.
+    pub fn allocation(&self, escrow: i64) -> Result<EscrowAllocation, String> {
+        if self.note.trim().len() < 10 {
+            return Err("note de résolution trop courte (10 caractères minimum)".to_string());
+        }
+        let escrow = escrow.max(0);
+        let refund_tokens = match self.outcome {
+            DisputeOutcome::FullRefund => escrow,
+            DisputeOutcome::FullRelease => 0,
+            DisputeOutcome::Split => {
+                let refund = self
+                    .refund_tokens
+                    .ok_or_else(|| "refund_tokens requis pour un partage".to_string())?;
+                if refund <= 0 || refund >= escrow {
+                    return Err(format!(
+                        "refund_tokens doit être strictement entre 0 et {}",
+                        escrow
+                    ));
+                }
+                refund
+            }
+        };
+        // Une preuve de session n'est émise que si le mentor est rémunéré
+        if self.proof_action == ProofAction::Issue && self.outcome == DisputeOutcome::FullRefund {
+            return Err("une preuve ne peut pas être émise sur un remboursement total".to_string());
+        }
+        Ok(EscrowAllocation {
+            refund_tokens,
+            release_tokens: escrow - refund_tokens,
+        })
+    }
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(outcome: DisputeOutcome, refund: Option<i64>) -> ResolveDisputePayload {
+        ResolveDisputePayload {
+            outcome,
+            refund_tokens: refund,
+            proof_action: ProofAction::None,
+            note: "Session écourtée, preuves du mentee".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_outcome_roundtrip() {
+        let o: DisputeOutcome = serde_json::from_str("\"full_release\"").unwrap();
+        assert_eq!(o, DisputeOutcome::FullRelease);
+        assert_eq!(o.to_string(), "full_release");
+        assert_eq!(DisputeStatus::UnderReview.to_string(), "under_review");
+        let p: ProofAction = serde_json::from_str("\"revoke\"").unwrap();
+        assert_eq!(p, ProofAction::Revoke);
+    }
+
+    #[test]
+    fn test_allocation_by_outcome() {
+        assert_eq!(
+            resolve(DisputeOutcome::FullRefund, None).allocation(40),
+            Ok(EscrowAllocation {
+                refund_tokens: 40,
+                release_tokens: 0
+            })
+        );
+        assert_eq!(
+            resolve(DisputeOutcome::FullRelease, None).allocation(40),
+            Ok(EscrowAllocation {
+                refund_tokens: 0,
+                release_tokens: 40
+            })
+        );
+        assert_eq!(
+            resolve(DisputeOutcome::Split, Some(10)).allocation(40),
+            Ok(EscrowAllocation {
+                refund_tokens: 10,
+                release_tokens: 30
+            })
+        );
+    }
+
+    #[test]
+    fn test_allocation_rejects_invalid_decisions() {
+        assert!(resolve(DisputeOutcome::Split, None).allocation(40).is_err());
+        assert!(resolve(DisputeOutcome::Split, Some(40))
+            .allocation(40)
+            .is_err());
+        assert!(resolve(DisputeOutcome::Split, Some(0))
+            .allocation(40)
+            .is_err());
+
+        let short_note = ResolveDisputePayload {
+            note: "ok".to_string(),
+            ..resolve(DisputeOutcome::FullRefund, None)
+        };
+        assert!(short_note.allocation(40).is_err());
+
+        let proof_on_refund = ResolveDisputePayload {
+            proof_action: ProofAction::Issue,
+            ..resolve(DisputeOutcome::FullRefund, None)
+        };
+        assert!(proof_on_refund.allocation(40).is_err());
+    }
+
+    #[test]
+    fn test_evidence_validation() {
+        let ok = DisputeMessagePayload {
+            message: "Le mentor ne s'est pas connecté".to_string(),
+            attachments: vec!["https://files.t4g.io/capture.png".to_string()],
+        };
+        assert!(ok.validate().is_ok());
+        let bad = DisputeMessagePayload {
+            attachments: vec!["javascript:alert(1)".to_string()],
+            ..ok
+        };
+        assert!(bad.validate().is_err());
+    }
+}
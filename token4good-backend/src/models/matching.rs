@@ -0,0 +1,512 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::Serialize;
+
+// ============================================================
+// Niveaux
+// ============================================================
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Level {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl Level {
+    /// Niveau d'un mentee sur un sujet, d'après ses sessions complétées
+    pub fn from_experience(completed_sessions: i64) -> Self {
+        match completed_sessions {
+            0 => Level::Beginner,
+            1..=2 => Level::Intermediate,
+            _ => Level::Advanced,
+        }
+    }
+
+    fn rank(self) -> i32 {
+        self as i32
+    }
+}
+
+impl std::str::FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "beginner" => Ok(Level::Beginner),
+            "intermediate" => Ok(Level::Intermediate),
+            "advanced" => Ok(Level::Advanced),
+            _ => Err(format!("Invalid level: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Level::Beginner => write!(f, "beginner"),
+            Level::Intermediate => write!(f, "intermediate"),
+            Level::Advanced => write!(f, "advanced"),
+        }
+    }
+}
+
+// ============================================================
+// Entrées du classement
+// ============================================================
+
+/// Passé du mentee avec un mentor
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MentorHistory {
+    pub completed: i64,
+    pub disputed: i64,
+    /// Moyenne des notes données par le mentee à ce mentor
+    pub average_rating: Option<f64>,
+}
+
+/// Ce que l'on sait du mentee au moment de la recommandation
+#[derive(Debug, Clone, Default)]
+pub struct MenteeProfile {
+    pub mentee_id: String,
+    /// Sujets que le mentee veut apprendre
+    pub learning_topics: HashSet<String>,
+    /// Catégories de ces sujets
+    pub learning_categories: HashSet<String>,
+    /// Sessions complétées par sujet
+    pub topic_experience: HashMap<String, i64>,
+    pub balance: i64,
+    /// Habitudes horaires (jour de semaine depuis lundi, heure UTC)
+    pub habits: HashSet<(u32, u32)>,
+    pub mentor_history: HashMap<String, MentorHistory>,
+}
+
+/// Offre ouverte candidate
+#[derive(Debug, Clone)]
+pub struct OfferCandidate {
+    pub offer_id: String,
+    pub mentor_id: String,
+    pub topic_slug: String,
+    pub category_slug: Option<String>,
+    pub target_level: Level,
+    pub token_cost: i32,
+    /// Moyenne bayésienne du mentor et nombre d'avis
+    pub mentor_rating: f64,
+    pub mentor_review_count: i64,
+    /// Taux d'absence lissé du mentor (`models::attendance::smoothed_no_show_rate`)
+    pub mentor_no_show_rate: f64,
+    /// Débuts des sessions réservables à venir ; `None` si l'offre n'a pas de
+    /// créneaux (horaire libre)
+    pub upcoming_slots: Option<Vec<DateTime<Utc>>>,
+}
+
+// ============================================================
+// Signaux et pondération
+// ============================================================
+
+/// Signaux normalisés dans [0, 1]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Default)]
+pub struct MatchSignals {
+    pub topic: f64,
+    pub level: f64,
+    pub rating: f64,
+    pub price: f64,
+    pub availability: f64,
+    pub history: f64,
+    /// Fiabilité du mentor (1 − pénalité d'absence), appliquée au score
+    pub reliability: f64,
+}
+
+/// Pénalité maximale du score pour un mentor toujours absent
+pub const NO_SHOW_PENALTY: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchWeights {
+    pub topic: f64,
+    pub level: f64,
+    pub rating: f64,
+    pub price: f64,
+    pub availability: f64,
+    pub history: f64,
+}
+
+impl Default for MatchWeights {
+    fn default() -> Self {
+        MatchWeights {
+            topic: 0.35,
+            level: 0.15,
+            rating: 0.15,
+            price: 0.10,
+            availability: 0.15,
+            history: 0.10,
+        }
+    }
+}
+
+impl MatchWeights {
+    pub fn score(&self, s: &MatchSignals) -> f64 {
+        self.topic * s.topic
+            + self.level * s.level
+            + self.rating * s.rating
+            + self.price * s.price
+            + self.availability * s.availability
+            + self.history * s.history
+    }
+}
+
+/// Raison lisible d'une suggestion
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MatchReason {
+    /// Signal à l'origine de la raison (topic, level, rating, ...)
+    pub signal: &'static str,
+    pub message: String,
+}
+
+/// Offre classée pour un mentee
+#[derive(Debug, Serialize, Clone)]
+pub struct OfferMatch {
+    pub offer_id: String,
+    pub mentor_id: String,
+    pub topic_slug: String,
+    pub token_cost: i32,
+    pub score: f64,
+    pub signals: MatchSignals,
+    pub reasons: Vec<MatchReason>,
+}
+
+fn topic_signal(profile: &MenteeProfile, offer: &OfferCandidate) -> f64 {
+    if profile.learning_topics.contains(&offer.topic_slug) {
+        1.0
+    } else if offer
+        .category_slug
+        .as_ref()
+        .is_some_and(|c| profile.learning_categories.contains(c))
+    {
+        0.5
+    } else {
+        0.0
+    }
+}
+
+fn level_signal(mentee: Level, offer: Level) -> f64 {
+    match offer.rank() - mentee.rank() {
+        0 => 1.0,
+        // Un cran au-dessus : progression
+        1 => 0.6,
+        -1 => 0.3,
+        _ => 0.0,
+    }
+}
+
+fn price_signal(token_cost: i32, balance: i64) -> f64 {
+    let cost = token_cost.max(0) as i64;
+    if cost > balance {
+        0.0
+    } else if balance == 0 {
+        1.0
+    } else {
+        1.0 - 0.5 * cost as f64 / balance as f64
+    }
+}
+
+fn matches_habit(habits: &HashSet<(u32, u32)>, start: &DateTime<Utc>) -> bool {
+    let day = start.weekday().num_days_from_monday();
+    let hour = start.hour();
+    [hour.saturating_sub(1), hour, (hour + 1).min(23)]
+        .iter()
+        .any(|h| habits.contains(&(day, *h)))
+}
+
+/// Part des sessions à venir qui tombent dans les habitudes du mentee
+fn availability_signal(habits: &HashSet<(u32, u32)>, slots: Option<&[DateTime<Utc>]>) -> f64 {
+    match slots {
+        None => 0.5,
+        Some([]) => 0.0,
+        Some(_) if habits.is_empty() => 0.7,
+        Some(slots) => {
+            let matching = slots.iter().filter(|s| matches_habit(habits, s)).count();
+            0.5 + 0.5 * matching as f64 / slots.len() as f64
+        }
+    }
+}
+
+fn history_signal(history: Option<&MentorHistory>) -> f64 {
+    let Some(h) = history else {
+        return 0.5;
+    };
+    if h.disputed > 0 || h.average_rating.is_some_and(|r| r < 3.0) {
+        0.0
+    } else if h.completed > 0 && h.average_rating.is_some_and(|r| r >= 4.0) {
+        1.0
+    } else {
+        0.6
+    }
+}
+
+/// Calcule les signaux, le score et les raisons d'une offre pour un mentee
+pub fn score_offer(
+    profile: &MenteeProfile,
+    offer: &OfferCandidate,
+    weights: &MatchWeights,
+) -> OfferMatch {
+    let mentee_level = Level::from_experience(
+        profile
+            .topic_experience
+            .get(&offer.topic_slug)
+            .copied()
+            .unwrap_or(0),
+    );
+    let history = profile.mentor_history.get(&offer.mentor_id);
+    let signals = MatchSignals {
+        topic: topic_signal(profile, offer),
+        level: level_signal(mentee_level, offer.target_level),
+        rating: ((offer.mentor_rating - 1.0) / 4.0).clamp(0.0, 1.0),
+        price: price_signal(offer.token_cost, profile.balance),
+        availability: availability_signal(&profile.habits, offer.upcoming_slots.as_deref()),
+        history: history_signal(history),
+        reliability: 1.0 - NO_SHOW_PENALTY * offer.mentor_no_show_rate.clamp(0.0, 1.0),
+    };
+
+    let mut reasons = Vec::new();
+    if signals.topic >= 1.0 {
+        reasons.push(MatchReason {
+            signal: "topic",
+            message: format!(
+                "«{}» fait partie de tes sujets d'apprentissage",
+                offer.topic_slug
+            ),
+        });
+    } else if signals.topic > 0.0 {
+        reasons.push(MatchReason {
+            signal: "topic",
+            message: "Sujet proche de ceux que tu veux apprendre".to_string(),
+        });
+    }
+    if signals.level >= 1.0 {
+        reasons.push(MatchReason {
+            signal: "level",
+            message: format!("Niveau adapté ({})", offer.target_level),
+        });
+    } else if offer.target_level > mentee_level && signals.level > 0.0 {
+        reasons.push(MatchReason {
+            signal: "level",
+            message: "Un cran au-dessus de ton niveau actuel pour progresser".to_string(),
+        });
+    }
+    if offer.mentor_review_count >= 3 && offer.mentor_rating >= 4.0 {
+        reasons.push(MatchReason {
+            signal: "rating",
+            message: format!(
+                "Mentor très bien noté ({:.1}/5 sur {} avis)",
+                offer.mentor_rating, offer.mentor_review_count
+            ),
+        });
+    }
+    if signals.price > 0.0 {
+        reasons.push(MatchReason {
+            signal: "price",
+            message: format!(
+                "{} T4G, dans ton budget ({} disponibles)",
+                offer.token_cost, profile.balance
+            ),
+        });
+    }
+    if signals.availability > 0.7 {
+        reasons.push(MatchReason {
+            signal: "availability",
+            message: "Des créneaux à tes horaires habituels".to_string(),
+        });
+    }
+    if signals.history >= 1.0 {
+        reasons.push(MatchReason {
+            signal: "history",
+            message: "Tu as déjà apprécié une session avec ce mentor".to_string(),
+        });
+    }
+
+    OfferMatch {
+        offer_id: offer.offer_id.clone(),
+        mentor_id: offer.mentor_id.clone(),
+        topic_slug: offer.topic_slug.clone(),
+        token_cost: offer.token_cost,
+        score: weights.score(&signals) * signals.reliability,
+        signals,
+        reasons,
+    }
+}
+
+/// Classe les offres, meilleur score d'abord (à égalité : la moins chère)
+pub fn rank_offers(
+    profile: &MenteeProfile,
+    offers: &[OfferCandidate],
+    weights: &MatchWeights,
+) -> Vec<OfferMatch> {
+    let mut ranked: Vec<OfferMatch> = offers
+        .iter()
+        .map(|o| score_offer(profile, o, weights))
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(a.token_cost.cmp(&b.token_cost))
+    });
+    ranked
+}
+
+// ============================================================
+// Évaluation hors ligne
+// ============================================================
+
+/// Qualité du classement rejoué sur des réservations complétées
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MatchingEvaluation {
+    pub bookings_evaluated: usize,
+    pub k: usize,
+    /// Part des réservations dont l'offre réservée figure dans les `k` premiers
+    pub hit_rate_at_k: f64,
+    /// Moyenne des inverses de rang
+    pub mean_reciprocal_rank: f64,
+    pub median_rank: Option<usize>,
+    pub mean_candidates: f64,
+}
+
+impl MatchingEvaluation {
+    /// `ranks` : rang (à partir de 1) de l'offre réservée et taille de la liste
+    pub fn from_ranks(ranks: &[(usize, usize)], k: usize) -> Self {
+        let n = ranks.len();
+        if n == 0 {
+            return MatchingEvaluation {
+                bookings_evaluated: 0,
+                k,
+                hit_rate_at_k: 0.0,
+                mean_reciprocal_rank: 0.0,
+                median_rank: None,
+                mean_candidates: 0.0,
+            };
+        }
+        let hits = ranks.iter().filter(|(r, _)| *r <= k).count();
+        let mrr = ranks.iter().map(|(r, _)| 1.0 / *r as f64).sum::<f64>() / n as f64;
+        let mut sorted: Vec<usize> = ranks.iter().map(|(r, _)| *r).collect();
+        sorted.sort_unstable();
+        MatchingEvaluation {
+            bookings_evaluated: n,
+            k,
+            hit_rate_at_k: hits as f64 / n as f64,
+            mean_reciprocal_rank: mrr,
+            median_rank: Some(sorted[n / 2]),
+            mean_candidates: ranks.iter().map(|(_, c)| *c as f64).sum::<f64>() / n as f64,
+        }
+    }
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offer(id: &str, topic: &str, level: Level, cost: i32, rating: f64) -> OfferCandidate {
+        OfferCandidate {
+            offer_id: id.to_string(),
+            mentor_id: format!("mentor-{}", id),
+            topic_slug: topic.to_string(),
+            category_slug: Some("dev".to_string()),
+            target_level: level,
+            token_cost: cost,
+            mentor_rating: rating,
+            mentor_review_count: 10,
+            mentor_no_show_rate: 0.0,
+            upcoming_slots: None,
+        }
+    }
+
+    fn profile() -> MenteeProfile {
+        MenteeProfile {
+            mentee_id: "mentee".to_string(),
+            learning_topics: HashSet::from(["rust".to_string()]),
+            learning_categories: HashSet::from(["dev".to_string()]),
+            balance: 100,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_topic_match_ranks_first_with_reasons() {
+        let offers = vec![
+            offer("a", "python", Level::Beginner, 20, 4.5),
+            offer("b", "rust", Level::Beginner, 20, 4.5),
+        ];
+        let ranked = rank_offers(&profile(), &offers, &MatchWeights::default());
+        assert_eq!(ranked[0].offer_id, "b");
+        assert!(ranked[0].reasons.iter().any(|r| r.signal == "topic"));
+        assert!(ranked[0].reasons.iter().any(|r| r.signal == "rating"));
+        assert_eq!(ranked[1].signals.topic, 0.5);
+    }
+
+    #[test]
+    fn test_no_shows_lower_the_score() {
+        let reliable = offer("a", "rust", Level::Beginner, 20, 4.5);
+        let flaky = OfferCandidate {
+            mentor_no_show_rate: 0.4,
+            ..offer("b", "rust", Level::Beginner, 20, 4.5)
+        };
+        let ranked = rank_offers(&profile(), &[flaky, reliable], &MatchWeights::default());
+        assert_eq!(ranked[0].offer_id, "a");
+        assert_eq!(ranked[1].signals.reliability, 0.8);
+        assert!((ranked[1].score - ranked[0].score * 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_level_and_price_signals() {
+        assert_eq!(level_signal(Level::Beginner, Level::Beginner), 1.0);
+        assert_eq!(level_signal(Level::Beginner, Level::Intermediate), 0.6);
+        assert_eq!(level_signal(Level::Advanced, Level::Beginner), 0.0);
+        assert_eq!(Level::from_experience(2), Level::Intermediate);
+
+        assert_eq!(price_signal(150, 100), 0.0);
+        assert_eq!(price_signal(50, 100), 0.75);
+        assert_eq!(price_signal(0, 0), 1.0);
+    }
+
+    #[test]
+    fn test_history_and_availability() {
+        let bad = MentorHistory {
+            completed: 1,
+            disputed: 1,
+            average_rating: Some(5.0),
+        };
+        let good = MentorHistory {
+            completed: 2,
+            disputed: 0,
+            average_rating: Some(4.5),
+        };
+        assert_eq!(history_signal(Some(&bad)), 0.0);
+        assert_eq!(history_signal(Some(&good)), 1.0);
+        assert_eq!(history_signal(None), 0.5);
+
+        // Lundi 18h UTC dans les habitudes : 19h correspond, jeudi non
+        let habits = HashSet::from([(0, 18)]);
+        let monday = "2026-03-02T19:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let thursday = "2026-03-05T19:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            availability_signal(&habits, Some(&[monday, thursday])),
+            0.75
+        );
+        assert_eq!(availability_signal(&habits, Some(&[])), 0.0);
+        assert_eq!(availability_signal(&habits, None), 0.5);
+    }
+
+    #[test]
+    fn test_evaluation_metrics() {
+        let eval = MatchingEvaluation::from_ranks(&[(1, 10), (4, 10), (12, 20)], 5);
+        assert_eq!(eval.bookings_evaluated, 3);
+        assert!((eval.hit_rate_at_k - 2.0 / 3.0).abs() < 1e-9);
+        assert!((eval.mean_reciprocal_rank - (1.0 + 0.25 + 1.0 / 12.0) / 3.0).abs() < 1e-9);
+        assert_eq!(eval.median_rank, Some(4));
+        assert!((eval.mean_candidates - 40.0 / 3.0).abs() < 1e-9);
+        assert_eq!(MatchingEvaluation::from_ranks(&[], 5).median_rank, None);
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// La salle s'ouvre ce délai avant l'heure prévue de la session
+pub const JOIN_OPENS_BEFORE_MINUTES: i64 = 15;
+/// La salle reste accessible ce délai après la fin prévue de la session
+pub const JOIN_CLOSES_AFTER_MINUTES: i64 = 30;
+
+/// Fenêtre pendant laquelle les participants peuvent rejoindre la salle
+pub fn join_window(
+    scheduled_at: DateTime<Utc>,
+    duration_minutes: i32,
+) -> (DateTime<Utc>, DateTime<Utc>) {
+    let opens_at = scheduled_at - Duration::minutes(JOIN_OPENS_BEFORE_MINUTES);
+    let closes_at = scheduled_at
+        + Duration::minutes(duration_minutes.max(0) as i64 + JOIN_CLOSES_AFTER_MINUTES);
+    (opens_at, closes_at)
+}
+
+// ============================================================
+// MeetingRoom — salle attribuée à une réservation
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MeetingRoom {
+    pub booking_id: String,
+    pub provider: String,
+    pub room_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Accès d'un participant à la salle ; `join_url` n'est renseignée que dans
+/// la fenêtre de connexion.
+#[derive(Debug, Serialize, Clone)]
+pub struct RoomAccess {
+    pub booking_id: String,
+    pub provider: String,
+    pub room_name: String,
+    pub opens_at: DateTime<Utc>,
+    pub closes_at: DateTime<Utc>,
+    pub join_url: Option<String>,
+}
+
+// ============================================================
+// Présence — rappels de connexion/déconnexion du fournisseur
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AttendanceKind {
+    Join,
+    Leave,
+}
+
+impl AttendanceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AttendanceKind::Join => "join",
+            AttendanceKind::Leave => "leave",
+        }
+    }
+}
+
+/// Rappel envoyé par le fournisseur de visioconférence
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MeetingCallbackPayload {
+    pub room_name: String,
+    /// Identifiant porté par le jeton de salle du participant
+    pub user_id: String,
+    pub event: AttendanceKind,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AttendanceEvent {
+    pub booking_id: String,
+    pub user_id: String,
+    pub event: String,
+    pub occurred_at: DateTime<Utc>,
+    pub source: String,
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_window_brackets_session() {
+        let at = Utc::now();
+        let (opens_at, closes_at) = join_window(at, 60);
+        assert_eq!(at - opens_at, Duration::minutes(JOIN_OPENS_BEFORE_MINUTES));
+        assert_eq!(
+            closes_at - at,
+            Duration::minutes(60 + JOIN_CLOSES_AFTER_MINUTES)
+        );
+
+        let callback: MeetingCallbackPayload = serde_json::from_value(serde_json::json!({
+            "room_name": "t4g-abc",
+            "user_id": "u1",
+            "event": "leave",
+            "occurred_at": at,
+        }))
+        .unwrap();
+        assert_eq!(callback.event, AttendanceKind::Leave);
+        assert_eq!(callback.event.as_str(), "leave");
+    }
+}
@@ -2,6 +2,17 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::mentoring_offer::{CancellationPolicy, MentoringOffer};
+use crate::models::time_slot::{validate_availability, TimeSlot};
+
+/// Longueurs maximales d'une demande
+pub const MAX_REQUEST_TITLE_LEN: usize = 200;
+pub const MAX_REQUEST_DESCRIPTION_LEN: usize = 5000;
+pub const MAX_REQUEST_TAGS: usize = 10;
+
+/// Statut d'une demande : `assigned` quand une offre privée a été réservée,
+/// `completed` quand la session l'est ; l'annulation de la réservation
+/// rouvre la demande.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum RequestStatus {
     #[serde(rename = "open")]
@@ -10,6 +21,8 @@ pub enum RequestStatus {
     Assigned,
     #[serde(rename = "completed")]
     Completed,
+    #[serde(rename = "cancelled")]
+    Cancelled,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -22,6 +35,14 @@ pub struct MentoringRequest {
     pub mentee_id: String,
     pub mentor_id: Option<String>,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub topic_slug: Option<String>,
+    /// Budget maximal en T4G
+    #[serde(default)]
+    pub max_tokens: Option<i32>,
+    /// Réservation issue de l'offre privée retenue
+    #[serde(default)]
+    pub booking_id: Option<String>,
 }
 
 impl std::fmt::Display for RequestStatus {
@@ -30,6 +51,21 @@ impl std::fmt::Display for RequestStatus {
             RequestStatus::Open => write!(f, "open"),
             RequestStatus::Assigned => write!(f, "assigned"),
             RequestStatus::Completed => write!(f, "completed"),
+            RequestStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::str::FromStr for RequestStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(RequestStatus::Open),
+            "assigned" => Ok(RequestStatus::Assigned),
+            "completed" => Ok(RequestStatus::Completed),
+            "cancelled" => Ok(RequestStatus::Cancelled),
+            _ => Err(format!("Invalid request status: {}", s)),
         }
     }
 }
@@ -45,6 +81,9 @@ impl MentoringRequest {
             mentee_id,
             mentor_id: None,
             tags,
+            topic_slug: None,
+            max_tokens: None,
+            booking_id: None,
         }
     }
 }
@@ -54,6 +93,87 @@ pub struct CreateRequestPayload {
     pub title: String,
     pub description: String,
     pub tags: Vec<String>,
+    #[serde(default)]
+    pub topic_slug: Option<String>,
+    #[serde(default)]
+    pub max_tokens: Option<i32>,
+}
+
+impl CreateRequestPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        let title = self.title.trim();
+        if title.is_empty() || title.chars().count() > MAX_REQUEST_TITLE_LEN {
+            return Err(format!(
+                "le titre doit faire entre 1 et {} caractères",
+                MAX_REQUEST_TITLE_LEN
+            ));
+        }
+        if self.description.chars().count() > MAX_REQUEST_DESCRIPTION_LEN {
+            return Err(format!(
+                "description trop longue ({} caractères maximum)",
+                MAX_REQUEST_DESCRIPTION_LEN
+            ));
+        }
+        if self.tags.len() > MAX_REQUEST_TAGS {
+            return Err(format!("{} tags maximum", MAX_REQUEST_TAGS));
+        }
+        if self.max_tokens.is_some_and(|m| m < 0) {
+            return Err("max_tokens doit être positif".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestsQuery {
+    pub topic_slug: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Réponse d'un mentor : offre privée réservable par le seul mentee ; le
+/// sujet est celui de la demande sauf s'il est précisé.
+#[derive(Debug, Deserialize)]
+pub struct RespondToRequestPayload {
+    pub topic_slug: Option<String>,
+    pub target_level: Option<String>,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    pub format: String,
+    pub token_cost: i32,
+    #[serde(default)]
+    pub availability: Vec<TimeSlot>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+}
+
+impl RespondToRequestPayload {
+    /// Vérifie l'offre au regard du budget de la demande
+    pub fn validate(&self, max_tokens: Option<i32>) -> Result<(), String> {
+        if self.duration_minutes <= 0 || self.token_cost < 0 {
+            return Err("durée et coût doivent être positifs".to_string());
+        }
+        if let Some(max) = max_tokens.filter(|max| self.token_cost > *max) {
+            return Err(format!(
+                "coût supérieur au budget de la demande ({} T4G)",
+                max
+            ));
+        }
+        validate_availability(&self.availability, self.duration_minutes)?;
+        if let Some(policy) = &self.cancellation_policy {
+            policy.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Demande et offres privées reçues ; le mentee voit toutes les réponses,
+/// un mentor seulement les siennes.
+#[derive(Debug, Serialize)]
+pub struct MentoringRequestDetail {
+    #[serde(flatten)]
+    pub request: MentoringRequest,
+    pub offers: Vec<MentoringOffer>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -136,8 +256,29 @@ mod tests {
 
     #[test]
     fn test_request_status_deserialize_invalide() {
-        let result: Result<RequestStatus, _> = serde_json::from_str("\"cancelled\"");
+        let result: Result<RequestStatus, _> = serde_json::from_str("\"archived\"");
         assert!(result.is_err(), "Devrait échouer pour un statut inconnu");
+        // Demande retirée par le mentee
+        let s: RequestStatus = serde_json::from_str("\"cancelled\"").unwrap();
+        assert_eq!(s, RequestStatus::Cancelled);
+        assert_eq!("cancelled".parse(), Ok(RequestStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_create_request_payload_validation() {
+        let payload = |title: &str, max_tokens| CreateRequestPayload {
+            title: title.to_string(),
+            description: "Besoin d'aide".to_string(),
+            tags: vec![],
+            topic_slug: None,
+            max_tokens,
+        };
+        assert!(payload("Lightning en prod", Some(50)).validate().is_ok());
+        assert!(payload("   ", None).validate().is_err());
+        assert!(payload("Lightning", Some(-1)).validate().is_err());
+        assert!(payload(&"x".repeat(MAX_REQUEST_TITLE_LEN + 1), None)
+            .validate()
+            .is_err());
     }
 
     // ========== MentoringRequest::new() ==========
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::models::time_slot::TimeSlot;
+
 // ============================================================
 // Enums
 // ============================================================
@@ -29,7 +31,7 @@ impl std::fmt::Display for OfferStatus {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
 #[sqlx(type_name = "varchar", rename_all = "snake_case")]
 pub enum BookingStatus {
     #[serde(rename = "pending")]
@@ -62,6 +64,23 @@ impl std::fmt::Display for BookingStatus {
     }
 }
 
+impl std::str::FromStr for BookingStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(BookingStatus::Pending),
+            "confirmed" => Ok(BookingStatus::Confirmed),
+            "pending_completion" => Ok(BookingStatus::PendingCompletion),
+            "completed" => Ok(BookingStatus::Completed),
+            "auto_completed" => Ok(BookingStatus::AutoCompleted),
+            "disputed" => Ok(BookingStatus::Disputed),
+            "cancelled" => Ok(BookingStatus::Cancelled),
+            _ => Err(format!("Invalid booking status: {}", s)),
+        }
+    }
+}
+
 // ============================================================
 // MentoringOffer — offre publiée par un mentor
 // ============================================================
@@ -76,9 +95,18 @@ pub struct MentoringOffer {
     pub duration_minutes: i32,
     pub format: String,
     pub token_cost: i32,
-    /// Créneaux disponibles : Vec<TimeSlot> sérialisé en JSONB
-    pub availability: serde_json::Value,
+    /// Créneaux disponibles, sérialisés en JSONB
+    pub availability: Vec<TimeSlot>,
     pub status: String,
+    /// Places par session (1 = session individuelle)
+    pub capacity: i32,
+    /// Série récurrente d'origine, et date de l'occurrence
+    pub series_id: Option<String>,
+    pub occurrence_at: Option<DateTime<Utc>>,
+    /// Remboursement du mentee selon le délai d'annulation
+    pub cancellation_policy: CancellationPolicy,
+    /// Demande mentee à laquelle répond cette offre privée
+    pub request_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -92,7 +120,10 @@ pub struct CreateOfferPayload {
     pub duration_minutes: i32,
     pub format: String,
     pub token_cost: i32,
-    pub availability: serde_json::Value,
+    #[serde(default)]
+    pub availability: Vec<TimeSlot>,
+    pub capacity: Option<i32>,
+    pub cancellation_policy: Option<CancellationPolicy>,
 }
 
 /// Payload de mise à jour partielle d'une offre
@@ -102,8 +133,124 @@ pub struct UpdateOfferPayload {
     pub duration_minutes: Option<i32>,
     pub format: Option<String>,
     pub token_cost: Option<i32>,
-    pub availability: Option<serde_json::Value>,
+    pub availability: Option<Vec<TimeSlot>>,
     pub status: Option<String>,
+    pub capacity: Option<i32>,
+    pub cancellation_policy: Option<CancellationPolicy>,
+}
+
+// ============================================================
+// CancellationPolicy — remboursement d'une annulation mentee
+// ============================================================
+
+/// Nombre maximal de paliers d'une politique d'annulation
+pub const MAX_CANCELLATION_TIERS: usize = 10;
+
+/// Palier : annulation au moins `min_hours_before` heures avant la session
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CancellationTier {
+    pub min_hours_before: i32,
+    pub refund_percent: i32,
+}
+
+/// Paliers de remboursement ; en deçà du plus petit délai, rien n'est
+/// remboursé. La part non remboursée du séquestre dédommage le mentor.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CancellationPolicy {
+    pub tiers: Vec<CancellationTier>,
+}
+
+impl Default for CancellationPolicy {
+    /// Gratuit jusqu'à 24h avant, 50 % jusqu'à 2h, rien ensuite
+    fn default() -> Self {
+        Self {
+            tiers: vec![
+                CancellationTier {
+                    min_hours_before: 24,
+                    refund_percent: 100,
+                },
+                CancellationTier {
+                    min_hours_before: 2,
+                    refund_percent: 50,
+                },
+            ],
+        }
+    }
+}
+
+/// Partage du séquestre lors d'une annulation
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct CancellationSplit {
+    pub refund_percent: i32,
+    pub refund_tokens: i64,
+    pub compensation_tokens: i64,
+}
+
+impl CancellationPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.tiers.len() > MAX_CANCELLATION_TIERS {
+            return Err(format!(
+                "trop de paliers ({} maximum)",
+                MAX_CANCELLATION_TIERS
+            ));
+        }
+        for t in &self.tiers {
+            if t.min_hours_before < 0 || !(0..=100).contains(&t.refund_percent) {
+                return Err("palier invalide (heures ≥ 0, pourcentage 0–100)".to_string());
+            }
+        }
+        let mut sorted = self.tiers.clone();
+        sorted.sort_by_key(|t| std::cmp::Reverse(t.min_hours_before));
+        for pair in sorted.windows(2) {
+            if pair[0].min_hours_before == pair[1].min_hours_before {
+                return Err("délais de palier en double".to_string());
+            }
+            if pair[0].refund_percent < pair[1].refund_percent {
+                return Err("un préavis plus long ne peut pas rembourser moins".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Lecture tolérante de la colonne JSONB (politique par défaut si illisible)
+    pub fn from_value(value: serde_json::Value) -> Self {
+        serde_json::from_value(value).unwrap_or_default()
+    }
+
+    /// Pourcentage remboursé pour une annulation à `now` d'une session à `scheduled_at`.
+    pub fn refund_percent(&self, scheduled_at: DateTime<Utc>, now: DateTime<Utc>) -> i32 {
+        let notice_minutes = (scheduled_at - now).num_minutes();
+        self.tiers
+            .iter()
+            .filter(|t| notice_minutes >= t.min_hours_before as i64 * 60)
+            .max_by_key(|t| t.min_hours_before)
+            .map(|t| t.refund_percent)
+            .unwrap_or(0)
+    }
+
+    /// Partage du séquestre : remboursement arrondi à l'inférieur, le reste au mentor.
+    pub fn split(
+        &self,
+        escrow: i64,
+        scheduled_at: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> CancellationSplit {
+        let refund_percent = self.refund_percent(scheduled_at, now);
+        let refund_tokens = escrow.max(0) * refund_percent as i64 / 100;
+        CancellationSplit {
+            refund_percent,
+            refund_tokens,
+            compensation_tokens: escrow.max(0) - refund_tokens,
+        }
+    }
+}
+
+/// Payload d'annulation d'une réservation par le mentee
+#[derive(Debug, Deserialize, Default)]
+pub struct CancelBookingPayload {
+    pub reason: Option<String>,
 }
 
 // ============================================================
@@ -137,6 +284,41 @@ pub struct CreateBookingPayload {
     pub notes: Option<String>,
 }
 
+/// Payload de clôture d'une session de groupe par le mentor
+#[derive(Debug, Deserialize)]
+pub struct CompleteGroupSessionPayload {
+    pub scheduled_at: DateTime<Utc>,
+    /// Mentees présents ; les autres places sont annulées et remboursées
+    pub attendees: Vec<String>,
+}
+
+// ============================================================
+// BookingReschedule — proposition de report d'une réservation
+// ============================================================
+
+/// Statuts : pending, accepted, declined, countered, withdrawn, expired
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BookingReschedule {
+    pub id: String,
+    pub booking_id: String,
+    pub proposed_by: String,
+    pub previous_scheduled_at: DateTime<Utc>,
+    pub proposed_scheduled_at: DateTime<Utc>,
+    pub message: Option<String>,
+    pub status: String,
+    pub counter_of: Option<String>,
+    pub responded_by: Option<String>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Payload de proposition (ou contre-proposition) d'un nouvel horaire
+#[derive(Debug, Deserialize)]
+pub struct ProposeReschedulePayload {
+    pub scheduled_at: DateTime<Utc>,
+    pub message: Option<String>,
+}
+
 /// Payload de confirmation de complétion
 #[derive(Debug, Deserialize)]
 pub struct ConfirmBookingPayload {
@@ -195,6 +377,74 @@ mod tests {
         assert_eq!(OfferStatus::Cancelled.to_string(), "cancelled");
     }
 
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_default_cancellation_policy_tiers() {
+        let policy = CancellationPolicy::default();
+        assert!(policy.validate().is_ok());
+        let session = utc("2026-06-10T18:00:00Z");
+
+        let early = policy.split(30, session, utc("2026-06-09T17:00:00Z"));
+        assert_eq!((early.refund_tokens, early.compensation_tokens), (30, 0));
+
+        // Exactement 24h avant : encore gratuit
+        assert_eq!(
+            policy.refund_percent(session, utc("2026-06-09T18:00:00Z")),
+            100
+        );
+
+        let late = policy.split(31, session, utc("2026-06-10T12:00:00Z"));
+        assert_eq!(late.refund_percent, 50);
+        assert_eq!((late.refund_tokens, late.compensation_tokens), (15, 16));
+
+        let last_minute = policy.split(30, session, utc("2026-06-10T17:00:00Z"));
+        assert_eq!(
+            (last_minute.refund_tokens, last_minute.compensation_tokens),
+            (0, 30)
+        );
+    }
+
+    #[test]
+    fn test_cancellation_policy_validation() {
+        let policy: CancellationPolicy = serde_json::from_value(serde_json::json!({
+            "tiers": [{ "min_hours_before": 48, "refund_percent": 100 }]
+        }))
+        .unwrap();
+        assert!(policy.validate().is_ok());
+        assert!(CancellationPolicy { tiers: vec![] }.validate().is_ok());
+
+        let increasing = CancellationPolicy {
+            tiers: vec![
+                CancellationTier {
+                    min_hours_before: 48,
+                    refund_percent: 50,
+                },
+                CancellationTier {
+                    min_hours_before: 2,
+                    refund_percent: 100,
+                },
+            ],
+        };
+        assert!(increasing.validate().is_err());
+
+        let over = CancellationPolicy {
+            tiers: vec![CancellationTier {
+                min_hours_before: 1,
+                refund_percent: 150,
+            }],
+        };
+        assert!(over.validate().is_err());
+
+        // Colonne illisible → politique par défaut
+        assert_eq!(
+            CancellationPolicy::from_value(serde_json::json!("n/a")),
+            CancellationPolicy::default()
+        );
+    }
+
     #[test]
     fn test_booking_status_display() {
         assert_eq!(
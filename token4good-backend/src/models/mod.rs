@@ -1,7 +1,24 @@
+pub mod attendance;
+pub mod certificate;
+pub mod chat;
+pub mod dispute;
 pub mod learning;
+pub mod matching;
+pub mod meeting;
 pub mod mentoring;
 pub mod mentoring_offer;
+pub mod offer_series;
+pub mod outbox;
 pub mod proof;
+pub mod review;
+pub mod scheduled_job;
 pub mod service;
+pub mod session_notes;
+pub mod skills;
+pub mod time_off;
+pub mod time_slot;
+pub mod token_adjustment;
+pub mod token_lot;
 pub mod transaction;
 pub mod user;
+pub mod waitlist;
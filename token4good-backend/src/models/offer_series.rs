@@ -0,0 +1,258 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::models::time_slot::MAX_CAPACITY;
+
+// ============================================================
+// SeriesRule — récurrence hebdomadaire d'une série d'offres
+// ============================================================
+
+/// Tous les `interval_weeks` semaines, le `weekday` à `start_time` (heure
+/// locale de `timezone`), à partir de `starts_on`, jusqu'à `ends_on` ou
+/// `max_occurrences` occurrences.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SeriesRule {
+    pub weekday: Weekday,
+    pub start_time: NaiveTime,
+    pub timezone: Tz,
+    #[serde(default = "default_interval_weeks")]
+    pub interval_weeks: i32,
+    pub starts_on: NaiveDate,
+    pub ends_on: Option<NaiveDate>,
+    pub max_occurrences: Option<i32>,
+}
+
+fn default_interval_weeks() -> i32 {
+    1
+}
+
+impl SeriesRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=4).contains(&self.interval_weeks) {
+            return Err("interval_weeks doit être entre 1 et 4".to_string());
+        }
+        if let Some(ends_on) = self.ends_on {
+            if ends_on < self.starts_on {
+                return Err("ends_on antérieur à starts_on".to_string());
+            }
+        }
+        if self.max_occurrences.is_some_and(|n| n <= 0) {
+            return Err("max_occurrences doit être positif".to_string());
+        }
+        if self.ends_on.is_none() && self.max_occurrences.is_none() {
+            return Err("ends_on ou max_occurrences requis".to_string());
+        }
+        Ok(())
+    }
+
+    /// Date locale de la première occurrence
+    fn first_day(&self) -> NaiveDate {
+        let offset = (7 + self.weekday.num_days_from_monday() as i64
+            - self.starts_on.weekday().num_days_from_monday() as i64)
+            % 7;
+        self.starts_on + Duration::days(offset)
+    }
+
+    /// Occurrences (UTC) dont le début tombe dans [from, to). Une occurrence
+    /// tombant dans un trou d'horloge (passage à l'heure d'été) est sautée mais
+    /// compte dans `max_occurrences`.
+    pub fn occurrences(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+        let mut out = Vec::new();
+        let step = Duration::weeks(self.interval_weeks.max(1) as i64);
+        let mut day = self.first_day();
+        let mut index = 0;
+        loop {
+            if self.max_occurrences.is_some_and(|max| index >= max)
+                || self.ends_on.is_some_and(|end| day > end)
+            {
+                break;
+            }
+            let Some(start) = self
+                .timezone
+                .from_local_datetime(&day.and_time(self.start_time))
+                .earliest()
+                .map(|d| d.with_timezone(&Utc))
+            else {
+                day += step;
+                index += 1;
+                continue;
+            };
+            if start >= to {
+                break;
+            }
+            if start >= from {
+                out.push(start);
+            }
+            day += step;
+            index += 1;
+        }
+        out
+    }
+
+    /// Vrai s'il reste au moins une occurrence après `after`.
+    pub fn has_occurrences_after(&self, after: DateTime<Utc>) -> bool {
+        let far = after + Duration::weeks(4 * 53);
+        !self.occurrences(after, far).is_empty()
+    }
+}
+
+// ============================================================
+// OfferSeries — série récurrente publiée par un mentor
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OfferSeries {
+    pub id: String,
+    pub mentor_id: String,
+    pub topic_slug: String,
+    pub target_level: String,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    pub format: String,
+    pub token_cost: i32,
+    pub capacity: i32,
+    #[serde(flatten)]
+    pub rule: SeriesRule,
+    pub status: String,
+    pub generated_until: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Payload de création d'une série
+#[derive(Debug, Deserialize)]
+pub struct CreateSeriesPayload {
+    pub topic_slug: String,
+    pub target_level: String,
+    pub description: Option<String>,
+    pub duration_minutes: i32,
+    pub format: String,
+    pub token_cost: i32,
+    #[serde(default = "default_capacity")]
+    pub capacity: i32,
+    #[serde(flatten)]
+    pub rule: SeriesRule,
+}
+
+fn default_capacity() -> i32 {
+    1
+}
+
+impl CreateSeriesPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.duration_minutes <= 0 || self.token_cost < 0 {
+            return Err("duration_minutes et token_cost invalides".to_string());
+        }
+        if !(1..=MAX_CAPACITY).contains(&self.capacity) {
+            return Err(format!("capacity doit être entre 1 et {}", MAX_CAPACITY));
+        }
+        self.rule.validate()
+    }
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn rule() -> SeriesRule {
+        serde_json::from_value(serde_json::json!({
+            "weekday": "Wed",
+            "start_time": "18:30:00",
+            "timezone": "Europe/Paris",
+            "starts_on": "2026-03-16",
+            "max_occurrences": 4
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_rule() {
+        assert!(rule().validate().is_ok());
+        assert!(SeriesRule {
+            interval_weeks: 6,
+            ..rule()
+        }
+        .validate()
+        .is_err());
+        assert!(SeriesRule {
+            max_occurrences: None,
+            ..rule()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_occurrences_are_weekly_in_local_time() {
+        let all = rule().occurrences(utc("2026-01-01T00:00:00Z"), utc("2027-01-01T00:00:00Z"));
+        // Mercredis 18 et 25 mars (UTC+1), 1er et 8 avril (UTC+2), puis arrêt
+        assert_eq!(
+            all,
+            vec![
+                utc("2026-03-18T17:30:00Z"),
+                utc("2026-03-25T17:30:00Z"),
+                utc("2026-04-01T16:30:00Z"),
+                utc("2026-04-08T16:30:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_occurrences_window_and_interval() {
+        let biweekly = SeriesRule {
+            interval_weeks: 2,
+            ..rule()
+        };
+        let all = biweekly.occurrences(utc("2026-03-20T00:00:00Z"), utc("2027-01-01T00:00:00Z"));
+        // 18/03 hors fenêtre mais compté : restent 01/04, 15/04, 29/04
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], utc("2026-04-01T16:30:00Z"));
+
+        let bounded = SeriesRule {
+            max_occurrences: None,
+            ends_on: Some(NaiveDate::from_ymd_opt(2026, 3, 25).unwrap()),
+            ..rule()
+        };
+        assert_eq!(
+            bounded
+                .occurrences(utc("2026-01-01T00:00:00Z"), utc("2027-01-01T00:00:00Z"))
+                .len(),
+            2
+        );
+        assert!(!bounded.has_occurrences_after(utc("2026-03-26T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_payload_validation() {
+        let payload: CreateSeriesPayload = serde_json::from_value(serde_json::json!({
+            "topic_slug": "rust",
+            "target_level": "beginner",
+            "duration_minutes": 90,
+            "format": "video",
+            "token_cost": 20,
+            "capacity": 8,
+            "weekday": "Wed",
+            "start_time": "18:30:00",
+            "timezone": "Europe/Paris",
+            "starts_on": "2026-03-16",
+            "ends_on": "2026-06-30"
+        }))
+        .unwrap();
+        assert!(payload.validate().is_ok());
+        assert!(CreateSeriesPayload {
+            capacity: 0,
+            ..payload
+        }
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Délai avant la première relance d'une entrée en échec
+pub const OUTBOX_BASE_DELAY_SECONDS: i64 = 15;
+/// Délai maximal entre deux tentatives
+pub const OUTBOX_MAX_DELAY_SECONDS: i64 = 3600;
+
+// ============================================================
+// Effets de bord
+// ============================================================
+
+/// Effet de bord à livrer ; `kind` et `payload` sont stockés séparément
+/// dans `outbox_events`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
+pub enum OutboxMessage {
+    /// Notification in-app (table `notifications`)
+    Notification {
+        user_id: String,
+        title: String,
+        message: String,
+        notif_type: String,
+        link: Option<String>,
+        #[serde(default)]
+        metadata: serde_json::Value,
+    },
+    /// E-mail transmis au relais configuré (`EMAIL_RELAY_URL`)
+    Email {
+        user_id: String,
+        subject: String,
+        body: String,
+    },
+    /// Points de gamification côté Dazno
+    DaznoSync {
+        user_id: String,
+        points: u64,
+        action: String,
+    },
+    /// Émission de la preuve RGB d'une session complétée
+    RgbProof {
+        booking_id: String,
+        mentor_id: String,
+        mentee_id: String,
+        rating: i32,
+        comment: Option<String>,
+    },
+}
+
+impl OutboxMessage {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OutboxMessage::Notification { .. } => "notification",
+            OutboxMessage::Email { .. } => "email",
+            OutboxMessage::DaznoSync { .. } => "dazno_sync",
+            OutboxMessage::RgbProof { .. } => "rgb_proof",
+        }
+    }
+
+    pub fn payload(&self) -> serde_json::Value {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|mut v| v.get_mut("payload").map(serde_json::Value::take))
+            .unwrap_or_default()
+    }
+
+    /// Reconstitue un message depuis les colonnes `kind` et `payload`
+    pub fn from_parts(kind: &str, payload: serde_json::Value) -> Result<Self, String> {
+        serde_json::from_value(serde_json::json!({ "kind": kind, "payload": payload }))
+            .map_err(|e| format!("entrée {} illisible: {}", kind, e))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxStatus {
+    Pending,
+    Delivered,
+    Skipped,
+    Dead,
+}
+
+impl std::fmt::Display for OutboxStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboxStatus::Pending => write!(f, "pending"),
+            OutboxStatus::Delivered => write!(f, "delivered"),
+            OutboxStatus::Skipped => write!(f, "skipped"),
+            OutboxStatus::Dead => write!(f, "dead"),
+        }
+    }
+}
+
+impl std::str::FromStr for OutboxStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(OutboxStatus::Pending),
+            "delivered" => Ok(OutboxStatus::Delivered),
+            "skipped" => Ok(OutboxStatus::Skipped),
+            "dead" => Ok(OutboxStatus::Dead),
+            _ => Err(format!("Invalid outbox status: {}", s)),
+        }
+    }
+}
+
+// ============================================================
+// Entrées
+// ============================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OutboxEvent {
+    pub id: String,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub dedup_key: Option<String>,
+    pub status: OutboxStatus,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub result: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutboxQuery {
+    pub status: Option<String>,
+    pub kind: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Issue d'une livraison réussie
+#[derive(Debug, Clone, PartialEq)]
+pub enum Delivery {
+    Delivered(String),
+    /// Rien à faire (relais absent, effet déjà produit)
+    Skipped(String),
+}
+
+/// Délai avant la tentative suivante, après `attempts` échecs : backoff
+/// exponentiel plafonné à `OUTBOX_MAX_DELAY_SECONDS`.
+pub fn retry_delay(attempts: i32) -> Duration {
+    let factor = 1i64 << attempts.clamp(1, 20).saturating_sub(1);
+    Duration::seconds((OUTBOX_BASE_DELAY_SECONDS * factor).min(OUTBOX_MAX_DELAY_SECONDS))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip_through_columns() {
+        let message = OutboxMessage::RgbProof {
+            booking_id: "b1".to_string(),
+            mentor_id: "m1".to_string(),
+            mentee_id: "e1".to_string(),
+            rating: 5,
+            comment: None,
+        };
+        assert_eq!(message.kind(), "rgb_proof");
+        let payload = message.payload();
+        assert_eq!(payload["booking_id"], "b1");
+        assert_eq!(
+            OutboxMessage::from_parts(message.kind(), payload),
+            Ok(message)
+        );
+
+        let sync = OutboxMessage::DaznoSync {
+            user_id: "u1".to_string(),
+            points: 12,
+            action: "mentoring_completed".to_string(),
+        };
+        assert_eq!(
+            OutboxMessage::from_parts("dazno_sync", sync.payload()),
+            Ok(sync)
+        );
+        assert!(OutboxMessage::from_parts("sms", serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::seconds(15));
+        assert_eq!(retry_delay(3), Duration::seconds(60));
+        assert_eq!(retry_delay(12), Duration::seconds(OUTBOX_MAX_DELAY_SECONDS));
+    }
+
+    #[test]
+    fn test_status_parse() {
+        for status in [
+            OutboxStatus::Pending,
+            OutboxStatus::Delivered,
+            OutboxStatus::Skipped,
+            OutboxStatus::Dead,
+        ] {
+            assert_eq!(status.to_string().parse(), Ok(status));
+        }
+        assert!("failed".parse::<OutboxStatus>().is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Poids de l'a priori dans la moyenne bayésienne, en nombre d'avis fictifs
+pub const REVIEW_PRIOR_WEIGHT: f64 = 5.0;
+/// Moyenne a priori tant qu'aucun avis n'existe sur la plateforme
+pub const DEFAULT_PRIOR_MEAN: f64 = 3.0;
+/// Longueur maximale d'une réponse ou d'un motif de signalement
+pub const MAX_REVIEW_TEXT_LEN: usize = 2000;
+
+// ============================================================
+// Review — avis laissé par un mentee sur une session
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Review {
+    pub booking_id: String,
+    pub mentor_id: String,
+    pub mentee_id: String,
+    pub mentee_firstname: Option<String>,
+    pub topic_slug: String,
+    pub rating: i32,
+    pub comment: Option<String>,
+    pub rated_at: Option<DateTime<Utc>>,
+    pub mentor_reply: Option<String>,
+    pub mentor_replied_at: Option<DateTime<Utc>>,
+}
+
+/// Moyenne bayésienne : `count` avis de somme `sum`, tirés vers `prior_mean`
+/// avec le poids de `REVIEW_PRIOR_WEIGHT` avis.
+pub fn bayesian_average(sum: i64, count: i64, prior_mean: f64) -> f64 {
+    (REVIEW_PRIOR_WEIGHT * prior_mean + sum as f64) / (REVIEW_PRIOR_WEIGHT + count as f64)
+}
+
+/// Agrégats des avis visibles d'un mentor, d'un sujet ou des deux
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RatingSummary {
+    pub review_count: i64,
+    /// Moyenne brute, absente sans avis
+    pub average: Option<f64>,
+    /// Moyenne bayésienne, utilisée pour le classement
+    pub bayesian_average: f64,
+    /// Nombre d'avis par note, de 1 à 5 étoiles
+    pub histogram: [i64; 5],
+}
+
+impl RatingSummary {
+    pub fn from_histogram(histogram: [i64; 5], prior_mean: f64) -> Self {
+        let review_count: i64 = histogram.iter().sum();
+        let sum: i64 = histogram
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (i as i64 + 1) * n)
+            .sum();
+        RatingSummary {
+            review_count,
+            average: (review_count > 0).then(|| sum as f64 / review_count as f64),
+            bayesian_average: bayesian_average(sum, review_count, prior_mean),
+            histogram,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReviewsPage {
+    pub summary: RatingSummary,
+    pub reviews: Vec<Review>,
+}
+
+// ============================================================
+// Réponse du mentor et signalements
+// ============================================================
+
+fn validate_text(text: &str, field: &str, min_len: usize) -> Result<(), String> {
+    let len = text.trim().chars().count();
+    if len < min_len {
+        return Err(format!(
+            "{} trop court ({} caractères minimum)",
+            field, min_len
+        ));
+    }
+    if len > MAX_REVIEW_TEXT_LEN {
+        return Err(format!(
+            "{} trop long ({} caractères maximum)",
+            field, MAX_REVIEW_TEXT_LEN
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewReplyPayload {
+    pub reply: String,
+}
+
+impl ReviewReplyPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_text(&self.reply, "réponse", 1)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportReviewPayload {
+    pub reason: String,
+}
+
+impl ReportReviewPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        validate_text(&self.reason, "motif", 10)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewReport {
+    pub id: String,
+    pub booking_id: String,
+    pub reporter_id: String,
+    pub reason: String,
+    /// pending, upheld, dismissed
+    pub status: String,
+    pub resolved_by: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Décision de modération sur un avis signalé
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Masque l'avis (retiré des agrégats), signalements retenus
+    Hide,
+    /// Laisse l'avis en ligne, signalements classés
+    Dismiss,
+}
+
+impl ModerationAction {
+    /// Statut donné aux signalements en attente
+    pub fn report_status(self) -> &'static str {
+        match self {
+            ModerationAction::Hide => "upheld",
+            ModerationAction::Dismiss => "dismissed",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ModerateReviewPayload {
+    pub action: ModerationAction,
+    pub note: Option<String>,
+}
+
+/// Entrée de la file de modération : l'avis et ses signalements
+#[derive(Debug, Serialize)]
+pub struct ModerationItem {
+    pub review: Review,
+    pub hidden: bool,
+    pub reports: Vec<ReviewReport>,
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary_from_histogram() {
+        let summary = RatingSummary::from_histogram([0, 0, 0, 1, 3], 3.0);
+        assert_eq!(summary.review_count, 4);
+        assert_eq!(summary.average, Some(4.75));
+        // (5 × 3 + 19) / (5 + 4)
+        assert!((summary.bayesian_average - 34.0 / 9.0).abs() < 1e-9);
+
+        let empty = RatingSummary::from_histogram([0; 5], 4.2);
+        assert_eq!(empty.average, None);
+        assert_eq!(empty.bayesian_average, 4.2);
+    }
+
+    #[test]
+    fn test_bayesian_average_favours_volume() {
+        // Un seul 5 étoiles ne passe pas devant quarante avis à 4.8
+        let single = bayesian_average(5, 1, 3.5);
+        let many = bayesian_average(192, 40, 3.5);
+        assert!(many > single);
+    }
+
+    #[test]
+    fn test_payload_validation() {
+        let reply = |s: &str| ReviewReplyPayload {
+            reply: s.to_string(),
+        };
+        assert!(reply("Merci !").validate().is_ok());
+        assert!(reply("   ").validate().is_err());
+        assert!(reply(&"x".repeat(MAX_REVIEW_TEXT_LEN + 1))
+            .validate()
+            .is_err());
+
+        let report = |s: &str| ReportReviewPayload {
+            reason: s.to_string(),
+        };
+        assert!(report("spam").validate().is_err());
+        assert!(report("Propos insultants envers le mentor")
+            .validate()
+            .is_ok());
+    }
+}
@@ -0,0 +1,213 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Délai maximal entre deux tentatives d'une tâche en échec
+pub const MAX_RETRY_DELAY_SECONDS: i64 = 6 * 3600;
+
+// ============================================================
+// Statuts
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    /// Instance perdue en cours d'exécution
+    Interrupted,
+}
+
+impl std::fmt::Display for JobRunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobRunStatus::Running => write!(f, "running"),
+            JobRunStatus::Succeeded => write!(f, "succeeded"),
+            JobRunStatus::Failed => write!(f, "failed"),
+            JobRunStatus::Interrupted => write!(f, "interrupted"),
+        }
+    }
+}
+
+impl FromStr for JobRunStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(JobRunStatus::Running),
+            "succeeded" => Ok(JobRunStatus::Succeeded),
+            "failed" => Ok(JobRunStatus::Failed),
+            "interrupted" => Ok(JobRunStatus::Interrupted),
+            _ => Err(format!("Invalid job run status: {}", s)),
+        }
+    }
+}
+
+/// Origine d'une exécution
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobTrigger {
+    Schedule,
+    Manual,
+    Retry,
+}
+
+impl std::fmt::Display for JobTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobTrigger::Schedule => write!(f, "schedule"),
+            JobTrigger::Manual => write!(f, "manual"),
+            JobTrigger::Retry => write!(f, "retry"),
+        }
+    }
+}
+
+impl FromStr for JobTrigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "schedule" => Ok(JobTrigger::Schedule),
+            "manual" => Ok(JobTrigger::Manual),
+            "retry" => Ok(JobTrigger::Retry),
+            _ => Err(format!("Invalid job trigger: {}", s)),
+        }
+    }
+}
+
+// ============================================================
+// Tâches et exécutions
+// ============================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScheduledJob {
+    pub name: String,
+    pub description: String,
+    /// Expression cron à 6 champs, UTC
+    pub schedule: String,
+    pub paused: bool,
+    pub max_retries: i32,
+    pub retry_backoff_seconds: i32,
+    /// Échecs consécutifs de l'occurrence en cours
+    pub attempt: i32,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub trigger_requested_at: Option<DateTime<Utc>>,
+    pub last_run: Option<JobRun>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct JobRun {
+    pub id: String,
+    pub job_name: String,
+    pub trigger: JobTrigger,
+    pub attempt: i32,
+    pub instance_id: String,
+    pub status: JobRunStatus,
+    pub result: Option<String>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SchedulerLeader {
+    pub instance_id: String,
+    pub acquired_at: DateTime<Utc>,
+    pub heartbeat_at: DateTime<Utc>,
+}
+
+/// Vue d'ensemble pour l'administration
+#[derive(Debug, Serialize)]
+pub struct SchedulerOverview {
+    pub leader: Option<SchedulerLeader>,
+    pub jobs: Vec<ScheduledJob>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JobRunsQuery {
+    pub limit: Option<i64>,
+}
+
+// ============================================================
+// Planification
+// ============================================================
+
+/// Valide une expression cron (6 champs : seconde minute heure jour mois
+/// jour_semaine)
+pub fn parse_schedule(expr: &str) -> Result<cron::Schedule, String> {
+    cron::Schedule::from_str(expr).map_err(|e| format!("expression cron invalide: {}", e))
+}
+
+/// Prochaine occurrence strictement postérieure à `after`
+pub fn next_occurrence(schedule: &cron::Schedule, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    schedule.after(&after).next()
+}
+
+/// Délai avant la tentative `attempt` (1 = première relance) : backoff
+/// exponentiel plafonné à `MAX_RETRY_DELAY_SECONDS`.
+pub fn retry_delay(backoff_seconds: i32, attempt: i32) -> Duration {
+    let factor = 1i64 << attempt.clamp(1, 20).saturating_sub(1);
+    Duration::seconds((backoff_seconds.max(1) as i64 * factor).min(MAX_RETRY_DELAY_SECONDS))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_occurrence() {
+        let every_15_min = parse_schedule("0 */15 * * * *").unwrap();
+        let at = Utc.with_ymd_and_hms(2026, 3, 10, 9, 7, 30).unwrap();
+        assert_eq!(
+            next_occurrence(&every_15_min, at),
+            Some(Utc.with_ymd_and_hms(2026, 3, 10, 9, 15, 0).unwrap())
+        );
+
+        // Strictement postérieure : une tâche qui vient de tourner ne rejoue pas
+        let daily = parse_schedule("0 0 3 * * *").unwrap();
+        let three = Utc.with_ymd_and_hms(2026, 3, 10, 3, 0, 0).unwrap();
+        assert_eq!(
+            next_occurrence(&daily, three),
+            Some(Utc.with_ymd_and_hms(2026, 3, 11, 3, 0, 0).unwrap())
+        );
+
+        assert!(parse_schedule("toutes les heures").is_err());
+    }
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(60, 1), Duration::seconds(60));
+        assert_eq!(retry_delay(60, 2), Duration::seconds(120));
+        assert_eq!(retry_delay(60, 4), Duration::seconds(480));
+        assert_eq!(
+            retry_delay(60, 30),
+            Duration::seconds(MAX_RETRY_DELAY_SECONDS)
+        );
+    }
+
+    #[test]
+    fn test_status_roundtrip() {
+        for status in [
+            JobRunStatus::Running,
+            JobRunStatus::Succeeded,
+            JobRunStatus::Failed,
+            JobRunStatus::Interrupted,
+        ] {
+            assert_eq!(status.to_string().parse(), Ok(status));
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status)
+            );
+        }
+        assert_eq!("retry".parse(), Ok(JobTrigger::Retry));
+        assert!("cron".parse::<JobTrigger>().is_err());
+    }
+}
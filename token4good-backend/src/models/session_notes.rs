@@ -0,0 +1,332 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::models::mentoring_offer::BookingStatus;
+
+/// Limites d'une fiche de notes
+pub const MAX_SUMMARY_LEN: usize = 10_000;
+pub const MAX_PRIVATE_NOTES_LEN: usize = 10_000;
+pub const MAX_ACTION_ITEMS: usize = 20;
+pub const MAX_ACTION_ITEM_LEN: usize = 500;
+pub const MAX_NOTE_LINKS: usize = 20;
+/// Limites des pièces jointes
+pub const MAX_ATTACHMENTS_PER_BOOKING: i64 = 20;
+pub const MAX_ATTACHMENT_NAME_LEN: usize = 255;
+pub const MAX_URL_LEN: usize = 2000;
+
+// ============================================================
+// Notes de session
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NoteLink {
+    pub title: String,
+    pub url: String,
+}
+
+/// Fiche de notes d'un participant ; `private_notes` n'est renseigné que
+/// pour son auteur.
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionNotes {
+    pub booking_id: String,
+    pub author_id: String,
+    /// mentor, mentee
+    pub author_role: String,
+    pub summary: String,
+    pub action_items: Vec<String>,
+    pub links: Vec<NoteLink>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_notes: Option<String>,
+    pub frozen_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Référence vers un document hébergé ailleurs (devoir, support…)
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionAttachment {
+    pub id: String,
+    pub booking_id: String,
+    pub uploader_id: Option<String>,
+    /// mentor, mentee
+    pub uploader_role: String,
+    pub name: String,
+    pub url: String,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Notes et pièces jointes d'une réservation, vues par un participant
+#[derive(Debug, Serialize)]
+pub struct BookingNotes {
+    pub booking_id: String,
+    pub notes: Vec<SessionNotes>,
+    pub attachments: Vec<SessionAttachment>,
+    pub editable: bool,
+    /// Empreinte des parties partagées (celle engagée dans la preuve RGB
+    /// une fois la session complétée)
+    pub notes_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertNotesPayload {
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub action_items: Vec<String>,
+    #[serde(default)]
+    pub links: Vec<NoteLink>,
+    #[serde(default)]
+    pub private_notes: String,
+}
+
+impl UpsertNotesPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.summary.chars().count() > MAX_SUMMARY_LEN {
+            return Err(format!(
+                "résumé trop long ({} caractères maximum)",
+                MAX_SUMMARY_LEN
+            ));
+        }
+        if self.private_notes.chars().count() > MAX_PRIVATE_NOTES_LEN {
+            return Err(format!(
+                "notes privées trop longues ({} caractères maximum)",
+                MAX_PRIVATE_NOTES_LEN
+            ));
+        }
+        if self.action_items.len() > MAX_ACTION_ITEMS {
+            return Err(format!("{} actions maximum", MAX_ACTION_ITEMS));
+        }
+        if self.action_items.iter().any(|item| {
+            let len = item.trim().chars().count();
+            len == 0 || len > MAX_ACTION_ITEM_LEN
+        }) {
+            return Err(format!(
+                "chaque action fait entre 1 et {} caractères",
+                MAX_ACTION_ITEM_LEN
+            ));
+        }
+        if self.links.len() > MAX_NOTE_LINKS {
+            return Err(format!("{} liens maximum", MAX_NOTE_LINKS));
+        }
+        if self.links.iter().any(|l| !is_valid_url(&l.url)) {
+            return Err("les liens doivent être des URLs https".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddAttachmentPayload {
+    pub name: String,
+    pub url: String,
+    pub content_type: Option<String>,
+    pub size_bytes: Option<i64>,
+}
+
+impl AddAttachmentPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        let name = self.name.trim();
+        if name.is_empty() || name.chars().count() > MAX_ATTACHMENT_NAME_LEN {
+            return Err(format!(
+                "le nom doit faire entre 1 et {} caractères",
+                MAX_ATTACHMENT_NAME_LEN
+            ));
+        }
+        if !is_valid_url(&self.url) {
+            return Err("la pièce jointe doit être une URL https".to_string());
+        }
+        if self.size_bytes.is_some_and(|s| s < 0) {
+            return Err("size_bytes doit être positif".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn is_valid_url(url: &str) -> bool {
+    url.starts_with("https://") && url.len() > "https://".len() && url.len() <= MAX_URL_LEN
+}
+
+/// Notes et pièces jointes sont modifiables jusqu'à la clôture de la
+/// réservation.
+pub fn is_editable(status: BookingStatus) -> bool {
+    !matches!(
+        status,
+        BookingStatus::Completed | BookingStatus::AutoCompleted | BookingStatus::Cancelled
+    )
+}
+
+// ============================================================
+// Empreinte des parties partagées
+// ============================================================
+
+#[derive(Serialize)]
+struct CanonicalNotes<'a> {
+    author_role: &'a str,
+    author_id: &'a str,
+    summary: &'a str,
+    action_items: &'a [String],
+    links: &'a [NoteLink],
+}
+
+#[derive(Serialize)]
+struct CanonicalAttachment<'a> {
+    name: &'a str,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+struct CanonicalContent<'a> {
+    booking_id: &'a str,
+    notes: Vec<CanonicalNotes<'a>>,
+    attachments: Vec<CanonicalAttachment<'a>>,
+}
+
+/// Empreinte SHA256 (hex) des parties partagées des notes et des pièces
+/// jointes, indépendante de l'ordre de lecture ; les notes privées n'y
+/// entrent pas. `None` quand rien n'a été partagé.
+pub fn notes_hash(
+    booking_id: &str,
+    notes: &[SessionNotes],
+    attachments: &[SessionAttachment],
+) -> Option<String> {
+    let mut notes: Vec<&SessionNotes> = notes
+        .iter()
+        .filter(|n| {
+            !n.summary.trim().is_empty() || !n.action_items.is_empty() || !n.links.is_empty()
+        })
+        .collect();
+    if notes.is_empty() && attachments.is_empty() {
+        return None;
+    }
+    notes.sort_by(|a, b| (&a.author_role, &a.author_id).cmp(&(&b.author_role, &b.author_id)));
+    let mut attachments: Vec<&SessionAttachment> = attachments.iter().collect();
+    attachments.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+
+    let content = CanonicalContent {
+        booking_id,
+        notes: notes
+            .iter()
+            .map(|n| CanonicalNotes {
+                author_role: &n.author_role,
+                author_id: &n.author_id,
+                summary: n.summary.trim(),
+                action_items: &n.action_items,
+                links: &n.links,
+            })
+            .collect(),
+        attachments: attachments
+            .iter()
+            .map(|a| CanonicalAttachment {
+                name: &a.name,
+                url: &a.url,
+            })
+            .collect(),
+    };
+    let bytes = serde_json::to_vec(&content).ok()?;
+    Some(hex::encode(Sha256::digest(&bytes)))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notes(role: &str, summary: &str, private: &str) -> SessionNotes {
+        SessionNotes {
+            booking_id: "b1".to_string(),
+            author_id: format!("{}-1", role),
+            author_role: role.to_string(),
+            summary: summary.to_string(),
+            action_items: vec!["Relire le chapitre 3".to_string()],
+            links: vec![],
+            private_notes: Some(private.to_string()),
+            frozen_at: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_notes_hash_covers_shared_sections_only() {
+        let mentor = notes("mentor", "Canaux Lightning", "à revoir");
+        let mentee = notes("mentee", "J'ai compris le routage", "");
+        let hash = notes_hash("b1", &[mentor.clone(), mentee.clone()], &[]).unwrap();
+        assert_eq!(hash.len(), 64);
+
+        // Ordre de lecture et notes privées sans effet
+        let mut other = mentor.clone();
+        other.private_notes = Some("autre chose".to_string());
+        assert_eq!(
+            notes_hash("b1", &[mentee.clone(), other], &[]),
+            Some(hash.clone())
+        );
+
+        // Toute modification partagée change l'empreinte
+        let mut edited = mentor.clone();
+        edited.action_items.push("Ouvrir un canal".to_string());
+        assert_ne!(notes_hash("b1", &[edited, mentee], &[]), Some(hash));
+    }
+
+    #[test]
+    fn test_notes_hash_none_without_shared_content() {
+        let mut empty = notes("mentor", "  ", "seulement privé");
+        empty.action_items.clear();
+        assert_eq!(notes_hash("b1", &[empty], &[]), None);
+
+        let homework = SessionAttachment {
+            id: "a1".to_string(),
+            booking_id: "b1".to_string(),
+            uploader_id: Some("mentee-1".to_string()),
+            uploader_role: "mentee".to_string(),
+            name: "devoir.pdf".to_string(),
+            url: "https://files.example.com/devoir.pdf".to_string(),
+            content_type: Some("application/pdf".to_string()),
+            size_bytes: Some(1024),
+            created_at: Utc::now(),
+        };
+        assert!(notes_hash("b1", &[], &[homework]).is_some());
+    }
+
+    #[test]
+    fn test_payload_validation() {
+        let payload = |items: Vec<&str>, url: &str| UpsertNotesPayload {
+            summary: "Résumé".to_string(),
+            action_items: items.into_iter().map(String::from).collect(),
+            links: vec![NoteLink {
+                title: "Doc".to_string(),
+                url: url.to_string(),
+            }],
+            private_notes: String::new(),
+        };
+        assert!(payload(vec!["Lire"], "https://docs.example.com")
+            .validate()
+            .is_ok());
+        assert!(payload(vec![" "], "https://docs.example.com")
+            .validate()
+            .is_err());
+        assert!(payload(vec!["Lire"], "http://docs.example.com")
+            .validate()
+            .is_err());
+
+        let attachment = AddAttachmentPayload {
+            name: "devoir.pdf".to_string(),
+            url: "https://".to_string(),
+            content_type: None,
+            size_bytes: None,
+        };
+        assert!(attachment.validate().is_err());
+    }
+
+    #[test]
+    fn test_notes_frozen_once_closed() {
+        assert!(is_editable(BookingStatus::Confirmed));
+        assert!(is_editable(BookingStatus::PendingCompletion));
+        assert!(!is_editable(BookingStatus::Completed));
+        assert!(!is_editable(BookingStatus::AutoCompleted));
+        assert!(!is_editable(BookingStatus::Cancelled));
+    }
+}
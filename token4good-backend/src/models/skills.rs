@@ -0,0 +1,313 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::matching::Level;
+
+/// Sessions nécessaires pour atteindre le niveau intermédiaire sur un sujet
+pub const INTERMEDIATE_SESSIONS: i64 = 3;
+/// Sessions nécessaires pour atteindre le niveau avancé
+pub const ADVANCED_SESSIONS: i64 = 6;
+/// Suggestions de prochains sujets retournées au plus
+pub const MAX_SUGGESTIONS: usize = 10;
+pub const MAX_ENDORSEMENT_COMMENT_LEN: usize = 1000;
+
+/// Niveau acquis sur un sujet : d'après le nombre de sessions, relevé par la
+/// meilleure recommandation d'un mentor.
+pub fn skill_level(sessions: i64, endorsed: Option<Level>) -> Level {
+    let from_sessions = if sessions >= ADVANCED_SESSIONS {
+        Level::Advanced
+    } else if sessions >= INTERMEDIATE_SESSIONS {
+        Level::Intermediate
+    } else {
+        Level::Beginner
+    };
+    endorsed.map_or(from_sessions, |e| e.max(from_sessions))
+}
+
+// ============================================================
+// Progression
+// ============================================================
+
+/// Session (et sa preuve RGB) attestant un apprentissage
+#[derive(Debug, Serialize, Clone)]
+pub struct SkillEvidence {
+    pub booking_id: String,
+    pub mentor_id: String,
+    /// Libellé déclaré à la confirmation ; absent pour le sujet de l'offre
+    pub label: Option<String>,
+    pub rgb_contract_id: Option<String>,
+    pub learned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SkillEndorsement {
+    pub id: String,
+    pub user_id: String,
+    pub topic_slug: String,
+    pub mentor_id: String,
+    pub booking_id: String,
+    pub level: Level,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SkillProgress {
+    pub topic_slug: String,
+    pub topic_name: String,
+    pub category_slug: Option<String>,
+    pub level: Level,
+    pub sessions: i64,
+    pub first_learned_at: Option<DateTime<Utc>>,
+    pub last_learned_at: Option<DateTime<Utc>>,
+    pub evidence: Vec<SkillEvidence>,
+    pub endorsements: Vec<SkillEndorsement>,
+}
+
+/// Sujet suggéré pour la suite du parcours
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TopicSuggestion {
+    pub topic_slug: String,
+    pub topic_name: String,
+    pub category_slug: Option<String>,
+    pub level: Level,
+    /// Compétences acquises qui ouvrent ce sujet
+    pub unlocked_by: Vec<String>,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LearningPath {
+    pub user_id: String,
+    pub skills: Vec<SkillProgress>,
+    pub suggestions: Vec<TopicSuggestion>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EndorseSkillPayload {
+    /// Sujet recommandé ; par défaut celui de l'offre
+    pub topic_slug: Option<String>,
+    pub level: String,
+    pub comment: Option<String>,
+}
+
+impl EndorseSkillPayload {
+    pub fn validate(&self) -> Result<Level, String> {
+        if self
+            .comment
+            .as_ref()
+            .is_some_and(|c| c.chars().count() > MAX_ENDORSEMENT_COMMENT_LEN)
+        {
+            return Err(format!(
+                "commentaire trop long ({} caractères maximum)",
+                MAX_ENDORSEMENT_COMMENT_LEN
+            ));
+        }
+        self.level.parse()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPrerequisitesPayload {
+    pub prerequisites: Vec<String>,
+}
+
+// ============================================================
+// Suggestions
+// ============================================================
+
+/// Sujet du référentiel, pour le calcul des suggestions
+#[derive(Debug, Clone)]
+pub struct TopicNode {
+    pub slug: String,
+    pub name: String,
+    pub category_slug: Option<String>,
+    pub level: Level,
+}
+
+/// Sujets ouverts par les compétences acquises : par leurs prérequis
+/// explicites (tous acquis) ou, à défaut, par l'ordre des niveaux de leur
+/// catégorie — un sujet débutant ou intermédiaire s'ouvre après tout sujet
+/// acquis de la catégorie, un sujet avancé après un sujet intermédiaire (ou
+/// une compétence de niveau intermédiaire). Un sujet débutant sans lien avec
+/// le parcours n'est suggéré que s'il fait partie des objectifs du mentee.
+/// Objectifs d'abord, puis niveaux croissants.
+pub fn suggest_topics(
+    topics: &[TopicNode],
+    prerequisites: &HashMap<String, Vec<String>>,
+    acquired: &HashMap<String, Level>,
+    goals: &HashSet<String>,
+) -> Vec<TopicSuggestion> {
+    let acquired_topics: Vec<&TopicNode> = topics
+        .iter()
+        .filter(|t| acquired.contains_key(&t.slug))
+        .collect();
+
+    let mut suggestions: Vec<(bool, TopicSuggestion)> = Vec::new();
+    for topic in topics.iter().filter(|t| !acquired.contains_key(&t.slug)) {
+        let is_goal = goals.contains(&topic.slug);
+        let (unlocked_by, reason) = match prerequisites.get(&topic.slug) {
+            Some(required) if !required.is_empty() => {
+                if !required.iter().all(|p| acquired.contains_key(p)) {
+                    continue;
+                }
+                (
+                    required.clone(),
+                    "Tous les prérequis sont acquis".to_string(),
+                )
+            }
+            _ => {
+                let previous: Vec<String> = acquired_topics
+                    .iter()
+                    .filter(|a| a.category_slug.is_some() && a.category_slug == topic.category_slug)
+                    .filter(|a| match topic.level {
+                        Level::Beginner | Level::Intermediate => true,
+                        Level::Advanced => {
+                            a.level >= Level::Intermediate
+                                || acquired[&a.slug] >= Level::Intermediate
+                        }
+                    })
+                    .map(|a| a.slug.clone())
+                    .collect();
+                if previous.is_empty() && !(is_goal && topic.level == Level::Beginner) {
+                    continue;
+                }
+                let reason = if previous.is_empty() {
+                    "Fait partie de tes objectifs d'apprentissage".to_string()
+                } else {
+                    format!("Suite logique de {}", previous.join(", "))
+                };
+                (previous, reason)
+            }
+        };
+        suggestions.push((
+            is_goal,
+            TopicSuggestion {
+                topic_slug: topic.slug.clone(),
+                topic_name: topic.name.clone(),
+                category_slug: topic.category_slug.clone(),
+                level: topic.level,
+                unlocked_by,
+                reason,
+            },
+        ));
+    }
+
+    suggestions.sort_by(|(goal_a, a), (goal_b, b)| {
+        goal_b
+            .cmp(goal_a)
+            .then(a.level.cmp(&b.level))
+            .then(b.unlocked_by.len().cmp(&a.unlocked_by.len()))
+    });
+    suggestions
+        .into_iter()
+        .map(|(_, s)| s)
+        .take(MAX_SUGGESTIONS)
+        .collect()
+}
+
+/// Vrai si ajouter `prerequisites` à `topic` créerait un cycle
+pub fn creates_cycle(
+    topic: &str,
+    prerequisites: &[String],
+    existing: &HashMap<String, Vec<String>>,
+) -> bool {
+    let mut stack: Vec<&str> = prerequisites.iter().map(String::as_str).collect();
+    let mut seen = HashSet::new();
+    while let Some(current) = stack.pop() {
+        if current == topic {
+            return true;
+        }
+        if seen.insert(current) {
+            if let Some(next) = existing.get(current) {
+                stack.extend(next.iter().map(String::as_str));
+            }
+        }
+    }
+    false
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(slug: &str, category: &str, level: Level) -> TopicNode {
+        TopicNode {
+            slug: slug.to_string(),
+            name: slug.to_string(),
+            category_slug: Some(category.to_string()),
+            level,
+        }
+    }
+
+    #[test]
+    fn test_skill_level_from_sessions_and_endorsements() {
+        assert_eq!(skill_level(1, None), Level::Beginner);
+        assert_eq!(
+            skill_level(INTERMEDIATE_SESSIONS, None),
+            Level::Intermediate
+        );
+        assert_eq!(skill_level(ADVANCED_SESSIONS, None), Level::Advanced);
+        assert_eq!(skill_level(1, Some(Level::Advanced)), Level::Advanced);
+        assert_eq!(
+            skill_level(ADVANCED_SESSIONS, Some(Level::Beginner)),
+            Level::Advanced
+        );
+    }
+
+    #[test]
+    fn test_suggestions_follow_levels_and_prerequisites() {
+        let topics = vec![
+            node("ln-basics", "lightning", Level::Beginner),
+            node("ln-channels", "lightning", Level::Intermediate),
+            node("ln-routing", "lightning", Level::Advanced),
+            node("rust-intro", "dev", Level::Beginner),
+            node("python-intro", "dev", Level::Beginner),
+            node("ln-lsp", "lightning", Level::Advanced),
+        ];
+        let prerequisites = HashMap::from([(
+            "ln-lsp".to_string(),
+            vec!["ln-basics".to_string(), "ln-channels".to_string()],
+        )]);
+        let acquired = HashMap::from([("ln-basics".to_string(), Level::Beginner)]);
+        let goals = HashSet::from(["rust-intro".to_string()]);
+
+        let slugs: Vec<String> = suggest_topics(&topics, &prerequisites, &acquired, &goals)
+            .into_iter()
+            .map(|s| s.topic_slug)
+            .collect();
+        // Objectif d'abord ; l'avancé attend un intermédiaire ; les prérequis
+        // explicites doivent tous être acquis
+        assert_eq!(slugs, vec!["rust-intro", "ln-channels"]);
+
+        let acquired = HashMap::from([
+            ("ln-basics".to_string(), Level::Beginner),
+            ("ln-channels".to_string(), Level::Beginner),
+        ]);
+        let suggestions = suggest_topics(&topics, &prerequisites, &acquired, &HashSet::new());
+        let lsp = suggestions
+            .iter()
+            .find(|s| s.topic_slug == "ln-lsp")
+            .unwrap();
+        assert_eq!(lsp.unlocked_by.len(), 2);
+        assert!(suggestions.iter().any(|s| s.topic_slug == "ln-routing"));
+    }
+
+    #[test]
+    fn test_prerequisite_cycles_are_detected() {
+        let existing = HashMap::from([
+            ("b".to_string(), vec!["a".to_string()]),
+            ("c".to_string(), vec!["b".to_string()]),
+        ]);
+        assert!(creates_cycle("a", &["c".to_string()], &existing));
+        assert!(!creates_cycle("d", &["c".to_string()], &existing));
+        assert!(creates_cycle("a", &["a".to_string()], &existing));
+    }
+}
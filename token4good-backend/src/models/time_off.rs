@@ -0,0 +1,163 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Durée maximale d'une absence
+pub const MAX_TIME_OFF_DAYS: i64 = 180;
+pub const MAX_TIME_OFF_REASON_LEN: usize = 500;
+
+// ============================================================
+// Absences
+// ============================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TimeOff {
+    pub id: String,
+    pub mentor_id: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TimeOff {
+    pub fn is_current(&self, now: DateTime<Utc>) -> bool {
+        self.starts_at <= now && now < self.ends_at
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTimeOffPayload {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+impl CreateTimeOffPayload {
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), String> {
+        if self.ends_at <= self.starts_at {
+            return Err("ends_at doit être postérieur à starts_at".to_string());
+        }
+        if self.ends_at <= now {
+            return Err("l'absence est déjà terminée".to_string());
+        }
+        if self.ends_at - self.starts_at > Duration::days(MAX_TIME_OFF_DAYS) {
+            return Err(format!(
+                "une absence dure au plus {} jours",
+                MAX_TIME_OFF_DAYS
+            ));
+        }
+        if self
+            .reason
+            .as_ref()
+            .is_some_and(|r| r.chars().count() > MAX_TIME_OFF_REASON_LEN)
+        {
+            return Err(format!(
+                "motif trop long ({} caractères maximum)",
+                MAX_TIME_OFF_REASON_LEN
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================
+// Réservations en conflit
+// ============================================================
+
+/// Suite proposée au mentor pour une réservation recoupant son absence
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictAction {
+    /// Proposer un nouvel horaire (`POST /bookings/:id/reschedules`)
+    Reschedule,
+    /// Se retirer, séquestre remboursé (`POST /bookings/:id/withdraw`)
+    Withdraw,
+    /// Refuser la demande en attente (`POST /bookings/:id/decline`)
+    Decline,
+}
+
+/// Suites possibles selon le statut ; un report déjà proposé reste en
+/// attente de la réponse du mentee, le retrait reste possible.
+pub fn conflict_actions(status: &str, reschedule_pending: bool) -> Vec<ConflictAction> {
+    match status {
+        "pending" => vec![ConflictAction::Decline],
+        "confirmed" if reschedule_pending => vec![ConflictAction::Withdraw],
+        "confirmed" => vec![ConflictAction::Reschedule, ConflictAction::Withdraw],
+        _ => vec![],
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TimeOffConflict {
+    pub booking_id: String,
+    pub offer_id: String,
+    pub topic_slug: String,
+    pub mentee_id: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub status: String,
+    pub reschedule_pending: bool,
+    pub actions: Vec<ConflictAction>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeOffDetail {
+    #[serde(flatten)]
+    pub time_off: TimeOff,
+    pub conflicts: Vec<TimeOffConflict>,
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_time_off_validation() {
+        let now = utc("2026-07-01T00:00:00Z");
+        let payload = |start: &str, end: &str| CreateTimeOffPayload {
+            starts_at: utc(start),
+            ends_at: utc(end),
+            reason: None,
+        };
+        assert!(payload("2026-07-10T00:00:00Z", "2026-07-20T00:00:00Z")
+            .validate(now)
+            .is_ok());
+        // Absence déjà commencée : acceptée tant qu'elle n'est pas finie
+        assert!(payload("2026-06-20T00:00:00Z", "2026-07-05T00:00:00Z")
+            .validate(now)
+            .is_ok());
+        assert!(payload("2026-07-20T00:00:00Z", "2026-07-10T00:00:00Z")
+            .validate(now)
+            .is_err());
+        assert!(payload("2026-06-01T00:00:00Z", "2026-06-10T00:00:00Z")
+            .validate(now)
+            .is_err());
+        assert!(payload("2026-07-10T00:00:00Z", "2027-07-10T00:00:00Z")
+            .validate(now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_conflict_actions() {
+        assert_eq!(
+            conflict_actions("confirmed", false),
+            vec![ConflictAction::Reschedule, ConflictAction::Withdraw]
+        );
+        assert_eq!(
+            conflict_actions("confirmed", true),
+            vec![ConflictAction::Withdraw]
+        );
+        assert_eq!(
+            conflict_actions("pending", false),
+            vec![ConflictAction::Decline]
+        );
+        assert!(conflict_actions("cancelled", false).is_empty());
+    }
+}
@@ -0,0 +1,487 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Nombre maximal de créneaux déclarés sur une offre
+pub const MAX_SLOTS_PER_OFFER: usize = 100;
+
+/// Fenêtre de recherche maximale pour le calcul des créneaux réservables
+pub const MAX_SLOT_RANGE_DAYS: i64 = 62;
+
+/// Nombre maximal de places sur une session de groupe
+pub const MAX_CAPACITY: i32 = 50;
+
+// ============================================================
+// TimeSlot — fenêtre de disponibilité d'une offre
+// ============================================================
+
+/// Créneau ponctuel : date ISO 8601 avec décalage horaire, durée en minutes.
+/// Format historique du front (`{ date, duration_minutes }`).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct OneOffSlot {
+    pub date: DateTime<FixedOffset>,
+    pub duration_minutes: i32,
+    /// Places sur ce créneau (à défaut : capacité de l'offre)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<i32>,
+}
+
+/// Règle hebdomadaire exprimée en heure locale d'un fuseau IANA
+/// (ex. tous les mardis 18:00–20:00 Europe/Paris), changements d'heure compris.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklySlot {
+    pub weekday: Weekday,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+    pub timezone: Tz,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_from: Option<NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<NaiveDate>,
+    /// Places sur ce créneau (à défaut : capacité de l'offre)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seats: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum TimeSlot {
+    Weekly(WeeklySlot),
+    OneOff(OneOffSlot),
+}
+
+/// Intervalle concret [start, end) en UTC
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SlotWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl SlotWindow {
+    pub fn new(start: DateTime<Utc>, duration_minutes: i32) -> Self {
+        Self {
+            start,
+            end: start + Duration::minutes(duration_minutes as i64),
+        }
+    }
+
+    pub fn overlaps(&self, other: &SlotWindow) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+/// Réservation active occupant l'agenda d'un mentor
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occupancy {
+    pub offer_id: String,
+    pub window: SlotWindow,
+}
+
+/// Session réservable avec ses places restantes
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct BookableSlot {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub seats: i32,
+    pub seats_left: i32,
+}
+
+impl TimeSlot {
+    pub fn seats(&self) -> Option<i32> {
+        match self {
+            TimeSlot::OneOff(s) => s.seats,
+            TimeSlot::Weekly(w) => w.seats,
+        }
+    }
+
+    pub fn validate(&self, session_minutes: i32) -> Result<(), String> {
+        if let Some(seats) = self.seats() {
+            if !(1..=MAX_CAPACITY).contains(&seats) {
+                return Err(format!("seats doit être entre 1 et {}", MAX_CAPACITY));
+            }
+        }
+        match self {
+            TimeSlot::OneOff(s) => {
+                if s.duration_minutes < session_minutes {
+                    return Err(format!(
+                        "créneau du {} plus court que la session ({} min)",
+                        s.date, session_minutes
+                    ));
+                }
+            }
+            TimeSlot::Weekly(w) => {
+                if w.end_time <= w.start_time {
+                    return Err(format!(
+                        "créneau {:?} : end_time doit suivre start_time",
+                        w.weekday
+                    ));
+                }
+                if (w.end_time - w.start_time).num_minutes() < session_minutes as i64 {
+                    return Err(format!(
+                        "créneau {:?} plus court que la session ({} min)",
+                        w.weekday, session_minutes
+                    ));
+                }
+                if let (Some(from), Some(until)) = (w.valid_from, w.valid_until) {
+                    if until < from {
+                        return Err("valid_until antérieur à valid_from".to_string());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fenêtres concrètes (UTC) de ce créneau qui recoupent [from, to).
+    pub fn windows(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Vec<SlotWindow> {
+        match self {
+            TimeSlot::OneOff(s) => {
+                let w = SlotWindow::new(s.date.with_timezone(&Utc), s.duration_minutes);
+                if w.end > from && w.start < to {
+                    vec![w]
+                } else {
+                    vec![]
+                }
+            }
+            TimeSlot::Weekly(w) => {
+                let mut out = Vec::new();
+                // Marge d'un jour : le jour local peut différer du jour UTC
+                let mut day = from.with_timezone(&w.timezone).date_naive() - Duration::days(1);
+                let last = to.with_timezone(&w.timezone).date_naive() + Duration::days(1);
+                while day <= last {
+                    let in_validity = w.valid_from.is_none_or(|d| day >= d)
+                        && w.valid_until.is_none_or(|d| day <= d);
+                    if day.weekday() == w.weekday && in_validity {
+                        let start = w.timezone.from_local_datetime(&day.and_time(w.start_time));
+                        let end = w.timezone.from_local_datetime(&day.and_time(w.end_time));
+                        // Heure inexistante (passage à l'heure d'été) : créneau ignoré
+                        if let (Some(start), Some(end)) = (start.earliest(), end.latest()) {
+                            let window = SlotWindow {
+                                start: start.with_timezone(&Utc),
+                                end: end.with_timezone(&Utc),
+                            };
+                            if window.end > from && window.start < to {
+                                out.push(window);
+                            }
+                        }
+                    }
+                    day += Duration::days(1);
+                }
+                out
+            }
+        }
+    }
+}
+
+// ============================================================
+// Disponibilités d'une offre
+// ============================================================
+
+/// Valide la liste de créneaux d'une offre pour une durée de session donnée.
+pub fn validate_availability(slots: &[TimeSlot], session_minutes: i32) -> Result<(), String> {
+    if slots.len() > MAX_SLOTS_PER_OFFER {
+        return Err(format!(
+            "trop de créneaux ({} maximum)",
+            MAX_SLOTS_PER_OFFER
+        ));
+    }
+    slots.iter().try_for_each(|s| s.validate(session_minutes))
+}
+
+/// Lecture tolérante de la colonne JSONB : un contenu illisible vaut « aucun créneau ».
+pub fn parse_availability(value: serde_json::Value) -> Vec<TimeSlot> {
+    if value.is_null() {
+        return vec![];
+    }
+    serde_json::from_value(value).unwrap_or_else(|e| {
+        tracing::warn!("Unreadable offer availability: {}", e);
+        vec![]
+    })
+}
+
+/// Découpe les fenêtres en sessions de `session_minutes`, avec le nombre de
+/// places propre au créneau s'il est précisé. Trié par début de session.
+fn session_windows(
+    slots: &[TimeSlot],
+    session_minutes: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<(SlotWindow, Option<i32>)> {
+    let step = Duration::minutes(session_minutes.max(1) as i64);
+    let mut out: Vec<(SlotWindow, Option<i32>)> = Vec::new();
+    for slot in slots {
+        for window in slot.windows(from, to) {
+            let mut start = window.start;
+            while start + step <= window.end {
+                if start >= from && start < to {
+                    out.push((SlotWindow::new(start, session_minutes), slot.seats()));
+                }
+                start += step;
+            }
+        }
+    }
+    out.sort_by_key(|(w, _)| *w);
+    out.dedup_by_key(|(w, _)| *w);
+    out
+}
+
+/// Places déjà prises sur la session `candidate` de l'offre `offer_id`.
+/// `None` si le mentor est occupé par une autre session sur cet intervalle.
+pub fn seats_taken(offer_id: &str, candidate: &SlotWindow, occupancy: &[Occupancy]) -> Option<i32> {
+    let mut taken = 0;
+    for o in occupancy.iter().filter(|o| o.window.overlaps(candidate)) {
+        if o.offer_id == offer_id && o.window.start == candidate.start {
+            taken += 1;
+        } else {
+            return None;
+        }
+    }
+    Some(taken)
+}
+
+/// Sessions de l'offre réservables sur [from, to) : ni en conflit avec une
+/// autre session du mentor, ni complètes.
+pub fn bookable_slots(
+    offer_id: &str,
+    slots: &[TimeSlot],
+    session_minutes: i32,
+    capacity: i32,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    occupancy: &[Occupancy],
+) -> Vec<BookableSlot> {
+    session_windows(slots, session_minutes, from, to)
+        .into_iter()
+        .filter_map(|(window, seats)| {
+            let seats = seats.unwrap_or(capacity);
+            let taken = seats_taken(offer_id, &window, occupancy)?;
+            (taken < seats).then_some(BookableSlot {
+                start: window.start,
+                end: window.end,
+                seats,
+                seats_left: seats - taken,
+            })
+        })
+        .collect()
+}
+
+/// Nombre de places de la session commençant à `start`, si l'offre la propose
+/// (début aligné sur le découpage d'un créneau). Une offre sans créneau
+/// accepte tout horaire avec sa capacité.
+pub fn offered_seats(
+    slots: &[TimeSlot],
+    session_minutes: i32,
+    capacity: i32,
+    start: DateTime<Utc>,
+) -> Option<i32> {
+    if slots.is_empty() {
+        return Some(capacity);
+    }
+    let end = start + Duration::minutes(session_minutes as i64);
+    session_windows(slots, session_minutes, start, end)
+        .into_iter()
+        .find(|(w, _)| w.start == start)
+        .map(|(_, seats)| seats.unwrap_or(capacity))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn weekly_paris() -> TimeSlot {
+        serde_json::from_value(serde_json::json!({
+            "weekday": "Tue",
+            "start_time": "18:00:00",
+            "end_time": "20:00:00",
+            "timezone": "Europe/Paris"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_legacy_and_weekly_slots() {
+        let slots = parse_availability(serde_json::json!([
+            { "date": "2026-03-10T09:00:00+01:00", "duration_minutes": 60 },
+            { "weekday": "Tue", "start_time": "18:00:00", "end_time": "20:00:00",
+              "timezone": "Europe/Paris" }
+        ]));
+        assert_eq!(slots.len(), 2);
+        assert!(matches!(slots[0], TimeSlot::OneOff(_)));
+        assert!(matches!(slots[1], TimeSlot::Weekly(_)));
+
+        // Le format historique est réémis à l'identique
+        let json = serde_json::to_value(&slots[0]).unwrap();
+        assert_eq!(json["duration_minutes"], 60);
+
+        assert!(parse_availability(serde_json::json!({"foo": 1})).is_empty());
+        assert!(serde_json::from_value::<TimeSlot>(serde_json::json!({
+            "weekday": "Tue", "start_time": "18:00:00", "end_time": "20:00:00",
+            "timezone": "Mars/Olympus"
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_short_or_inverted_slots() {
+        assert!(validate_availability(&[weekly_paris()], 60).is_ok());
+        assert!(validate_availability(&[weekly_paris()], 180).is_err());
+
+        let inverted = TimeSlot::Weekly(WeeklySlot {
+            weekday: Weekday::Mon,
+            start_time: NaiveTime::from_hms_opt(20, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+            timezone: chrono_tz::UTC,
+            valid_from: None,
+            valid_until: None,
+            seats: None,
+        });
+        assert!(validate_availability(&[inverted], 30).is_err());
+    }
+
+    #[test]
+    fn test_weekly_slots_follow_daylight_saving() {
+        // Mardi 18h Paris = 17h UTC en hiver, 16h UTC en été
+        let winter = bookable_slots(
+            "o1",
+            &[weekly_paris()],
+            60,
+            1,
+            utc("2026-03-09T00:00:00Z"),
+            utc("2026-03-11T00:00:00Z"),
+            &[],
+        );
+        assert_eq!(
+            winter.iter().map(|s| s.start).collect::<Vec<_>>(),
+            vec![utc("2026-03-10T17:00:00Z"), utc("2026-03-10T18:00:00Z")]
+        );
+
+        let summer = bookable_slots(
+            "o1",
+            &[weekly_paris()],
+            60,
+            1,
+            utc("2026-04-06T00:00:00Z"),
+            utc("2026-04-08T00:00:00Z"),
+            &[],
+        );
+        assert_eq!(summer[0].start, utc("2026-04-07T16:00:00Z"));
+    }
+
+    fn occupied(offer_id: &str, start: &str, minutes: i32) -> Occupancy {
+        Occupancy {
+            offer_id: offer_id.to_string(),
+            window: SlotWindow::new(utc(start), minutes),
+        }
+    }
+
+    #[test]
+    fn test_other_sessions_block_overlapping_slots() {
+        let busy = [occupied("other", "2026-03-10T17:30:00Z", 30)];
+        let slots = bookable_slots(
+            "o1",
+            &[weekly_paris()],
+            60,
+            1,
+            utc("2026-03-09T00:00:00Z"),
+            utc("2026-03-11T00:00:00Z"),
+            &busy,
+        );
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].start, utc("2026-03-10T18:00:00Z"));
+    }
+
+    #[test]
+    fn test_group_seats_fill_up() {
+        let taken = [
+            occupied("o1", "2026-03-10T17:00:00Z", 60),
+            occupied("o1", "2026-03-10T17:00:00Z", 60),
+        ];
+        let range = (utc("2026-03-09T00:00:00Z"), utc("2026-03-11T00:00:00Z"));
+
+        let slots = bookable_slots("o1", &[weekly_paris()], 60, 3, range.0, range.1, &taken);
+        assert_eq!(slots[0].start, utc("2026-03-10T17:00:00Z"));
+        assert_eq!((slots[0].seats, slots[0].seats_left), (3, 1));
+        assert_eq!(slots[1].seats_left, 3);
+
+        // Complet à 2 places
+        let slots = bookable_slots("o1", &[weekly_paris()], 60, 2, range.0, range.1, &taken);
+        assert_eq!(slots.len(), 1);
+        assert_eq!(slots[0].start, utc("2026-03-10T18:00:00Z"));
+    }
+
+    #[test]
+    fn test_slot_seats_override_capacity() {
+        let slot: TimeSlot = serde_json::from_value(serde_json::json!({
+            "date": "2026-03-10T18:00:00+01:00", "duration_minutes": 60, "seats": 8
+        }))
+        .unwrap();
+        assert_eq!(
+            offered_seats(
+                std::slice::from_ref(&slot),
+                60,
+                1,
+                utc("2026-03-10T17:00:00Z")
+            ),
+            Some(8)
+        );
+
+        let too_many = TimeSlot::OneOff(OneOffSlot {
+            seats: Some(MAX_CAPACITY + 1),
+            ..match slot {
+                TimeSlot::OneOff(s) => s,
+                _ => unreachable!(),
+            }
+        });
+        assert!(validate_availability(&[too_many], 60).is_err());
+    }
+
+    #[test]
+    fn test_seats_taken_detects_overlap() {
+        let window = SlotWindow::new(utc("2026-03-10T17:00:00Z"), 60);
+        assert_eq!(seats_taken("o1", &window, &[]), Some(0));
+        assert_eq!(
+            seats_taken("o1", &window, &[occupied("o1", "2026-03-10T17:00:00Z", 60)]),
+            Some(1)
+        );
+        // Même offre, autre début qui chevauche : conflit
+        assert_eq!(
+            seats_taken("o1", &window, &[occupied("o1", "2026-03-10T17:30:00Z", 60)]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_offered_seats() {
+        let slots = [weekly_paris()];
+        assert_eq!(
+            offered_seats(&slots, 60, 4, utc("2026-03-10T18:00:00Z")),
+            Some(4)
+        );
+        // Non aligné sur le découpage, ou hors fenêtre
+        assert_eq!(
+            offered_seats(&slots, 60, 4, utc("2026-03-10T17:15:00Z")),
+            None
+        );
+        assert_eq!(
+            offered_seats(&slots, 60, 4, utc("2026-03-11T17:00:00Z")),
+            None
+        );
+        // Offre historique sans créneau : pas de contrainte
+        assert_eq!(
+            offered_seats(&[], 60, 1, utc("2026-03-11T17:00:00Z")),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================
+// Enums
+// ============================================================
+
+/// Code motif obligatoire d'un ajustement manuel de solde.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentReason {
+    ErrorCorrection,
+    FailedSessionRefund,
+    GoodwillCredit,
+    FraudReversal,
+    DataMigration,
+    Other,
+}
+
+impl std::fmt::Display for AdjustmentReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdjustmentReason::ErrorCorrection => write!(f, "error_correction"),
+            AdjustmentReason::FailedSessionRefund => write!(f, "failed_session_refund"),
+            AdjustmentReason::GoodwillCredit => write!(f, "goodwill_credit"),
+            AdjustmentReason::FraudReversal => write!(f, "fraud_reversal"),
+            AdjustmentReason::DataMigration => write!(f, "data_migration"),
+            AdjustmentReason::Other => write!(f, "other"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdjustmentStatus {
+    PendingApproval,
+    Applied,
+    Rejected,
+}
+
+impl std::fmt::Display for AdjustmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdjustmentStatus::PendingApproval => write!(f, "pending_approval"),
+            AdjustmentStatus::Applied => write!(f, "applied"),
+            AdjustmentStatus::Rejected => write!(f, "rejected"),
+        }
+    }
+}
+
+// ============================================================
+// TokenAdjustment — crédit/débit manuel d'un admin
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct TokenAdjustment {
+    pub id: String,
+    pub user_id: String,
+    /// Positif = crédit, négatif = débit
+    pub amount: i32,
+    pub reason_code: String,
+    pub justification: String,
+    pub status: String,
+    pub requires_approval: bool,
+    pub requested_by: String,
+    pub reviewed_by: Option<String>,
+    pub review_comment: Option<String>,
+    pub transaction_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Entrée de la piste d'audit d'un ajustement
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct AdjustmentEvent {
+    pub id: String,
+    pub adjustment_id: String,
+    pub actor_id: String,
+    pub event: String,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Payload de demande d'ajustement
+#[derive(Debug, Deserialize)]
+pub struct CreateAdjustmentPayload {
+    pub user_id: String,
+    pub amount: i32,
+    pub reason_code: AdjustmentReason,
+    pub justification: String,
+}
+
+impl CreateAdjustmentPayload {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.amount == 0 {
+            return Err("amount ne peut pas être nul".to_string());
+        }
+        if self.justification.trim().len() < 10 {
+            return Err("justification trop courte (10 caractères minimum)".to_string());
+        }
+        Ok(())
+    }
+
+    /// Un ajustement dont la valeur absolue dépasse le seuil exige un second admin.
+    pub fn requires_approval(&self, threshold: i64) -> bool {
+        (self.amount as i64).abs() > threshold
+    }
+}
+
+/// Payload d'approbation ou de rejet
+#[derive(Debug, Deserialize)]
+pub struct ReviewAdjustmentPayload {
+    pub comment: Option<String>,
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(amount: i32, justification: &str) -> CreateAdjustmentPayload {
+        CreateAdjustmentPayload {
+            user_id: "u1".to_string(),
+            amount,
+            reason_code: AdjustmentReason::ErrorCorrection,
+            justification: justification.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_reason_code_roundtrip() {
+        let r: AdjustmentReason = serde_json::from_str("\"failed_session_refund\"").unwrap();
+        assert_eq!(r, AdjustmentReason::FailedSessionRefund);
+        assert_eq!(r.to_string(), "failed_session_refund");
+        assert!(serde_json::from_str::<AdjustmentReason>("\"because\"").is_err());
+    }
+
+    #[test]
+    fn test_status_display() {
+        assert_eq!(
+            AdjustmentStatus::PendingApproval.to_string(),
+            "pending_approval"
+        );
+        assert_eq!(AdjustmentStatus::Applied.to_string(), "applied");
+    }
+
+    #[test]
+    fn test_validate_payload() {
+        assert!(payload(50, "Double débit du 12/03 sur la session")
+            .validate()
+            .is_ok());
+        assert!(payload(0, "Double débit du 12/03 sur la session")
+            .validate()
+            .is_err());
+        assert!(payload(50, "  oups   ").validate().is_err());
+    }
+
+    #[test]
+    fn test_requires_approval_above_threshold() {
+        let j = "Correction suite ticket support";
+        assert!(!payload(100, j).requires_approval(100));
+        assert!(payload(101, j).requires_approval(100));
+        assert!(payload(-150, j).requires_approval(100));
+        assert!(!payload(-100, j).requires_approval(100));
+    }
+}
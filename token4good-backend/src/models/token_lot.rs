@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================
+// TokenLot — crédit T4G avec expiration et/ou vesting
+// ============================================================
+
+/// Lot de tokens issu d'un crédit du ledger (bonus de bienvenue, campagne...).
+///
+/// Le montant est acquis linéairement entre `vesting_start` et `vesting_end`
+/// (ou en totalité à `vesting_start` si `vesting_end` est absent), et la part
+/// non consommée est perdue à `expires_at`.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct TokenLot {
+    pub id: String,
+    pub user_id: String,
+    pub transaction_id: String,
+    pub amount: i32,
+    pub consumed: i32,
+    pub vesting_start: DateTime<Utc>,
+    pub vesting_end: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub expiry_notified_at: Option<DateTime<Utc>>,
+    pub expired_at: Option<DateTime<Utc>>,
+    pub expired_amount: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TokenLot {
+    /// Part acquise du lot à l'instant `now`.
+    pub fn vested_at(&self, now: DateTime<Utc>) -> i64 {
+        let amount = self.amount as i64;
+        if now < self.vesting_start {
+            return 0;
+        }
+        match self.vesting_end {
+            Some(end) if now < end => {
+                let total = (end - self.vesting_start).num_seconds().max(1);
+                let elapsed = (now - self.vesting_start).num_seconds();
+                amount * elapsed / total
+            }
+            _ => amount,
+        }
+    }
+
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        self.expired_at.is_some() || self.expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Tokens encore présents dans le lot (acquis ou non).
+    pub fn remaining(&self) -> i64 {
+        (self.amount - self.consumed - self.expired_amount).max(0) as i64
+    }
+
+    /// Tokens dépensables immédiatement : acquis, non consommés, non expirés.
+    pub fn spendable_at(&self, now: DateTime<Utc>) -> i64 {
+        if self.is_expired_at(now) {
+            return 0;
+        }
+        (self.vested_at(now) - self.consumed as i64).max(0)
+    }
+
+    /// Tokens crédités au ledger mais pas encore dépensables (non acquis ou expirés).
+    pub fn locked_at(&self, now: DateTime<Utc>) -> i64 {
+        self.remaining() - self.spendable_at(now)
+    }
+}
+
+/// Conditions optionnelles d'un crédit (vesting et expiration).
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GrantTerms {
+    pub vesting_start: Option<DateTime<Utc>>,
+    pub vesting_end: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl GrantTerms {
+    /// Un crédit sans aucune condition n'a pas besoin de lot.
+    pub fn is_unrestricted(&self) -> bool {
+        self.vesting_start.is_none() && self.vesting_end.is_none() && self.expires_at.is_none()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let start = self.vesting_start.unwrap_or_else(Utc::now);
+        if let Some(end) = self.vesting_end {
+            if end <= start {
+                return Err("vesting_end doit être postérieur à vesting_start".to_string());
+            }
+        }
+        if let (Some(expires), Some(end)) = (self.expires_at, self.vesting_end) {
+            if expires <= end {
+                return Err("expires_at doit être postérieur à la fin du vesting".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Répartit un débit de `amount` tokens sur les lots en FIFO (plus ancien d'abord).
+///
+/// Seule la part dépensable de chaque lot est consommée. Retourne la liste
+/// `(lot_id, quantité)` ; le reliquat éventuel est imputé sur le solde libre.
+pub fn allocate_fifo(lots: &[TokenLot], amount: i64, now: DateTime<Utc>) -> Vec<(String, i64)> {
+    let mut ordered: Vec<&TokenLot> = lots.iter().collect();
+    ordered.sort_by_key(|l| l.created_at);
+
+    let mut left = amount;
+    let mut allocation = Vec::new();
+    for lot in ordered {
+        if left <= 0 {
+            break;
+        }
+        let take = lot.spendable_at(now).min(left);
+        if take > 0 {
+            allocation.push((lot.id.clone(), take));
+            left -= take;
+        }
+    }
+    allocation
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn lot(id: &str, amount: i32, created_at: DateTime<Utc>) -> TokenLot {
+        TokenLot {
+            id: id.to_string(),
+            user_id: "u1".to_string(),
+            transaction_id: format!("tx_{}", id),
+            amount,
+            consumed: 0,
+            vesting_start: created_at,
+            vesting_end: None,
+            expires_at: None,
+            expiry_notified_at: None,
+            expired_at: None,
+            expired_amount: 0,
+            created_at,
+        }
+    }
+
+    #[test]
+    fn test_linear_vesting() {
+        let now = Utc::now();
+        let mut l = lot("a", 100, now - Duration::days(10));
+        l.vesting_end = Some(now + Duration::days(10));
+        assert_eq!(l.vested_at(now), 50);
+        assert_eq!(l.vested_at(now - Duration::days(11)), 0);
+        assert_eq!(l.vested_at(now + Duration::days(10)), 100);
+        assert_eq!(l.spendable_at(now), 50);
+        assert_eq!(l.locked_at(now), 50);
+    }
+
+    #[test]
+    fn test_cliff_vesting() {
+        let now = Utc::now();
+        let mut l = lot("a", 40, now);
+        l.vesting_start = now + Duration::days(30);
+        assert_eq!(l.spendable_at(now), 0);
+        assert_eq!(l.locked_at(now), 40);
+        assert_eq!(l.spendable_at(now + Duration::days(30)), 40);
+    }
+
+    #[test]
+    fn test_expired_lot_not_spendable() {
+        let now = Utc::now();
+        let mut l = lot("a", 100, now - Duration::days(400));
+        l.consumed = 30;
+        l.expires_at = Some(now - Duration::days(1));
+        assert!(l.is_expired_at(now));
+        assert_eq!(l.spendable_at(now), 0);
+        assert_eq!(l.remaining(), 70);
+    }
+
+    #[test]
+    fn test_fifo_allocation_oldest_first() {
+        let now = Utc::now();
+        let old = lot("old", 30, now - Duration::days(5));
+        let mut young = lot("young", 50, now - Duration::days(1));
+        young.consumed = 10;
+
+        let allocation = allocate_fifo(&[young, old], 50, now);
+        assert_eq!(
+            allocation,
+            vec![("old".to_string(), 30), ("young".to_string(), 20)]
+        );
+    }
+
+    #[test]
+    fn test_fifo_allocation_skips_locked_lots() {
+        let now = Utc::now();
+        let mut unvested = lot("unvested", 100, now - Duration::days(5));
+        unvested.vesting_start = now + Duration::days(1);
+        let free = lot("free", 10, now - Duration::days(1));
+
+        let allocation = allocate_fifo(&[unvested, free], 25, now);
+        assert_eq!(allocation, vec![("free".to_string(), 10)]);
+    }
+
+    #[test]
+    fn test_grant_terms_validation() {
+        let now = Utc::now();
+        assert!(GrantTerms::default().is_unrestricted());
+        assert!(GrantTerms::default().validate().is_ok());
+
+        let inverted = GrantTerms {
+            vesting_start: Some(now),
+            vesting_end: Some(now - Duration::days(1)),
+            expires_at: None,
+        };
+        assert!(inverted.validate().is_err());
+
+        let expires_before_vested = GrantTerms {
+            vesting_start: Some(now),
+            vesting_end: Some(now + Duration::days(30)),
+            expires_at: Some(now + Duration::days(10)),
+        };
+        assert!(expires_before_vested.validate().is_err());
+    }
+}
@@ -45,6 +45,12 @@ impl std::str::FromStr for UserRole {
     }
 }
 
+/// Rôle administrateur, quelle que soit la casse stockée : les anciens
+/// comptes portent `ADMIN`, `UserRole` émet `admin`.
+pub fn is_admin_role(role: &str) -> bool {
+    role.eq_ignore_ascii_case("admin")
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct User {
     pub id: uuid::Uuid,
@@ -184,6 +190,14 @@ mod tests {
         assert!(UserRole::from_str("").is_err());
     }
 
+    #[test]
+    fn test_is_admin_role_ignore_la_casse() {
+        assert!(is_admin_role(&UserRole::Admin.to_string()));
+        assert!(is_admin_role("ADMIN"));
+        assert!(!is_admin_role("mentor"));
+        assert!(!is_admin_role("administrator"));
+    }
+
     // ========== UserRole Serde ==========
 
     #[test]
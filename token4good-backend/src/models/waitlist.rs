@@ -0,0 +1,125 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// ============================================================
+// WaitlistEntry — inscription d'un mentee en liste d'attente
+// ============================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WaitlistEntry {
+    pub id: String,
+    pub mentee_id: String,
+    pub offer_id: Option<String>,
+    pub mentor_id: Option<String>,
+    pub topic_slug: Option<String>,
+    /// Budget maximal accepté, en T4G
+    pub max_tokens: Option<i32>,
+    /// waiting, offered, claimed, expired, left
+    pub status: String,
+    /// Offre réservée pour ce mentee pendant l'option
+    pub hold_offer_id: Option<String>,
+    pub hold_expires_at: Option<DateTime<Utc>>,
+    /// Rang parmi les inscrits en attente sur la même cible (1 = prochain)
+    pub position: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Cible d'une inscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WaitlistTarget {
+    Offer(String),
+    MentorTopic {
+        mentor_id: String,
+        topic_slug: String,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinWaitlistPayload {
+    pub offer_id: Option<String>,
+    pub mentor_id: Option<String>,
+    pub topic_slug: Option<String>,
+    pub max_tokens: Option<i32>,
+}
+
+impl JoinWaitlistPayload {
+    /// Valide le payload : une offre, ou un couple mentor/sujet, pas les deux.
+    pub fn target(&self) -> Result<WaitlistTarget, String> {
+        if self.max_tokens.is_some_and(|t| t < 0) {
+            return Err("max_tokens doit être positif".to_string());
+        }
+        let non_empty = |s: &Option<String>| {
+            s.as_deref()
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+        match (
+            non_empty(&self.offer_id),
+            non_empty(&self.mentor_id),
+            non_empty(&self.topic_slug),
+        ) {
+            (Some(offer_id), None, None) => Ok(WaitlistTarget::Offer(offer_id)),
+            (None, Some(mentor_id), Some(topic_slug)) => Ok(WaitlistTarget::MentorTopic {
+                mentor_id,
+                topic_slug,
+            }),
+            (Some(_), _, _) => Err("offer_id exclut mentor_id et topic_slug".to_string()),
+            _ => Err("offer_id, ou mentor_id et topic_slug, requis".to_string()),
+        }
+    }
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(
+        offer: Option<&str>,
+        mentor: Option<&str>,
+        topic: Option<&str>,
+    ) -> JoinWaitlistPayload {
+        JoinWaitlistPayload {
+            offer_id: offer.map(str::to_string),
+            mentor_id: mentor.map(str::to_string),
+            topic_slug: topic.map(str::to_string),
+            max_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_target_resolution() {
+        assert_eq!(
+            payload(Some("o1"), None, None).target(),
+            Ok(WaitlistTarget::Offer("o1".to_string()))
+        );
+        assert_eq!(
+            payload(None, Some("m1"), Some("rust")).target(),
+            Ok(WaitlistTarget::MentorTopic {
+                mentor_id: "m1".to_string(),
+                topic_slug: "rust".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        assert!(payload(None, None, None).target().is_err());
+        assert!(payload(None, Some("m1"), None).target().is_err());
+        assert!(payload(None, Some("m1"), Some("  ")).target().is_err());
+        assert!(payload(Some("o1"), Some("m1"), Some("rust"))
+            .target()
+            .is_err());
+
+        let negative = JoinWaitlistPayload {
+            max_tokens: Some(-1),
+            ..payload(Some("o1"), None, None)
+        };
+        assert!(negative.target().is_err());
+    }
+}
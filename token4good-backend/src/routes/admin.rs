@@ -1,18 +1,96 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     response::Json,
-    routing::get,
+    routing::{get, post, put},
     Router,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::middleware::auth::AuthUser;
+use crate::models::certificate::{CertificateBranding, UpsertBrandingPayload};
+use crate::models::dispute::{
+    AssignDisputePayload, Dispute, DisputeCase, DisputeEvent, DisputeMessagePayload,
+    DisputeOutcome, ResolveDisputePayload,
+};
+use crate::models::matching::MatchingEvaluation;
+use crate::models::outbox::{OutboxEvent, OutboxQuery};
+use crate::models::review::{ModerateReviewPayload, ModerationAction, ModerationItem};
+use crate::models::scheduled_job::{JobRun, JobRunsQuery, ScheduledJob, SchedulerOverview};
+use crate::models::skills::SetPrerequisitesPayload;
+use crate::models::token_adjustment::{
+    AdjustmentEvent, CreateAdjustmentPayload, ReviewAdjustmentPayload, TokenAdjustment,
+};
+use crate::routes::mentoring_offers::{
+    certificate_status, dispute_status, notify, review_status, skill_status,
+};
+use crate::routes::token4good::statement_response;
+use crate::services::{
+    certificate, disputes,
+    escrow_reconciliation::{self, ReconciliationError, ReconciliationReport, ReconciliationRun},
+    ledger_chain,
+    ledger_statement::{self, StatementFormat, StatementQuery},
+    matching, mentoring_completion,
+    outbox::{self, OutboxError},
+    reviews,
+    scheduler::{self, SchedulerError},
+    skills,
+    token_adjustment::{self, AdjustmentError},
+};
 use crate::AppState;
 
 pub fn admin_routes() -> Router<AppState> {
     Router::new()
         .route("/wallets", get(get_all_wallets))
         .route("/stats", get(get_admin_stats))
+        .route("/ledger/verify", get(verify_ledger_chain))
+        .route("/ledger/sign-head", post(sign_ledger_head))
+        .route("/ledger/statement", get(get_platform_statement))
+        .route("/escrow/reconciliation", get(get_reconciliation_report))
+        .route("/escrow/reconciliation/runs", get(list_reconciliation_runs))
+        .route(
+            "/escrow/reconciliation/run",
+            post(run_escrow_reconciliation),
+        )
+        .route(
+            "/token-adjustments",
+            get(list_token_adjustments).post(create_token_adjustment),
+        )
+        .route("/token-adjustments/audit", get(get_adjustment_audit_trail))
+        .route("/token-adjustments/:id", get(get_token_adjustment))
+        .route(
+            "/token-adjustments/:id/approve",
+            post(approve_token_adjustment),
+        )
+        .route(
+            "/token-adjustments/:id/reject",
+            post(reject_token_adjustment),
+        )
+        .route("/disputes", get(list_disputes))
+        .route("/disputes/:id", get(get_dispute))
+        .route("/disputes/:id/assign", post(assign_dispute))
+        .route("/disputes/:id/notes", post(add_dispute_note))
+        .route("/disputes/:id/resolve", post(resolve_dispute))
+        .route("/matching/evaluation", get(get_matching_evaluation))
+        .route("/reviews/reports", get(get_review_moderation_queue))
+        .route("/reviews/:booking_id/moderate", post(moderate_review))
+        .route(
+            "/learning/topics/:slug/prerequisites",
+            put(set_topic_prerequisites),
+        )
+        .route("/certificate-brandings", get(list_certificate_brandings))
+        .route(
+            "/certificate-brandings/:slug",
+            put(upsert_certificate_branding),
+        )
+        .route("/jobs", get(get_scheduler_overview))
+        .route("/jobs/:name/runs", get(list_job_runs))
+        .route("/jobs/:name/pause", post(pause_job))
+        .route("/jobs/:name/resume", post(resume_job))
+        .route("/jobs/:name/trigger", post(trigger_job))
+        .route("/outbox", get(list_outbox_events))
+        .route("/outbox/replay-dead", post(replay_dead_outbox_events))
+        .route("/outbox/:id/replay", post(replay_outbox_event))
 }
 
 #[derive(Debug, Deserialize)]
@@ -116,6 +194,223 @@ pub async fn get_admin_stats(
     Ok(Json(stats))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct LedgerVerifyQuery {
+    pub user_id: Option<String>,
+}
+
+/// Parcourt la chaîne de hachage du ledger et signale le premier maillon cassé.
+pub async fn verify_ledger_chain(
+    State(state): State<AppState>,
+    Query(query): Query<LedgerVerifyQuery>,
+) -> Result<Json<ledger_chain::ChainReport>, StatusCode> {
+    let report = ledger_chain::verify_chain(
+        state.db.pool(),
+        query.user_id.as_deref(),
+        state.rgb.issuer_pubkey(),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Ledger chain verification failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(b) = &report.first_break {
+        tracing::warn!(
+            "Ledger chain broken at #{} ({}): {}",
+            b.chain_seq,
+            b.transaction_id,
+            b.reason
+        );
+    }
+
+    Ok(Json(report))
+}
+
+/// Force le scellement et la signature de la tête de chaîne (hors job périodique).
+pub async fn sign_ledger_head(
+    State(state): State<AppState>,
+) -> Result<Json<Option<ledger_chain::SignedHead>>, StatusCode> {
+    let head = ledger_chain::sign_chain_head(state.db.pool(), &state.rgb)
+        .await
+        .map_err(|e| {
+            tracing::error!("Ledger head signing failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(head))
+}
+
+/// Export comptable plateforme : toutes les opérations de la période.
+pub async fn get_platform_statement(
+    State(state): State<AppState>,
+    Query(params): Query<StatementQuery>,
+) -> Result<axum::response::Response, StatusCode> {
+    let format = StatementFormat::parse(params.format.as_deref()).ok_or(StatusCode::BAD_REQUEST)?;
+    let (from, to) = params.period().map_err(|e| {
+        tracing::warn!("Invalid statement period: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let statement = ledger_statement::build_statement(state.db.pool(), None, from, to)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error building platform statement: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(statement_response(&statement, format))
+}
+
+// ============================================================
+// Ajustements manuels de solde (quatre yeux)
+// ============================================================
+
+fn adjustment_error_status(e: AdjustmentError) -> StatusCode {
+    match e {
+        AdjustmentError::NotFound => StatusCode::NOT_FOUND,
+        AdjustmentError::Validation(msg) => {
+            tracing::warn!("Rejected token adjustment: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        AdjustmentError::SelfReview => StatusCode::FORBIDDEN,
+        AdjustmentError::InvalidState(_) | AdjustmentError::InsufficientBalance => {
+            StatusCode::CONFLICT
+        }
+        AdjustmentError::Database(e) => {
+            tracing::error!("Token adjustment database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdjustmentQuery {
+    pub status: Option<String>,
+    pub user_id: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdjustmentDetail {
+    #[serde(flatten)]
+    pub adjustment: TokenAdjustment,
+    pub events: Vec<AdjustmentEvent>,
+}
+
+/// Crédit ou débit manuel. Au-delà du seuil, reste en attente d'un second admin.
+pub async fn create_token_adjustment(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Json(payload): Json<CreateAdjustmentPayload>,
+) -> Result<(StatusCode, Json<TokenAdjustment>), StatusCode> {
+    let adjustment = token_adjustment::request_adjustment(state.db.pool(), &auth_user.id, &payload)
+        .await
+        .map_err(adjustment_error_status)?;
+
+    let status = if adjustment.requires_approval {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::CREATED
+    };
+    Ok((status, Json(adjustment)))
+}
+
+pub async fn list_token_adjustments(
+    State(state): State<AppState>,
+    Query(query): Query<AdjustmentQuery>,
+) -> Result<Json<Vec<TokenAdjustment>>, StatusCode> {
+    let adjustments = token_adjustment::list_adjustments(
+        state.db.pool(),
+        query.status.as_deref(),
+        query.user_id.as_deref(),
+        query.limit.unwrap_or(50).min(200),
+        query.offset.unwrap_or(0),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to list token adjustments: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(adjustments))
+}
+
+pub async fn get_token_adjustment(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<AdjustmentDetail>, StatusCode> {
+    let pool = state.db.pool();
+    let adjustment = token_adjustment::get_adjustment(pool, &id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch token adjustment {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let events = token_adjustment::list_events(pool, Some(&id), 200, 0)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch adjustment events {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(AdjustmentDetail { adjustment, events }))
+}
+
+pub async fn approve_token_adjustment(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReviewAdjustmentPayload>,
+) -> Result<Json<TokenAdjustment>, StatusCode> {
+    token_adjustment::approve_adjustment(
+        state.db.pool(),
+        &id,
+        &auth_user.id,
+        payload.comment.as_deref(),
+    )
+    .await
+    .map(Json)
+    .map_err(adjustment_error_status)
+}
+
+pub async fn reject_token_adjustment(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReviewAdjustmentPayload>,
+) -> Result<Json<TokenAdjustment>, StatusCode> {
+    token_adjustment::reject_adjustment(
+        state.db.pool(),
+        &id,
+        &auth_user.id,
+        payload.comment.as_deref(),
+    )
+    .await
+    .map(Json)
+    .map_err(adjustment_error_status)
+}
+
+/// Piste d'audit complète : demandes, approbations, rejets et applications.
+pub async fn get_adjustment_audit_trail(
+    State(state): State<AppState>,
+    Query(query): Query<AdjustmentQuery>,
+) -> Result<Json<Vec<AdjustmentEvent>>, StatusCode> {
+    let events = token_adjustment::list_events(
+        state.db.pool(),
+        None,
+        query.limit.unwrap_or(100).min(500),
+        query.offset.unwrap_or(0),
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to fetch adjustment audit trail: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok(Json(events))
+}
+
 #[derive(Debug, Serialize)]
 pub struct AdminWalletInfo {
     pub user_id: String,
@@ -140,3 +435,402 @@ pub struct AdminStats {
     pub active_users_last_30_days: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ReconciliationQuery {
+    pub run_id: Option<String>,
+    pub auto_fix: Option<bool>,
+    pub limit: Option<i64>,
+}
+
+/// Lance une passe de rapprochement séquestres ↔ ledger (corrections sûres si `auto_fix=true`).
+pub async fn run_escrow_reconciliation(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthUser>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>, StatusCode> {
+    let report = escrow_reconciliation::run_reconciliation(
+        state.db.pool(),
+        query.auto_fix.unwrap_or(false),
+        Some(&admin.id),
+    )
+    .await
+    .map_err(|e| match e {
+        ReconciliationError::AlreadyRunning => StatusCode::CONFLICT,
+        ReconciliationError::Database(e) => {
+            tracing::error!("Escrow reconciliation failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    })?;
+    Ok(Json(report))
+}
+
+/// Rapport d'une passe (la dernière terminée par défaut).
+pub async fn get_reconciliation_report(
+    State(state): State<AppState>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<ReconciliationReport>, StatusCode> {
+    escrow_reconciliation::get_report(state.db.pool(), query.run_id.as_deref().unwrap_or(""))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load reconciliation report: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+pub async fn list_reconciliation_runs(
+    State(state): State<AppState>,
+    Query(query): Query<ReconciliationQuery>,
+) -> Result<Json<Vec<ReconciliationRun>>, StatusCode> {
+    let runs =
+        escrow_reconciliation::list_runs(state.db.pool(), query.limit.unwrap_or(30).clamp(1, 200))
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to list reconciliation runs: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    Ok(Json(runs))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DisputeQuery {
+    pub status: Option<String>,
+}
+
+/// File des litiges, les plus anciens d'abord.
+pub async fn list_disputes(
+    State(state): State<AppState>,
+    Query(query): Query<DisputeQuery>,
+) -> Result<Json<Vec<Dispute>>, StatusCode> {
+    disputes::list(state.db.pool(), query.status.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Failed to list disputes: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Dossier complet, notes internes comprises.
+pub async fn get_dispute(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<DisputeCase>, StatusCode> {
+    disputes::get(state.db.pool(), &id)
+        .await
+        .map(Json)
+        .map_err(dispute_status)
+}
+
+/// Assigne le litige (à soi-même si `admin_id` est absent).
+pub async fn assign_dispute(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    payload: Option<Json<AssignDisputePayload>>,
+) -> Result<Json<Dispute>, StatusCode> {
+    let assignee = payload
+        .and_then(|Json(p)| p.admin_id)
+        .unwrap_or_else(|| auth_user.id.clone());
+    disputes::assign(state.db.pool(), &id, &auth_user.id, &assignee)
+        .await
+        .map(Json)
+        .map_err(dispute_status)
+}
+
+pub async fn add_dispute_note(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<DisputeMessagePayload>,
+) -> Result<Json<DisputeEvent>, StatusCode> {
+    disputes::add_note(state.db.pool(), &id, &auth_user.id, &payload)
+        .await
+        .map(Json)
+        .map_err(dispute_status)
+}
+
+/// Tranche le litige, règle le séquestre et prévient les deux parties.
+pub async fn resolve_dispute(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(id): Path<String>,
+    Json(payload): Json<ResolveDisputePayload>,
+) -> Result<Json<Dispute>, StatusCode> {
+    let pool = state.db.pool();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let resolution = disputes::resolve(&mut tx, &id, &auth_user.id, &payload)
+        .await
+        .map_err(dispute_status)?;
+
+    let parties = &resolution.parties;
+    let allocation = resolution.allocation;
+    let decision = match payload.outcome {
+        DisputeOutcome::FullRefund => "séquestre intégralement remboursé au mentee",
+        DisputeOutcome::FullRelease => "séquestre intégralement versé au mentor",
+        DisputeOutcome::Split => "séquestre partagé entre le mentee et le mentor",
+    };
+    let link = format!("/mentoring/session/{}", parties.booking_id);
+    for (user_id, amount) in [
+        (&parties.mentee_id, allocation.refund_tokens),
+        (&parties.mentor_id, allocation.release_tokens),
+    ] {
+        notify(
+            &mut *tx,
+            user_id,
+            "Litige résolu",
+            &format!(
+                "Le litige sur la session «{}» a été tranché : {}. Vous recevez {} T4G.",
+                parties.topic_slug, decision, amount
+            ),
+            "MENTORING_DISPUTE_RESOLVED",
+            Some(&link),
+            Some(amount as i32),
+        )
+        .await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if resolution.proof_queued {
+        mentoring_completion::record_skill_evidence(pool, &parties.booking_id).await;
+    }
+
+    Ok(Json(resolution.dispute))
+}
+
+/// File de modération des avis signalés, plus ancien signalement d'abord.
+pub async fn get_review_moderation_queue(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ModerationItem>>, StatusCode> {
+    reviews::moderation_queue(state.db.pool())
+        .await
+        .map(Json)
+        .map_err(review_status)
+}
+
+/// Masque l'avis signalé ou classe ses signalements.
+pub async fn moderate_review(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(booking_id): Path<String>,
+    Json(payload): Json<ModerateReviewPayload>,
+) -> Result<Json<ModerationItem>, StatusCode> {
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let item = reviews::moderate(&mut tx, &booking_id, &auth_user.id, &payload)
+        .await
+        .map_err(review_status)?;
+
+    if payload.action == ModerationAction::Hide {
+        notify(
+            &mut *tx,
+            &item.review.mentee_id,
+            "Avis masqué",
+            &format!(
+                "Ton avis sur la session «{}» a été masqué après modération.",
+                item.review.topic_slug
+            ),
+            "MENTORING_REVIEW_HIDDEN",
+            Some(&format!("/mentoring/session/{}", booking_id)),
+            None,
+        )
+        .await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(item))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatchingEvaluationQuery {
+    pub days: Option<i64>,
+    pub k: Option<usize>,
+}
+
+/// Évaluation hors ligne du classement sur les réservations complétées.
+pub async fn get_matching_evaluation(
+    State(state): State<AppState>,
+    Query(query): Query<MatchingEvaluationQuery>,
+) -> Result<Json<MatchingEvaluation>, StatusCode> {
+    let days = query.days.unwrap_or(90).clamp(1, 365);
+    let k = query.k.unwrap_or(5).clamp(1, 50);
+    matching::evaluate(state.db.pool(), days, k)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Matching evaluation failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// Remplace les prérequis d'un sujet du référentiel (refuse les cycles).
+pub async fn set_topic_prerequisites(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Json(payload): Json<SetPrerequisitesPayload>,
+) -> Result<Json<Vec<String>>, StatusCode> {
+    skills::set_prerequisites(state.db.pool(), &slug, &payload.prerequisites)
+        .await
+        .map(Json)
+        .map_err(skill_status)
+}
+
+/// GET /api/admin/certificate-brandings — habillages des certificats
+pub async fn list_certificate_brandings(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<CertificateBranding>>, StatusCode> {
+    certificate::list_brandings(state.db.pool())
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error listing certificate brandings: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// PUT /api/admin/certificate-brandings/:slug — crée ou remplace un habillage
+pub async fn upsert_certificate_branding(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Json(payload): Json<UpsertBrandingPayload>,
+) -> Result<Json<CertificateBranding>, StatusCode> {
+    certificate::upsert_branding(state.db.pool(), &slug, &payload)
+        .await
+        .map(Json)
+        .map_err(certificate_status)
+}
+
+fn scheduler_status(e: SchedulerError) -> StatusCode {
+    match e {
+        SchedulerError::NotFound => StatusCode::NOT_FOUND,
+        SchedulerError::Database(e) => {
+            tracing::error!("Scheduler query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// GET /api/admin/jobs — tâches planifiées, dernière exécution, instance élue
+pub async fn get_scheduler_overview(
+    State(state): State<AppState>,
+) -> Result<Json<SchedulerOverview>, StatusCode> {
+    scheduler::overview(state.db.pool())
+        .await
+        .map(Json)
+        .map_err(|e| scheduler_status(e.into()))
+}
+
+/// GET /api/admin/jobs/:name/runs — historique des exécutions
+pub async fn list_job_runs(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Query(query): Query<JobRunsQuery>,
+) -> Result<Json<Vec<JobRun>>, StatusCode> {
+    scheduler::list_runs(
+        state.db.pool(),
+        &name,
+        query.limit.unwrap_or(50).clamp(1, 500),
+    )
+    .await
+    .map(Json)
+    .map_err(scheduler_status)
+}
+
+/// POST /api/admin/jobs/:name/pause
+pub async fn pause_job(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ScheduledJob>, StatusCode> {
+    scheduler::set_paused(state.db.pool(), &name, true)
+        .await
+        .map(Json)
+        .map_err(scheduler_status)
+}
+
+/// POST /api/admin/jobs/:name/resume
+pub async fn resume_job(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Result<Json<ScheduledJob>, StatusCode> {
+    scheduler::set_paused(state.db.pool(), &name, false)
+        .await
+        .map(Json)
+        .map_err(scheduler_status)
+}
+
+/// POST /api/admin/jobs/:name/trigger — exécution immédiate, lancée par
+/// l'instance élue à sa prochaine scrutation
+pub async fn trigger_job(
+    State(state): State<AppState>,
+    Extension(admin): Extension<AuthUser>,
+    Path(name): Path<String>,
+) -> Result<(StatusCode, Json<ScheduledJob>), StatusCode> {
+    scheduler::request_trigger(state.db.pool(), &name, &admin.id)
+        .await
+        .map(|job| (StatusCode::ACCEPTED, Json(job)))
+        .map_err(scheduler_status)
+}
+
+fn outbox_status(e: OutboxError) -> StatusCode {
+    match e {
+        OutboxError::NotFound => StatusCode::NOT_FOUND,
+        OutboxError::NotReplayable(_) => StatusCode::CONFLICT,
+        OutboxError::Database(e) => {
+            tracing::error!("Outbox query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// GET /api/admin/outbox?status=dead&kind=rgb_proof — effets de bord en file,
+/// livrés ou abandonnés
+pub async fn list_outbox_events(
+    State(state): State<AppState>,
+    Query(query): Query<OutboxQuery>,
+) -> Result<Json<Vec<OutboxEvent>>, StatusCode> {
+    outbox::list(state.db.pool(), &query)
+        .await
+        .map(Json)
+        .map_err(|e| outbox_status(e.into()))
+}
+
+/// POST /api/admin/outbox/:id/replay — remet en file une entrée abandonnée
+/// ou ignorée
+pub async fn replay_outbox_event(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<OutboxEvent>, StatusCode> {
+    outbox::replay(state.db.pool(), &id)
+        .await
+        .map(Json)
+        .map_err(outbox_status)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayDeadQuery {
+    pub kind: Option<String>,
+}
+
+/// POST /api/admin/outbox/replay-dead?kind=dazno_sync — remet en file toutes
+/// les entrées abandonnées
+pub async fn replay_dead_outbox_events(
+    State(state): State<AppState>,
+    Query(query): Query<ReplayDeadQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    outbox::replay_dead(state.db.pool(), query.kind.as_deref())
+        .await
+        .map(|replayed| Json(serde_json::json!({ "replayed": replayed })))
+        .map_err(|e| outbox_status(e.into()))
+}
@@ -855,7 +855,7 @@ fn generate_auth_response(user: User) -> Result<Json<AuthResponse>, StatusCode>
 fn base64url_encode(data: &[u8]) -> String {
     const CHARS: &[u8] =
         b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
-    let mut result = Vec::with_capacity((data.len() * 4 + 2) / 3);
+    let mut result = Vec::with_capacity((data.len() * 4).div_ceil(3));
     let mut chunks = data.chunks_exact(3);
 
     for chunk in &mut chunks {
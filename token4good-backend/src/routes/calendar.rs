@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+
+use crate::{middleware::auth_extractor::AuthUserExtractor, services::calendar, AppState};
+
+/// Flux iCalendar public — le jeton secret de l'URL fait office d'authentification
+pub fn calendar_feed_routes() -> Router<AppState> {
+    Router::new().route("/feed/:token", get(get_feed))
+}
+
+// Routes sur /api/users/me — gestion de l'URL de flux
+pub fn calendar_user_routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/me/calendar-feed",
+            get(get_feed_status).delete(revoke_feed),
+        )
+        .route("/me/calendar-feed/rotate", post(rotate_feed))
+}
+
+/// URL publique du flux, basée sur API_PUBLIC_URL
+fn feed_url(token: &str) -> String {
+    format!(
+        "{}/api/calendar/feed/{}.ics",
+        calendar::api_public_url(),
+        token
+    )
+}
+
+/// Réponse `text/calendar` (partagée avec l'export d'une réservation)
+pub fn ics_response(body: String, filename: Option<&str>) -> Response {
+    let mut response = (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        body,
+    )
+        .into_response();
+    if let Some(filename) = filename {
+        if let Ok(value) = format!("attachment; filename=\"{}\"", filename).parse() {
+            response
+                .headers_mut()
+                .insert(header::CONTENT_DISPOSITION, value);
+        }
+    }
+    response
+}
+
+/// GET /api/calendar/feed/:token.ics — flux d'abonnement (Google, Outlook, Apple)
+pub async fn get_feed(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, StatusCode> {
+    let token = token.trim_end_matches(".ics");
+    if token.is_empty() || !token.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let user_id = calendar::user_for_token(state.db.pool(), token)
+        .await
+        .map_err(|e| {
+            tracing::error!("Calendar feed token lookup failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let body = calendar::user_feed(state.db.pool(), &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Calendar feed build failed for {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(ics_response(body, None))
+}
+
+/// GET /api/users/me/calendar-feed — état du flux (l'URL n'est affichée qu'à la génération)
+pub async fn get_feed_status(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let status = calendar::feed_status(state.db.pool(), &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Calendar feed status failed for {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(match status {
+        Some((created_at, last_accessed_at)) => serde_json::json!({
+            "enabled": true,
+            "created_at": created_at,
+            "last_accessed_at": last_accessed_at,
+        }),
+        None => serde_json::json!({ "enabled": false }),
+    }))
+}
+
+/// POST /api/users/me/calendar-feed/rotate — génère une nouvelle URL et invalide l'ancienne
+pub async fn rotate_feed(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let token = calendar::rotate_feed_token(state.db.pool(), &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Calendar feed rotation failed for {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    tracing::info!("Calendar feed token rotated for user {}", auth_user.id);
+    Ok(Json(serde_json::json!({ "url": feed_url(&token) })))
+}
+
+/// DELETE /api/users/me/calendar-feed — désactive le flux
+pub async fn revoke_feed(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<StatusCode, StatusCode> {
+    let revoked = calendar::revoke_feed_token(state.db.pool(), &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                "Calendar feed revocation failed for {}: {}",
+                auth_user.id,
+                e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if revoked {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(StatusCode::NOT_FOUND)
+    }
+}
@@ -0,0 +1,150 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use futures::Stream;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
+    models::chat::{ChatEvent, ChatMessage, ChatThread, ReadReceipt, SendMessagePayload},
+    services::chat::{self, ChatError},
+    AppState,
+};
+
+/// Fils de discussion — montées sous /api/mentoring avec les routes de réservation
+pub fn chat_routes() -> Router<AppState> {
+    Router::new()
+        .route("/bookings/:id/messages", get(get_thread).post(post_message))
+        .route("/bookings/:id/messages/read", post(mark_thread_read))
+        .route("/bookings/:id/messages/stream", get(stream_thread))
+}
+
+fn chat_status(e: ChatError) -> StatusCode {
+    match e {
+        ChatError::NotFound => StatusCode::NOT_FOUND,
+        ChatError::Forbidden => StatusCode::FORBIDDEN,
+        ChatError::Closed => StatusCode::CONFLICT,
+        ChatError::Invalid(msg) => {
+            tracing::warn!("Invalid chat message: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ChatError::Database(e) => {
+            tracing::error!("Chat query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ThreadQuery {
+    pub before: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/mentoring/bookings/:id/messages — historique paginé, accusés de lecture
+pub async fn get_thread(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Query(query): Query<ThreadQuery>,
+) -> Result<Json<ChatThread>, StatusCode> {
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    chat::history(state.db.pool(), &id, &auth_user.id, query.before, limit)
+        .await
+        .map(Json)
+        .map_err(chat_status)
+}
+
+/// POST /api/mentoring/bookings/:id/messages
+pub async fn post_message(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<SendMessagePayload>,
+) -> Result<(StatusCode, Json<ChatMessage>), StatusCode> {
+    chat::send(state.db.pool(), &id, &auth_user.id, &payload)
+        .await
+        .map(|message| (StatusCode::CREATED, Json(message)))
+        .map_err(chat_status)
+}
+
+/// POST /api/mentoring/bookings/:id/messages/read — accusé de lecture
+pub async fn mark_thread_read(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<ReadReceipt>, StatusCode> {
+    chat::mark_read(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(chat_status)
+}
+
+/// Événement SSE correspondant à un événement du fil
+async fn sse_event(pool: &sqlx::PgPool, event: &ChatEvent) -> Option<Event> {
+    match event {
+        ChatEvent::Message { message_id, .. } => {
+            let message = chat::get_message(pool, message_id).await.ok()?;
+            Event::default()
+                .event("message")
+                .id(message.id.clone())
+                .json_data(&message)
+                .ok()
+        }
+        ChatEvent::Read {
+            user_id,
+            last_read_at,
+            ..
+        } => Event::default()
+            .event("read")
+            .json_data(ReadReceipt {
+                user_id: user_id.clone(),
+                last_read_at: *last_read_at,
+            })
+            .ok(),
+    }
+}
+
+/// GET /api/mentoring/bookings/:id/messages/stream — flux SSE du fil
+///
+/// Authentifié par l'en-tête Authorization (client SSE basé sur fetch).
+/// Événements : `message`, `read`, et `resync` si le client a décroché et
+/// doit recharger l'historique.
+pub async fn stream_thread(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
+    chat::access(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map_err(chat_status)?;
+
+    let rx = state.chat.subscribe();
+    let pool = state.db.pool().clone();
+    let stream = futures::stream::unfold((rx, pool, id), |(mut rx, pool, booking_id)| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) if event.booking_id() == booking_id => {
+                    if let Some(sse) = sse_event(&pool, &event).await {
+                        return Some((Ok(sse), (rx, pool, booking_id)));
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(_)) => {
+                    let resync = Event::default().event("resync").data("");
+                    return Some((Ok(resync), (rx, pool, booking_id)));
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
@@ -1,6 +1,7 @@
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::Response,
     routing::get,
     Json, Router,
 };
@@ -8,7 +9,12 @@ use serde::Deserialize;
 use sqlx::Row;
 
 use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
+    models::certificate::CertificateQuery,
     models::learning::{LearningCategory, LearningCategoryRef, LearningTopicWithCategory},
+    models::skills::{LearningPath, SkillProgress},
+    routes::{mentoring_offers::certificate_status, proofs::pdf_response},
+    services::{certificate, skills},
     AppState,
 };
 
@@ -18,6 +24,14 @@ pub fn learning_routes() -> Router<AppState> {
         .route("/topics", get(get_topics))
 }
 
+// Routes sur /api/users — à enregistrer dans users_routes
+pub fn learning_user_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/learning-path", get(get_my_learning_path))
+        .route("/:id/skills", get(get_user_skills))
+        .route("/me/skills/:slug/certificate", get(get_my_path_certificate))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TopicsQuery {
     pub category: Option<String>,
@@ -117,3 +131,55 @@ async fn get_topics(
 
     Ok(Json(topics))
 }
+
+/// GET /api/users/me/learning-path — compétences acquises et prochains sujets suggérés
+async fn get_my_learning_path(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<LearningPath>, StatusCode> {
+    skills::learning_path(state.db.pool(), &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error building learning path for {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// GET /api/users/:id/skills — compétences d'un utilisateur, avec preuves et recommandations
+async fn get_user_skills(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<SkillProgress>>, StatusCode> {
+    skills::progress(state.db.pool(), &id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error fetching skills for {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// GET /api/users/me/skills/:slug/certificate?lang=&branding= — certificat PDF
+/// du parcours sur un sujet
+async fn get_my_path_certificate(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(slug): Path<String>,
+    Query(query): Query<CertificateQuery>,
+) -> Result<Response, StatusCode> {
+    let lang = query.language().map_err(|e| {
+        tracing::warn!("{}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let pool = state.db.pool();
+    let cert = certificate::path_certificate(pool, &auth_user.id, &slug)
+        .await
+        .map_err(certificate_status)?;
+    let branding = certificate::resolve_branding(pool, query.branding.as_deref(), &auth_user.id)
+        .await
+        .map_err(certificate_status)?;
+
+    let bytes = certificate::render_path(&cert, &branding, lang);
+    Ok(pdf_response(bytes, &cert.file_name()))
+}
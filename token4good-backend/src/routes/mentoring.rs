@@ -1,196 +1,247 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::StatusCode,
     routing::{get, post},
     Json, Router,
 };
 
 use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
     models::mentoring::{
-        CreateProofPayload, CreateRequestPayload, MentoringProof, MentoringRequest, RequestStatus,
+        CreateRequestPayload, MentoringProof, MentoringRequest, MentoringRequestDetail,
+        RequestsQuery, RespondToRequestPayload,
     },
+    models::mentoring_offer::MentoringOffer,
+    routes::mentoring_offers::{notify, request_status, user_display_name},
+    services::mentoring_requests,
     AppState,
 };
 
 pub fn mentoring_routes() -> Router<AppState> {
     Router::new()
         .route("/requests", get(list_requests).post(create_request))
+        .route("/requests/mine", get(list_my_requests))
         .route("/requests/:id", get(get_request))
-        .route("/requests/:id/assign", post(assign_request))
-        .route("/proofs", get(list_proofs).post(create_proof))
+        .route("/requests/:id/offers", post(respond_to_request))
+        .route("/requests/:id/cancel", post(cancel_request))
+        .route("/proofs", get(list_proofs))
         .route("/proofs/:id", get(get_proof))
         .route("/proofs/:id/verify", get(verify_proof))
 }
 
-// Liste des demandes de mentoring
+// Demandes ouvertes, pour les mentors (filtres ?topic_slug=&tag=)
 async fn list_requests(
     State(state): State<AppState>,
-) -> Result<Json<Vec<MentoringRequest>>, String> {
-    state
-        .db
-        .find_requests_by_status("open")
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Query(query): Query<RequestsQuery>,
+) -> Result<Json<Vec<MentoringRequest>>, StatusCode> {
+    mentoring_requests::list_open(state.db.pool(), &auth_user.id, &query)
         .await
         .map(Json)
-        .map_err(|e| e.to_string())
+        .map_err(|e| {
+            tracing::error!("Error listing mentoring requests: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
 
-// Création d'une nouvelle demande
+// Création d'une nouvelle demande ; les mentors du sujet sont prévenus
 async fn create_request(
     State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
     Json(payload): Json<CreateRequestPayload>,
-) -> Result<Json<MentoringRequest>, String> {
-    let request = MentoringRequest::new(
-        payload.title,
-        payload.description,
-        "user_123".to_string(), // TODO: Get from auth context
-        payload.tags,
-    );
-
-    state
+) -> Result<(StatusCode, Json<MentoringRequest>), StatusCode> {
+    let mut tx = state
         .db
-        .create_request(&request)
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let request = mentoring_requests::create(&mut tx, &auth_user.id, &payload)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(request_status)?;
 
-    Ok(Json(request))
+    let mentors = mentoring_requests::interested_mentors(&mut *tx, &request)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Could not list mentors for request {}: {}", request.id, e);
+            vec![]
+        });
+    for mentor_id in mentors {
+        notify(
+            &mut *tx,
+            &mentor_id,
+            "Nouvelle demande de mentoring",
+            &format!(
+                "Un mentee cherche de l'aide : «{}». Propose-lui une session.",
+                request.title
+            ),
+            "MENTORING_REQUEST_NEW",
+            Some(&format!("/mentoring/requests/{}", request.id)),
+            None,
+        )
+        .await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(request)))
+}
+
+// Demandes du mentee connecté
+async fn list_my_requests(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<Vec<MentoringRequest>>, StatusCode> {
+    mentoring_requests::list_for_mentee(state.db.pool(), &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error listing requests of {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
 
-// Récupération d'une demande spécifique
+// Demande et offres privées reçues
 async fn get_request(
     State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
     Path(id): Path<String>,
-) -> Result<Json<Option<MentoringRequest>>, String> {
-    state
-        .db
-        .find_request_by_id(&id)
+) -> Result<Json<MentoringRequestDetail>, StatusCode> {
+    mentoring_requests::detail(state.db.pool(), &id, &auth_user.id)
         .await
         .map(Json)
-        .map_err(|e| e.to_string())
+        .map_err(request_status)
 }
 
-// Assignation d'un mentor à une demande
-async fn assign_request(
+// Réponse d'un mentor : offre privée, réservable par le mentee via
+// POST /api/mentoring/bookings (séquestre habituel)
+async fn respond_to_request(
     State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
     Path(id): Path<String>,
-) -> Result<Json<MentoringRequest>, String> {
-    // 1. Vérifier que la demande existe
-    let mut request = state
-        .db
-        .find_request_by_id(&id)
+    Json(payload): Json<RespondToRequestPayload>,
+) -> Result<(StatusCode, Json<MentoringOffer>), StatusCode> {
+    let pool = state.db.pool();
+    let mut tx = pool
+        .begin()
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Request not found".to_string())?;
-
-    // 2. Mettre à jour le statut
-    request.status = RequestStatus::Assigned;
-    request.mentor_id = Some("mentor_123".to_string()); // TODO: Get from auth context
-
-    // 3. Sauvegarder les modifications
-    state
-        .db
-        .update_request_status(&id, "assigned")
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (request, offer) = mentoring_requests::respond(&mut tx, &id, &auth_user.id, &payload)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(Json(request))
-}
-
-// Liste des preuves de mentoring
-async fn list_proofs(State(state): State<AppState>) -> Result<Json<Vec<MentoringProof>>, String> {
-    // TODO: Ajouter des filtres (mentor_id, mentee_id, etc.)
-    state
-        .db
-        .find_proofs_by_mentor("mentor_123") // TODO: Get from auth context
+        .map_err(request_status)?;
+
+    let mentor_name = user_display_name(pool, &auth_user.id).await;
+    notify(
+        &mut *tx,
+        &request.mentee_id,
+        "Un mentor répond à ta demande",
+        &format!(
+            "{} te propose une session pour «{}» ({} T4G).",
+            mentor_name, request.title, offer.token_cost
+        ),
+        "MENTORING_REQUEST_OFFER",
+        Some(&format!("/mentoring/requests/{}", request.id)),
+        None,
+    )
+    .await?;
+    tx.commit()
         .await
-        .map(Json)
-        .map_err(|e| e.to_string())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(offer)))
 }
 
-// Création d'une nouvelle preuve
-async fn create_proof(
+// Retrait d'une demande ouverte par son mentee
+async fn cancel_request(
     State(state): State<AppState>,
-    Json(payload): Json<CreateProofPayload>,
-) -> Result<Json<MentoringProof>, String> {
-    // 1. Vérifier que la demande existe
-    let request = state
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<MentoringRequest>, StatusCode> {
+    let mut tx = state
         .db
-        .find_request_by_id(&payload.request_id)
+        .pool()
+        .begin()
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Request not found".to_string())?;
-
-    // 2. Créer le contrat RGB
-    let (contract_id, signature) = state
-        .rgb
-        .create_proof_contract(
-            request.mentor_id.as_ref().unwrap_or(&String::new()),
-            &request.mentee_id,
-            &request.id,
-            payload.rating,
-            payload.comment.clone(),
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (request, mentors) = mentoring_requests::cancel(&mut tx, &id, &auth_user.id)
+        .await
+        .map_err(request_status)?;
+
+    for mentor_id in mentors {
+        notify(
+            &mut *tx,
+            &mentor_id,
+            "Demande retirée",
+            &format!(
+                "La demande «{}» a été retirée ; ta proposition est annulée.",
+                request.title
+            ),
+            "MENTORING_REQUEST_CANCELLED",
+            None,
+            None,
         )
+        .await?;
+    }
+    tx.commit()
         .await
-        .map_err(|e| e.to_string())?;
-
-    // 3. Créer la preuve dans MongoDB
-    let request_id = request.id.clone();
-    let proof = MentoringProof::new(
-        request.id,
-        request.mentor_id.clone().unwrap_or_default(),
-        request.mentee_id,
-        contract_id.to_string(),
-        signature,
-        payload.rating,
-        payload.comment,
-    );
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    state
-        .db
-        .create_proof(&proof)
-        .await
-        .map_err(|e| e.to_string())?;
+    Ok(Json(request))
+}
 
-    // 4. Mettre à jour le statut de la demande
+// Preuves de mentoring émises pour le mentor connecté
+async fn list_proofs(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<Vec<MentoringProof>>, StatusCode> {
     state
         .db
-        .update_request_status(&request_id, "completed")
+        .find_proofs_by_mentor(&auth_user.id)
         .await
-        .map_err(|e| e.to_string())?;
-
-    Ok(Json(proof))
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error listing proofs of {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
 
 // Récupération d'une preuve spécifique
 async fn get_proof(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<Option<MentoringProof>>, String> {
+) -> Result<Json<MentoringProof>, StatusCode> {
     state
         .db
         .find_proof_by_id(&id)
         .await
+        .map_err(|e| {
+            tracing::error!("Error fetching proof {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
         .map(Json)
-        .map_err(|e| e.to_string())
+        .ok_or(StatusCode::NOT_FOUND)
 }
 
 // Vérification d'une preuve
 async fn verify_proof(
     State(state): State<AppState>,
     Path(id): Path<String>,
-) -> Result<Json<bool>, String> {
+) -> Result<Json<bool>, StatusCode> {
     // 1. Récupérer la preuve
     let proof = state
         .db
         .find_proof_by_id(&id)
         .await
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| "Proof not found".to_string())?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
 
     // 2. Vérifier la signature RGB
     let is_valid = state
         .rgb
         .verify_proof(&proof.rgb_contract_id, &proof.signature)
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(is_valid))
 }
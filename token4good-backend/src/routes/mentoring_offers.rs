@@ -9,47 +9,88 @@ use sqlx::Row;
 
 use crate::{
     middleware::auth_extractor::AuthUserExtractor,
+    models::dispute::{
+        Dispute, DisputeCase, DisputeEvent, DisputeMessagePayload, OpenDisputePayload,
+    },
+    models::attendance::{AttendanceStatus, CheckInCode, CheckInPayload},
+    models::matching::OfferMatch,
+    models::meeting::RoomAccess,
     models::mentoring_offer::{
-        ConfirmBookingPayload, CreateBookingPayload, CreateOfferPayload, MentoringBooking,
-        MentoringOffer, UpdateOfferPayload,
+        BookingReschedule, BookingStatus, CancelBookingPayload, CancellationPolicy,
+        CancellationSplit, CompleteGroupSessionPayload, ConfirmBookingPayload,
+        CreateBookingPayload, CreateOfferPayload, MentoringBooking, MentoringOffer,
+        ProposeReschedulePayload, UpdateOfferPayload,
+    },
+    models::offer_series::{CreateSeriesPayload, OfferSeries},
+    models::outbox::OutboxMessage,
+    models::skills::{EndorseSkillPayload, SkillEndorsement},
+    models::review::{
+        bayesian_average, ReportReviewPayload, Review, ReviewReplyPayload, ReviewReport,
+        ReviewsPage, REVIEW_PRIOR_WEIGHT,
+    },
+    models::time_slot::{
+        bookable_slots, offered_seats, parse_availability, seats_taken, validate_availability,
+        SlotWindow, MAX_CAPACITY, MAX_SLOT_RANGE_DAYS,
+    },
+    models::waitlist::{JoinWaitlistPayload, WaitlistEntry},
+    routes::calendar::ics_response,
+    services::{
+        attendance::{self, AttendanceError},
+        availability,
+        booking_state::{self, Actor, BookingEvent, SideEffect, TransitionError},
+        calendar,
+        certificate::CertificateError,
+        disputes::{self, DisputeError},
+        matching::{self, MatchingError},
+        meeting::{self, MeetingError},
+        mentoring_completion,
+        mentoring_requests::{self, RequestError},
+        offer_series, outbox,
+        rescheduling::{self, RescheduleError},
+        reviews::{self, ReviewError, ReviewQuery},
+        skills::{self, SkillError},
+        time_off::{self, TimeOffError},
+        token_ledger,
+        waitlist::{self, WaitlistError},
     },
-    services::mentoring_completion,
     AppState,
 };
 
 // ============================================================
-// Helper — notification fire-and-forget
+// Helper — notification via l'outbox
 // ============================================================
 
-async fn notify(
-    pool: &sqlx::PgPool,
+/// Inscrit une notification in-app dans l'outbox, dans la transaction de
+/// l'appelant le cas échéant ; le répartiteur la livre. Un échec est remonté :
+/// dans une transaction, il l'a déjà annulée.
+pub(crate) async fn notify<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
     user_id: &str,
     title: &str,
     message: &str,
     notif_type: &str,
     link: Option<&str>,
     amount: Option<i32>,
-) {
-    let metadata = serde_json::json!({ "amount": amount.unwrap_or(0) });
-    if let Err(e) = sqlx::query(
-        "INSERT INTO notifications (user_id, title, message, type, link, metadata)
-         VALUES ($1, $2, $3, $4, $5, $6)",
-    )
-    .bind(user_id)
-    .bind(title)
-    .bind(message)
-    .bind(notif_type)
-    .bind(link)
-    .bind(metadata)
-    .execute(pool)
-    .await
-    {
-        tracing::warn!("Failed to insert notification for {}: {}", user_id, e);
-    }
+) -> Result<(), StatusCode> {
+    let notification = OutboxMessage::Notification {
+        user_id: user_id.to_string(),
+        title: title.to_string(),
+        message: message.to_string(),
+        notif_type: notif_type.to_string(),
+        link: link.map(str::to_string),
+        metadata: serde_json::json!({ "amount": amount.unwrap_or(0) }),
+    };
+    outbox::enqueue(executor, &notification, None)
+        .await
+        .map(|_| ())
+        .map_err(|e| {
+            tracing::error!("Failed to enqueue notification for {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
 
 /// Fetch display name for a user (firstname lastname, fallback to "Un utilisateur")
-async fn user_display_name(pool: &sqlx::PgPool, user_id: &str) -> String {
+pub(crate) async fn user_display_name(pool: &sqlx::PgPool, user_id: &str) -> String {
     sqlx::query("SELECT firstname, lastname FROM users WHERE id = $1")
         .bind(user_id)
         .fetch_optional(pool)
@@ -64,20 +105,79 @@ async fn user_display_name(pool: &sqlx::PgPool, user_id: &str) -> String {
         .unwrap_or_else(|| "Un utilisateur".to_string())
 }
 
+/// Attribue une salle de visioconférence à une réservation confirmée (non-bloquant)
+async fn provision_room(pool: &sqlx::PgPool, booking_id: &str) {
+    if let Err(e) = meeting::provision(pool, booking_id).await {
+        tracing::error!("Meeting room provisioning failed for booking {}: {}", booking_id, e);
+    }
+}
+
+/// Propose une offre redevenue disponible à la liste d'attente (non-bloquant)
+async fn offer_to_waitlist(pool: &sqlx::PgPool, offer_id: &str) {
+    if let Err(e) = waitlist::offer_available(pool, offer_id).await {
+        tracing::error!("Waitlist hand-off failed for offer {}: {}", offer_id, e);
+    }
+}
+
 pub fn mentoring_offer_routes() -> Router<AppState> {
     Router::new()
         // Offres
         .route("/offers", get(list_offers).post(create_offer))
         .route("/offers/:id", get(get_offer))
+        .route("/offers/:id/slots", get(get_offer_slots))
         .route("/offers/:id/update", post(update_offer))
         .route("/offers/:id/cancel", post(cancel_offer))
+        .route("/offers/:id/complete-session", post(complete_group_session))
+        // Séries récurrentes
+        .route("/series", post(create_series))
+        .route("/series/:id", get(get_series))
+        .route("/series/:id/cancel", post(cancel_series))
         // Réservations
         .route("/bookings", post(create_booking))
         .route("/bookings/:id", get(get_booking))
         .route("/bookings/:id/confirm", post(confirm_booking))
-        .route("/bookings/:id/dispute", post(dispute_booking))
+        .route(
+            "/bookings/:id/dispute",
+            get(get_booking_dispute).post(dispute_booking),
+        )
+        .route("/bookings/:id/dispute/messages", post(add_dispute_evidence))
         .route("/bookings/:id/accept", post(accept_booking))
         .route("/bookings/:id/decline", post(decline_booking))
+        .route("/bookings/:id/withdraw", post(withdraw_booking))
+        .route(
+            "/bookings/:id/cancel",
+            get(get_cancellation_quote).post(cancel_booking),
+        )
+        .route("/bookings/:id/ics", get(get_booking_ics))
+        .route("/bookings/:id/history", get(get_booking_history))
+        .route("/bookings/:id/room", get(get_booking_room))
+        .route("/bookings/:id/attendance", get(get_booking_attendance))
+        .route("/bookings/:id/checkin-code", get(get_checkin_code))
+        .route("/bookings/:id/check-in", post(check_in_booking))
+        .route(
+            "/bookings/:id/reschedules",
+            get(list_reschedules).post(propose_reschedule),
+        )
+        .route(
+            "/bookings/:id/reschedules/:proposal_id/accept",
+            post(accept_reschedule),
+        )
+        .route(
+            "/bookings/:id/reschedules/:proposal_id/decline",
+            post(decline_reschedule),
+        )
+        // Recommandations personnalisées
+        .route("/recommendations", get(get_recommendations))
+        // Avis
+        .route("/mentors/:id/reviews", get(list_mentor_reviews))
+        .route("/topics/:slug/reviews", get(list_topic_reviews))
+        .route("/bookings/:id/review/reply", post(reply_to_review))
+        .route("/bookings/:id/review/report", post(report_review))
+        // Compétences
+        .route("/bookings/:id/endorsements", post(endorse_skill))
+        // Listes d'attente
+        .route("/waitlist", post(join_waitlist))
+        .route("/waitlist/:id", axum::routing::delete(leave_waitlist))
         // Vue enrichie (booking + offre)
         .route("/sessions/:id", get(get_session_full))
 }
@@ -86,8 +186,10 @@ pub fn mentoring_offer_routes() -> Router<AppState> {
 pub fn mentoring_user_routes() -> Router<AppState> {
     Router::new()
         .route("/me/mentoring-offers", get(get_my_offers))
+        .route("/me/mentoring-series", get(get_my_series))
         .route("/me/mentoring-bookings", get(get_my_bookings))
         .route("/me/mentoring-received-bookings", get(get_received_bookings))
+        .route("/me/mentoring-waitlist", get(get_my_waitlist))
 }
 
 // ============================================================
@@ -102,10 +204,25 @@ pub struct OffersFilter {
     pub format: Option<String>,
     pub max_cost: Option<i32>,
     pub mentor_id: Option<String>,
+    #[serde(default)]
+    pub sort: OfferSort,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
 }
 
+/// Ordre de la liste des offres
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OfferSort {
+    /// Plus récentes d'abord
+    #[default]
+    Newest,
+    /// Meilleure note bayésienne du mentor d'abord
+    Rating,
+    /// Moins chères d'abord
+    Price,
+}
+
 // ============================================================
 // Handlers — Offres
 // ============================================================
@@ -117,25 +234,35 @@ pub async fn list_offers(
 ) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
     let limit = filters.limit.unwrap_or(50).min(100);
     let offset = filters.offset.unwrap_or(0);
+    let prior = reviews::prior_mean(state.db.pool()).await.map_err(|e| {
+        tracing::error!("Error computing rating prior: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
-    let mut sql = String::from(
+    let mut sql = format!(
         r#"
         SELECT
             o.id, o.mentor_id, o.topic_slug, o.target_level, o.description,
             o.duration_minutes, o.format, o.token_cost, o.availability,
-            o.status, o.created_at, o.updated_at,
+            o.status, o.capacity, o.series_id, o.occurrence_at, o.cancellation_policy,
+            o.created_at, o.updated_at,
             u.firstname AS mentor_firstname,
             u.lastname  AS mentor_lastname,
             u.avatar    AS mentor_avatar,
             u.score     AS mentor_score,
             u.mentor_bio AS mentor_bio,
-            t.name      AS topic_name
+            t.name      AS topic_name,
+            COALESCE(r.review_count, 0) AS mentor_review_count,
+            COALESCE(r.rating_sum, 0)   AS mentor_rating_sum
         FROM mentoring_offers o
         LEFT JOIN users u ON u.id::text = o.mentor_id
         LEFT JOIN learning_topics t ON t.slug = o.topic_slug
         LEFT JOIN learning_categories c ON c.id = t.category_id
-        WHERE o.status = 'open'
+        LEFT JOIN {} r ON r.mentor_id = o.mentor_id
+        WHERE o.status = 'open' AND o.request_id IS NULL AND NOT {}
         "#,
+        reviews::mentor_ratings_subquery(),
+        time_off::HIDDEN_BY_TIME_OFF
     );
 
     let mut param_idx: usize = 1;
@@ -172,8 +299,20 @@ pub async fn list_offers(
         param_idx += 1;
     }
 
+    // A priori calculé côté serveur, injecté tel quel dans le tri
+    let order_by = match filters.sort {
+        OfferSort::Newest => "o.created_at DESC".to_string(),
+        OfferSort::Rating => format!(
+            "({w} * {prior} + COALESCE(r.rating_sum, 0)) / ({w} + COALESCE(r.review_count, 0)) DESC, \
+             o.created_at DESC",
+            w = REVIEW_PRIOR_WEIGHT,
+            prior = prior
+        ),
+        OfferSort::Price => "o.token_cost ASC, o.created_at DESC".to_string(),
+    };
     sql.push_str(&format!(
-        " ORDER BY o.created_at DESC LIMIT ${} OFFSET ${}",
+        " ORDER BY {} LIMIT ${} OFFSET ${}",
+        order_by,
         param_idx,
         param_idx + 1
     ));
@@ -200,6 +339,8 @@ pub async fn list_offers(
             let mentor_score: Option<i32> = row.try_get("mentor_score").ok();
             let mentor_bio: Option<String> = row.try_get("mentor_bio").ok();
             let topic_name: Option<String> = row.try_get("topic_name").ok();
+            let review_count: i64 = row.try_get("mentor_review_count").unwrap_or(0);
+            let rating_sum: i64 = row.try_get("mentor_rating_sum").unwrap_or(0);
 
             serde_json::json!({
                 "id":               row.try_get::<String, _>("id").unwrap_or_default(),
@@ -212,6 +353,10 @@ pub async fn list_offers(
                 "token_cost":       row.try_get::<i32, _>("token_cost").unwrap_or(0),
                 "availability":     row.try_get::<serde_json::Value, _>("availability").unwrap_or(serde_json::Value::Array(vec![])),
                 "status":           row.try_get::<String, _>("status").unwrap_or_else(|_| "open".to_string()),
+                "capacity":         row.try_get::<i32, _>("capacity").unwrap_or(1),
+                "series_id":        row.try_get::<Option<String>, _>("series_id").unwrap_or(None),
+                "occurrence_at":    row.try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("occurrence_at").unwrap_or(None),
+                "cancellation_policy": CancellationPolicy::from_value(row.try_get("cancellation_policy").unwrap_or_default()),
                 "created_at":       row.try_get::<chrono::DateTime<chrono::Utc>, _>("created_at").unwrap_or_else(|_| chrono::Utc::now()),
                 "updated_at":       row.try_get::<chrono::DateTime<chrono::Utc>, _>("updated_at").unwrap_or_else(|_| chrono::Utc::now()),
                 "mentor": {
@@ -221,6 +366,11 @@ pub async fn list_offers(
                     "avatar_url": mentor_avatar,
                     "score":      mentor_score,
                     "mentor_bio": mentor_bio,
+                    "rating": {
+                        "review_count":     review_count,
+                        "average":          (review_count > 0).then(|| rating_sum as f64 / review_count as f64),
+                        "bayesian_average": bayesian_average(rating_sum, review_count, prior),
+                    },
                 },
                 "topic": {
                     "slug": topic_slug.clone(),
@@ -257,10 +407,15 @@ pub async fn get_offer(
         duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
         format: row.try_get("format").unwrap_or_default(),
         token_cost: row.try_get("token_cost").unwrap_or(0),
-        availability: row
-            .try_get("availability")
-            .unwrap_or(serde_json::Value::Array(vec![])),
+        availability: parse_availability(row.try_get("availability").unwrap_or_default()),
         status: row.try_get("status").unwrap_or_else(|_| "open".to_string()),
+        capacity: row.try_get("capacity").unwrap_or(1),
+        series_id: row.try_get("series_id").ok().flatten(),
+        occurrence_at: row.try_get("occurrence_at").ok().flatten(),
+        cancellation_policy: CancellationPolicy::from_value(
+            row.try_get("cancellation_policy").unwrap_or_default(),
+        ),
+        request_id: row.try_get("request_id").ok().flatten(),
         created_at: row
             .try_get("created_at")
             .unwrap_or_else(|_| chrono::Utc::now()),
@@ -270,6 +425,80 @@ pub async fn get_offer(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SlotsQuery {
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// GET /api/mentoring/offers/:id/slots — créneaux réservables sur une période
+/// (par défaut les 14 prochains jours), hors créneaux déjà pris chez le mentor
+pub async fn get_offer_slots(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<SlotsQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let now = chrono::Utc::now();
+    let from = query.from.unwrap_or(now).max(now);
+    let to = query.to.unwrap_or(from + chrono::Duration::days(14));
+    if to <= from || to - from > chrono::Duration::days(MAX_SLOT_RANGE_DAYS) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let row = sqlx::query(
+        "SELECT mentor_id, status, duration_minutes, capacity, availability FROM mentoring_offers WHERE id = $1",
+    )
+    .bind(&id)
+    .fetch_optional(state.db.pool())
+    .await
+    .map_err(|e| {
+        tracing::error!("Error fetching offer {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    let status: String = row.try_get("status").unwrap_or_default();
+    let duration_minutes: i32 = row.try_get("duration_minutes").unwrap_or(60);
+    let capacity: i32 = row.try_get("capacity").unwrap_or(1);
+    let slots = parse_availability(row.try_get("availability").unwrap_or_default());
+
+    let bookable = if status == "open" {
+        let mut conn = state
+            .db
+            .pool()
+            .acquire()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let occupancy = availability::mentor_occupancy(&mut conn, &mentor_id, from, to, None)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error loading schedule of mentor {}: {}", mentor_id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        bookable_slots(
+            &id,
+            &slots,
+            duration_minutes,
+            capacity,
+            from,
+            to,
+            &occupancy,
+        )
+    } else {
+        vec![]
+    };
+
+    Ok(Json(serde_json::json!({
+        "offer_id":         id,
+        "duration_minutes": duration_minutes,
+        "capacity":         capacity,
+        "from":             from,
+        "to":               to,
+        "slots":            bookable,
+    })))
+}
+
 /// POST /api/mentoring/offers — crée une offre (mentor authentifié)
 pub async fn create_offer(
     State(state): State<AppState>,
@@ -280,13 +509,26 @@ pub async fn create_offer(
     if payload.token_cost < 0 || payload.duration_minutes <= 0 {
         return Err(StatusCode::BAD_REQUEST);
     }
+    let capacity = payload.capacity.unwrap_or(1);
+    if !(1..=MAX_CAPACITY).contains(&capacity) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    validate_availability(&payload.availability, payload.duration_minutes).map_err(|e| {
+        tracing::warn!("Invalid availability on new offer: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+    let cancellation_policy = payload.cancellation_policy.clone().unwrap_or_default();
+    cancellation_policy.validate().map_err(|e| {
+        tracing::warn!("Invalid cancellation policy on new offer: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
 
     let row = sqlx::query(
         r#"
         INSERT INTO mentoring_offers
             (mentor_id, topic_slug, target_level, description, duration_minutes,
-             format, token_cost, availability, status)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'open')
+             format, token_cost, availability, status, capacity, cancellation_policy)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'open', $9, $10)
         RETURNING *
         "#,
     )
@@ -297,7 +539,9 @@ pub async fn create_offer(
     .bind(payload.duration_minutes)
     .bind(&payload.format)
     .bind(payload.token_cost)
-    .bind(&payload.availability)
+    .bind(serde_json::to_value(&payload.availability).unwrap_or_default())
+    .bind(capacity)
+    .bind(serde_json::to_value(&cancellation_policy).unwrap_or_default())
     .fetch_one(state.db.pool())
     .await
     .map_err(|e| {
@@ -305,11 +549,9 @@ pub async fn create_offer(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    tracing::info!(
-        "Mentor {} created offer {}",
-        auth_user.id,
-        row.try_get::<String, _>("id").unwrap_or_default()
-    );
+    let offer_id: String = row.try_get("id").unwrap_or_default();
+    tracing::info!("Mentor {} created offer {}", auth_user.id, offer_id);
+    offer_to_waitlist(state.db.pool(), &offer_id).await;
 
     Ok(Json(MentoringOffer {
         id: row.try_get("id").unwrap_or_default(),
@@ -320,10 +562,15 @@ pub async fn create_offer(
         duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
         format: row.try_get("format").unwrap_or_default(),
         token_cost: row.try_get("token_cost").unwrap_or(0),
-        availability: row
-            .try_get("availability")
-            .unwrap_or(serde_json::Value::Array(vec![])),
+        availability: parse_availability(row.try_get("availability").unwrap_or_default()),
         status: row.try_get("status").unwrap_or_else(|_| "open".to_string()),
+        capacity: row.try_get("capacity").unwrap_or(1),
+        series_id: row.try_get("series_id").ok().flatten(),
+        occurrence_at: row.try_get("occurrence_at").ok().flatten(),
+        cancellation_policy: CancellationPolicy::from_value(
+            row.try_get("cancellation_policy").unwrap_or_default(),
+        ),
+        request_id: row.try_get("request_id").ok().flatten(),
         created_at: row
             .try_get("created_at")
             .unwrap_or_else(|_| chrono::Utc::now()),
@@ -341,12 +588,14 @@ pub async fn update_offer(
     Json(payload): Json<UpdateOfferPayload>,
 ) -> Result<Json<MentoringOffer>, StatusCode> {
     // Vérifier que l'offre appartient bien à ce mentor
-    let existing = sqlx::query("SELECT mentor_id FROM mentoring_offers WHERE id = $1")
-        .bind(&id)
-        .fetch_optional(state.db.pool())
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let existing = sqlx::query(
+        "SELECT mentor_id, duration_minutes, availability FROM mentoring_offers WHERE id = $1",
+    )
+    .bind(&id)
+    .fetch_optional(state.db.pool())
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
 
     let owner_id: String = existing
         .try_get("mentor_id")
@@ -355,6 +604,38 @@ pub async fn update_offer(
         return Err(StatusCode::FORBIDDEN);
     }
 
+    if payload
+        .capacity
+        .is_some_and(|c| !(1..=MAX_CAPACITY).contains(&c))
+    {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if let Some(policy) = &payload.cancellation_policy {
+        policy.validate().map_err(|e| {
+            tracing::warn!("Invalid cancellation policy on offer {}: {}", id, e);
+            StatusCode::UNPROCESSABLE_ENTITY
+        })?;
+    }
+
+    // Les créneaux (nouveaux ou existants) doivent rester compatibles avec la durée
+    if payload.availability.is_some() || payload.duration_minutes.is_some() {
+        let duration = payload
+            .duration_minutes
+            .unwrap_or_else(|| existing.try_get("duration_minutes").unwrap_or(60));
+        if duration <= 0 {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let slots = match &payload.availability {
+            Some(slots) => slots.clone(),
+            None => parse_availability(existing.try_get("availability").unwrap_or_default()),
+        };
+        validate_availability(&slots, duration).map_err(|e| {
+            tracing::warn!("Invalid availability on offer {}: {}", id, e);
+            StatusCode::UNPROCESSABLE_ENTITY
+        })?;
+    }
+
     let row = sqlx::query(
         r#"
         UPDATE mentoring_offers SET
@@ -363,8 +644,10 @@ pub async fn update_offer(
             format           = COALESCE($3, format),
             token_cost       = COALESCE($4, token_cost),
             availability     = COALESCE($5, availability),
-            status           = COALESCE($6, status)
-        WHERE id = $7
+            status           = COALESCE($6, status),
+            capacity         = COALESCE($7, capacity),
+            cancellation_policy = COALESCE($8, cancellation_policy)
+        WHERE id = $9
         RETURNING *
         "#,
     )
@@ -372,8 +655,20 @@ pub async fn update_offer(
     .bind(payload.duration_minutes)
     .bind(&payload.format)
     .bind(payload.token_cost)
-    .bind(&payload.availability)
+    .bind(
+        payload
+            .availability
+            .as_ref()
+            .map(|a| serde_json::to_value(a).unwrap_or_default()),
+    )
     .bind(&payload.status)
+    .bind(payload.capacity)
+    .bind(
+        payload
+            .cancellation_policy
+            .as_ref()
+            .map(|p| serde_json::to_value(p).unwrap_or_default()),
+    )
     .bind(&id)
     .fetch_one(state.db.pool())
     .await
@@ -382,6 +677,11 @@ pub async fn update_offer(
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
+    // Nouveaux créneaux ou réouverture : proposer l'offre à la liste d'attente
+    if payload.availability.is_some() || payload.status.is_some() {
+        offer_to_waitlist(state.db.pool(), &id).await;
+    }
+
     Ok(Json(MentoringOffer {
         id: row.try_get("id").unwrap_or_default(),
         mentor_id: row.try_get("mentor_id").unwrap_or_default(),
@@ -391,10 +691,15 @@ pub async fn update_offer(
         duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
         format: row.try_get("format").unwrap_or_default(),
         token_cost: row.try_get("token_cost").unwrap_or(0),
-        availability: row
-            .try_get("availability")
-            .unwrap_or(serde_json::Value::Array(vec![])),
+        availability: parse_availability(row.try_get("availability").unwrap_or_default()),
         status: row.try_get("status").unwrap_or_else(|_| "open".to_string()),
+        capacity: row.try_get("capacity").unwrap_or(1),
+        series_id: row.try_get("series_id").ok().flatten(),
+        occurrence_at: row.try_get("occurrence_at").ok().flatten(),
+        cancellation_policy: CancellationPolicy::from_value(
+            row.try_get("cancellation_policy").unwrap_or_default(),
+        ),
+        request_id: row.try_get("request_id").ok().flatten(),
         created_at: row
             .try_get("created_at")
             .unwrap_or_else(|_| chrono::Utc::now()),
@@ -436,6 +741,210 @@ pub async fn cancel_offer(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// POST /api/mentoring/offers/:id/complete-session — le mentor clôture une
+/// session de groupe en déclarant les présents
+pub async fn complete_group_session(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<CompleteGroupSessionPayload>,
+) -> Result<Json<mentoring_completion::GroupCompletion>, StatusCode> {
+    let offer = sqlx::query(
+        "SELECT mentor_id, topic_slug, duration_minutes FROM mentoring_offers WHERE id = $1",
+    )
+    .bind(&id)
+    .fetch_optional(state.db.pool())
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mentor_id: String = offer.try_get("mentor_id").unwrap_or_default();
+    if mentor_id != auth_user.id {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    if payload.scheduled_at > chrono::Utc::now() {
+        return Err(StatusCode::CONFLICT);
+    }
+    let topic_slug: String = offer.try_get("topic_slug").unwrap_or_default();
+    let duration: i32 = offer.try_get("duration_minutes").unwrap_or(60);
+
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let outcome = mentoring_completion::complete_group_session(
+        &mut tx,
+        &id,
+        &mentor_id,
+        &topic_slug,
+        duration,
+        payload.scheduled_at,
+        &payload.attendees,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Error completing group session on offer {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if outcome.attended.is_empty() && outcome.absent.is_empty() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    for seat in &outcome.attended {
+        notify(
+            &mut *tx,
+            &seat.mentee_id,
+            "Session complétée 🎉",
+            &format!(
+                "Session de groupe «{}» terminée ! Tu as reçu {} T4G en bonus d'apprentissage.",
+                topic_slug, seat.tokens
+            ),
+            "MENTORING_COMPLETED",
+            Some(&format!("/mentoring/session/{}", seat.booking_id)),
+            Some(seat.tokens as i32),
+        )
+        .await?;
+    }
+    for seat in &outcome.absent {
+        notify(
+            &mut *tx,
+            &seat.mentee_id,
+            "Absence à la session",
+            &format!(
+                "Tu n'as pas été marqué présent à la session «{}» : {} T4G remboursés, \
+                 le reste revient au mentor.",
+                topic_slug, seat.tokens
+            ),
+            "MENTORING_CANCELLED",
+            Some(&format!("/mentoring/session/{}", seat.booking_id)),
+            Some(seat.tokens as i32),
+        )
+        .await?;
+    }
+    notify(
+        &mut *tx,
+        &mentor_id,
+        "Session de groupe complétée 🎉",
+        &format!(
+            "Tu as reçu {} T4G pour {} participant(s) à ta session sur «{}».",
+            outcome.tokens_to_mentor,
+            outcome.attended.len(),
+            topic_slug
+        ),
+        "MENTORING_COMPLETED",
+        None,
+        Some(outcome.tokens_to_mentor as i32),
+    )
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    for seat in &outcome.attended {
+        mentoring_completion::record_skill_evidence(state.db.pool(), &seat.booking_id).await;
+    }
+
+    Ok(Json(outcome))
+}
+
+// ============================================================
+// Handlers — Séries récurrentes
+// ============================================================
+
+/// POST /api/mentoring/series — crée une série et ses premières occurrences
+pub async fn create_series(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Json(payload): Json<CreateSeriesPayload>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    payload.validate().map_err(|e| {
+        tracing::warn!("Invalid offer series: {}", e);
+        StatusCode::UNPROCESSABLE_ENTITY
+    })?;
+
+    let series = offer_series::create_series(state.db.pool(), &auth_user.id, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error creating offer series: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let occurrences = offer_series::list_occurrences(state.db.pool(), &series.id)
+        .await
+        .unwrap_or_default();
+
+    Ok(Json(serde_json::json!({
+        "series":      series,
+        "occurrences": occurrences,
+    })))
+}
+
+/// GET /api/mentoring/series/:id — série et occurrences générées
+pub async fn get_series(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let series = offer_series::get_series(state.db.pool(), &id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error fetching series {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let occurrences = offer_series::list_occurrences(state.db.pool(), &id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(serde_json::json!({
+        "series":      series,
+        "occurrences": occurrences,
+    })))
+}
+
+/// POST /api/mentoring/series/:id/cancel — arrête la série ; les occurrences
+/// déjà réservées sont maintenues
+pub async fn cancel_series(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let series = offer_series::get_series(state.db.pool(), &id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if series.mentor_id != auth_user.id {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let cancelled = offer_series::cancel_series(state.db.pool(), &id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error cancelling series {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(serde_json::json!({
+        "series_id":             id,
+        "cancelled_occurrences": cancelled,
+    })))
+}
+
+/// GET /api/users/me/mentoring-series — séries du mentor connecté
+pub async fn get_my_series(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<Vec<OfferSeries>>, StatusCode> {
+    let series = offer_series::list_mentor_series(state.db.pool(), &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error fetching my series: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    Ok(Json(series))
+}
+
 // ============================================================
 // Handlers — Sessions /me
 // ============================================================
@@ -466,10 +975,15 @@ pub async fn get_my_offers(
             duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
             format: row.try_get("format").unwrap_or_default(),
             token_cost: row.try_get("token_cost").unwrap_or(0),
-            availability: row
-                .try_get("availability")
-                .unwrap_or(serde_json::Value::Array(vec![])),
+            availability: parse_availability(row.try_get("availability").unwrap_or_default()),
             status: row.try_get("status").unwrap_or_else(|_| "open".to_string()),
+            capacity: row.try_get("capacity").unwrap_or(1),
+            series_id: row.try_get("series_id").ok().flatten(),
+            occurrence_at: row.try_get("occurrence_at").ok().flatten(),
+            cancellation_policy: CancellationPolicy::from_value(
+                row.try_get("cancellation_policy").unwrap_or_default(),
+            ),
+            request_id: row.try_get("request_id").ok().flatten(),
             created_at: row
                 .try_get("created_at")
                 .unwrap_or_else(|_| chrono::Utc::now()),
@@ -503,7 +1017,7 @@ pub async fn get_my_bookings(
         .map(|row| MentoringBooking {
             id: row.try_get("id").unwrap_or_default(),
             offer_id: row.try_get("offer_id").unwrap_or_default(),
-            mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+                mentee_id: row.try_get("mentee_id").unwrap_or_default(),
             scheduled_at: row
                 .try_get("scheduled_at")
                 .unwrap_or_else(|_| chrono::Utc::now()),
@@ -542,7 +1056,11 @@ pub async fn create_booking(
 ) -> Result<Json<MentoringBooking>, StatusCode> {
     // Récupérer l'offre
     let offer_row = sqlx::query(
-        "SELECT token_cost, status, mentor_id, topic_slug FROM mentoring_offers WHERE id = $1",
+        r#"
+        SELECT token_cost, status, mentor_id, topic_slug, duration_minutes, availability,
+               capacity, occurrence_at
+        FROM mentoring_offers WHERE id = $1
+        "#,
     )
     .bind(&payload.offer_id)
     .fetch_optional(state.db.pool())
@@ -570,14 +1088,21 @@ pub async fn create_booking(
     let topic_slug: String = offer_row
         .try_get("topic_slug")
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let duration_minutes: i32 = offer_row.try_get("duration_minutes").unwrap_or(60);
+    let capacity: i32 = offer_row.try_get("capacity").unwrap_or(1);
+    let is_occurrence = offer_row
+        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("occurrence_at")
+        .ok()
+        .flatten()
+        .is_some();
+    let slots = parse_availability(offer_row.try_get("availability").unwrap_or_default());
 
-    // ── Vérification du solde T4G du mentee ──
-    mentoring_completion::check_balance(state.db.pool(), &auth_user.id, token_cost as i64)
-        .await
-        .map_err(|e| {
-            tracing::warn!("Insufficient balance for mentee {}: {}", auth_user.id, e);
-            StatusCode::PAYMENT_REQUIRED
-        })?;
+    // ── Le créneau demandé doit être futur et proposé par l'offre ──
+    if payload.scheduled_at <= chrono::Utc::now() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let seats = offered_seats(&slots, duration_minutes, capacity, payload.scheduled_at)
+        .ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
 
     let mut tx = state
         .db
@@ -586,19 +1111,107 @@ pub async fn create_booking(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Créer la réservation
-    let booking_row = sqlx::query(
+    // ── Créneau libre sur toutes les offres du mentor (agenda verrouillé) ──
+    availability::lock_mentor_schedule(&mut tx, &mentor_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to lock schedule of mentor {}: {}", mentor_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // ── Vérification du solde T4G du mentee (solde verrouillé jusqu'au débit) ──
+    token_ledger::lock_balance(&mut tx, &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to lock balance of mentee {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    mentoring_completion::check_balance(&mut tx, &auth_user.id, token_cost as i64)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Insufficient balance for mentee {}: {}", auth_user.id, e);
+            StatusCode::PAYMENT_REQUIRED
+        })?;
+
+    let still_open: Option<String> =
+        sqlx::query_scalar("SELECT status FROM mentoring_offers WHERE id = $1")
+            .bind(&payload.offer_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if still_open.as_deref() != Some("open") {
+        return Err(StatusCode::CONFLICT);
+    }
+    // Offre privée : réservable par le seul auteur de la demande, encore ouverte
+    mentoring_requests::check_bookable(&mut tx, &payload.offer_id, &auth_user.id)
+        .await
+        .map_err(request_status)?;
+
+    let requested = SlotWindow::new(payload.scheduled_at, duration_minutes);
+    let occupancy = availability::mentor_occupancy(
+        &mut tx,
+        &mentor_id,
+        requested.start,
+        requested.end,
+        None,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Error checking schedule of mentor {}: {}", mentor_id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    // Conflit avec une autre session du mentor, ou session complète
+    let taken = seats_taken(&payload.offer_id, &requested, &occupancy)
+        .filter(|taken| *taken < seats)
+        .ok_or(StatusCode::CONFLICT)?;
+
+    let already_seated: bool = sqlx::query_scalar(
         r#"
-        INSERT INTO mentoring_bookings
-            (offer_id, mentee_id, scheduled_at, status, tokens_escrowed,
-             completion_deadline, notes)
-        VALUES ($1, $2, $3, 'pending', $4, NOW() + INTERVAL '48 hours', $5)
-        RETURNING *
+        SELECT EXISTS(
+            SELECT 1 FROM mentoring_bookings
+            WHERE offer_id = $1 AND scheduled_at = $2 AND mentee_id = $3
+              AND status = ANY($4)
+        )
+        "#,
+    )
+    .bind(&payload.offer_id)
+    .bind(payload.scheduled_at)
+    .bind(&auth_user.id)
+    .bind(&availability::BLOCKING_STATUSES[..])
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if already_seated {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Offre sous option pour un inscrit de la liste d'attente
+    let holder = waitlist::active_hold(&mut tx, &payload.offer_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if holder.is_some_and(|holder| holder != auth_user.id) {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Créer la réservation — les places de groupe sont confirmées d'office
+    let initial_status = if capacity > 1 {
+        BookingStatus::Confirmed
+    } else {
+        BookingStatus::Pending
+    };
+    let booking_row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_bookings
+            (offer_id, mentee_id, scheduled_at, status, tokens_escrowed,
+             completion_deadline, notes)
+        VALUES ($1, $2, $3, $4, $5, NOW() + INTERVAL '48 hours', $6)
+        RETURNING *
         "#,
     )
     .bind(&payload.offer_id)
     .bind(&auth_user.id)
     .bind(payload.scheduled_at)
+    .bind(initial_status.to_string())
     .bind(token_cost)
     .bind(&payload.notes)
     .fetch_one(&mut *tx)
@@ -609,48 +1222,79 @@ pub async fn create_booking(
     })?;
 
     let booking_id: String = booking_row.try_get("id").unwrap_or_default();
-
-    // Passer l'offre en "booked"
-    sqlx::query("UPDATE mentoring_offers SET status = 'booked' WHERE id = $1")
-        .bind(&payload.offer_id)
-        .execute(&mut *tx)
+    booking_state::record_creation(&mut tx, &booking_id, initial_status, &auth_user.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    tx.commit()
+        .map_err(|e| {
+            tracing::error!("Error recording booking {} creation: {}", booking_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    waitlist::mark_claimed(&mut tx, &payload.offer_id, &auth_user.id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    mentoring_requests::mark_booked(&mut tx, &payload.offer_id, &booking_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error assigning request of booking {}: {}", booking_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    // ── Débit séquestre (hors transaction SQL pour éviter deadlock) ──
+    // Passer l'offre en "booked" : session individuelle, ou occurrence unique complète
+    if capacity == 1 || (is_occurrence && taken + 1 >= seats) {
+        sqlx::query("UPDATE mentoring_offers SET status = 'booked' WHERE id = $1")
+            .bind(&payload.offer_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+
+    // ── Débit séquestre : la réservation échoue avec lui ──
     if token_cost > 0 {
-        if let Err(e) = mentoring_completion::debit_escrow(
-            state.db.pool(),
+        mentoring_completion::debit_escrow(
+            &mut tx,
             &auth_user.id,
             token_cost as i64,
             &booking_id,
             &topic_slug,
         )
         .await
-        {
+        .map_err(|e| {
             tracing::error!("Escrow debit failed for booking {}: {}", booking_id, e);
-        }
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
     }
 
-    // Notifier le mentor — fire-and-forget
+    // Notifier le mentor, avec la réservation
     let mentee_name = user_display_name(state.db.pool(), &auth_user.id).await;
     notify(
-        state.db.pool(),
+        &mut *tx,
         &mentor_id,
         "Nouvelle réservation",
-        &format!(
-            "{} a réservé ta session sur «{}» pour {} T4G.",
-            mentee_name, topic_slug, token_cost
-        ),
+        &if capacity > 1 {
+            format!(
+                "{} a réservé une place ({}/{}) à ta session de groupe sur «{}».",
+                mentee_name,
+                taken + 1,
+                seats,
+                topic_slug
+            )
+        } else {
+            format!(
+                "{} a réservé ta session sur «{}» pour {} T4G.",
+                mentee_name, topic_slug, token_cost
+            )
+        },
         "MENTORING_BOOKED",
         Some(&format!("/mentoring/session/{}", booking_id)),
         None,
     )
-    .await;
+    .await?;
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if initial_status == BookingStatus::Confirmed {
+        provision_room(state.db.pool(), &booking_id).await;
+    }
 
     tracing::info!(
         "Booking {} created: mentee {} booked offer {} for {} T4G (escrowed)",
@@ -738,14 +1382,48 @@ pub async fn confirm_booking(
         )
     };
 
-    let other_confirmed: bool = booking_row.try_get(mentor_col).unwrap_or(false);
+    let actor = if is_mentee { Actor::Mentee } else { Actor::Mentor };
+
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let new_status = if other_confirmed {
-        "completed"
+    // Confirmations relues sous verrou : la seconde confirmation complète la session
+    let flags = sqlx::query(
+        "SELECT mentee_confirmed, mentor_confirmed FROM mentoring_bookings WHERE id = $1 FOR UPDATE",
+    )
+    .bind(&id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if flags.try_get::<Option<bool>, _>(mentee_col).ok().flatten().unwrap_or(false) {
+        return Err(StatusCode::CONFLICT);
+    }
+    let other_confirmed = flags
+        .try_get::<Option<bool>, _>(mentor_col)
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    let event = if other_confirmed {
+        BookingEvent::ConfirmCompletion
     } else {
-        "pending_completion"
+        BookingEvent::ConfirmAttendance
     };
 
+    let transition = booking_state::apply(
+        &mut tx,
+        &id,
+        event,
+        actor,
+        Some(&auth_user.id),
+        serde_json::json!({ "rating": payload.rating }),
+    )
+    .await
+    .map_err(transition_status)?;
+
     let updated = sqlx::query(&format!(
         r#"
             UPDATE mentoring_bookings SET
@@ -753,8 +1431,9 @@ pub async fn confirm_booking(
                 {rating_col}  = COALESCE($1, {rating_col}),
                 {comment_col} = COALESCE($2, {comment_col}),
                 learned_skills = COALESCE($3, learned_skills),
-                status        = $4
-            WHERE id = $5
+                mentee_rated_at = CASE WHEN $5 AND $1::INT IS NOT NULL
+                                       THEN NOW() ELSE mentee_rated_at END
+            WHERE id = $4
             RETURNING *
             "#,
         mentee_col = mentee_col,
@@ -764,17 +1443,18 @@ pub async fn confirm_booking(
     .bind(payload.rating)
     .bind(&payload.comment)
     .bind(payload.learned_skills.as_deref())
-    .bind(new_status)
     .bind(&id)
-    .fetch_one(state.db.pool())
+    .bind(is_mentee)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| {
         tracing::error!("Error confirming booking {}: {}", id, e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
-    // Si complétion totale → libérer le séquestre + proof RGB
-    if new_status == "completed" {
+    // Si complétion totale → libérer le séquestre ; la preuve RGB et les
+    // notifications partent par l'outbox avec la transition
+    if transition.has(SideEffect::ReleaseEscrow) {
         let offer_id: String = updated.try_get("offer_id").unwrap_or_default();
         let escrow: i32 = updated.try_get("tokens_escrowed").unwrap_or(0);
         let m_rating: Option<i32> = updated.try_get("mentee_rating").ok();
@@ -784,10 +1464,9 @@ pub async fn confirm_booking(
         let offer_extra =
             sqlx::query("SELECT topic_slug, duration_minutes FROM mentoring_offers WHERE id = $1")
                 .bind(&offer_id)
-                .fetch_optional(state.db.pool())
+                .fetch_optional(&mut *tx)
                 .await
-                .ok()
-                .flatten();
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
         let topic_slug = offer_extra
             .as_ref()
@@ -798,16 +1477,18 @@ pub async fn confirm_booking(
             .and_then(|r| r.try_get::<i32, _>("duration_minutes").ok())
             .unwrap_or(60);
 
-        // Passer l'offre en completed
-        let _ = sqlx::query("UPDATE mentoring_offers SET status = 'completed' WHERE id = $1")
-            .bind(&offer_id)
-            .execute(state.db.pool())
-            .await;
+        // Passer l'offre en completed (les offres de groupe restent ouvertes aux autres places)
+        sqlx::query(
+            "UPDATE mentoring_offers SET status = 'completed' WHERE id = $1 AND capacity = 1",
+        )
+        .bind(&offer_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-        // Attribution tokens + proof RGB (errors are logged inside, always returns)
+        // Attribution tokens + effets de bord, dans la transaction de la transition
         let result = mentoring_completion::complete_and_award(
-            state.db.pool(),
-            &state.rgb,
+            &mut tx,
             &id,
             &mentor_id,
             &mentee_id,
@@ -817,38 +1498,15 @@ pub async fn confirm_booking(
             m_rating,
             m_comment,
         )
-        .await;
-
-        tracing::info!(
-            "Booking {} completed: {} T4G → mentor {}, {} T4G → mentee {}, RGB={}",
-            id,
-            result.tokens_to_mentor,
-            mentor_id,
-            result.tokens_to_mentee,
-            mentee_id,
-            result.rgb_contract_id.as_deref().unwrap_or("none")
-        );
-
-        // Persister les montants et l'ID proof RGB dans le booking
-        let _ = sqlx::query(
-            r#"UPDATE mentoring_bookings SET
-                tokens_awarded_mentor = $1,
-                tokens_awarded_mentee = $2,
-                rgb_contract_id       = $3,
-                rgb_signature         = $4
-               WHERE id = $5"#,
-        )
-        .bind(result.tokens_to_mentor as i32)
-        .bind(result.tokens_to_mentee as i32)
-        .bind(&result.rgb_contract_id)
-        .bind(&result.rgb_signature)
-        .bind(&id)
-        .execute(state.db.pool())
-        .await;
+        .await
+        .map_err(|e| {
+            tracing::error!("Error awarding tokens for booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
         // Notifier les deux parties — session complétée
         notify(
-            state.db.pool(),
+            &mut *tx,
             &mentor_id,
             "Session complétée 🎉",
             &format!(
@@ -859,9 +1517,9 @@ pub async fn confirm_booking(
             Some(&format!("/mentoring/session/{}", id)),
             Some(result.tokens_to_mentor as i32),
         )
-        .await;
+        .await?;
         notify(
-            state.db.pool(),
+            &mut *tx,
             &mentee_id,
             "Session complétée 🎉",
             &format!(
@@ -872,12 +1530,27 @@ pub async fn confirm_booking(
             Some(&format!("/mentoring/session/{}", id)),
             Some(result.tokens_to_mentee as i32),
         )
-        .await;
+        .await?;
+
+        tx.commit()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        mentoring_completion::record_skill_evidence(state.db.pool(), &id).await;
+
+        tracing::info!(
+            "Booking {} completed: {} T4G → mentor {}, {} T4G → mentee {}",
+            id,
+            result.tokens_to_mentor,
+            mentor_id,
+            result.tokens_to_mentee,
+            mentee_id
+        );
     } else {
         // pending_completion — notifier l'autre parti
         let notified_user = if is_mentee { &mentor_id } else { &mentee_id };
         notify(
-            state.db.pool(),
+            &mut *tx,
             notified_user,
             "Confirmation en attente",
             "L'autre participant a confirmé la session. Confirme à ton tour pour libérer les tokens.",
@@ -885,7 +1558,10 @@ pub async fn confirm_booking(
             Some(&format!("/mentoring/session/{}", id)),
             None,
         )
-        .await;
+        .await?;
+        tx.commit()
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     }
 
     Ok(Json(MentoringBooking {
@@ -972,6 +1648,23 @@ pub async fn get_booking(
     }))
 }
 
+/// GET /api/mentoring/bookings/:id/ics — fichier .ics de la réservation (CANCEL si annulée)
+pub async fn get_booking_ics(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<axum::response::Response, StatusCode> {
+    let body = calendar::booking_ics(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map_err(|e| {
+            tracing::error!("ICS export failed for booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(ics_response(body, Some(&format!("mentoring-{}.ics", id))))
+}
+
 /// GET /api/mentoring/sessions/:id — vue enrichie booking + offre
 pub async fn get_session_full(
     State(state): State<AppState>,
@@ -1014,6 +1707,9 @@ pub async fn get_session_full(
         return Err(StatusCode::FORBIDDEN);
     }
 
+    // Salle de visioconférence — absente hors ligne ou avant confirmation
+    let room = meeting::access(state.db.pool(), &id, &auth_user.id).await.ok();
+
     let json = serde_json::json!({
         "id":                    row.try_get::<String, _>("booking_id").unwrap_or_default(),
         "offer_id":              row.try_get::<String, _>("offer_id").unwrap_or_default(),
@@ -1046,20 +1742,21 @@ pub async fn get_session_full(
             "availability":     row.try_get::<serde_json::Value, _>("availability").unwrap_or(serde_json::Value::Array(vec![])),
             "status":           row.try_get::<String, _>("offer_status").unwrap_or_default(),
             "created_at":       row.try_get::<chrono::DateTime<chrono::Utc>, _>("offer_created_at").unwrap_or_else(|_| chrono::Utc::now()),
-        }
+        },
+        "room": room,
     });
 
     Ok(Json(json))
 }
 
-/// POST /api/mentoring/bookings/:id/dispute — ouvrir un litige
-pub async fn dispute_booking(
+/// GET /api/mentoring/bookings/:id/history — transitions de statut de la réservation
+pub async fn get_booking_history(
     State(state): State<AppState>,
     AuthUserExtractor(auth_user): AuthUserExtractor,
     Path(id): Path<String>,
-) -> Result<StatusCode, StatusCode> {
-    let booking_row = sqlx::query(
-        r#"SELECT b.mentee_id, o.mentor_id, o.topic_slug
+) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
+    let row = sqlx::query(
+        r#"SELECT b.mentee_id, o.mentor_id
            FROM mentoring_bookings b
            JOIN mentoring_offers o ON o.id = b.offer_id
            WHERE b.id = $1"#,
@@ -1069,39 +1766,284 @@ pub async fn dispute_booking(
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
     .ok_or(StatusCode::NOT_FOUND)?;
-
-    let mentee_id: String = booking_row
-        .try_get("mentee_id")
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    if mentee_id != auth_user.id {
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    if Actor::participant(&auth_user.id, &mentor_id, &mentee_id).is_none() {
         return Err(StatusCode::FORBIDDEN);
     }
-    let mentor_id: String = booking_row.try_get("mentor_id").unwrap_or_default();
-    let topic_slug: String = booking_row.try_get("topic_slug").unwrap_or_default();
 
-    sqlx::query("UPDATE mentoring_bookings SET status = 'disputed' WHERE id = $1")
-        .bind(&id)
-        .execute(state.db.pool())
+    booking_state::history(state.db.pool(), &id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error fetching history of booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// GET /api/mentoring/bookings/:id/room — salle de visioconférence
+///
+/// `join_url` n'est renseignée que dans la fenêtre de connexion.
+pub async fn get_booking_room(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<RoomAccess>, StatusCode> {
+    meeting::access(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(meeting_status)
+}
+
+fn meeting_status(e: MeetingError) -> StatusCode {
+    match e {
+        MeetingError::NotFound => StatusCode::NOT_FOUND,
+        MeetingError::Forbidden => StatusCode::FORBIDDEN,
+        MeetingError::NoRoom(_) => StatusCode::CONFLICT,
+        MeetingError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        MeetingError::Token(e) => {
+            tracing::error!("Meeting room token signing failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        MeetingError::Database(e) => {
+            tracing::error!("Meeting room query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// GET /api/mentoring/bookings/:id/attendance — pointages et constat de présence
+pub async fn get_booking_attendance(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<AttendanceStatus>, StatusCode> {
+    attendance::status(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(attendance_status)
+}
+
+/// GET /api/mentoring/bookings/:id/checkin-code — code à montrer au mentee (mentor)
+pub async fn get_checkin_code(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<CheckInCode>, StatusCode> {
+    attendance::checkin_code(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(attendance_status)
+}
+
+/// POST /api/mentoring/bookings/:id/check-in — pointage du mentee avec le code
+pub async fn check_in_booking(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<CheckInPayload>,
+) -> Result<Json<AttendanceStatus>, StatusCode> {
+    attendance::check_in(state.db.pool(), &id, &auth_user.id, &payload.code)
+        .await
+        .map(Json)
+        .map_err(attendance_status)
+}
+
+fn attendance_status(e: AttendanceError) -> StatusCode {
+    match e {
+        AttendanceError::NotFound => StatusCode::NOT_FOUND,
+        AttendanceError::Forbidden => StatusCode::FORBIDDEN,
+        AttendanceError::Closed => StatusCode::CONFLICT,
+        AttendanceError::InvalidCode => StatusCode::UNPROCESSABLE_ENTITY,
+        AttendanceError::TooManyAttempts => StatusCode::TOO_MANY_REQUESTS,
+        AttendanceError::Database(e) => {
+            tracing::error!("Attendance query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Statut HTTP d'une transition refusée par la machine à états
+pub(crate) fn transition_status(e: TransitionError) -> StatusCode {
+    match e {
+        TransitionError::NotFound => StatusCode::NOT_FOUND,
+        TransitionError::Forbidden { .. } => StatusCode::FORBIDDEN,
+        TransitionError::Illegal { .. } => StatusCode::CONFLICT,
+        TransitionError::Database(e) => {
+            tracing::error!("Booking transition failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Statut HTTP d'une erreur d'absence (partagé avec les routes d'absence)
+pub(crate) fn time_off_status(e: TimeOffError) -> StatusCode {
+    match e {
+        TimeOffError::NotFound => StatusCode::NOT_FOUND,
+        TimeOffError::Forbidden => StatusCode::FORBIDDEN,
+        TimeOffError::Invalid(msg) => {
+            tracing::warn!("Invalid time off request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        TimeOffError::NotInConflict => StatusCode::CONFLICT,
+        TimeOffError::Transition(e) => transition_status(e),
+        TimeOffError::Database(e) => {
+            tracing::error!("Time off query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Statut HTTP d'une erreur de demande (partagé avec les routes demandes)
+pub(crate) fn request_status(e: RequestError) -> StatusCode {
+    match e {
+        RequestError::NotFound => StatusCode::NOT_FOUND,
+        RequestError::Forbidden => StatusCode::FORBIDDEN,
+        RequestError::Closed | RequestError::AlreadyResponded => StatusCode::CONFLICT,
+        RequestError::Invalid(msg) => {
+            tracing::warn!("Invalid mentoring request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        RequestError::Database(e) => {
+            tracing::error!("Mentoring request query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Statut HTTP d'une erreur de certificat (routes preuves, parcours et admin)
+pub(crate) fn certificate_status(e: CertificateError) -> StatusCode {
+    match e {
+        CertificateError::NotFound => StatusCode::NOT_FOUND,
+        CertificateError::Forbidden => StatusCode::FORBIDDEN,
+        CertificateError::Revoked => StatusCode::GONE,
+        CertificateError::Invalid(msg) => {
+            tracing::warn!("Invalid certificate request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        CertificateError::Database(e) => {
+            tracing::error!("Certificate query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Statut HTTP d'une erreur de litige (partagé avec les routes admin)
+pub(crate) fn dispute_status(e: DisputeError) -> StatusCode {
+    match e {
+        DisputeError::NotFound => StatusCode::NOT_FOUND,
+        DisputeError::Forbidden => StatusCode::FORBIDDEN,
+        DisputeError::InvalidState => StatusCode::CONFLICT,
+        DisputeError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        DisputeError::Database(e) => {
+            tracing::error!("Dispute query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// POST /api/mentoring/bookings/:id/dispute — ouvrir un litige (mentor ou mentee)
+pub async fn dispute_booking(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    payload: Option<Json<OpenDisputePayload>>,
+) -> Result<Json<Dispute>, StatusCode> {
+    let reason = payload
+        .and_then(|Json(p)| p.reason)
+        .filter(|r| !r.trim().is_empty())
+        .unwrap_or_else(|| "Litige ouvert sans motif détaillé".to_string());
+
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (dispute, parties) = disputes::open(&mut tx, &id, &auth_user.id, &reason)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Dispute rejected on booking {}: {}", id, e);
+            dispute_status(e)
+        })?;
 
-    // Notifier le mentor
+    // Notifier l'autre partie
+    let counterpart = if auth_user.id == parties.mentor_id {
+        &parties.mentee_id
+    } else {
+        &parties.mentor_id
+    };
     notify(
-        state.db.pool(),
-        &mentor_id,
+        &mut *tx,
+        counterpart,
         "Litige ouvert",
         &format!(
             "Un litige a été ouvert sur la session «{}». L'équipe T4G va examiner la situation.",
-            topic_slug
+            parties.topic_slug
         ),
         "MENTORING_DISPUTED",
         Some(&format!("/mentoring/session/{}", id)),
         None,
     )
-    .await;
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    tracing::warn!("Dispute opened on booking {}", id);
-    Ok(StatusCode::NO_CONTENT)
+    tracing::warn!("Dispute {} opened on booking {}", dispute.id, id);
+    Ok(Json(dispute))
+}
+
+/// GET /api/mentoring/bookings/:id/dispute — dossier du litige (sans notes internes)
+pub async fn get_booking_dispute(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<DisputeCase>, StatusCode> {
+    disputes::get_for_booking(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(dispute_status)
+}
+
+/// POST /api/mentoring/bookings/:id/dispute/messages — verser une preuve au dossier
+pub async fn add_dispute_evidence(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<DisputeMessagePayload>,
+) -> Result<Json<DisputeEvent>, StatusCode> {
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (dispute, parties, event) = disputes::add_evidence(&mut tx, &id, &auth_user.id, &payload)
+        .await
+        .map_err(dispute_status)?;
+
+    // L'admin en charge est prévenu des nouvelles pièces
+    if let Some(admin_id) = dispute.assigned_to.as_deref() {
+        notify(
+            &mut *tx,
+            admin_id,
+            "Nouvelle pièce au dossier",
+            &format!(
+                "Une nouvelle pièce a été versée au litige sur la session «{}».",
+                parties.topic_slug
+            ),
+            "DISPUTE_EVIDENCE_ADDED",
+            Some(&format!("/admin/disputes/{}", dispute.id)),
+            None,
+        )
+        .await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(event))
 }
 
 // ============================================================
@@ -1135,29 +2077,38 @@ pub async fn accept_booking(
         return Err(StatusCode::FORBIDDEN);
     }
 
-    let status: String = booking_row.try_get("status").unwrap_or_default();
-    if status != "pending" {
-        return Err(StatusCode::CONFLICT);
-    }
-
     let mentee_id: String = booking_row.try_get("mentee_id").unwrap_or_default();
     let topic_slug: String = booking_row.try_get("topic_slug").unwrap_or_default();
 
-    let updated = sqlx::query(
-        "UPDATE mentoring_bookings SET status = 'confirmed', updated_at = NOW() WHERE id = $1 RETURNING *",
-    )
-    .bind(&id)
-    .fetch_one(state.db.pool())
-    .await
-    .map_err(|e| {
-        tracing::error!("Error accepting booking {}: {}", id, e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    booking_state::apply(
+        &mut tx,
+        &id,
+        BookingEvent::Accept,
+        Actor::Mentor,
+        Some(&auth_user.id),
+        serde_json::json!({}),
+    )
+    .await
+    .map_err(transition_status)?;
+    let updated = sqlx::query("SELECT * FROM mentoring_bookings WHERE id = $1")
+        .bind(&id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error accepting booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
     // Notifier le mentee
     let mentor_name = user_display_name(state.db.pool(), &auth_user.id).await;
     notify(
-        state.db.pool(),
+        &mut *tx,
         &mentee_id,
         "Session confirmée",
         &format!(
@@ -1168,7 +2119,11 @@ pub async fn accept_booking(
         Some(&format!("/mentoring/session/{}", id)),
         None,
     )
-    .await;
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    provision_room(state.db.pool(), &id).await;
 
     tracing::info!("Booking {} accepted by mentor {}", id, auth_user.id);
 
@@ -1218,11 +2173,6 @@ pub async fn decline_booking(
         return Err(StatusCode::FORBIDDEN);
     }
 
-    let status: String = booking_row.try_get("status").unwrap_or_default();
-    if status != "pending" {
-        return Err(StatusCode::CONFLICT);
-    }
-
     let mentee_id: String = booking_row.try_get("mentee_id").unwrap_or_default();
     let offer_id: String = booking_row.try_get("offer_id").unwrap_or_default();
     let topic_slug: String = booking_row.try_get("topic_slug").unwrap_or_default();
@@ -1235,44 +2185,32 @@ pub async fn decline_booking(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    // Annuler la réservation
-    sqlx::query(
-        "UPDATE mentoring_bookings SET status = 'cancelled', updated_at = NOW() WHERE id = $1",
+    // Annuler la réservation (l'offre est remise en open par la transition)
+    let transition = booking_state::apply(
+        &mut tx,
+        &id,
+        BookingEvent::Decline,
+        Actor::Mentor,
+        Some(&auth_user.id),
+        serde_json::json!({}),
     )
-    .bind(&id)
-    .execute(&mut *tx)
     .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Remettre l'offre en open
-    sqlx::query("UPDATE mentoring_offers SET status = 'open', updated_at = NOW() WHERE id = $1")
-        .bind(&offer_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    tx.commit()
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .map_err(transition_status)?;
 
-    // Remboursement du séquestre (hors transaction)
-    if tokens_escrowed > 0 {
-        if let Err(e) = mentoring_completion::refund_escrow(
-            state.db.pool(),
-            &mentee_id,
-            tokens_escrowed as i64,
-            &id,
-        )
-        .await
-        {
-            tracing::error!("Escrow refund failed for declined booking {}: {}", id, e);
-        }
+    // Remboursement du séquestre, avec la transition
+    if transition.has(SideEffect::RefundEscrow) && tokens_escrowed > 0 {
+        mentoring_completion::refund_escrow(&mut tx, &mentee_id, tokens_escrowed as i64, &id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Escrow refund failed for declined booking {}: {}", id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
     }
 
     // Notifier le mentee
     let mentor_name = user_display_name(state.db.pool(), &auth_user.id).await;
     notify(
-        state.db.pool(),
+        &mut *tx,
         &mentee_id,
         "Demande de session refusée",
         &format!(
@@ -1283,12 +2221,449 @@ pub async fn decline_booking(
         Some("/mentoring/find"),
         Some(tokens_escrowed),
     )
-    .await;
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if transition.has(SideEffect::ReopenOffer) {
+        offer_to_waitlist(state.db.pool(), &offer_id).await;
+    }
 
     tracing::info!("Booking {} declined by mentor {}", id, auth_user.id);
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// POST /api/mentoring/bookings/:id/withdraw — le mentor se retire d'une
+/// session confirmée qui tombe pendant son absence (remboursement intégral)
+pub async fn withdraw_booking(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<CancelBookingPayload>,
+) -> Result<StatusCode, StatusCode> {
+    let pool = state.db.pool();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let withdrawal = time_off::withdraw(&mut tx, &id, &auth_user.id, payload.reason.as_deref())
+        .await
+        .map_err(time_off_status)?;
+
+    if withdrawal.transition.has(SideEffect::RefundEscrow) && withdrawal.tokens_escrowed > 0 {
+        mentoring_completion::refund_escrow(
+            &mut tx,
+            &withdrawal.mentee_id,
+            withdrawal.tokens_escrowed as i64,
+            &id,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Escrow refund failed for withdrawn booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    let mentor_name = user_display_name(pool, &auth_user.id).await;
+    notify(
+        &mut *tx,
+        &withdrawal.mentee_id,
+        "Session annulée par le mentor",
+        &format!(
+            "{} est absent le {} et annule la session «{}». Tes T4G ont été remboursés.",
+            mentor_name,
+            withdrawal.scheduled_at.format("%d/%m/%Y"),
+            withdrawal.topic_slug
+        ),
+        "MENTORING_WITHDRAWN",
+        Some("/mentoring/find"),
+        Some(withdrawal.tokens_escrowed),
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if withdrawal.transition.has(SideEffect::ReopenOffer) {
+        offer_to_waitlist(pool, &withdrawal.offer_id).await;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================
+// Handlers — Annulation par le mentee
+// ============================================================
+
+/// Réservation annulable par le mentee, avec le partage du séquestre
+struct CancellationTerms {
+    status: String,
+    offer_id: String,
+    mentor_id: String,
+    topic_slug: String,
+    split: CancellationSplit,
+}
+
+/// Vérifie que `user_id` peut annuler et calcule le partage : une demande
+/// encore en attente est remboursée intégralement, une session confirmée
+/// suit la politique de l'offre.
+async fn cancellation_terms(
+    pool: &sqlx::PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<CancellationTerms, StatusCode> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.mentee_id, b.status, b.scheduled_at, b.tokens_escrowed, b.offer_id,
+               o.mentor_id, o.topic_slug, o.cancellation_policy
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    if mentee_id != user_id {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let status: String = row.try_get("status").unwrap_or_default();
+    let scheduled_at: chrono::DateTime<chrono::Utc> = row
+        .try_get("scheduled_at")
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let escrow = row.try_get::<i32, _>("tokens_escrowed").unwrap_or(0) as i64;
+    let now = chrono::Utc::now();
+
+    let split = match status.as_str() {
+        "pending" => CancellationSplit {
+            refund_percent: 100,
+            refund_tokens: escrow,
+            compensation_tokens: 0,
+        },
+        // Une session commencée ne s'annule plus (litige ou complétion)
+        "confirmed" if scheduled_at > now => {
+            CancellationPolicy::from_value(row.try_get("cancellation_policy").unwrap_or_default())
+                .split(escrow, scheduled_at, now)
+        }
+        _ => return Err(StatusCode::CONFLICT),
+    };
+
+    Ok(CancellationTerms {
+        status,
+        offer_id: row.try_get("offer_id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        split,
+    })
+}
+
+/// GET /api/mentoring/bookings/:id/cancel — remboursement si le mentee annule maintenant
+pub async fn get_cancellation_quote(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<CancellationSplit>, StatusCode> {
+    let terms = cancellation_terms(state.db.pool(), &id, &auth_user.id).await?;
+    Ok(Json(terms.split))
+}
+
+/// POST /api/mentoring/bookings/:id/cancel — le mentee annule (séquestre partagé, offre rouverte)
+pub async fn cancel_booking(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    payload: Option<Json<CancelBookingPayload>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let reason = payload.and_then(|Json(p)| p.reason);
+    let terms = cancellation_terms(state.db.pool(), &id, &auth_user.id).await?;
+    let split = terms.split;
+
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Transition gardée : une complétion ou un refus concurrent l'emporte, et
+    // le partage calculé doit correspondre au statut verrouillé
+    let transition = booking_state::apply(
+        &mut tx,
+        &id,
+        BookingEvent::Cancel,
+        Actor::Mentee,
+        Some(&auth_user.id),
+        serde_json::json!({ "refund_percent": split.refund_percent }),
+    )
+    .await
+    .map_err(transition_status)?;
+    if transition.from.to_string() != terms.status {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE mentoring_bookings SET
+            cancelled_by = $2, cancelled_at = NOW(), cancellation_reason = $3,
+            refund_tokens = $4, compensation_tokens = $5
+        WHERE id = $1
+        "#,
+    )
+    .bind(&id)
+    .bind(&auth_user.id)
+    .bind(&reason)
+    .bind(split.refund_tokens as i32)
+    .bind(split.compensation_tokens as i32)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        tracing::error!("Error cancelling booking {}: {}", id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Partage du séquestre, avec la transition
+    if transition.has(SideEffect::SettleCancellation)
+        && (split.refund_tokens > 0 || split.compensation_tokens > 0)
+    {
+        mentoring_completion::settle_cancellation(
+            &mut tx,
+            &id,
+            &auth_user.id,
+            &terms.mentor_id,
+            split.refund_tokens,
+            split.compensation_tokens,
+            &terms.topic_slug,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Escrow settlement failed for cancelled booking {}: {}", id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    }
+
+    let mentee_name = user_display_name(state.db.pool(), &auth_user.id).await;
+    let message = if split.compensation_tokens > 0 {
+        format!(
+            "{} a annulé la session «{}». Tu reçois {} T4G de dédommagement.",
+            mentee_name, terms.topic_slug, split.compensation_tokens
+        )
+    } else {
+        format!("{} a annulé la session «{}».", mentee_name, terms.topic_slug)
+    };
+    notify(
+        &mut *tx,
+        &terms.mentor_id,
+        "Session annulée",
+        &message,
+        "MENTORING_CANCELLED",
+        Some(&format!("/mentoring/session/{}", id)),
+        Some(split.compensation_tokens as i32),
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if transition.has(SideEffect::ReopenOffer) {
+        offer_to_waitlist(state.db.pool(), &terms.offer_id).await;
+    }
+
+    tracing::info!(
+        "Booking {} cancelled by mentee {} ({}% refunded)",
+        id,
+        auth_user.id,
+        split.refund_percent
+    );
+    Ok(Json(serde_json::json!({
+        "booking_id":          id,
+        "status":              "cancelled",
+        "refund_percent":      split.refund_percent,
+        "refund_tokens":       split.refund_tokens,
+        "compensation_tokens": split.compensation_tokens,
+    })))
+}
+
+// ============================================================
+// Handlers — Report d'une réservation confirmée
+// ============================================================
+
+fn reschedule_status(e: RescheduleError) -> StatusCode {
+    match e {
+        RescheduleError::NotFound => StatusCode::NOT_FOUND,
+        RescheduleError::Forbidden => StatusCode::FORBIDDEN,
+        RescheduleError::InvalidState | RescheduleError::SlotTaken | RescheduleError::Expired => {
+            StatusCode::CONFLICT
+        }
+        RescheduleError::SlotNotOffered => StatusCode::UNPROCESSABLE_ENTITY,
+        RescheduleError::Database(e) => {
+            tracing::error!("Reschedule database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// GET /api/mentoring/bookings/:id/reschedules — historique des reports
+pub async fn list_reschedules(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<BookingReschedule>>, StatusCode> {
+    rescheduling::history(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(reschedule_status)
+}
+
+/// POST /api/mentoring/bookings/:id/reschedules — propose (ou contre-propose) un nouvel horaire
+pub async fn propose_reschedule(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<ProposeReschedulePayload>,
+) -> Result<(StatusCode, Json<BookingReschedule>), StatusCode> {
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (proposal, ctx) = rescheduling::propose(
+        &mut tx,
+        &id,
+        &auth_user.id,
+        payload.scheduled_at,
+        payload.message.as_deref(),
+    )
+    .await
+    .map_err(reschedule_status)?;
+
+    let counterpart = ctx.counterpart(&auth_user.id).unwrap_or_default();
+    let proposer_name = user_display_name(state.db.pool(), &auth_user.id).await;
+    let title = if proposal.counter_of.is_some() {
+        "Contre-proposition d'horaire"
+    } else {
+        "Proposition de report"
+    };
+    notify(
+        &mut *tx,
+        counterpart,
+        title,
+        &format!(
+            "{} propose de déplacer la session «{}» du {} au {}.",
+            proposer_name,
+            ctx.topic_slug,
+            ctx.scheduled_at.format("%d/%m à %Hh%M"),
+            proposal.proposed_scheduled_at.format("%d/%m à %Hh%M")
+        ),
+        "MENTORING_RESCHEDULE_PROPOSED",
+        Some(&format!("/mentoring/session/{}", id)),
+        None,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::CREATED, Json(proposal)))
+}
+
+/// POST /api/mentoring/bookings/:id/reschedules/:proposal_id/accept — la réservation passe au nouvel horaire
+pub async fn accept_reschedule(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path((id, proposal_id)): Path<(String, String)>,
+) -> Result<Json<BookingReschedule>, StatusCode> {
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (proposal, ctx) =
+        match rescheduling::respond(&mut tx, &id, &proposal_id, &auth_user.id, true).await {
+            Ok(res) => res,
+            Err(RescheduleError::Expired) => {
+                // La proposition vient d'être marquée expirée : on le consigne.
+                tx.commit()
+                    .await
+                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+                return Err(reschedule_status(RescheduleError::Expired));
+            }
+            Err(e) => return Err(reschedule_status(e)),
+        };
+
+    let name = user_display_name(state.db.pool(), &auth_user.id).await;
+    notify(
+        &mut *tx,
+        &proposal.proposed_by,
+        "Report accepté",
+        &format!(
+            "{} a accepté le nouvel horaire : la session «{}» aura lieu le {}.",
+            name,
+            ctx.topic_slug,
+            proposal.proposed_scheduled_at.format("%d/%m à %Hh%M")
+        ),
+        "MENTORING_RESCHEDULE_ACCEPTED",
+        Some(&format!("/mentoring/session/{}", id)),
+        None,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(proposal))
+}
+
+/// POST /api/mentoring/bookings/:id/reschedules/:proposal_id/decline — refus (ou retrait par son auteur)
+pub async fn decline_reschedule(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path((id, proposal_id)): Path<(String, String)>,
+) -> Result<Json<BookingReschedule>, StatusCode> {
+    let mut tx = state
+        .db
+        .pool()
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (proposal, ctx) =
+        rescheduling::respond(&mut tx, &id, &proposal_id, &auth_user.id, false)
+            .await
+            .map_err(reschedule_status)?;
+
+    if proposal.status == "declined" {
+        let name = user_display_name(state.db.pool(), &auth_user.id).await;
+        notify(
+            &mut *tx,
+            &proposal.proposed_by,
+            "Report refusé",
+            &format!(
+                "{} a refusé le nouvel horaire proposé : la session «{}» reste prévue le {}.",
+                name,
+                ctx.topic_slug,
+                ctx.scheduled_at.format("%d/%m à %Hh%M")
+            ),
+            "MENTORING_RESCHEDULE_DECLINED",
+            Some(&format!("/mentoring/session/{}", id)),
+            None,
+        )
+        .await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(proposal))
+}
+
 /// GET /api/users/me/mentoring-received-bookings — réservations reçues en tant que mentor
 pub async fn get_received_bookings(
     State(state): State<AppState>,
@@ -1342,3 +2717,265 @@ pub async fn get_received_bookings(
 
     Ok(Json(bookings))
 }
+
+// ============================================================
+// Handlers — Listes d'attente
+// ============================================================
+
+fn waitlist_status(e: WaitlistError) -> StatusCode {
+    match e {
+        WaitlistError::NotFound => StatusCode::NOT_FOUND,
+        WaitlistError::Forbidden => StatusCode::FORBIDDEN,
+        WaitlistError::AlreadyWaiting => StatusCode::CONFLICT,
+        WaitlistError::Invalid(msg) => {
+            tracing::warn!("Invalid waitlist request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        WaitlistError::Database(e) => {
+            tracing::error!("Waitlist query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// POST /api/mentoring/waitlist — s'inscrire sur une offre ou un couple mentor/sujet
+pub async fn join_waitlist(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Json(payload): Json<JoinWaitlistPayload>,
+) -> Result<(StatusCode, Json<WaitlistEntry>), StatusCode> {
+    waitlist::join(state.db.pool(), &auth_user.id, &payload)
+        .await
+        .map(|entry| (StatusCode::CREATED, Json(entry)))
+        .map_err(waitlist_status)
+}
+
+/// DELETE /api/mentoring/waitlist/:id — quitter la liste (l'option passe au suivant)
+pub async fn leave_waitlist(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    waitlist::leave(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(waitlist_status)
+}
+
+/// GET /api/users/me/mentoring-waitlist — inscriptions du mentee et options en cours
+pub async fn get_my_waitlist(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<Vec<WaitlistEntry>>, StatusCode> {
+    waitlist::list_for_mentee(state.db.pool(), &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error fetching waitlist of {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+// ============================================================
+// Handlers — Avis
+// ============================================================
+
+pub(crate) fn review_status(e: ReviewError) -> StatusCode {
+    match e {
+        ReviewError::NotFound => StatusCode::NOT_FOUND,
+        ReviewError::Forbidden => StatusCode::FORBIDDEN,
+        ReviewError::Conflict => StatusCode::CONFLICT,
+        ReviewError::Invalid(msg) => {
+            tracing::warn!("Invalid review request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        ReviewError::Database(e) => {
+            tracing::error!("Review query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub(crate) fn skill_status(e: SkillError) -> StatusCode {
+    match e {
+        SkillError::NotFound => StatusCode::NOT_FOUND,
+        SkillError::Forbidden => StatusCode::FORBIDDEN,
+        SkillError::Invalid(msg) => {
+            tracing::warn!("Invalid skill request: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        SkillError::Database(e) => {
+            tracing::error!("Skill query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewsFilter {
+    pub topic_slug: Option<String>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+async fn reviews_page(
+    pool: &sqlx::PgPool,
+    mentor_id: Option<&str>,
+    topic_slug: Option<&str>,
+    filters: &ReviewsFilter,
+) -> Result<Json<ReviewsPage>, StatusCode> {
+    let query = ReviewQuery {
+        mentor_id,
+        topic_slug,
+        limit: filters.limit.unwrap_or(20).clamp(1, 100),
+        offset: filters.offset.unwrap_or(0).max(0),
+    };
+    reviews::list(pool, &query).await.map(Json).map_err(|e| {
+        tracing::error!("Error listing reviews: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// GET /api/mentoring/mentors/:id/reviews — avis d'un mentor (filtrables par sujet)
+pub async fn list_mentor_reviews(
+    State(state): State<AppState>,
+    Path(mentor_id): Path<String>,
+    Query(filters): Query<ReviewsFilter>,
+) -> Result<Json<ReviewsPage>, StatusCode> {
+    reviews_page(
+        state.db.pool(),
+        Some(&mentor_id),
+        filters.topic_slug.as_deref(),
+        &filters,
+    )
+    .await
+}
+
+/// GET /api/mentoring/topics/:slug/reviews — avis sur un sujet, tous mentors confondus
+pub async fn list_topic_reviews(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(filters): Query<ReviewsFilter>,
+) -> Result<Json<ReviewsPage>, StatusCode> {
+    reviews_page(state.db.pool(), None, Some(&slug), &filters).await
+}
+
+/// POST /api/mentoring/bookings/:id/review/reply — réponse publique du mentor
+pub async fn reply_to_review(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<ReviewReplyPayload>,
+) -> Result<Json<Review>, StatusCode> {
+    let pool = state.db.pool();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let review = reviews::reply(&mut tx, &id, &auth_user.id, &payload)
+        .await
+        .map_err(review_status)?;
+
+    let mentor_name = user_display_name(pool, &auth_user.id).await;
+    notify(
+        &mut *tx,
+        &review.mentee_id,
+        "Réponse à ton avis",
+        &format!(
+            "{} a répondu à ton avis sur la session «{}».",
+            mentor_name, review.topic_slug
+        ),
+        "MENTORING_REVIEW_REPLY",
+        Some(&format!("/mentoring/session/{}", review.booking_id)),
+        None,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(review))
+}
+
+/// POST /api/mentoring/bookings/:id/review/report — signaler un avis abusif
+pub async fn report_review(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<ReportReviewPayload>,
+) -> Result<(StatusCode, Json<ReviewReport>), StatusCode> {
+    reviews::report(state.db.pool(), &id, &auth_user.id, &payload)
+        .await
+        .map(|report| (StatusCode::CREATED, Json(report)))
+        .map_err(review_status)
+}
+
+// ============================================================
+// Handlers — Recommandations
+// ============================================================
+
+#[derive(Debug, Deserialize)]
+pub struct RecommendationsQuery {
+    pub limit: Option<usize>,
+}
+
+/// GET /api/mentoring/recommendations — offres ouvertes classées pour le mentee, avec raisons
+pub async fn get_recommendations(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Query(query): Query<RecommendationsQuery>,
+) -> Result<Json<Vec<OfferMatch>>, StatusCode> {
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+    matching::recommend(state.db.pool(), &auth_user.id, limit)
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            MatchingError::NotFound => StatusCode::NOT_FOUND,
+            MatchingError::Database(e) => {
+                tracing::error!("Error computing recommendations for {}: {}", auth_user.id, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })
+}
+
+// ============================================================
+// Compétences
+// ============================================================
+
+/// POST /api/mentoring/bookings/:id/endorsements — le mentor recommande une
+/// compétence travaillée pendant la session
+pub async fn endorse_skill(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<EndorseSkillPayload>,
+) -> Result<Json<SkillEndorsement>, StatusCode> {
+    let pool = state.db.pool();
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let endorsement = skills::endorse(&mut tx, &id, &auth_user.id, &payload)
+        .await
+        .map_err(skill_status)?;
+
+    let mentor_name = user_display_name(pool, &auth_user.id).await;
+    notify(
+        &mut *tx,
+        &endorsement.user_id,
+        "Compétence recommandée",
+        &format!(
+            "{} a recommandé ta compétence «{}» (niveau {}).",
+            mentor_name, endorsement.topic_slug, endorsement.level
+        ),
+        "MENTORING_SKILL_ENDORSED",
+        Some("/learning/path"),
+        None,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(endorsement))
+}
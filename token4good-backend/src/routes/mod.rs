@@ -1,5 +1,7 @@
 pub mod admin;
 pub mod auth;
+pub mod calendar;
+pub mod chat;
 pub mod dazno;
 pub mod health;
 pub mod learning;
@@ -9,6 +11,8 @@ pub mod metrics;
 pub mod proofs;
 pub mod service_categories;
 pub mod services;
+pub mod session_notes;
+pub mod time_off;
 pub mod token4good;
 pub mod transactions;
 pub mod transfer;
@@ -1,7 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -9,8 +9,11 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
+    models::certificate::{CertificateQuery, CertificateVerification},
     models::proof::{CreateProofRequest, Proof, ProofStatus},
-    services::rgb::ProofDetails,
+    routes::mentoring_offers::certificate_status,
+    services::{certificate, rgb::ProofDetails},
     AppState,
 };
 
@@ -47,6 +50,70 @@ pub fn proof_routes() -> Router<AppState> {
         .route("/:id/transfer", post(transfer_proof_rgb))
         .route("/:id/history", get(get_proof_history))
         .route("/rgb/:contract_id", get(get_proof_by_contract))
+        .route("/:id/certificate", get(get_certificate))
+}
+
+/// Vérification publique des certificats (cible du QR code), sans auth
+pub fn certificate_routes() -> Router<AppState> {
+    Router::new().route("/verify/:contract_id", get(verify_certificate))
+}
+
+/// Réponse `application/pdf` téléchargeable (partagée avec les certificats
+/// de parcours)
+pub fn pdf_response(bytes: Vec<u8>, filename: &str) -> Response {
+    let disposition = format!("attachment; filename=\"{}\"", filename);
+    (
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+/// GET /api/proofs/:id/certificate?lang=fr|en&branding=slug — certificat PDF
+/// de la session (mentee, mentor ou admin)
+pub async fn get_certificate(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Query(query): Query<CertificateQuery>,
+) -> Result<Response, StatusCode> {
+    let lang = query.language().map_err(|e| {
+        tracing::warn!("{}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    let pool = state.db.pool();
+    let is_admin = auth_user.is_admin();
+    let cert = certificate::session_certificate(pool, &id, &auth_user.id, is_admin)
+        .await
+        .map_err(certificate_status)?;
+    let branding = certificate::resolve_branding(pool, query.branding.as_deref(), &cert.mentee_id)
+        .await
+        .map_err(certificate_status)?;
+
+    let bytes = certificate::render_session(&cert, &branding, lang);
+    Ok(pdf_response(bytes, &cert.file_name()))
+}
+
+/// GET /api/certificates/verify/:contract_id — public : la preuve existe,
+/// n'est pas révoquée et sa signature RGB est valide
+pub async fn verify_certificate(
+    State(state): State<AppState>,
+    Path(contract_id): Path<String>,
+) -> Result<Json<CertificateVerification>, StatusCode> {
+    let (mut verification, signature) = certificate::verification(state.db.pool(), &contract_id)
+        .await
+        .map_err(certificate_status)?;
+
+    let signature_valid = state
+        .rgb
+        .verify_proof(&contract_id, &signature)
+        .await
+        .unwrap_or(false);
+    verification.valid = signature_valid && !verification.revoked;
+    Ok(Json(verification))
 }
 
 pub async fn list_proofs(
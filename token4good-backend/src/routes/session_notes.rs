@@ -0,0 +1,93 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+
+use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
+    models::session_notes::{
+        AddAttachmentPayload, BookingNotes, SessionAttachment, SessionNotes, UpsertNotesPayload,
+    },
+    services::session_notes::{self, NotesError},
+    AppState,
+};
+
+/// Notes de session — montées sous /api/mentoring avec les routes de réservation
+pub fn session_notes_routes() -> Router<AppState> {
+    Router::new()
+        .route("/bookings/:id/notes", get(get_notes).put(put_notes))
+        .route("/bookings/:id/attachments", post(add_attachment))
+        .route(
+            "/bookings/:id/attachments/:attachment_id",
+            delete(remove_attachment),
+        )
+}
+
+fn notes_status(e: NotesError) -> StatusCode {
+    match e {
+        NotesError::NotFound => StatusCode::NOT_FOUND,
+        NotesError::Forbidden => StatusCode::FORBIDDEN,
+        NotesError::Frozen => StatusCode::CONFLICT,
+        NotesError::Invalid(msg) => {
+            tracing::warn!("Invalid session notes: {}", msg);
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        NotesError::Database(e) => {
+            tracing::error!("Session notes query failed: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// GET /api/mentoring/bookings/:id/notes — fiches des participants (notes
+/// privées de l'appelant seulement) et pièces jointes
+pub async fn get_notes(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<BookingNotes>, StatusCode> {
+    session_notes::get(state.db.pool(), &id, &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(notes_status)
+}
+
+/// PUT /api/mentoring/bookings/:id/notes — fiche de l'appelant
+pub async fn put_notes(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<UpsertNotesPayload>,
+) -> Result<Json<SessionNotes>, StatusCode> {
+    session_notes::upsert(state.db.pool(), &id, &auth_user.id, &payload)
+        .await
+        .map(Json)
+        .map_err(notes_status)
+}
+
+/// POST /api/mentoring/bookings/:id/attachments
+pub async fn add_attachment(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+    Json(payload): Json<AddAttachmentPayload>,
+) -> Result<(StatusCode, Json<SessionAttachment>), StatusCode> {
+    session_notes::add_attachment(state.db.pool(), &id, &auth_user.id, &payload)
+        .await
+        .map(|attachment| (StatusCode::CREATED, Json(attachment)))
+        .map_err(notes_status)
+}
+
+/// DELETE /api/mentoring/bookings/:id/attachments/:attachment_id
+pub async fn remove_attachment(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path((id, attachment_id)): Path<(String, String)>,
+) -> Result<StatusCode, StatusCode> {
+    session_notes::remove_attachment(state.db.pool(), &id, &attachment_id, &auth_user.id)
+        .await
+        .map(|_| StatusCode::NO_CONTENT)
+        .map_err(notes_status)
+}
@@ -0,0 +1,77 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+
+use crate::{
+    middleware::auth_extractor::AuthUserExtractor,
+    models::time_off::{CreateTimeOffPayload, TimeOff, TimeOffDetail},
+    routes::mentoring_offers::time_off_status,
+    services::time_off,
+    AppState,
+};
+
+// Routes sur /api/users/me — absences du mentor
+pub fn time_off_user_routes() -> Router<AppState> {
+    Router::new()
+        .route("/me/time-off", get(list_time_off).post(create_time_off))
+        .route(
+            "/me/time-off/:id",
+            get(get_time_off).delete(delete_time_off),
+        )
+}
+
+/// GET /api/users/me/time-off — absences en cours et à venir
+pub async fn list_time_off(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+) -> Result<Json<Vec<TimeOff>>, StatusCode> {
+    time_off::list(state.db.pool(), &auth_user.id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            tracing::error!("Error listing time off of {}: {}", auth_user.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
+}
+
+/// POST /api/users/me/time-off — déclare une absence ; la réponse liste les
+/// réservations à reporter ou annuler
+pub async fn create_time_off(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Json(payload): Json<CreateTimeOffPayload>,
+) -> Result<(StatusCode, Json<TimeOffDetail>), StatusCode> {
+    // Les mentees concernés sont prévenus dans la transaction de la déclaration
+    let detail = time_off::create(state.db.pool(), &auth_user.id, &payload)
+        .await
+        .map_err(time_off_status)?;
+
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// GET /api/users/me/time-off/:id — absence et réservations restant à traiter
+pub async fn get_time_off(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<Json<TimeOffDetail>, StatusCode> {
+    time_off::detail(state.db.pool(), &auth_user.id, &id)
+        .await
+        .map(Json)
+        .map_err(time_off_status)
+}
+
+/// DELETE /api/users/me/time-off/:id
+pub async fn delete_time_off(
+    State(state): State<AppState>,
+    AuthUserExtractor(auth_user): AuthUserExtractor,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    time_off::delete(state.db.pool(), &auth_user.id, &id)
+        .await
+        .map_err(time_off_status)?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -3,8 +3,8 @@
 
 use axum::{
     extract::{Extension, Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -12,7 +12,15 @@ use serde::{Deserialize, Serialize};
 use sqlx::Row;
 use std::collections::HashMap;
 
-use crate::{middleware::auth::AuthUser, AppState};
+use crate::{
+    middleware::auth::AuthUser,
+    models::token_lot::GrantTerms,
+    services::{
+        ledger_statement::{self, StatementFormat, StatementQuery},
+        token_ledger,
+    },
+    AppState,
+};
 
 pub fn token4good_routes() -> Router<AppState> {
     Router::new()
@@ -29,6 +37,7 @@ pub fn token4good_routes() -> Router<AppState> {
         .route("/tokens/award", post(award_tokens))
         .route("/tokens/:user_id/balance", get(get_token_balance))
         .route("/tokens/:user_id/transactions", get(get_token_transactions))
+        .route("/tokens/:user_id/statement", get(get_token_statement))
         // Mentoring Sessions
         .route("/mentoring/sessions", post(create_mentoring_session))
         .route(
@@ -336,6 +345,9 @@ pub struct AwardTokensRequest {
     pub description: String,
     pub metadata: Option<serde_json::Value>,
     pub impact_score: Option<f64>,
+    /// Vesting et expiration optionnels (bonus de bienvenue, campagnes...)
+    #[serde(default, flatten)]
+    pub terms: GrantTerms,
 }
 
 #[derive(Debug, Serialize)]
@@ -347,6 +359,7 @@ pub struct TokenAwardResponse {
     pub description: String,
     pub timestamp: chrono::DateTime<chrono::Utc>,
     pub impact_score: Option<f64>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 pub async fn award_tokens(
@@ -354,26 +367,31 @@ pub async fn award_tokens(
     Extension(_auth_user): Extension<AuthUser>,
     Json(payload): Json<AwardTokensRequest>,
 ) -> Result<Json<TokenAwardResponse>, StatusCode> {
+    payload.terms.validate().map_err(|e| {
+        tracing::warn!("Invalid grant terms: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
     // Calculer les tokens avec impact score
     let impact = payload.impact_score.unwrap_or(1.0);
     let tokens_earned = (payload.tokens as f64 * impact) as i64;
 
-    // Créer la transaction
-    let transaction_id = state
-        .db
-        .create_token_transaction(
-            &payload.user_id,
-            &payload.action_type,
-            tokens_earned,
-            &payload.description,
-            payload.metadata,
-            Some(impact),
-        )
-        .await
-        .map_err(|e| {
-            tracing::error!("Error awarding tokens: {}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    // Créer la transaction (et le lot si vesting/expiration)
+    let transaction_id = token_ledger::grant_tokens(
+        state.db.pool(),
+        &payload.user_id,
+        &payload.action_type,
+        tokens_earned,
+        &payload.description,
+        payload.metadata,
+        impact,
+        &payload.terms,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Error awarding tokens: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     Ok(Json(TokenAwardResponse {
         id: transaction_id,
@@ -383,6 +401,7 @@ pub async fn award_tokens(
         description: payload.description,
         timestamp: chrono::Utc::now(),
         impact_score: Some(impact),
+        expires_at: payload.terms.expires_at,
     }))
 }
 
@@ -408,11 +427,24 @@ pub async fn get_token_balance(
         "expert"
     };
 
+    let mut conn = state.db.pool().acquire().await.map_err(|e| {
+        tracing::error!("Error acquiring connection: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let locked_balance = token_ledger::locked_balance(&mut conn, &user_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error fetching locked balance: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
     Ok(Json(T4GBalanceResponse {
         user_id,
         total_earned,
         total_spent,
         available_balance,
+        spendable_balance: (available_balance - locked_balance).max(0),
+        locked_balance,
         user_level: user_level.to_string(),
     }))
 }
@@ -435,6 +467,53 @@ pub async fn get_token_transactions(
     Ok(Json(transactions))
 }
 
+/// Relevé de compte sur une période : JSON, CSV ou PDF (`?format=`).
+pub async fn get_token_statement(
+    State(state): State<AppState>,
+    Extension(auth_user): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+    Query(params): Query<StatementQuery>,
+) -> Result<Response, StatusCode> {
+    if auth_user.id != user_id && !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let format = StatementFormat::parse(params.format.as_deref()).ok_or(StatusCode::BAD_REQUEST)?;
+    let (from, to) = params.period().map_err(|e| {
+        tracing::warn!("Invalid statement period: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let statement = ledger_statement::build_statement(state.db.pool(), Some(&user_id), from, to)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error building statement for {}: {}", user_id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(statement_response(&statement, format))
+}
+
+/// Réponse fichier pour un relevé (partagée avec l'export admin).
+pub fn statement_response(
+    statement: &ledger_statement::Statement,
+    format: StatementFormat,
+) -> Response {
+    let disposition = format!(
+        "attachment; filename=\"{}.{}\"",
+        statement.file_stem(),
+        format.extension()
+    );
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        ledger_statement::render(statement, format),
+    )
+        .into_response()
+}
+
 // ============= MENTORING SESSIONS =============
 
 #[derive(Debug, Deserialize)]
@@ -886,12 +965,13 @@ pub async fn get_system_status(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    // Calculer l'économie des tokens
+    // Calculer l'économie des tokens (les expirations ne sont pas des dépenses)
     let row = sqlx::query(
         r#"
         SELECT 
             COALESCE(SUM(CASE WHEN tokens > 0 THEN tokens ELSE 0 END), 0) as total_earned,
-            COALESCE(ABS(SUM(CASE WHEN tokens < 0 THEN tokens ELSE 0 END)), 0) as total_spent
+            COALESCE(ABS(SUM(CASE WHEN tokens < 0 AND action_type <> 'token_expiry' THEN tokens ELSE 0 END)), 0) as total_spent,
+            COALESCE(ABS(SUM(CASE WHEN action_type = 'token_expiry' THEN tokens ELSE 0 END)), 0) as total_expired
         FROM t4g_token_transactions
         "#,
     )
@@ -904,6 +984,34 @@ pub async fn get_system_status(
 
     let total_earned: Option<i64> = row.try_get("total_earned").ok();
     let total_spent: Option<i64> = row.try_get("total_spent").ok();
+    let total_expired: Option<i64> = row.try_get("total_expired").ok();
+
+    // Tokens crédités mais non dépensables : vesting en cours ou lots échus
+    // en attente du job d'expiration
+    let locked: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(
+            CASE
+                WHEN expires_at IS NOT NULL AND expires_at <= NOW() THEN amount - consumed
+                ELSE amount - consumed - GREATEST(0,
+                    CASE
+                        WHEN NOW() < vesting_start THEN 0
+                        WHEN vesting_end IS NULL OR NOW() >= vesting_end THEN amount
+                        ELSE FLOOR(amount * EXTRACT(EPOCH FROM NOW() - vesting_start)
+                                   / EXTRACT(EPOCH FROM vesting_end - vesting_start))::INT
+                    END - consumed)
+            END
+        ), 0)::BIGINT
+        FROM t4g_token_lots
+        WHERE expired_at IS NULL AND consumed < amount
+        "#,
+    )
+    .fetch_one(state.db.pool())
+    .await
+    .map_err(|e| {
+        tracing::error!("Error fetching locked tokens: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
 
     Ok(Json(SystemStatus {
         health: "healthy".to_string(),
@@ -912,9 +1020,14 @@ pub async fn get_system_status(
         active_services,
         level_distribution,
         token_economy: TokenEconomy {
-            in_circulation: total_earned.unwrap_or(0) - total_spent.unwrap_or(0),
+            in_circulation: total_earned.unwrap_or(0)
+                - total_spent.unwrap_or(0)
+                - total_expired.unwrap_or(0)
+                - locked,
             total_earned: total_earned.unwrap_or(0),
             total_spent: total_spent.unwrap_or(0),
+            total_expired: total_expired.unwrap_or(0),
+            locked,
         },
     }))
 }
@@ -1201,6 +1314,9 @@ pub struct T4GBalanceResponse {
     pub total_earned: i64,
     pub total_spent: i64,
     pub available_balance: i64,
+    /// Solde réellement utilisable (hors tokens en vesting ou expirés)
+    pub spendable_balance: i64,
+    pub locked_balance: i64,
     pub user_level: String,
 }
 
@@ -1309,6 +1425,9 @@ pub struct TokenEconomy {
     pub in_circulation: i64,
     pub total_earned: i64,
     pub total_spent: i64,
+    pub total_expired: i64,
+    /// Tokens en vesting ou échus non encore expirés par le job
+    pub locked: i64,
 }
 
 #[derive(Debug, Serialize)]
@@ -10,7 +10,9 @@ use uuid::Uuid;
 
 use crate::{
     middleware::auth_extractor::AuthUserExtractor,
+    models::attendance::AttendanceRecord,
     models::user::{CreateUserRequest, UpdateUserRequest, User, UserRole},
+    services::attendance,
     AppState,
 };
 
@@ -179,6 +181,14 @@ pub async fn get_user_profile(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
+    // Absences constatées en session de mentoring
+    let attendance = attendance::record(state.db.pool(), &id)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to load attendance record of {}: {}", id, e);
+            AttendanceRecord::default()
+        });
+
     let profile = UserProfile {
         id: user.id.to_string(),
         username: user.username,
@@ -190,6 +200,7 @@ pub async fn get_user_profile(
         score: user.score,
         created_at: user.created_at,
         lightning_address: user.lightning_address,
+        attendance,
     };
 
     Ok(Json(profile))
@@ -298,7 +309,7 @@ pub async fn get_user_transactions(
     }
 
     // Trier par date de création (plus récent en premier)
-    transactions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    transactions.sort_by_key(|t| std::cmp::Reverse(t.timestamp));
 
     Ok(Json(transactions))
 }
@@ -335,13 +346,36 @@ pub async fn get_user_notifications(
     State(state): State<AppState>,
     AuthUserExtractor(auth_user): AuthUserExtractor,
 ) -> Result<Json<Vec<Notification>>, StatusCode> {
-    match state.db.get_user_notifications(&auth_user.id).await {
-        Ok(notifications) => Ok(Json(notifications)),
+    let notifications = match state.db.get_user_notifications(&auth_user.id).await {
+        Ok(notifications) => notifications,
         Err(e) => {
             tracing::warn!("Failed to get notifications for user {}: {}", auth_user.id, e);
-            Ok(Json(vec![]))
+            vec![]
         }
-    }
+    };
+
+    // Fils de discussion non lus, en tête : une entrée par réservation
+    let unread = crate::services::chat::unread_threads(state.db.pool(), &auth_user.id)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to count unread messages for {}: {}", auth_user.id, e);
+            vec![]
+        });
+    let chat_entries = unread.into_iter().map(|t| Notification {
+        id: format!("chat:{}", t.booking_id),
+        title: "Messages non lus".to_string(),
+        message: format!(
+            "{} nouveau(x) message(s) sur la session «{}»",
+            t.unread_count, t.topic_slug
+        ),
+        notification_type: "MENTORING_CHAT_UNREAD".to_string(),
+        is_read: false,
+        link: Some(format!("/mentoring/session/{}", t.booking_id)),
+        amount: t.unread_count as i32,
+        created_at: t.last_message_at,
+    });
+
+    Ok(Json(chat_entries.chain(notifications).collect()))
 }
 
 pub async fn mark_all_notifications_read(
@@ -649,6 +683,7 @@ pub struct UserProfile {
     pub score: u32,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub lightning_address: String,
+    pub attendance: AttendanceRecord,
 }
 
 #[derive(Debug, Serialize)]
@@ -8,7 +8,11 @@ use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 
-use crate::AppState;
+use crate::{
+    models::meeting::MeetingCallbackPayload,
+    services::meeting::{self, MeetingError},
+    AppState,
+};
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -64,7 +68,9 @@ pub struct WebhookResponse {
 }
 
 pub fn webhook_routes() -> Router<AppState> {
-    Router::new().route("/dazno", post(handle_dazno_webhook))
+    Router::new()
+        .route("/dazno", post(handle_dazno_webhook))
+        .route("/meeting", post(handle_meeting_webhook))
 }
 
 /// Handler principal pour les webhooks Dazno
@@ -87,14 +93,60 @@ pub async fn handle_dazno_webhook(
     }))
 }
 
+/// Rappel de connexion/déconnexion du fournisseur de visioconférence
+///
+/// Signé comme les webhooks Dazno, avec `MEETING_WEBHOOK_SECRET`.
+pub async fn handle_meeting_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<MeetingCallbackPayload>,
+) -> Result<StatusCode, StatusCode> {
+    verify_signature(&headers, "MEETING_WEBHOOK_SECRET", &payload)?;
+
+    match meeting::record_callback(state.db.pool(), &payload, "provider").await {
+        Ok(recorded) => {
+            tracing::info!(
+                "Meeting {} {} for {} ({} event(s) recorded)",
+                payload.room_name,
+                payload.event.as_str(),
+                payload.user_id,
+                recorded
+            );
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(MeetingError::NotFound) => {
+            tracing::warn!(
+                "Meeting callback for unknown room {} or participant {}",
+                payload.room_name,
+                payload.user_id
+            );
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(e) => {
+            tracing::error!("Meeting callback failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 /// Vérifie la signature HMAC du webhook
 fn verify_webhook_signature(
     headers: &HeaderMap,
     payload: &WebhookPayload,
+) -> Result<(), StatusCode> {
+    verify_signature(headers, "T4G_WEBHOOK_SECRET", payload)
+}
+
+/// Vérifie la signature HMAC `x-t4g-signature` d'un payload avec le secret
+/// lu dans la variable d'environnement `secret_var`
+fn verify_signature<T: Serialize>(
+    headers: &HeaderMap,
+    secret_var: &str,
+    payload: &T,
 ) -> Result<(), StatusCode> {
     // Récupérer le secret depuis l'env
-    let webhook_secret = std::env::var("T4G_WEBHOOK_SECRET").map_err(|_| {
-        tracing::error!("T4G_WEBHOOK_SECRET non configuré");
+    let webhook_secret = std::env::var(secret_var).map_err(|_| {
+        tracing::error!("{} non configuré", secret_var);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
 
@@ -0,0 +1,483 @@
+//! Pointage des sessions et constat des absences
+//!
+//! Pendant la fenêtre de connexion (`models::meeting::join_window`), le mentor
+//! affiche un code court que le mentee saisit ; un code valide atteste la
+//! présence des deux parties. Les connexions remontées par le fournisseur de
+//! visioconférence valent aussi pointage.
+//!
+//! Une réservation individuelle est suivie dès qu'elle a une salle, un code
+//! ou un pointage. Passé la fenêtre, `run_no_show_detection` constate la
+//! présence : les deux parties présentes → la session passe en attente de
+//! complétion ; mentor absent (ou personne) → séquestre remboursé ; mentee
+//! absent → séquestre partiellement versé au mentor. Une réservation suivie
+//! n'est pas auto-complétée tant que le constat n'est pas fait. Les sessions
+//! de groupe sont pointées par le mentor à la clôture.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::{error, info, warn};
+
+use crate::models::attendance::{
+    AttendanceOutcome, AttendanceRecord, AttendanceStatus, CheckInCode, NoShowStats,
+    CHECKIN_CODE_LEN, MAX_CHECKIN_ATTEMPTS,
+};
+use crate::models::meeting::{join_window, JOIN_CLOSES_AFTER_MINUTES};
+use crate::models::outbox::OutboxMessage;
+use crate::services::{
+    booking_state::{self, Actor, BookingEvent, TransitionError},
+    meeting, mentoring_completion, outbox,
+};
+
+/// Réservation suivie : salle attribuée, code généré ou pointage reçu
+pub const TRACKED_BOOKING: &str = "(b.checkin_code IS NOT NULL \
+     OR EXISTS (SELECT 1 FROM mentoring_meeting_rooms mr WHERE mr.booking_id = b.id) \
+     OR EXISTS (SELECT 1 FROM mentoring_attendance_events ae WHERE ae.booking_id = b.id))";
+
+/// Constats qui comptent comme une absence du mentor
+const MENTOR_NO_SHOWS: &str = "('mentor_no_show', 'nobody')";
+/// Constats qui comptent comme une absence du mentee
+const MENTEE_NO_SHOWS: &str = "('mentee_no_show', 'nobody')";
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttendanceError {
+    #[error("Booking not found")]
+    NotFound,
+    #[error("Not allowed to check in for this booking")]
+    Forbidden,
+    #[error("Check-in is not open for this booking")]
+    Closed,
+    #[error("Invalid check-in code")]
+    InvalidCode,
+    #[error("Too many invalid check-in attempts")]
+    TooManyAttempts,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Sous-requête des absences par mentor (`mentor_id`, `attendance_sessions`, `no_shows`)
+pub fn mentor_no_show_subquery() -> String {
+    format!(
+        r#"(
+            SELECT ao.mentor_id, COUNT(*) AS attendance_sessions,
+                   COUNT(*) FILTER (WHERE b.attendance_outcome IN {}) AS no_shows
+            FROM mentoring_bookings b
+            JOIN mentoring_offers ao ON ao.id = b.offer_id
+            WHERE b.attendance_outcome IS NOT NULL
+            GROUP BY ao.mentor_id
+        )"#,
+        MENTOR_NO_SHOWS
+    )
+}
+
+struct BookingAttendance {
+    mentor_id: String,
+    mentee_id: String,
+    status: String,
+    opens_at: DateTime<Utc>,
+    closes_at: DateTime<Utc>,
+    outcome: Option<AttendanceOutcome>,
+}
+
+async fn load(pool: &PgPool, booking_id: &str) -> Result<BookingAttendance, AttendanceError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.mentee_id, b.status, b.scheduled_at, b.attendance_outcome,
+               o.mentor_id, o.duration_minutes
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(AttendanceError::NotFound)?;
+
+    let scheduled_at: DateTime<Utc> = row.try_get("scheduled_at").unwrap_or_else(|_| Utc::now());
+    let (opens_at, closes_at) =
+        join_window(scheduled_at, row.try_get("duration_minutes").unwrap_or(60));
+    Ok(BookingAttendance {
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        status: row.try_get("status").unwrap_or_default(),
+        opens_at,
+        closes_at,
+        outcome: row
+            .try_get::<Option<String>, _>("attendance_outcome")
+            .ok()
+            .flatten()
+            .and_then(|o| o.parse().ok()),
+    })
+}
+
+impl BookingAttendance {
+    /// Le pointage n'est ouvert que dans la fenêtre, sur une session à venir
+    fn check_open(&self, now: DateTime<Utc>) -> Result<(), AttendanceError> {
+        let active = self.status == "confirmed" || self.status == "pending_completion";
+        if !active || self.outcome.is_some() || now < self.opens_at || now > self.closes_at {
+            return Err(AttendanceError::Closed);
+        }
+        Ok(())
+    }
+}
+
+/// Pointage par code ; ignoré si l'utilisateur a déjà pointé par code
+async fn record_checkin(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO mentoring_attendance_events (booking_id, user_id, event, occurred_at, source)
+        SELECT $1, $2, 'join', NOW(), 'checkin'
+        WHERE NOT EXISTS (
+            SELECT 1 FROM mentoring_attendance_events
+            WHERE booking_id = $1 AND user_id = $2 AND event = 'join' AND source = 'checkin'
+        )
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Code de pointage à afficher au mentor ; l'afficher vaut pointage du mentor
+pub async fn checkin_code(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<CheckInCode, AttendanceError> {
+    let booking = load(pool, booking_id).await?;
+    if user_id != booking.mentor_id {
+        return Err(AttendanceError::Forbidden);
+    }
+    booking.check_open(Utc::now())?;
+
+    let fresh = format!(
+        "{:0width$}",
+        rand::thread_rng().gen_range(0..10u32.pow(CHECKIN_CODE_LEN as u32)),
+        width = CHECKIN_CODE_LEN
+    );
+    let mut tx = pool.begin().await?;
+    let code: String = sqlx::query_scalar(
+        "UPDATE mentoring_bookings SET checkin_code = COALESCE(checkin_code, $2)
+         WHERE id = $1 RETURNING checkin_code",
+    )
+    .bind(booking_id)
+    .bind(&fresh)
+    .fetch_one(&mut *tx)
+    .await?;
+    record_checkin(&mut tx, booking_id, user_id).await?;
+    tx.commit().await?;
+
+    Ok(CheckInCode {
+        booking_id: booking_id.to_string(),
+        code,
+        expires_at: booking.closes_at,
+    })
+}
+
+/// Pointage du mentee avec le code affiché par le mentor
+pub async fn check_in(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+    code: &str,
+) -> Result<AttendanceStatus, AttendanceError> {
+    let booking = load(pool, booking_id).await?;
+    if user_id != booking.mentee_id {
+        return Err(AttendanceError::Forbidden);
+    }
+    booking.check_open(Utc::now())?;
+
+    let mut tx = pool.begin().await?;
+    let expected: Option<String> = sqlx::query_scalar(
+        "UPDATE mentoring_bookings SET checkin_attempts = checkin_attempts + 1
+         WHERE id = $1 AND checkin_attempts < $2 RETURNING checkin_code",
+    )
+    .bind(booking_id)
+    .bind(MAX_CHECKIN_ATTEMPTS)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(AttendanceError::TooManyAttempts)?;
+
+    if expected.as_deref() != Some(code.trim()) {
+        // La tentative reste décomptée
+        tx.commit().await?;
+        return Err(AttendanceError::InvalidCode);
+    }
+    // Le code n'a pu être obtenu qu'auprès du mentor : les deux parties sont là
+    record_checkin(&mut tx, booking_id, &booking.mentee_id).await?;
+    record_checkin(&mut tx, booking_id, &booking.mentor_id).await?;
+    tx.commit().await?;
+
+    info!("Booking {} checked in by mentee {}", booking_id, user_id);
+    status(pool, booking_id, user_id).await
+}
+
+/// Première présence de chaque partie dans la fenêtre de connexion
+async fn presence(
+    pool: &PgPool,
+    booking_id: &str,
+    booking: &BookingAttendance,
+) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>), sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT user_id, MIN(occurred_at) AS first_join
+        FROM mentoring_attendance_events
+        WHERE booking_id = $1 AND event = 'join' AND occurred_at BETWEEN $2 AND $3
+        GROUP BY user_id
+        "#,
+    )
+    .bind(booking_id)
+    .bind(booking.opens_at)
+    .bind(booking.closes_at)
+    .fetch_all(pool)
+    .await?;
+
+    let first_join = |user_id: &str| {
+        rows.iter()
+            .find(|r| r.try_get::<String, _>("user_id").ok().as_deref() == Some(user_id))
+            .and_then(|r| r.try_get("first_join").ok())
+    };
+    Ok((
+        first_join(&booking.mentor_id),
+        first_join(&booking.mentee_id),
+    ))
+}
+
+/// Présence d'une réservation, pour ses participants
+pub async fn status(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<AttendanceStatus, AttendanceError> {
+    let booking = load(pool, booking_id).await?;
+    if user_id != booking.mentor_id && user_id != booking.mentee_id {
+        return Err(AttendanceError::Forbidden);
+    }
+    let (mentor_checked_in_at, mentee_checked_in_at) = presence(pool, booking_id, &booking).await?;
+    let events = meeting::attendance(pool, booking_id)
+        .await
+        .map_err(|e| match e {
+            meeting::MeetingError::Database(e) => AttendanceError::Database(e),
+            _ => AttendanceError::NotFound,
+        })?;
+
+    Ok(AttendanceStatus {
+        booking_id: booking_id.to_string(),
+        opens_at: booking.opens_at,
+        closes_at: booking.closes_at,
+        mentor_checked_in_at,
+        mentee_checked_in_at,
+        outcome: booking.outcome,
+        events,
+    })
+}
+
+/// Avis de constat, inscrit dans l'outbox avec la transition
+async fn notify(
+    conn: &mut PgConnection,
+    user_id: &str,
+    title: &str,
+    message: &str,
+    booking_id: &str,
+) -> Result<(), sqlx::Error> {
+    let notification = OutboxMessage::Notification {
+        user_id: user_id.to_string(),
+        title: title.to_string(),
+        message: message.to_string(),
+        notif_type: "MENTORING_NO_SHOW".to_string(),
+        link: Some(format!("/mentoring/session/{}", booking_id)),
+        metadata: serde_json::json!({ "booking_id": booking_id }),
+    };
+    outbox::enqueue(conn, &notification, None).await?;
+    Ok(())
+}
+
+/// Constate la présence des réservations individuelles suivies dont la
+/// fenêtre de connexion est close. Retourne le nombre de constats.
+pub async fn run_no_show_detection(pool: &PgPool) -> u64 {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT b.id, b.status, b.tokens_escrowed, o.topic_slug
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.status IN ('confirmed', 'pending_completion')
+          AND b.attendance_outcome IS NULL
+          AND o.capacity = 1
+          AND b.scheduled_at + make_interval(mins => o.duration_minutes + $1) < NOW()
+          AND {}
+        LIMIT 200
+        "#,
+        TRACKED_BOOKING
+    ))
+    .bind(JOIN_CLOSES_AFTER_MINUTES as i32)
+    .fetch_all(pool)
+    .await;
+
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            error!("No-show detection query failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut settled = 0u64;
+    for row in rows {
+        let booking_id: String = row.try_get("id").unwrap_or_default();
+        let escrow: i32 = row.try_get("tokens_escrowed").unwrap_or(0);
+        let topic_slug: String = row.try_get("topic_slug").unwrap_or_default();
+        match settle(pool, &booking_id, escrow as i64, &topic_slug).await {
+            Ok(Some(outcome)) => {
+                settled += 1;
+                info!("Attendance of booking {}: {}", booking_id, outcome.as_str());
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Attendance check skipped for {}: {}", booking_id, e),
+        }
+    }
+    settled
+}
+
+/// Constat d'une réservation ; `None` si elle a changé entre-temps
+async fn settle(
+    pool: &PgPool,
+    booking_id: &str,
+    escrow: i64,
+    topic_slug: &str,
+) -> Result<Option<AttendanceOutcome>, TransitionError> {
+    let booking = load(pool, booking_id).await.map_err(|e| match e {
+        AttendanceError::Database(e) => TransitionError::from(e),
+        _ => TransitionError::NotFound,
+    })?;
+    let (mentor_at, mentee_at) = presence(pool, booking_id, &booking).await?;
+    let outcome = AttendanceOutcome::from_presence(mentor_at.is_some(), mentee_at.is_some());
+    let metadata = serde_json::json!({
+        "outcome": outcome.as_str(),
+        "mentor_checked_in_at": mentor_at,
+        "mentee_checked_in_at": mentee_at,
+    });
+
+    let event = match outcome {
+        AttendanceOutcome::Attended if booking.status == "confirmed" => {
+            Some(BookingEvent::ConfirmAttendance)
+        }
+        AttendanceOutcome::Attended => None,
+        AttendanceOutcome::MentorNoShow | AttendanceOutcome::Nobody => {
+            Some(BookingEvent::MentorNoShow)
+        }
+        AttendanceOutcome::MenteeNoShow => Some(BookingEvent::MenteeNoShow),
+    };
+
+    let mut tx = pool.begin().await?;
+    let marked = sqlx::query(
+        "UPDATE mentoring_bookings SET attendance_outcome = $2, attendance_settled_at = NOW()
+         WHERE id = $1 AND attendance_outcome IS NULL",
+    )
+    .bind(booking_id)
+    .bind(outcome.as_str())
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if marked == 0 {
+        return Ok(None);
+    }
+    if let Some(event) = event {
+        booking_state::apply(&mut tx, booking_id, event, Actor::System, None, metadata).await?;
+    }
+
+    if let Some((refund, release)) = outcome.escrow_split(escrow) {
+        if escrow > 0 {
+            mentoring_completion::settle_cancellation(
+                &mut tx,
+                booking_id,
+                &booking.mentee_id,
+                &booking.mentor_id,
+                refund,
+                release,
+                topic_slug,
+            )
+            .await?;
+        }
+        let (mentor_msg, mentee_msg) = match outcome {
+            AttendanceOutcome::MenteeNoShow => (
+                format!(
+                    "Ton mentee ne s'est pas présenté à la session «{}» : {} T4G te sont versés.",
+                    topic_slug, release
+                ),
+                format!(
+                    "Absence constatée à la session «{}» : {} T4G remboursés sur {}.",
+                    topic_slug, refund, escrow
+                ),
+            ),
+            _ => (
+                format!(
+                    "Absence constatée à la session «{}» : le mentee est remboursé.",
+                    topic_slug
+                ),
+                format!(
+                    "Ton mentor ne s'est pas présenté à la session «{}» : {} T4G remboursés.",
+                    topic_slug, refund
+                ),
+            ),
+        };
+        notify(
+            &mut tx,
+            &booking.mentor_id,
+            "Session manquée",
+            &mentor_msg,
+            booking_id,
+        )
+        .await?;
+        notify(
+            &mut tx,
+            &booking.mentee_id,
+            "Session manquée",
+            &mentee_msg,
+            booking_id,
+        )
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(Some(outcome))
+}
+
+/// Absences constatées d'un utilisateur, comme mentor et comme mentee
+pub async fn record(pool: &PgPool, user_id: &str) -> Result<AttendanceRecord, sqlx::Error> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT
+            COUNT(*) FILTER (WHERE o.mentor_id = $1) AS mentor_sessions,
+            COUNT(*) FILTER (WHERE o.mentor_id = $1 AND b.attendance_outcome IN {})
+                AS mentor_no_shows,
+            COUNT(*) FILTER (WHERE b.mentee_id = $1) AS mentee_sessions,
+            COUNT(*) FILTER (WHERE b.mentee_id = $1 AND b.attendance_outcome IN {})
+                AS mentee_no_shows
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.attendance_outcome IS NOT NULL
+          AND (o.mentor_id = $1 OR b.mentee_id = $1)
+        "#,
+        MENTOR_NO_SHOWS, MENTEE_NO_SHOWS
+    ))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(AttendanceRecord {
+        as_mentor: NoShowStats::new(
+            row.try_get("mentor_sessions").unwrap_or(0),
+            row.try_get("mentor_no_shows").unwrap_or(0),
+        ),
+        as_mentee: NoShowStats::new(
+            row.try_get("mentee_sessions").unwrap_or(0),
+            row.try_get("mentee_no_shows").unwrap_or(0),
+        ),
+    })
+}
@@ -0,0 +1,101 @@
+//! Occupation de l'agenda des mentors
+//!
+//! Une réservation active bloque le créneau [scheduled_at, scheduled_at + durée)
+//! sur toutes les offres du mentor, sauf pour les autres places de la même
+//! session de groupe. Une absence du mentor (`mentor_time_off`) bloque tout
+//! son agenda sur la période. Les réservations concurrentes sur un même
+//! mentor sont sérialisées par un verrou consultatif de transaction.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, Row};
+
+use crate::models::time_slot::{Occupancy, SlotWindow};
+
+/// Statuts de réservation qui occupent l'agenda du mentor
+pub const BLOCKING_STATUSES: [&str; 4] = ["pending", "confirmed", "pending_completion", "disputed"];
+
+/// Offre des occupations dues à une absence : ne correspond à aucune offre,
+/// le créneau n'est donc réservable sur aucune
+pub const TIME_OFF_OFFER_ID: &str = "";
+
+/// Espace de clés des verrous d'agenda (première moitié du verrou à deux entiers)
+const SCHEDULE_LOCK_NAMESPACE: i32 = 0x7434_6167; // "t4ag"
+
+/// Verrouille l'agenda du mentor jusqu'à la fin de la transaction courante.
+pub async fn lock_mentor_schedule(
+    conn: &mut PgConnection,
+    mentor_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+        .bind(SCHEDULE_LOCK_NAMESPACE)
+        .bind(mentor_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Réservations actives et absences du mentor recoupant [from, to), toutes
+/// offres confondues.
+pub async fn mentor_occupancy(
+    conn: &mut PgConnection,
+    mentor_id: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    exclude_booking: Option<&str>,
+) -> Result<Vec<Occupancy>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT b.offer_id, b.scheduled_at, o.duration_minutes
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE o.mentor_id = $1
+          AND b.status = ANY($2)
+          AND b.scheduled_at < $4
+          AND b.scheduled_at + make_interval(mins => o.duration_minutes) > $3
+          AND ($5::text IS NULL OR b.id <> $5)
+        ORDER BY b.scheduled_at
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(&BLOCKING_STATUSES[..])
+    .bind(from)
+    .bind(to)
+    .bind(exclude_booking)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut occupancy: Vec<Occupancy> = rows
+        .iter()
+        .filter_map(|r| {
+            let start: DateTime<Utc> = r.try_get("scheduled_at").ok()?;
+            let minutes: i32 = r.try_get("duration_minutes").unwrap_or(60);
+            Some(Occupancy {
+                offer_id: r.try_get("offer_id").ok()?,
+                window: SlotWindow::new(start, minutes),
+            })
+        })
+        .collect();
+
+    let time_off = sqlx::query(
+        r#"
+        SELECT starts_at, ends_at FROM mentor_time_off
+        WHERE mentor_id = $1 AND starts_at < $3 AND ends_at > $2
+        ORDER BY starts_at
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(conn)
+    .await?;
+    occupancy.extend(time_off.iter().filter_map(|r| {
+        Some(Occupancy {
+            offer_id: TIME_OFF_OFFER_ID.to_string(),
+            window: SlotWindow {
+                start: r.try_get("starts_at").ok()?,
+                end: r.try_get("ends_at").ok()?,
+            },
+        })
+    }));
+    Ok(occupancy)
+}
@@ -0,0 +1,579 @@
+//! Machine à états des réservations de mentoring
+//!
+//! Point de passage unique des changements de statut d'une réservation :
+//! la table des transitions définit, pour chaque événement, les statuts de
+//! départ autorisés, le statut d'arrivée, les acteurs habilités et les effets
+//! de bord attendus (séquestre, preuve RGB, notifications).
+//!
+//! `apply` verrouille la réservation, vérifie la transition, met à jour le
+//! statut et la consigne dans `mentoring_booking_events`. Les effets internes
+//! à la base (réouverture de l'offre, expiration des reports, suivi de la
+//! demande mentee d'origine, gel des notes de session) sont appliqués dans
+//! la même transaction ; les autres sont retournés à l'appelant, qui les
+//! exécute après validation.
+//!
+//! ```text
+//! pending ──accept──▶ confirmed ──confirm_attendance──▶ pending_completion
+//!    │                    │                                   │
+//!    │ decline / cancel   │ cancel / withdraw /               │ confirm_completion
+//!    │                    │ session_missed / mentor_no_show / │
+//!    │                    │ mentee_no_show                    │
+//!    ▼                    ▼                                   ▼  / session_attended
+//! cancelled ◀──resolve_refund / resolve_split── disputed    completed
+//!                                                  │         ▲
+//!                       open_dispute (confirmed,   └─────────┘ resolve_release
+//!                       pending_completion)         auto_complete → auto_completed
+//! ```
+
+use sqlx::{PgConnection, Row};
+
+use crate::models::mentoring_offer::BookingStatus;
+use crate::services::{mentoring_requests, session_notes};
+
+/// Auteur d'une transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Actor {
+    Mentor,
+    Mentee,
+    Admin,
+    /// Tâches de fond (auto-complétion, constat des absences)
+    System,
+}
+
+impl Actor {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Actor::Mentor => "mentor",
+            Actor::Mentee => "mentee",
+            Actor::Admin => "admin",
+            Actor::System => "system",
+        }
+    }
+
+    /// Rôle de `user_id` dans la réservation, `None` s'il n'y participe pas.
+    pub fn participant(user_id: &str, mentor_id: &str, mentee_id: &str) -> Option<Self> {
+        if user_id == mentor_id {
+            Some(Actor::Mentor)
+        } else if user_id == mentee_id {
+            Some(Actor::Mentee)
+        } else {
+            None
+        }
+    }
+}
+
+/// Événement déclenchant un changement de statut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookingEvent {
+    /// Le mentor accepte la demande
+    Accept,
+    /// Le mentor refuse la demande
+    Decline,
+    /// Le mentee annule (demande en attente ou session confirmée)
+    Cancel,
+    /// Le mentor se retire d'une session confirmée pendant son absence
+    Withdraw,
+    /// Première partie à confirmer que la session a eu lieu
+    ConfirmAttendance,
+    /// Seconde partie à confirmer : la session est complétée
+    ConfirmCompletion,
+    /// Clôture d'une session de groupe : mentee présent
+    SessionAttended,
+    /// Clôture d'une session de groupe : mentee absent, libération partielle
+    SessionMissed,
+    /// Complétion automatique sans réponse de la seconde partie
+    AutoComplete,
+    /// Constat d'absence du mentor (ou des deux parties) : remboursement
+    MentorNoShow,
+    /// Constat d'absence du mentee : libération partielle du séquestre
+    MenteeNoShow,
+    /// Ouverture d'un litige par une partie
+    OpenDispute,
+    /// Arbitrage : séquestre remboursé au mentee
+    ResolveRefund,
+    /// Arbitrage : séquestre partagé
+    ResolveSplit,
+    /// Arbitrage : séquestre versé au mentor
+    ResolveRelease,
+}
+
+impl BookingEvent {
+    pub const ALL: [BookingEvent; 15] = [
+        BookingEvent::Accept,
+        BookingEvent::Decline,
+        BookingEvent::Cancel,
+        BookingEvent::Withdraw,
+        BookingEvent::ConfirmAttendance,
+        BookingEvent::ConfirmCompletion,
+        BookingEvent::SessionAttended,
+        BookingEvent::SessionMissed,
+        BookingEvent::AutoComplete,
+        BookingEvent::MentorNoShow,
+        BookingEvent::MenteeNoShow,
+        BookingEvent::OpenDispute,
+        BookingEvent::ResolveRefund,
+        BookingEvent::ResolveSplit,
+        BookingEvent::ResolveRelease,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookingEvent::Accept => "accept",
+            BookingEvent::Decline => "decline",
+            BookingEvent::Cancel => "cancel",
+            BookingEvent::Withdraw => "withdraw",
+            BookingEvent::ConfirmAttendance => "confirm_attendance",
+            BookingEvent::ConfirmCompletion => "confirm_completion",
+            BookingEvent::SessionAttended => "session_attended",
+            BookingEvent::SessionMissed => "session_missed",
+            BookingEvent::AutoComplete => "auto_complete",
+            BookingEvent::MentorNoShow => "mentor_no_show",
+            BookingEvent::MenteeNoShow => "mentee_no_show",
+            BookingEvent::OpenDispute => "open_dispute",
+            BookingEvent::ResolveRefund => "resolve_refund",
+            BookingEvent::ResolveSplit => "resolve_split",
+            BookingEvent::ResolveRelease => "resolve_release",
+        }
+    }
+}
+
+/// Effet de bord attendu après une transition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SideEffect {
+    /// Séquestre intégralement rendu au mentee
+    RefundEscrow,
+    /// Séquestre partagé entre remboursement et dédommagement du mentor
+    SettleCancellation,
+    /// Séquestre libéré vers le mentor
+    ReleaseEscrow,
+    /// Bonus d'apprentissage du mentee
+    LearningBonus,
+    /// Émission de la preuve RGB de la session
+    IssueProof,
+    /// La place libérée rouvre l'offre (appliqué par `apply`)
+    ReopenOffer,
+    /// Les propositions de report en attente expirent (appliqué par `apply`)
+    ExpireReschedules,
+    NotifyMentor,
+    NotifyMentee,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum TransitionError {
+    #[error("Booking not found")]
+    NotFound,
+    #[error("{actor} may not trigger {event}")]
+    Forbidden {
+        event: &'static str,
+        actor: &'static str,
+    },
+    #[error("Illegal transition {event} from {from}")]
+    Illegal {
+        from: BookingStatus,
+        event: &'static str,
+    },
+    #[error("Database error: {0}")]
+    Database(String),
+}
+
+impl From<sqlx::Error> for TransitionError {
+    fn from(e: sqlx::Error) -> Self {
+        TransitionError::Database(e.to_string())
+    }
+}
+
+/// Transition validée
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub from: BookingStatus,
+    pub to: BookingStatus,
+    pub effects: Vec<SideEffect>,
+}
+
+impl Transition {
+    pub fn has(&self, effect: SideEffect) -> bool {
+        self.effects.contains(&effect)
+    }
+}
+
+/// Statuts de départ, statut d'arrivée et acteurs habilités d'un événement
+fn rule(event: BookingEvent) -> (&'static [BookingStatus], BookingStatus, &'static [Actor]) {
+    use BookingStatus::*;
+    const PARTIES: &[Actor] = &[Actor::Mentor, Actor::Mentee];
+    match event {
+        BookingEvent::Accept => (&[Pending], Confirmed, &[Actor::Mentor]),
+        BookingEvent::Decline => (&[Pending], Cancelled, &[Actor::Mentor]),
+        BookingEvent::Cancel => (&[Pending, Confirmed], Cancelled, &[Actor::Mentee]),
+        BookingEvent::Withdraw => (&[Confirmed], Cancelled, &[Actor::Mentor]),
+        BookingEvent::ConfirmAttendance => (
+            &[Confirmed],
+            PendingCompletion,
+            // Le système confirme quand les deux parties ont pointé
+            &[Actor::Mentor, Actor::Mentee, Actor::System],
+        ),
+        BookingEvent::ConfirmCompletion => (&[PendingCompletion], Completed, PARTIES),
+        BookingEvent::SessionAttended => {
+            (&[Confirmed, PendingCompletion], Completed, &[Actor::Mentor])
+        }
+        BookingEvent::SessionMissed => {
+            (&[Confirmed, PendingCompletion], Cancelled, &[Actor::Mentor])
+        }
+        BookingEvent::AutoComplete => (&[PendingCompletion], AutoCompleted, &[Actor::System]),
+        BookingEvent::MentorNoShow | BookingEvent::MenteeNoShow => {
+            (&[Confirmed, PendingCompletion], Cancelled, &[Actor::System])
+        }
+        BookingEvent::OpenDispute => (&[Confirmed, PendingCompletion], Disputed, PARTIES),
+        BookingEvent::ResolveRefund => (&[Disputed], Cancelled, &[Actor::Admin]),
+        BookingEvent::ResolveSplit => (&[Disputed], Cancelled, &[Actor::Admin]),
+        BookingEvent::ResolveRelease => (&[Disputed], Completed, &[Actor::Admin]),
+    }
+}
+
+/// Effets de bord d'un événement, selon son auteur
+fn effects(event: BookingEvent, actor: Actor) -> Vec<SideEffect> {
+    use SideEffect::*;
+    let counterpart = if actor == Actor::Mentor {
+        NotifyMentee
+    } else {
+        NotifyMentor
+    };
+    match event {
+        BookingEvent::Accept => vec![NotifyMentee],
+        BookingEvent::Decline => vec![RefundEscrow, ReopenOffer, NotifyMentee],
+        BookingEvent::Cancel => vec![
+            SettleCancellation,
+            ReopenOffer,
+            ExpireReschedules,
+            NotifyMentor,
+        ],
+        BookingEvent::Withdraw => vec![RefundEscrow, ReopenOffer, ExpireReschedules, NotifyMentee],
+        BookingEvent::ConfirmAttendance if actor == Actor::System => vec![],
+        BookingEvent::ConfirmAttendance => vec![counterpart],
+        BookingEvent::ConfirmCompletion
+        | BookingEvent::SessionAttended
+        | BookingEvent::AutoComplete => vec![
+            ReleaseEscrow,
+            LearningBonus,
+            IssueProof,
+            NotifyMentor,
+            NotifyMentee,
+        ],
+        BookingEvent::SessionMissed => vec![SettleCancellation, NotifyMentee],
+        BookingEvent::MentorNoShow => vec![RefundEscrow, NotifyMentor, NotifyMentee],
+        BookingEvent::MenteeNoShow => vec![SettleCancellation, NotifyMentor, NotifyMentee],
+        BookingEvent::OpenDispute => vec![ExpireReschedules, counterpart],
+        BookingEvent::ResolveRefund => vec![RefundEscrow, NotifyMentor, NotifyMentee],
+        BookingEvent::ResolveSplit => vec![SettleCancellation, NotifyMentor, NotifyMentee],
+        BookingEvent::ResolveRelease => vec![ReleaseEscrow, NotifyMentor, NotifyMentee],
+    }
+}
+
+/// Vérifie la transition `event` depuis `from` par `actor`, sans base.
+pub fn transition(
+    from: BookingStatus,
+    event: BookingEvent,
+    actor: Actor,
+) -> Result<Transition, TransitionError> {
+    let (sources, to, actors) = rule(event);
+    if !actors.contains(&actor) {
+        return Err(TransitionError::Forbidden {
+            event: event.as_str(),
+            actor: actor.as_str(),
+        });
+    }
+    if !sources.contains(&from) {
+        return Err(TransitionError::Illegal {
+            from,
+            event: event.as_str(),
+        });
+    }
+    Ok(Transition {
+        from,
+        to,
+        effects: effects(event, actor),
+    })
+}
+
+/// Applique `event` à la réservation dans la transaction de `conn` :
+/// verrou, vérification, nouveau statut, journal et effets internes.
+pub async fn apply(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    event: BookingEvent,
+    actor: Actor,
+    actor_id: Option<&str>,
+    metadata: serde_json::Value,
+) -> Result<Transition, TransitionError> {
+    let row =
+        sqlx::query("SELECT status, offer_id FROM mentoring_bookings WHERE id = $1 FOR UPDATE")
+            .bind(booking_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or(TransitionError::NotFound)?;
+    let status: String = row.try_get("status").unwrap_or_default();
+    let offer_id: String = row.try_get("offer_id").unwrap_or_default();
+    let from = status
+        .parse::<BookingStatus>()
+        .map_err(TransitionError::Database)?;
+
+    let t = transition(from, event, actor)?;
+
+    sqlx::query("UPDATE mentoring_bookings SET status = $2, updated_at = NOW() WHERE id = $1")
+        .bind(booking_id)
+        .bind(t.to.to_string())
+        .execute(&mut *conn)
+        .await?;
+
+    record(
+        conn,
+        booking_id,
+        Some(t.from),
+        t.to,
+        event.as_str(),
+        actor,
+        actor_id,
+        metadata,
+    )
+    .await?;
+
+    if t.has(SideEffect::ReopenOffer) {
+        sqlx::query(
+            "UPDATE mentoring_offers SET status = 'open', updated_at = NOW()
+             WHERE id = $1 AND status = 'booked'",
+        )
+        .bind(&offer_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    if t.has(SideEffect::ExpireReschedules) {
+        sqlx::query(
+            "UPDATE mentoring_booking_reschedules SET status = 'expired', responded_at = NOW()
+             WHERE booking_id = $1 AND status = 'pending'",
+        )
+        .bind(booking_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    mentoring_requests::follow_booking(conn, booking_id, t.to).await?;
+    session_notes::freeze(conn, booking_id, t.to).await?;
+
+    Ok(t)
+}
+
+/// Consigne la création d'une réservation (statut initial)
+pub async fn record_creation(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    status: BookingStatus,
+    mentee_id: &str,
+) -> Result<(), sqlx::Error> {
+    record(
+        conn,
+        booking_id,
+        None,
+        status,
+        "requested",
+        Actor::Mentee,
+        Some(mentee_id),
+        serde_json::json!({}),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn record(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    from: Option<BookingStatus>,
+    to: BookingStatus,
+    event: &str,
+    actor: Actor,
+    actor_id: Option<&str>,
+    metadata: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO mentoring_booking_events
+            (booking_id, from_status, to_status, event, actor_role, actor_id, metadata)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+    )
+    .bind(booking_id)
+    .bind(from.map(|s| s.to_string()))
+    .bind(to.to_string())
+    .bind(event)
+    .bind(actor.as_str())
+    .bind(actor_id)
+    .bind(metadata)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Historique des transitions d'une réservation
+pub async fn history(
+    pool: &sqlx::PgPool,
+    booking_id: &str,
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT from_status, to_status, event, actor_role, actor_id, metadata, created_at
+        FROM mentoring_booking_events
+        WHERE booking_id = $1
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "from_status": r.try_get::<Option<String>, _>("from_status").unwrap_or(None),
+                "to_status":   r.try_get::<String, _>("to_status").unwrap_or_default(),
+                "event":       r.try_get::<String, _>("event").unwrap_or_default(),
+                "actor_role":  r.try_get::<String, _>("actor_role").unwrap_or_default(),
+                "actor_id":    r.try_get::<Option<String>, _>("actor_id").unwrap_or(None),
+                "metadata":    r.try_get::<serde_json::Value, _>("metadata").unwrap_or_default(),
+                "created_at":  r.try_get::<chrono::DateTime<chrono::Utc>, _>("created_at").ok(),
+            })
+        })
+        .collect())
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use BookingStatus::*;
+
+    const STATUSES: [BookingStatus; 7] = [
+        Pending,
+        Confirmed,
+        PendingCompletion,
+        Completed,
+        AutoCompleted,
+        Disputed,
+        Cancelled,
+    ];
+    const ACTORS: [Actor; 4] = [Actor::Mentor, Actor::Mentee, Actor::Admin, Actor::System];
+
+    /// Table de référence : (départ, événement, acteur) → arrivée
+    fn expected(from: BookingStatus, event: BookingEvent, actor: Actor) -> Option<BookingStatus> {
+        use Actor::*;
+        use BookingEvent::*;
+        match (from, event, actor) {
+            (Pending, Accept, Mentor) => Some(Confirmed),
+            (Pending, Decline, Mentor) => Some(Cancelled),
+            (Pending | Confirmed, Cancel, Mentee) => Some(Cancelled),
+            (Confirmed, Withdraw, Mentor) => Some(Cancelled),
+            (Confirmed, ConfirmAttendance, Mentor | Mentee | System) => Some(PendingCompletion),
+            (PendingCompletion, ConfirmCompletion, Mentor | Mentee) => Some(Completed),
+            (Confirmed | PendingCompletion, SessionAttended, Mentor) => Some(Completed),
+            (Confirmed | PendingCompletion, SessionMissed, Mentor) => Some(Cancelled),
+            (PendingCompletion, AutoComplete, System) => Some(AutoCompleted),
+            (Confirmed | PendingCompletion, MentorNoShow | MenteeNoShow, System) => Some(Cancelled),
+            (Confirmed | PendingCompletion, OpenDispute, Mentor | Mentee) => Some(Disputed),
+            (Disputed, ResolveRefund | ResolveSplit, Admin) => Some(Cancelled),
+            (Disputed, ResolveRelease, Admin) => Some(Completed),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_transitions_match_reference_table() {
+        for from in STATUSES {
+            for event in BookingEvent::ALL {
+                for actor in ACTORS {
+                    let got = transition(from, event, actor).ok().map(|t| t.to);
+                    assert_eq!(
+                        got,
+                        expected(from, event, actor),
+                        "{:?} --{:?}/{:?}-->",
+                        from,
+                        event,
+                        actor
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_terminal_statuses_have_no_exit() {
+        for from in [Completed, AutoCompleted, Cancelled] {
+            for event in BookingEvent::ALL {
+                for actor in ACTORS {
+                    assert!(transition(from, event, actor).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrong_actor_is_forbidden_before_state_check() {
+        // Un mentee ne peut pas accepter, quel que soit le statut
+        assert_eq!(
+            transition(Pending, BookingEvent::Accept, Actor::Mentee),
+            Err(TransitionError::Forbidden {
+                event: "accept",
+                actor: "mentee",
+            })
+        );
+        assert_eq!(
+            transition(Completed, BookingEvent::Accept, Actor::Mentor),
+            Err(TransitionError::Illegal {
+                from: Completed,
+                event: "accept",
+            })
+        );
+    }
+
+    #[test]
+    fn test_side_effects() {
+        let decline = transition(Pending, BookingEvent::Decline, Actor::Mentor).unwrap();
+        assert!(decline.has(SideEffect::RefundEscrow) && decline.has(SideEffect::ReopenOffer));
+
+        let done = transition(
+            PendingCompletion,
+            BookingEvent::ConfirmCompletion,
+            Actor::Mentee,
+        )
+        .unwrap();
+        assert!(done.has(SideEffect::ReleaseEscrow) && done.has(SideEffect::IssueProof));
+        assert!(!done.has(SideEffect::RefundEscrow));
+
+        // La notification va à l'autre partie
+        let dispute = transition(Confirmed, BookingEvent::OpenDispute, Actor::Mentor).unwrap();
+        assert!(dispute.has(SideEffect::NotifyMentee) && !dispute.has(SideEffect::NotifyMentor));
+        assert!(dispute.has(SideEffect::ExpireReschedules));
+
+        // Aucune transition ne rembourse et ne libère à la fois
+        for from in STATUSES {
+            for event in BookingEvent::ALL {
+                for actor in ACTORS {
+                    if let Ok(t) = transition(from, event, actor) {
+                        assert!(
+                            !(t.has(SideEffect::ReleaseEscrow)
+                                && (t.has(SideEffect::RefundEscrow)
+                                    || t.has(SideEffect::SettleCancellation)))
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_parse_roundtrip() {
+        for s in STATUSES {
+            assert_eq!(s.to_string().parse::<BookingStatus>(), Ok(s));
+        }
+        assert!("archived".parse::<BookingStatus>().is_err());
+    }
+}
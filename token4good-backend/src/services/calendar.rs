@@ -0,0 +1,598 @@
+//! Export iCalendar (RFC 5545) des réservations de mentorat
+//!
+//! Deux sorties : le flux d'abonnement d'un utilisateur (URL secrète) et le
+//! fichier `.ics` d'une réservation. L'UID d'un événement est stable et son
+//! SEQUENCE suit `mentoring_bookings.calendar_sequence`, incrémenté par
+//! trigger à chaque changement de statut ou d'horaire : les agendas abonnés
+//! mettent ainsi à jour ou retirent l'événement (refus, annulation, report).
+//!
+//! Côté mentor, les places d'une même session de groupe sont fusionnées en un
+//! seul événement.
+
+use chrono::{DateTime, Duration, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+
+/// Fenêtre passée exportée dans le flux
+const FEED_PAST_DAYS: i64 = 90;
+
+/// Longueur maximale d'une ligne iCalendar, en octets (hors CRLF)
+const MAX_LINE_OCTETS: usize = 75;
+
+const PRODID: &str = "-//Token4Good//Mentoring//FR";
+
+// ============================================================
+// Modèle
+// ============================================================
+
+/// Réservation vue depuis l'agenda d'un utilisateur
+#[derive(Debug, Clone)]
+pub struct CalendarBooking {
+    pub booking_id: String,
+    pub offer_id: String,
+    pub mentor_id: String,
+    pub mentee_id: String,
+    pub mentor_name: String,
+    pub mentee_name: String,
+    pub topic: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub duration_minutes: i32,
+    pub capacity: i32,
+    pub status: String,
+    pub sequence: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventStatus {
+    Tentative,
+    Confirmed,
+    Cancelled,
+}
+
+impl EventStatus {
+    pub fn from_booking_status(status: &str) -> Self {
+        match status {
+            "pending" => Self::Tentative,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Confirmed,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Tentative => "TENTATIVE",
+            Self::Confirmed => "CONFIRMED",
+            Self::Cancelled => "CANCELLED",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub sequence: i32,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub summary: String,
+    pub description: String,
+    pub url: String,
+    pub status: EventStatus,
+    pub last_modified: DateTime<Utc>,
+}
+
+/// Lien vers la page de session du frontend
+pub fn session_url(app_url: &str, booking_id: &str) -> String {
+    format!(
+        "{}/mentoring/session/{}",
+        app_url.trim_end_matches('/'),
+        booking_id
+    )
+}
+
+fn app_url() -> String {
+    std::env::var("APP_URL").unwrap_or_else(|_| "http://localhost:4200".to_string())
+}
+
+/// Base publique de l'API (API_PUBLIC_URL), sans `/` final
+pub fn api_public_url() -> String {
+    let base = std::env::var("API_PUBLIC_URL").unwrap_or_else(|_| {
+        let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
+        format!("http://localhost:{}", port)
+    });
+    base.trim_end_matches('/').to_string()
+}
+
+/// Événement d'une réservation individuelle, vu par `viewer_id`.
+fn single_event(b: &CalendarBooking, viewer_id: &str, app_url: &str) -> CalendarEvent {
+    let (summary, counterpart_line) = if viewer_id == b.mentor_id {
+        (
+            format!("Mentorat T4G — {} avec {}", b.topic, b.mentee_name),
+            format!("Mentee : {}", b.mentee_name),
+        )
+    } else {
+        (
+            format!("Mentorat T4G — {} avec {}", b.topic, b.mentor_name),
+            format!("Mentor : {}", b.mentor_name),
+        )
+    };
+    let url = session_url(app_url, &b.booking_id);
+    CalendarEvent {
+        uid: format!("booking-{}@token4good", b.booking_id),
+        sequence: b.sequence,
+        start: b.scheduled_at,
+        end: b.scheduled_at + Duration::minutes(b.duration_minutes as i64),
+        summary,
+        description: format!("Sujet : {}\n{}\n{}", b.topic, counterpart_line, url),
+        url,
+        status: EventStatus::from_booking_status(&b.status),
+        last_modified: b.updated_at,
+    }
+}
+
+/// Événement unique du mentor pour toutes les places d'une session de groupe.
+fn group_event(seats: &[&CalendarBooking], app_url: &str) -> CalendarEvent {
+    let first = seats[0];
+    let active: Vec<&CalendarBooking> = seats
+        .iter()
+        .copied()
+        .filter(|b| EventStatus::from_booking_status(&b.status) != EventStatus::Cancelled)
+        .collect();
+    let status = if active.is_empty() {
+        EventStatus::Cancelled
+    } else if active
+        .iter()
+        .all(|b| EventStatus::from_booking_status(&b.status) == EventStatus::Tentative)
+    {
+        EventStatus::Tentative
+    } else {
+        EventStatus::Confirmed
+    };
+    let link_booking = active.first().copied().unwrap_or(first);
+    let url = session_url(app_url, &link_booking.booking_id);
+    let participants: Vec<&str> = active.iter().map(|b| b.mentee_name.as_str()).collect();
+
+    CalendarEvent {
+        uid: format!(
+            "session-{}-{}@token4good",
+            first.offer_id,
+            first.scheduled_at.timestamp()
+        ),
+        sequence: seats.iter().map(|b| b.sequence).max().unwrap_or(0),
+        start: first.scheduled_at,
+        end: first.scheduled_at + Duration::minutes(first.duration_minutes as i64),
+        summary: format!(
+            "Mentorat T4G — {} (groupe, {}/{} participant(s))",
+            first.topic,
+            active.len(),
+            first.capacity
+        ),
+        description: format!(
+            "Sujet : {}\nParticipants : {}\n{}",
+            first.topic,
+            participants.join(", "),
+            url
+        ),
+        url,
+        status,
+        last_modified: seats
+            .iter()
+            .map(|b| b.updated_at)
+            .max()
+            .unwrap_or(first.updated_at),
+    }
+}
+
+/// Événements de l'agenda de `viewer_id`. Les places de groupe du mentor sont
+/// regroupées par (offre, horaire) ; l'ordre suit celui des réservations.
+pub fn events_for_viewer(
+    bookings: &[CalendarBooking],
+    viewer_id: &str,
+    app_url: &str,
+) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut seen_sessions: Vec<(&str, DateTime<Utc>)> = Vec::new();
+    for b in bookings {
+        if b.mentor_id != viewer_id || b.capacity <= 1 {
+            events.push(single_event(b, viewer_id, app_url));
+            continue;
+        }
+        let key = (b.offer_id.as_str(), b.scheduled_at);
+        if seen_sessions.contains(&key) {
+            continue;
+        }
+        seen_sessions.push(key);
+        let seats: Vec<&CalendarBooking> = bookings
+            .iter()
+            .filter(|s| {
+                s.mentor_id == viewer_id
+                    && s.offer_id == b.offer_id
+                    && s.scheduled_at == b.scheduled_at
+            })
+            .collect();
+        events.push(group_event(&seats, app_url));
+    }
+    events
+}
+
+// ============================================================
+// Rendu iCalendar
+// ============================================================
+
+fn format_utc(dt: DateTime<Utc>) -> String {
+    dt.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Échappement d'une valeur TEXT (RFC 5545 §3.3.11)
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Ajoute une ligne de contenu pliée à 75 octets, sans couper un caractère UTF-8.
+fn push_line(out: &mut String, line: &str) {
+    let mut budget = MAX_LINE_OCTETS;
+    let mut used = 0;
+    for c in line.chars() {
+        if used + c.len_utf8() > budget {
+            out.push_str("\r\n ");
+            budget = MAX_LINE_OCTETS - 1;
+            used = 0;
+        }
+        out.push(c);
+        used += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Rend un VCALENDAR. `method` vaut `PUBLISH` pour un flux ou un fichier, et
+/// `CANCEL` pour un fichier d'annulation ; `name` renseigne X-WR-CALNAME.
+pub fn render_calendar(
+    events: &[CalendarEvent],
+    method: &str,
+    name: Option<&str>,
+    now: DateTime<Utc>,
+) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("METHOD:{}", method));
+    if let Some(name) = name {
+        push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+        push_line(&mut out, "REFRESH-INTERVAL;VALUE=DURATION:PT1H");
+        push_line(&mut out, "X-PUBLISHED-TTL:PT1H");
+    }
+    let dtstamp = format_utc(now);
+    for e in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", e.uid));
+        push_line(&mut out, &format!("DTSTAMP:{}", dtstamp));
+        push_line(&mut out, &format!("SEQUENCE:{}", e.sequence));
+        push_line(&mut out, &format!("DTSTART:{}", format_utc(e.start)));
+        push_line(&mut out, &format!("DTEND:{}", format_utc(e.end)));
+        push_line(
+            &mut out,
+            &format!("LAST-MODIFIED:{}", format_utc(e.last_modified)),
+        );
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&e.summary)));
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape_text(&e.description)),
+        );
+        push_line(&mut out, &format!("URL:{}", e.url));
+        push_line(&mut out, &format!("STATUS:{}", e.status.as_str()));
+        if e.status == EventStatus::Cancelled {
+            push_line(&mut out, "TRANSP:TRANSPARENT");
+        }
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+// ============================================================
+// Accès base
+// ============================================================
+
+const BOOKING_SELECT: &str = r#"
+    SELECT b.id, b.offer_id, b.mentee_id, o.mentor_id, b.scheduled_at, b.status,
+           b.calendar_sequence, b.updated_at, o.duration_minutes, o.capacity,
+           COALESCE(t.name, o.topic_slug) AS topic,
+           TRIM(COALESCE(mr.firstname, '') || ' ' || COALESCE(mr.lastname, '')) AS mentor_name,
+           TRIM(COALESCE(me.firstname, '') || ' ' || COALESCE(me.lastname, '')) AS mentee_name
+    FROM mentoring_bookings b
+    JOIN mentoring_offers o ON o.id = b.offer_id
+    LEFT JOIN learning_topics t ON t.slug = o.topic_slug
+    LEFT JOIN users mr ON mr.id = o.mentor_id
+    LEFT JOIN users me ON me.id = b.mentee_id
+"#;
+
+fn booking_from_row(r: &sqlx::postgres::PgRow) -> Option<CalendarBooking> {
+    let name = |col: &str| {
+        let n: String = r.try_get(col).unwrap_or_default();
+        if n.is_empty() {
+            "Un utilisateur".to_string()
+        } else {
+            n
+        }
+    };
+    Some(CalendarBooking {
+        booking_id: r.try_get("id").ok()?,
+        offer_id: r.try_get("offer_id").unwrap_or_default(),
+        mentor_id: r.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: r.try_get("mentee_id").unwrap_or_default(),
+        mentor_name: name("mentor_name"),
+        mentee_name: name("mentee_name"),
+        topic: r.try_get("topic").unwrap_or_default(),
+        scheduled_at: r.try_get("scheduled_at").ok()?,
+        duration_minutes: r.try_get("duration_minutes").unwrap_or(60),
+        capacity: r.try_get("capacity").unwrap_or(1),
+        status: r.try_get("status").unwrap_or_default(),
+        sequence: r.try_get("calendar_sequence").unwrap_or(0),
+        updated_at: r.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+/// Flux complet d'un utilisateur (mentor et mentee). Les demandes encore en
+/// attente n'y figurent pas ; les réservations annulées restent exportées
+/// (STATUS:CANCELLED) pour que les agendas abonnés les retirent.
+pub async fn user_feed(pool: &PgPool, user_id: &str) -> Result<String, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "{} WHERE (b.mentee_id = $1 OR o.mentor_id = $1)
+           AND b.status <> 'pending'
+           AND b.scheduled_at >= $2
+         ORDER BY b.scheduled_at, b.id",
+        BOOKING_SELECT
+    ))
+    .bind(user_id)
+    .bind(Utc::now() - Duration::days(FEED_PAST_DAYS))
+    .fetch_all(pool)
+    .await?;
+
+    let bookings: Vec<CalendarBooking> = rows.iter().filter_map(booking_from_row).collect();
+    let events = events_for_viewer(&bookings, user_id, &app_url());
+    Ok(render_calendar(
+        &events,
+        "PUBLISH",
+        Some("Token4Good — Mentorat"),
+        Utc::now(),
+    ))
+}
+
+/// Fichier `.ics` d'une réservation pour `viewer_id` (mentor ou mentee).
+/// `Ok(None)` si la réservation n'existe pas ou ne le concerne pas. Une
+/// réservation annulée produit un fichier METHOD:CANCEL.
+pub async fn booking_ics(
+    pool: &PgPool,
+    booking_id: &str,
+    viewer_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let Some(row) = sqlx::query(&format!("{} WHERE b.id = $1", BOOKING_SELECT))
+        .bind(booking_id)
+        .fetch_optional(pool)
+        .await?
+    else {
+        return Ok(None);
+    };
+    let Some(booking) = booking_from_row(&row) else {
+        return Ok(None);
+    };
+    if viewer_id != booking.mentee_id && viewer_id != booking.mentor_id {
+        return Ok(None);
+    }
+
+    // Le mentor d'une session de groupe reçoit l'événement fusionné
+    let bookings = if viewer_id == booking.mentor_id && booking.capacity > 1 {
+        let rows = sqlx::query(&format!(
+            "{} WHERE b.offer_id = $1 AND b.scheduled_at = $2 ORDER BY b.id",
+            BOOKING_SELECT
+        ))
+        .bind(&booking.offer_id)
+        .bind(booking.scheduled_at)
+        .fetch_all(pool)
+        .await?;
+        rows.iter().filter_map(booking_from_row).collect()
+    } else {
+        vec![booking]
+    };
+
+    let events = events_for_viewer(&bookings, viewer_id, &app_url());
+    let method = if events.iter().all(|e| e.status == EventStatus::Cancelled) {
+        "CANCEL"
+    } else {
+        "PUBLISH"
+    };
+    Ok(Some(render_calendar(&events, method, None, Utc::now())))
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Génère (ou remplace) le jeton de flux de l'utilisateur ; l'ancien jeton
+/// cesse immédiatement de fonctionner. Le jeton en clair n'est jamais stocké.
+pub async fn rotate_feed_token(pool: &PgPool, user_id: &str) -> Result<String, sqlx::Error> {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = hex::encode(bytes);
+
+    sqlx::query(
+        r#"
+        INSERT INTO calendar_feed_tokens (user_id, token_hash)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+            SET token_hash = EXCLUDED.token_hash, created_at = NOW(), last_accessed_at = NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(hash_token(&token))
+    .execute(pool)
+    .await?;
+    Ok(token)
+}
+
+pub async fn revoke_feed_token(pool: &PgPool, user_id: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM calendar_feed_tokens WHERE user_id = $1")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// État du flux : `(created_at, last_accessed_at)` s'il est actif.
+pub async fn feed_status(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Option<(DateTime<Utc>, Option<DateTime<Utc>>)>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT created_at, last_accessed_at FROM calendar_feed_tokens WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| {
+        (
+            r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+            r.try_get("last_accessed_at").ok().flatten(),
+        )
+    }))
+}
+
+/// Résout un jeton de flux en utilisateur et note l'accès.
+pub async fn user_for_token(pool: &PgPool, token: &str) -> Result<Option<String>, sqlx::Error> {
+    let row = sqlx::query(
+        "UPDATE calendar_feed_tokens SET last_accessed_at = NOW()
+         WHERE token_hash = $1
+         RETURNING user_id",
+    )
+    .bind(hash_token(token))
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.and_then(|r| r.try_get("user_id").ok()))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn booking(id: &str, mentee: &str, status: &str) -> CalendarBooking {
+        CalendarBooking {
+            booking_id: id.to_string(),
+            offer_id: "offer-1".to_string(),
+            mentor_id: "mentor".to_string(),
+            mentee_id: mentee.to_string(),
+            mentor_name: "Alice Martin".to_string(),
+            mentee_name: format!("Mentee {}", mentee),
+            topic: "Lightning, niveau 1".to_string(),
+            scheduled_at: utc("2026-05-04T16:00:00Z"),
+            duration_minutes: 90,
+            capacity: 1,
+            status: status.to_string(),
+            sequence: 0,
+            updated_at: utc("2026-05-01T10:00:00Z"),
+        }
+    }
+
+    #[test]
+    fn test_single_booking_event_per_viewer() {
+        let b = booking("b1", "bob", "confirmed");
+        let mentee = events_for_viewer(std::slice::from_ref(&b), "bob", "https://app.t4g.io/");
+        assert_eq!(mentee.len(), 1);
+        assert_eq!(mentee[0].uid, "booking-b1@token4good");
+        assert_eq!(mentee[0].end, utc("2026-05-04T17:30:00Z"));
+        assert!(mentee[0].summary.contains("Alice Martin"));
+        assert_eq!(mentee[0].url, "https://app.t4g.io/mentoring/session/b1");
+
+        let mentor = events_for_viewer(&[b], "mentor", "https://app.t4g.io");
+        assert_eq!(mentor[0].uid, "booking-b1@token4good");
+        assert!(mentor[0].summary.contains("Mentee bob"));
+    }
+
+    #[test]
+    fn test_group_seats_merge_for_mentor_only() {
+        let seats: Vec<CalendarBooking> = [("s1", "a", "confirmed"), ("s2", "b", "cancelled")]
+            .iter()
+            .map(|(id, mentee, status)| CalendarBooking {
+                capacity: 8,
+                sequence: if *status == "cancelled" { 2 } else { 0 },
+                ..booking(id, mentee, status)
+            })
+            .collect();
+
+        let mentor = events_for_viewer(&seats, "mentor", "https://app");
+        assert_eq!(mentor.len(), 1);
+        assert_eq!(mentor[0].status, EventStatus::Confirmed);
+        assert_eq!(mentor[0].sequence, 2);
+        assert!(mentor[0].summary.contains("1/8"));
+        assert!(mentor[0].uid.starts_with("session-offer-1-"));
+
+        let all_cancelled: Vec<CalendarBooking> = seats
+            .iter()
+            .map(|s| CalendarBooking {
+                status: "cancelled".to_string(),
+                ..s.clone()
+            })
+            .collect();
+        let mentor = events_for_viewer(&all_cancelled, "mentor", "https://app");
+        assert_eq!(mentor[0].status, EventStatus::Cancelled);
+
+        // Le mentee voit sa propre place
+        let mentee = events_for_viewer(&seats[..1], "a", "https://app");
+        assert_eq!(mentee[0].uid, "booking-s1@token4good");
+    }
+
+    #[test]
+    fn test_render_escapes_and_cancels() {
+        let b = booking("b1", "bob", "cancelled");
+        let events = events_for_viewer(&[b], "bob", "https://app");
+        let ics = render_calendar(&events, "CANCEL", None, utc("2026-05-02T08:00:00Z"));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("METHOD:CANCEL\r\n"));
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+        assert!(ics.contains("DTSTART:20260504T160000Z\r\n"));
+        assert!(ics.contains("DTSTAMP:20260502T080000Z\r\n"));
+        assert!(ics.contains("Lightning\\, niveau 1"));
+        assert!(!ics.contains("X-WR-CALNAME"));
+    }
+
+    #[test]
+    fn test_line_folding_respects_utf8() {
+        let mut out = String::new();
+        let long = format!("SUMMARY:{}", "é".repeat(80));
+        push_line(&mut out, &long);
+        for line in out.split("\r\n").filter(|l| !l.is_empty()) {
+            assert!(line.len() <= MAX_LINE_OCTETS);
+        }
+        let unfolded = out.replace("\r\n ", "");
+        assert_eq!(unfolded, format!("{}\r\n", long));
+    }
+
+    #[test]
+    fn test_token_hash_is_stable() {
+        assert_eq!(hash_token("abc"), hash_token("abc"));
+        assert_ne!(hash_token("abc"), hash_token("abd"));
+        assert_eq!(hash_token("abc").len(), 64);
+    }
+}
@@ -0,0 +1,626 @@
+//! Certificats de complétion (PDF)
+//!
+//! Certificat de session adossé à une preuve `mentoring_proofs` et
+//! certificat de parcours sur un sujet. Le QR code pointe vers la
+//! vérification publique du `contract_id` RGB
+//! (`/api/certificates/verify/:contract_id`). Libellés par langue, couleurs et
+//! signataire selon l'habillage de l'organisation du mentee.
+
+use chrono::{DateTime, Utc};
+use qrcode::{Color, QrCode};
+use sqlx::{postgres::PgRow, PgPool, Row};
+
+use crate::models::certificate::{
+    parse_hex_color, CertificateBranding, CertificateLanguage, CertificateVerification,
+    PathCertificate, PathSession, SessionCertificate, UpsertBrandingPayload,
+};
+use crate::services::calendar::api_public_url;
+use crate::services::pdf::{text_width, truncate, Font, PdfDocument, A4_HEIGHT, A4_WIDTH};
+use crate::services::skills;
+
+/// Sessions listées au plus sur un certificat de parcours
+const MAX_PATH_SESSIONS: usize = 12;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CertificateError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Only the mentee, the mentor or an admin can download this certificate")]
+    Forbidden,
+    #[error("The proof behind this certificate has been revoked")]
+    Revoked,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// URL publique de vérification d'une preuve
+pub fn verify_url(contract_id: &str) -> String {
+    format!(
+        "{}/api/certificates/verify/{}",
+        api_public_url(),
+        contract_id
+    )
+}
+
+fn display_name(row: &PgRow, prefix: &str) -> String {
+    let first: String = row
+        .try_get(format!("{}_firstname", prefix).as_str())
+        .unwrap_or_default();
+    let last: String = row
+        .try_get(format!("{}_lastname", prefix).as_str())
+        .unwrap_or_default();
+    format!("{} {}", first, last).trim().to_string()
+}
+
+const PROOF_SELECT: &str = r#"
+    SELECT mp.id, mp.rgb_contract_id, mp.signature, mp.mentor_id, mp.mentee_id,
+           mp.revoked_at, mp.created_at,
+           b.scheduled_at, o.duration_minutes,
+           COALESCE(t.name, o.topic_slug, '') AS topic_name,
+           me.firstname AS mentee_firstname, me.lastname AS mentee_lastname,
+           mo.firstname AS mentor_firstname, mo.lastname AS mentor_lastname
+    FROM mentoring_proofs mp
+    LEFT JOIN mentoring_bookings b ON b.id = mp.request_id
+    LEFT JOIN mentoring_offers o ON o.id = b.offer_id
+    LEFT JOIN learning_topics t ON t.slug = o.topic_slug
+    LEFT JOIN users me ON me.id = mp.mentee_id
+    LEFT JOIN users mo ON mo.id = mp.mentor_id
+"#;
+
+/// Certificat d'une preuve de session, réservé au mentee, au mentor et aux
+/// admins ; une preuve révoquée (litige) ne donne plus de certificat.
+pub async fn session_certificate(
+    pool: &PgPool,
+    proof_id: &str,
+    viewer_id: &str,
+    viewer_is_admin: bool,
+) -> Result<SessionCertificate, CertificateError> {
+    let row = sqlx::query(&format!("{} WHERE mp.id = $1", PROOF_SELECT))
+        .bind(proof_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(CertificateError::NotFound)?;
+
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    if !viewer_is_admin && viewer_id != mentor_id && viewer_id != mentee_id {
+        return Err(CertificateError::Forbidden);
+    }
+    let revoked_at: Option<DateTime<Utc>> = row.try_get("revoked_at").ok().flatten();
+    if revoked_at.is_some() {
+        return Err(CertificateError::Revoked);
+    }
+
+    let contract_id: String = row.try_get("rgb_contract_id").unwrap_or_default();
+    let created_at: DateTime<Utc> = row.try_get("created_at").unwrap_or_else(|_| Utc::now());
+    Ok(SessionCertificate {
+        proof_id: row.try_get("id").unwrap_or_default(),
+        verify_url: verify_url(&contract_id),
+        contract_id,
+        mentee_id,
+        mentee_name: display_name(&row, "mentee"),
+        mentor_name: display_name(&row, "mentor"),
+        topic_name: row.try_get("topic_name").unwrap_or_default(),
+        scheduled_at: row
+            .try_get::<Option<DateTime<Utc>>, _>("scheduled_at")
+            .ok()
+            .flatten()
+            .unwrap_or(created_at),
+        duration_minutes: row
+            .try_get::<Option<i32>, _>("duration_minutes")
+            .ok()
+            .flatten()
+            .unwrap_or(0),
+    })
+}
+
+/// Vérification publique d'un `contract_id` ; renvoie aussi la signature
+/// stockée pour la vérification RGB faite par la route.
+pub async fn verification(
+    pool: &PgPool,
+    contract_id: &str,
+) -> Result<(CertificateVerification, String), CertificateError> {
+    let row = sqlx::query(&format!(
+        "{} WHERE mp.rgb_contract_id = $1 ORDER BY mp.created_at DESC LIMIT 1",
+        PROOF_SELECT
+    ))
+    .bind(contract_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(CertificateError::NotFound)?;
+
+    let revoked = row
+        .try_get::<Option<DateTime<Utc>>, _>("revoked_at")
+        .ok()
+        .flatten()
+        .is_some();
+    let verification = CertificateVerification {
+        contract_id: contract_id.to_string(),
+        valid: false,
+        revoked,
+        mentee_name: display_name(&row, "mentee"),
+        mentor_name: display_name(&row, "mentor"),
+        topic_name: row.try_get("topic_name").unwrap_or_default(),
+        session_date: row.try_get("scheduled_at").ok().flatten(),
+        issued_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    };
+    Ok((verification, row.try_get("signature").unwrap_or_default()))
+}
+
+/// Certificat de parcours d'un utilisateur sur un sujet travaillé
+pub async fn path_certificate(
+    pool: &PgPool,
+    user_id: &str,
+    topic_slug: &str,
+) -> Result<PathCertificate, CertificateError> {
+    let skill = skills::progress(pool, user_id)
+        .await?
+        .into_iter()
+        .find(|s| s.topic_slug == topic_slug && s.sessions > 0)
+        .ok_or(CertificateError::NotFound)?;
+
+    let mentor_ids: Vec<String> = skill.evidence.iter().map(|e| e.mentor_id.clone()).collect();
+    let names: std::collections::HashMap<String, String> = sqlx::query(
+        "SELECT id, firstname AS mentor_firstname, lastname AS mentor_lastname
+         FROM users WHERE id = ANY($1) OR id = $2",
+    )
+    .bind(&mentor_ids)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|r| {
+        (
+            r.try_get("id").unwrap_or_default(),
+            display_name(r, "mentor"),
+        )
+    })
+    .collect();
+
+    // Une session peut compter plusieurs preuves d'apprentissage sur le sujet
+    let mut seen = std::collections::HashSet::new();
+    let sessions: Vec<PathSession> = skill
+        .evidence
+        .iter()
+        .filter(|e| seen.insert(e.booking_id.clone()))
+        .map(|e| PathSession {
+            mentor_name: names.get(&e.mentor_id).cloned().unwrap_or_default(),
+            learned_at: e.learned_at,
+            contract_id: e.rgb_contract_id.clone(),
+        })
+        .collect();
+    let contract_id = sessions.iter().rev().find_map(|s| s.contract_id.clone());
+
+    Ok(PathCertificate {
+        topic_slug: skill.topic_slug,
+        mentee_name: names.get(user_id).cloned().unwrap_or_default(),
+        topic_name: skill.topic_name,
+        level: skill.level,
+        sessions,
+        verify_url: contract_id.as_deref().map(verify_url),
+        contract_id,
+    })
+}
+
+// ============================================================
+// Habillages
+// ============================================================
+
+fn branding_from_row(row: &PgRow) -> CertificateBranding {
+    CertificateBranding {
+        slug: row.try_get("slug").unwrap_or_default(),
+        organisation_name: row.try_get("organisation_name").unwrap_or_default(),
+        email_domain: row.try_get("email_domain").ok().flatten(),
+        primary_color: row.try_get("primary_color").unwrap_or_default(),
+        accent_color: row.try_get("accent_color").unwrap_or_default(),
+        signatory_name: row.try_get("signatory_name").ok().flatten(),
+        signatory_title: row.try_get("signatory_title").ok().flatten(),
+        footer: row.try_get("footer").ok().flatten(),
+    }
+}
+
+/// Habillage demandé (`?branding=`), sinon celui du domaine e-mail du
+/// mentee, sinon l'habillage par défaut.
+pub async fn resolve_branding(
+    pool: &PgPool,
+    requested: Option<&str>,
+    mentee_id: &str,
+) -> Result<CertificateBranding, CertificateError> {
+    if let Some(slug) = requested {
+        return sqlx::query("SELECT * FROM certificate_brandings WHERE slug = $1")
+            .bind(slug)
+            .fetch_optional(pool)
+            .await?
+            .map(|r| branding_from_row(&r))
+            .ok_or_else(|| CertificateError::Invalid(format!("unknown branding: {}", slug)));
+    }
+
+    let by_domain = sqlx::query(
+        r#"
+        SELECT cb.* FROM certificate_brandings cb
+        JOIN users u ON LOWER(SPLIT_PART(u.email, '@', 2)) = LOWER(cb.email_domain)
+        WHERE u.id = $1
+        "#,
+    )
+    .bind(mentee_id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(by_domain.map(|r| branding_from_row(&r)).unwrap_or_default())
+}
+
+pub async fn list_brandings(pool: &PgPool) -> Result<Vec<CertificateBranding>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM certificate_brandings ORDER BY organisation_name")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.iter().map(branding_from_row).collect())
+}
+
+pub async fn upsert_branding(
+    pool: &PgPool,
+    slug: &str,
+    payload: &UpsertBrandingPayload,
+) -> Result<CertificateBranding, CertificateError> {
+    payload.validate().map_err(CertificateError::Invalid)?;
+    let defaults = CertificateBranding::default();
+    let email_domain = payload
+        .email_domain
+        .as_deref()
+        .map(|d| d.trim().trim_start_matches('@').to_lowercase())
+        .filter(|d| !d.is_empty());
+
+    sqlx::query(
+        r#"
+        INSERT INTO certificate_brandings
+            (slug, organisation_name, email_domain, primary_color, accent_color,
+             signatory_name, signatory_title, footer)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (slug) DO UPDATE SET
+            organisation_name = EXCLUDED.organisation_name,
+            email_domain = EXCLUDED.email_domain,
+            primary_color = EXCLUDED.primary_color,
+            accent_color = EXCLUDED.accent_color,
+            signatory_name = EXCLUDED.signatory_name,
+            signatory_title = EXCLUDED.signatory_title,
+            footer = EXCLUDED.footer,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(slug)
+    .bind(payload.organisation_name.trim())
+    .bind(email_domain)
+    .bind(
+        payload
+            .primary_color
+            .as_ref()
+            .unwrap_or(&defaults.primary_color),
+    )
+    .bind(
+        payload
+            .accent_color
+            .as_ref()
+            .unwrap_or(&defaults.accent_color),
+    )
+    .bind(&payload.signatory_name)
+    .bind(&payload.signatory_title)
+    .bind(&payload.footer)
+    .fetch_one(pool)
+    .await
+    .map(|r| branding_from_row(&r))
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            CertificateError::Invalid("email_domain déjà associé à un autre habillage".into())
+        }
+        e => CertificateError::Database(e),
+    })
+}
+
+// ============================================================
+// Rendu
+// ============================================================
+
+const WHITE: (f32, f32, f32) = (1.0, 1.0, 1.0);
+const BLACK: (f32, f32, f32) = (0.0, 0.0, 0.0);
+const GREY: (f32, f32, f32) = (0.35, 0.35, 0.35);
+const CENTER: f32 = A4_WIDTH / 2.0;
+
+struct Palette {
+    primary: (f32, f32, f32),
+    accent: (f32, f32, f32),
+}
+
+impl Palette {
+    fn of(branding: &CertificateBranding) -> Self {
+        let fallback = CertificateBranding::default();
+        let color = |c: &str, d: &str| parse_hex_color(c).or(parse_hex_color(d)).unwrap_or(BLACK);
+        Palette {
+            primary: color(&branding.primary_color, &fallback.primary_color),
+            accent: color(&branding.accent_color, &fallback.accent_color),
+        }
+    }
+}
+
+fn centered(
+    doc: &mut PdfDocument,
+    y: f32,
+    size: f32,
+    font: Font,
+    text: &str,
+    rgb: (f32, f32, f32),
+) {
+    let x = CENTER - text_width(text, size) / 2.0;
+    doc.text_colored(x, y, size, font, text, rgb);
+}
+
+/// Cadre, bandeau de l'organisation, titre et bénéficiaire
+fn draw_frame(
+    doc: &mut PdfDocument,
+    branding: &CertificateBranding,
+    palette: &Palette,
+    title: &str,
+    awarded_to: &str,
+    mentee_name: &str,
+) {
+    doc.stroke_rect(
+        30.0,
+        30.0,
+        A4_WIDTH - 60.0,
+        A4_HEIGHT - 60.0,
+        3.0,
+        palette.primary,
+    );
+    doc.stroke_rect(
+        38.0,
+        38.0,
+        A4_WIDTH - 76.0,
+        A4_HEIGHT - 76.0,
+        0.8,
+        palette.accent,
+    );
+    doc.fill_rect(
+        38.0,
+        A4_HEIGHT - 102.0,
+        A4_WIDTH - 76.0,
+        64.0,
+        palette.primary,
+    );
+    centered(
+        doc,
+        A4_HEIGHT - 78.0,
+        20.0,
+        Font::Bold,
+        &truncate(&branding.organisation_name, 40),
+        WHITE,
+    );
+
+    centered(doc, 680.0, 26.0, Font::Bold, title, palette.primary);
+    centered(doc, 630.0, 12.0, Font::Regular, awarded_to, GREY);
+    centered(
+        doc,
+        596.0,
+        24.0,
+        Font::Bold,
+        &truncate(mentee_name, 40),
+        BLACK,
+    );
+    doc.fill_rect(CENTER - 90.0, 584.0, 180.0, 1.5, palette.accent);
+}
+
+/// Ligne « libellé : valeur » du bloc de détails
+fn detail(doc: &mut PdfDocument, y: f32, label: &str, value: &str) {
+    doc.text_colored(150.0, y, 11.0, Font::Bold, label, GREY);
+    doc.text(260.0, y, 11.0, Font::Regular, &truncate(value, 50));
+}
+
+/// Ligne de signature : nom au-dessus du trait, fonction en dessous
+fn signature(doc: &mut PdfDocument, x: f32, name: &str, title: &str) {
+    doc.text(x, 208.0, 11.0, Font::Bold, &truncate(name, 32));
+    doc.line(x, 200.0, x + 180.0, 200.0, 0.8);
+    doc.text_colored(x, 186.0, 9.0, Font::Regular, &truncate(title, 40), GREY);
+}
+
+/// QR code dessiné en modules pleins, coin bas-gauche en (x, y)
+fn draw_qr(doc: &mut PdfDocument, x: f32, y: f32, size: f32, data: &str) {
+    let Ok(code) = QrCode::new(data.as_bytes()) else {
+        return;
+    };
+    let width = code.width();
+    let module = size / width as f32;
+    let colors = code.to_colors();
+    for (row, line) in colors.chunks(width).enumerate() {
+        let top = y + size - (row + 1) as f32 * module;
+        // Modules sombres consécutifs fusionnés en un seul rectangle
+        let mut col = 0;
+        while col < width {
+            if line[col] != Color::Dark {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < width && line[col] == Color::Dark {
+                col += 1;
+            }
+            let left = x + start as f32 * module;
+            doc.fill_rect(left, top, (col - start) as f32 * module, module, BLACK);
+        }
+    }
+}
+
+/// Bloc preuve : QR code, contract_id et URL de vérification
+fn draw_proof(doc: &mut PdfDocument, lang: CertificateLanguage, contract_id: &str, url: &str) {
+    let labels = lang.labels();
+    draw_qr(doc, 60.0, 60.0, 90.0, url);
+    doc.text_colored(165.0, 130.0, 9.0, Font::Bold, labels.proof, GREY);
+    doc.text(165.0, 116.0, 8.0, Font::Regular, &truncate(contract_id, 70));
+    doc.text_colored(165.0, 96.0, 9.0, Font::Bold, labels.verify, GREY);
+    doc.text(165.0, 82.0, 8.0, Font::Regular, &truncate(url, 70));
+}
+
+fn draw_footer(doc: &mut PdfDocument, branding: &CertificateBranding) {
+    if let Some(footer) = &branding.footer {
+        centered(doc, 44.0, 8.0, Font::Regular, &truncate(footer, 110), GREY);
+    }
+}
+
+fn draw_signatory(doc: &mut PdfDocument, branding: &CertificateBranding) {
+    if let Some(name) = &branding.signatory_name {
+        let title = branding
+            .signatory_title
+            .as_deref()
+            .unwrap_or(&branding.organisation_name);
+        signature(doc, 335.0, name, title);
+    }
+}
+
+pub fn render_session(
+    cert: &SessionCertificate,
+    branding: &CertificateBranding,
+    lang: CertificateLanguage,
+) -> Vec<u8> {
+    let labels = lang.labels();
+    let palette = Palette::of(branding);
+    let mut doc = PdfDocument::new(labels.session_title);
+
+    draw_frame(
+        &mut doc,
+        branding,
+        &palette,
+        labels.session_title,
+        labels.awarded_to,
+        &cert.mentee_name,
+    );
+    centered(
+        &mut doc,
+        555.0,
+        12.0,
+        Font::Regular,
+        labels.session_body,
+        GREY,
+    );
+    centered(
+        &mut doc,
+        528.0,
+        16.0,
+        Font::Bold,
+        &truncate(&cert.topic_name, 50),
+        palette.primary,
+    );
+
+    detail(&mut doc, 470.0, labels.mentor, &cert.mentor_name);
+    detail(
+        &mut doc,
+        450.0,
+        labels.date,
+        &lang.format_date(cert.scheduled_at),
+    );
+    if cert.duration_minutes > 0 {
+        detail(
+            &mut doc,
+            430.0,
+            labels.duration,
+            &lang.format_duration(cert.duration_minutes),
+        );
+    }
+
+    signature(&mut doc, 80.0, &cert.mentor_name, labels.signature);
+    draw_signatory(&mut doc, branding);
+    draw_proof(&mut doc, lang, &cert.contract_id, &cert.verify_url);
+    draw_footer(&mut doc, branding);
+    doc.to_bytes()
+}
+
+pub fn render_path(
+    cert: &PathCertificate,
+    branding: &CertificateBranding,
+    lang: CertificateLanguage,
+) -> Vec<u8> {
+    let labels = lang.labels();
+    let palette = Palette::of(branding);
+    let mut doc = PdfDocument::new(labels.path_title);
+
+    draw_frame(
+        &mut doc,
+        branding,
+        &palette,
+        labels.path_title,
+        labels.awarded_to,
+        &cert.mentee_name,
+    );
+    centered(&mut doc, 555.0, 12.0, Font::Regular, labels.path_body, GREY);
+    centered(
+        &mut doc,
+        528.0,
+        16.0,
+        Font::Bold,
+        &truncate(&cert.topic_name, 50),
+        palette.primary,
+    );
+
+    detail(&mut doc, 480.0, labels.level, lang.level_label(cert.level));
+    detail(
+        &mut doc,
+        460.0,
+        labels.sessions,
+        &cert.sessions.len().to_string(),
+    );
+    let mut y = 436.0;
+    for session in cert.sessions.iter().rev().take(MAX_PATH_SESSIONS) {
+        let line = format!(
+            "{} - {}",
+            lang.format_date(session.learned_at),
+            session.mentor_name
+        );
+        doc.text(260.0, y, 9.0, Font::Regular, &truncate(&line, 60));
+        y -= 14.0;
+    }
+
+    draw_signatory(&mut doc, branding);
+    if let (Some(contract_id), Some(url)) = (&cert.contract_id, &cert.verify_url) {
+        draw_proof(&mut doc, lang, contract_id, url);
+    }
+    draw_footer(&mut doc, branding);
+    doc.to_bytes()
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_session_certificate() {
+        let cert = SessionCertificate {
+            proof_id: "proof-1".to_string(),
+            contract_id: "rgb:abc123".to_string(),
+            mentee_id: "user-1".to_string(),
+            mentee_name: "Alice Martin".to_string(),
+            mentor_name: "Bob Durand".to_string(),
+            topic_name: "Lightning Network".to_string(),
+            scheduled_at: Utc::now(),
+            duration_minutes: 60,
+            verify_url: "https://api.example.org/api/certificates/verify/rgb:abc123".to_string(),
+        };
+        let branding = CertificateBranding {
+            signatory_name: Some("Claire Petit".to_string()),
+            footer: Some("Token4Good - mentoring".to_string()),
+            ..CertificateBranding::default()
+        };
+
+        let bytes = render_session(&cert, &branding, CertificateLanguage::Fr);
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(text.contains("(Alice Martin)"));
+        assert!(text.contains("(rgb:abc123)"));
+        assert!(text.contains("(Claire Petit)"));
+        // Modules du QR code dessinés
+        assert!(text.matches(" re f Q").count() > 20);
+
+        let english = render_session(&cert, &branding, CertificateLanguage::En);
+        assert!(String::from_utf8_lossy(&english).contains("(Session certificate)"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Fils de discussion des réservations
+//!
+//! Seuls le mentor, le mentee et l'admin traitant un litige ouvert sur la
+//! réservation accèdent au fil. Chaque message et chaque accusé de lecture est
+//! publié sur le canal Postgres `mentoring_chat` ; chaque instance l'écoute et
+//! relaie les événements à ses abonnés SSE via `ChatHub`, ce qui garde la
+//! diffusion correcte derrière plusieurs instances.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{postgres::PgListener, PgPool, Row};
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+use crate::models::chat::{
+    is_read_only, ChatEvent, ChatMessage, ChatThread, ReadReceipt, SendMessagePayload,
+    UnreadThread, CHAT_RETENTION_DAYS,
+};
+use crate::models::mentoring_offer::BookingStatus;
+use crate::models::user::is_admin_role;
+
+/// Canal Postgres des événements de discussion
+const CHAT_CHANNEL: &str = "mentoring_chat";
+/// Événements en attente par abonné avant décrochage
+const HUB_CAPACITY: usize = 1024;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ChatError {
+    #[error("Booking not found")]
+    NotFound,
+    #[error("Not a participant of this booking thread")]
+    Forbidden,
+    #[error("Thread is read-only")]
+    Closed,
+    #[error("Invalid message: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Diffusion ────────────────────────────────────────────────────────────────
+
+/// Diffuseur des événements de discussion vers les abonnés de l'instance
+#[derive(Clone)]
+pub struct ChatHub {
+    tx: broadcast::Sender<ChatEvent>,
+}
+
+impl Default for ChatHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChatHub {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(HUB_CAPACITY);
+        ChatHub { tx }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ChatEvent> {
+        self.tx.subscribe()
+    }
+
+    /// Écoute le canal Postgres et relaie ses événements (reconnexion automatique).
+    pub fn spawn_listener(&self, pool: PgPool) {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let mut listener = match PgListener::connect_with(&pool).await {
+                    Ok(l) => l,
+                    Err(e) => {
+                        error!("Chat listener connection failed: {}", e);
+                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                        continue;
+                    }
+                };
+                if let Err(e) = listener.listen(CHAT_CHANNEL).await {
+                    error!("Chat listener LISTEN failed: {}", e);
+                    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                    continue;
+                }
+                loop {
+                    match listener.recv().await {
+                        Ok(n) => match serde_json::from_str::<ChatEvent>(n.payload()) {
+                            // Aucun abonné : l'événement est simplement perdu
+                            Ok(event) => {
+                                let _ = tx.send(event);
+                            }
+                            Err(e) => warn!("Unreadable chat event: {}", e),
+                        },
+                        Err(e) => {
+                            error!("Chat listener error: {}", e);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+async fn publish(conn: &mut sqlx::PgConnection, event: &ChatEvent) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(CHAT_CHANNEL)
+        .bind(serde_json::to_string(event).unwrap_or_default())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// ── Accès ────────────────────────────────────────────────────────────────────
+
+/// Rôle de l'appelant dans le fil et état du fil
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChatAccess {
+    pub role: &'static str,
+    pub read_only: bool,
+}
+
+pub async fn access(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<ChatAccess, ChatError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.mentee_id, o.mentor_id, b.status,
+               COALESCE(
+                   (SELECT MAX(e.created_at) FROM mentoring_booking_events e
+                    WHERE e.booking_id = b.id),
+                   b.updated_at
+               ) AS last_transition_at,
+               EXISTS(
+                   SELECT 1 FROM mentoring_disputes d
+                   WHERE d.booking_id = b.id AND d.status <> 'resolved'
+                     AND (d.assigned_to IS NULL OR d.assigned_to = $2)
+               ) AS open_dispute,
+               (SELECT role FROM users WHERE id = $2) AS viewer_role
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ChatError::NotFound)?;
+
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    let role = if user_id == mentee_id {
+        "mentee"
+    } else if user_id == mentor_id {
+        "mentor"
+    } else if row.try_get("open_dispute").unwrap_or(false)
+        && row
+            .try_get::<String, _>("viewer_role")
+            .is_ok_and(|r| is_admin_role(&r))
+    {
+        "admin"
+    } else {
+        return Err(ChatError::Forbidden);
+    };
+
+    let status: BookingStatus = row
+        .try_get::<String, _>("status")
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| ChatError::NotFound)?;
+    let closed_at: DateTime<Utc> = row
+        .try_get("last_transition_at")
+        .unwrap_or_else(|_| Utc::now());
+    Ok(ChatAccess {
+        role,
+        read_only: is_read_only(status, closed_at, Utc::now()),
+    })
+}
+
+// ── Messages ─────────────────────────────────────────────────────────────────
+
+fn message_from_row(r: &sqlx::postgres::PgRow) -> ChatMessage {
+    ChatMessage {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        sender_id: r.try_get("sender_id").ok().flatten(),
+        sender_role: r.try_get("sender_role").unwrap_or_default(),
+        body: r.try_get("body").unwrap_or_default(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+pub async fn get_message(pool: &PgPool, message_id: &str) -> Result<ChatMessage, ChatError> {
+    sqlx::query("SELECT * FROM mentoring_booking_messages WHERE id = $1")
+        .bind(message_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| message_from_row(&r))
+        .ok_or(ChatError::NotFound)
+}
+
+/// Page d'historique (messages antérieurs à `before`, `limit` au plus)
+pub async fn history(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+    before: Option<DateTime<Utc>>,
+    limit: i64,
+) -> Result<ChatThread, ChatError> {
+    let access = access(pool, booking_id, user_id).await?;
+
+    let mut messages: Vec<ChatMessage> = sqlx::query(
+        r#"
+        SELECT * FROM mentoring_booking_messages
+        WHERE booking_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(booking_id)
+    .bind(before)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(message_from_row)
+    .collect();
+    messages.reverse();
+
+    let read_receipts = sqlx::query(
+        "SELECT user_id, last_read_at FROM mentoring_booking_thread_reads WHERE booking_id = $1",
+    )
+    .bind(booking_id)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|r| ReadReceipt {
+        user_id: r.try_get("user_id").unwrap_or_default(),
+        last_read_at: r.try_get("last_read_at").unwrap_or_else(|_| Utc::now()),
+    })
+    .collect();
+
+    let unread_count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM mentoring_booking_messages m
+        LEFT JOIN mentoring_booking_thread_reads r
+          ON r.booking_id = m.booking_id AND r.user_id = $2
+        WHERE m.booking_id = $1
+          AND m.sender_id IS DISTINCT FROM $2
+          AND (r.last_read_at IS NULL OR m.created_at > r.last_read_at)
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(ChatThread {
+        booking_id: booking_id.to_string(),
+        messages,
+        read_receipts,
+        unread_count,
+        read_only: access.read_only,
+    })
+}
+
+/// Poste un message ; l'expéditeur a lu le fil jusqu'à son propre message.
+pub async fn send(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+    payload: &SendMessagePayload,
+) -> Result<ChatMessage, ChatError> {
+    payload.validate().map_err(ChatError::Invalid)?;
+    let access = access(pool, booking_id, user_id).await?;
+    if access.read_only {
+        return Err(ChatError::Closed);
+    }
+
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_booking_messages (booking_id, sender_id, sender_role, body)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(access.role)
+    .bind(payload.body.trim())
+    .fetch_one(&mut *tx)
+    .await?;
+    let message = message_from_row(&row);
+
+    upsert_read(&mut tx, booking_id, user_id, message.created_at).await?;
+    publish(
+        &mut tx,
+        &ChatEvent::Message {
+            booking_id: booking_id.to_string(),
+            message_id: message.id.clone(),
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(message)
+}
+
+async fn upsert_read(
+    conn: &mut sqlx::PgConnection,
+    booking_id: &str,
+    user_id: &str,
+    at: DateTime<Utc>,
+) -> Result<DateTime<Utc>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO mentoring_booking_thread_reads (booking_id, user_id, last_read_at)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (booking_id, user_id)
+        DO UPDATE SET last_read_at = GREATEST(mentoring_booking_thread_reads.last_read_at, $3)
+        RETURNING last_read_at
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(at)
+    .fetch_one(conn)
+    .await
+}
+
+/// Accusé de lecture : l'appelant a lu le fil jusqu'à maintenant.
+pub async fn mark_read(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<ReadReceipt, ChatError> {
+    access(pool, booking_id, user_id).await?;
+
+    let mut tx = pool.begin().await?;
+    let last_read_at = upsert_read(&mut tx, booking_id, user_id, Utc::now()).await?;
+    publish(
+        &mut tx,
+        &ChatEvent::Read {
+            booking_id: booking_id.to_string(),
+            user_id: user_id.to_string(),
+            last_read_at,
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(ReadReceipt {
+        user_id: user_id.to_string(),
+        last_read_at,
+    })
+}
+
+/// Fils où l'utilisateur (mentor ou mentee) a des messages non lus
+pub async fn unread_threads(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Vec<UnreadThread>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT b.id AS booking_id, o.topic_slug,
+               COUNT(*) AS unread_count, MAX(m.created_at) AS last_message_at
+        FROM mentoring_booking_messages m
+        JOIN mentoring_bookings b ON b.id = m.booking_id
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        LEFT JOIN mentoring_booking_thread_reads r
+          ON r.booking_id = b.id AND r.user_id = $1
+        WHERE (b.mentee_id = $1 OR o.mentor_id = $1)
+          AND m.sender_id IS DISTINCT FROM $1
+          AND (r.last_read_at IS NULL OR m.created_at > r.last_read_at)
+        GROUP BY b.id, o.topic_slug
+        ORDER BY MAX(m.created_at) DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| UnreadThread {
+            booking_id: r.try_get("booking_id").unwrap_or_default(),
+            topic_slug: r.try_get("topic_slug").unwrap_or_default(),
+            unread_count: r.try_get("unread_count").unwrap_or(0),
+            last_message_at: r.try_get("last_message_at").unwrap_or_else(|_| Utc::now()),
+        })
+        .collect())
+}
+
+/// Purge les messages des réservations closes depuis plus de
+/// `CHAT_RETENTION_DAYS`, hors litige non résolu. Retourne le nombre de
+/// messages supprimés.
+pub async fn run_chat_retention(pool: &PgPool) -> u64 {
+    let cutoff = Utc::now() - Duration::days(CHAT_RETENTION_DAYS);
+    let result = sqlx::query(
+        r#"
+        DELETE FROM mentoring_booking_messages m
+        USING mentoring_bookings b
+        WHERE b.id = m.booking_id
+          AND b.status IN ('completed', 'auto_completed', 'cancelled')
+          AND COALESCE(
+                  (SELECT MAX(e.created_at) FROM mentoring_booking_events e
+                   WHERE e.booking_id = b.id),
+                  b.updated_at
+              ) < $1
+          AND NOT EXISTS (
+              SELECT 1 FROM mentoring_disputes d
+              WHERE d.booking_id = b.id AND d.status <> 'resolved'
+          )
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await;
+
+    match result {
+        Ok(r) => r.rows_affected(),
+        Err(e) => {
+            error!("Chat retention failed: {}", e);
+            0
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::models::{
-    mentoring::{MentoringProof, MentoringRequest},
+    mentoring::MentoringProof,
     proof::Proof,
     user::User,
 };
 use crate::services::database_services::ServiceDatabaseOps;
+use crate::services::token_ledger;
 use sqlx::{PgPool, Row};
 use std::collections::HashMap;
 use std::error::Error;
@@ -224,11 +225,6 @@ impl DatabaseService {
     }
 
     // Proof operations - simplified
-    pub async fn create_proof(&self, _proof: &MentoringProof) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(())
-    }
-
     pub async fn create_proof_regular(&self, proof: Proof) -> Result<Proof, Box<dyn Error>> {
         // TODO: Implement properly
         Ok(proof)
@@ -247,51 +243,31 @@ impl DatabaseService {
         Ok(None)
     }
 
-    // Mentoring operations - simplified
-    pub async fn create_request(&self, _request: &MentoringRequest) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(())
-    }
-
-    pub async fn find_request_by_id(
-        &self,
-        _id: &str,
-    ) -> Result<Option<MentoringRequest>, Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(None)
-    }
-
-    pub async fn update_request_status(
-        &self,
-        _id: &str,
-        _status: &str,
-    ) -> Result<(), Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(())
-    }
-
-    pub async fn find_requests_by_status(
-        &self,
-        _status: &str,
-    ) -> Result<Vec<MentoringRequest>, Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(vec![])
-    }
+    // Mentoring operations
+    // Demandes : services::mentoring_requests
 
     pub async fn find_proofs_by_mentor(
         &self,
-        _mentor_id: &str,
+        mentor_id: &str,
     ) -> Result<Vec<MentoringProof>, Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(vec![])
+        let rows = sqlx::query(
+            "SELECT * FROM mentoring_proofs WHERE mentor_id = $1 ORDER BY created_at DESC",
+        )
+        .bind(mentor_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(mentoring_proof_from_row).collect())
     }
 
     pub async fn find_proof_by_id(
         &self,
-        _id: &str,
+        id: &str,
     ) -> Result<Option<MentoringProof>, Box<dyn Error>> {
-        // TODO: Implement properly
-        Ok(None)
+        let row = sqlx::query("SELECT * FROM mentoring_proofs WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.as_ref().map(mentoring_proof_from_row))
     }
 
     pub async fn get_proofs(
@@ -312,8 +288,10 @@ impl DatabaseService {
     }
 
     pub async fn count_mentoring_requests(&self) -> Result<u64, Box<dyn Error>> {
-        // TODO: Implement with actual query
-        Ok(0)
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mentoring_requests")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as u64)
     }
 
     pub async fn get_users(
@@ -1087,6 +1065,10 @@ impl DatabaseService {
     // ============= T4G BOOKINGS =============
 
     /// Créer une réservation de service
+    ///
+    /// Même parcours que la réservation de mentoring : verrou de solde, solde
+    /// dépensable (hors tokens non acquis ou expirés) et consommation FIFO des
+    /// lots, dans une seule transaction.
     pub async fn create_t4g_booking(
         &self,
         client_id: &str,
@@ -1100,8 +1082,11 @@ impl DatabaseService {
             .await?
             .ok_or("Service not found")?;
 
-        // Vérifier le solde du client
-        let (_, _, balance) = self.get_user_token_balance(client_id).await?;
+        let mut tx = self.pool.begin().await?;
+
+        // Vérifier le solde dépensable du client, sous verrou
+        token_ledger::lock_balance(&mut tx, client_id).await?;
+        let balance = token_ledger::spendable_balance(&mut tx, client_id).await?;
         if balance < service.token_cost {
             return Err("Insufficient token balance".into());
         }
@@ -1113,7 +1098,6 @@ impl DatabaseService {
             r#"
             INSERT INTO t4g_bookings (id, client_id, service_id, scheduled_at, notes, status, tokens_spent)
             VALUES ($1, $2, $3, $4, $5, 'pending', $6)
-            RETURNING id
             "#
         )
         .bind(&id)
@@ -1122,22 +1106,28 @@ impl DatabaseService {
         .bind(scheduled_at)
         .bind(notes)
         .bind(service.token_cost)
-        .fetch_one(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        // Déduire les tokens du client
-        self.create_token_transaction(
-            client_id,
-            "service_payment",
-            -service.token_cost,
-            &format!("Réservation service: {}", service.name),
-            Some(serde_json::json!({
-                "booking_id": id,
-                "service_id": service_id
-            })),
-            Some(1.0),
+        // Déduire les tokens du client, lots à expiration en premier
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_transactions (id, user_id, action_type, tokens, description, metadata, impact_score)
+            VALUES (gen_random_uuid()::text, $1, 'service_payment', $2, $3, $4, 1.0)
+            "#
         )
+        .bind(client_id)
+        .bind(-service.token_cost)
+        .bind(format!("Réservation service: {}", service.name))
+        .bind(serde_json::json!({
+            "booking_id": id,
+            "service_id": service_id
+        }))
+        .execute(&mut *tx)
         .await?;
+        token_ledger::consume_lots(&mut tx, client_id, service.token_cost).await?;
+
+        tx.commit().await?;
 
         Ok(id)
     }
@@ -1230,67 +1220,34 @@ impl DatabaseService {
         Ok(())
     }
 
-    /// Obtenir les opportunités pour un utilisateur (basées sur ses compétences)
+    /// Obtenir les opportunités pour un utilisateur (offres classées par le moteur de matching)
     pub async fn get_user_opportunities(
         &self,
         user_id: &str,
     ) -> Result<Vec<crate::routes::token4good::Opportunity>, Box<dyn Error>> {
-        // Récupérer les compétences de l'utilisateur
-        let user = self
-            .find_user_by_id(user_id)
-            .await?
-            .ok_or("User not found")?;
+        let matches = crate::services::matching::recommend(&self.pool, user_id, 10).await?;
 
-        let user_skills: Vec<String> = user
-            .preferences
-            .get("skills")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        // Rechercher des sessions de mentoring demandées qui correspondent aux compétences
-        let mut opportunities = Vec::new();
-
-        if !user_skills.is_empty() {
-            // Rechercher des offres de mentoring ouvertes qui correspondent aux compétences
-            let offers = sqlx::query(
-                r#"
-                SELECT mo.id, mo.topic_slug, mo.token_cost, lt.label_fr
-                FROM mentoring_offers mo
-                LEFT JOIN learning_topics lt ON lt.slug = mo.topic_slug
-                WHERE mo.status = 'open'
-                ORDER BY mo.created_at DESC
-                LIMIT 10
-                "#,
-            )
-            .fetch_all(&self.pool)
-            .await
-            .unwrap_or_default();
-
-            for row in offers {
-                let topic_slug: String = row.try_get("topic_slug").unwrap_or_default();
-                if user_skills
-                    .iter()
-                    .any(|skill| topic_slug.to_lowercase().contains(&skill.to_lowercase()))
-                {
-                    let label: String = row.try_get("label_fr").unwrap_or_else(|_| topic_slug.clone());
-                    let token_cost: i64 = row.try_get("token_cost").unwrap_or(50);
-                    opportunities.push(crate::routes::token4good::Opportunity {
-                        id: row.try_get("id").unwrap_or_default(),
-                        title: format!("Mentoring: {}", label),
-                        description: format!("Offre de mentoring sur le sujet {}", topic_slug),
-                        tokens_estimate: token_cost,
-                        category: topic_slug,
-                    });
+        Ok(matches
+            .into_iter()
+            .map(|m| {
+                let description = if m.reasons.is_empty() {
+                    format!("Offre de mentoring sur le sujet {}", m.topic_slug)
+                } else {
+                    m.reasons
+                        .iter()
+                        .map(|r| r.message.as_str())
+                        .collect::<Vec<_>>()
+                        .join(" · ")
+                };
+                crate::routes::token4good::Opportunity {
+                    id: m.offer_id,
+                    title: format!("Mentoring: {}", m.topic_slug),
+                    description,
+                    tokens_estimate: m.token_cost as i64,
+                    category: m.topic_slug,
                 }
-            }
-        }
-
-        Ok(opportunities)
+            })
+            .collect())
     }
 
     pub async fn get_user_notifications(
@@ -1351,9 +1308,20 @@ impl DatabaseService {
 
     pub async fn get_user_pending_mentoring(
         &self,
-        _user_id: &str,
+        user_id: &str,
     ) -> Result<Vec<crate::models::mentoring::MentoringRequest>, Box<dyn Error>> {
-        Ok(vec![])
+        let rows = sqlx::query(
+            "SELECT * FROM mentoring_requests
+             WHERE mentee_id = $1 AND status IN ('open', 'assigned')
+             ORDER BY created_at DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(crate::services::mentoring_requests::request_from_row)
+            .collect())
     }
 
     pub async fn increment_dashboard_access(&self, user_id: &str) -> Result<u32, Box<dyn Error>> {
@@ -1546,3 +1514,25 @@ impl DatabaseService {
             .await
     }
 }
+
+/// Preuve de session (`mentoring_proofs`) au format de l'API historique
+fn mentoring_proof_from_row(row: &sqlx::postgres::PgRow) -> MentoringProof {
+    MentoringProof {
+        id: row.try_get("id").unwrap_or_default(),
+        request_id: row.try_get("request_id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        timestamp: row
+            .try_get("created_at")
+            .unwrap_or_else(|_| chrono::Utc::now()),
+        rgb_contract_id: row.try_get("rgb_contract_id").unwrap_or_default(),
+        signature: row.try_get("signature").unwrap_or_default(),
+        rating: row
+            .try_get::<Option<i32>, _>("rating")
+            .ok()
+            .flatten()
+            .unwrap_or(0)
+            .clamp(0, 5) as u8,
+        comment: row.try_get("comment").ok().flatten(),
+    }
+}
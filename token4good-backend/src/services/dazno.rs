@@ -152,6 +152,28 @@ impl DaznoService {
         user_id: &str,
         points: u64,
         action: &str,
+    ) -> Result<(), DaznoError> {
+        self.post_gamification(Some(token), user_id, points, action)
+            .await
+    }
+
+    /// Synchronisation serveur à serveur (outbox), authentifiée par la seule
+    /// clé d'API
+    pub async fn sync_gamification(
+        &self,
+        user_id: &str,
+        points: u64,
+        action: &str,
+    ) -> Result<(), DaznoError> {
+        self.post_gamification(None, user_id, points, action).await
+    }
+
+    async fn post_gamification(
+        &self,
+        token: Option<&str>,
+        user_id: &str,
+        points: u64,
+        action: &str,
     ) -> Result<(), DaznoError> {
         let url = format!("{}/users/{}/gamification", self.users_api_base, user_id);
 
@@ -162,7 +184,7 @@ impl DaznoService {
         };
 
         let response = self
-            .authorized(self.client.post(&url), Some(token))
+            .authorized(self.client.post(&url), token)
             .header("Content-Type", "application/json")
             .json(&payload)
             .send()
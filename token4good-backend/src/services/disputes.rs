@@ -0,0 +1,736 @@
+//! Litiges sur les réservations et arbitrage du séquestre
+//!
+//! Le mentor ou le mentee ouvre un litige sur une réservation confirmée ou en
+//! attente de complétion : la réservation passe `disputed` et le séquestre
+//! reste bloqué. Les parties versent leurs preuves au dossier, un admin se
+//! l'assigne puis tranche :
+//! - `full_refund` : séquestre rendu au mentee, réservation annulée ;
+//! - `full_release` : séquestre versé au mentor, réservation complétée ;
+//! - `split` : remboursement partiel + dédommagement du mentor, réservation
+//!   annulée (mêmes écritures qu'une annulation tardive, cohérentes avec la
+//!   réconciliation du séquestre).
+//!
+//! Les écritures du ledger, le changement de statut et la preuve RGB sont
+//! atomiques : l'émission de la preuve est inscrite dans l'outbox
+//! (`services::outbox`), sa révocation appliquée avec la décision.
+
+use chrono::Utc;
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::info;
+
+use crate::models::dispute::{
+    Dispute, DisputeCase, DisputeEvent, DisputeMessagePayload, DisputeOutcome, EscrowAllocation,
+    ProofAction, ResolveDisputePayload,
+};
+use crate::models::outbox::OutboxMessage;
+use crate::models::user::is_admin_role;
+use crate::services::{
+    booking_state::{self, Actor, BookingEvent, SideEffect, TransitionError},
+    outbox,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DisputeError {
+    #[error("Dispute or booking not found")]
+    NotFound,
+    #[error("Not a participant of this booking")]
+    Forbidden,
+    #[error("Dispute or booking is not in a valid state for this action")]
+    InvalidState,
+    #[error("Invalid payload: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<TransitionError> for DisputeError {
+    fn from(e: TransitionError) -> Self {
+        match e {
+            TransitionError::NotFound => DisputeError::NotFound,
+            TransitionError::Forbidden { .. } => DisputeError::Forbidden,
+            TransitionError::Illegal { .. } => DisputeError::InvalidState,
+            TransitionError::Database(e) => DisputeError::Database(sqlx::Error::Protocol(e)),
+        }
+    }
+}
+
+/// Parties et séquestre de la réservation en litige
+#[derive(Debug, Clone)]
+pub struct BookingParties {
+    pub booking_id: String,
+    pub mentor_id: String,
+    pub mentee_id: String,
+    pub topic_slug: String,
+    pub status: String,
+    pub tokens_escrowed: i64,
+}
+
+impl BookingParties {
+    pub fn is_participant(&self, user_id: &str) -> bool {
+        user_id == self.mentor_id || user_id == self.mentee_id
+    }
+}
+
+/// Résultat d'un arbitrage
+#[derive(Debug)]
+pub struct Resolution {
+    pub dispute: Dispute,
+    pub parties: BookingParties,
+    pub allocation: EscrowAllocation,
+    /// Émission de la preuve RGB inscrite dans l'outbox
+    pub proof_queued: bool,
+}
+
+fn dispute_from_row(r: &sqlx::postgres::PgRow) -> Dispute {
+    Dispute {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        opened_by: r.try_get("opened_by").unwrap_or_default(),
+        reason: r.try_get("reason").unwrap_or_default(),
+        status: r.try_get("status").unwrap_or_default(),
+        booking_status_before: r.try_get("booking_status_before").unwrap_or_default(),
+        assigned_to: r.try_get("assigned_to").ok().flatten(),
+        outcome: r.try_get("outcome").ok().flatten(),
+        refund_tokens: r.try_get("refund_tokens").ok().flatten(),
+        release_tokens: r.try_get("release_tokens").ok().flatten(),
+        proof_action: r.try_get("proof_action").ok().flatten(),
+        resolution_note: r.try_get("resolution_note").ok().flatten(),
+        resolved_by: r.try_get("resolved_by").ok().flatten(),
+        resolved_at: r.try_get("resolved_at").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        updated_at: r.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+fn event_from_row(r: &sqlx::postgres::PgRow) -> DisputeEvent {
+    DisputeEvent {
+        id: r.try_get("id").unwrap_or_default(),
+        dispute_id: r.try_get("dispute_id").unwrap_or_default(),
+        actor_id: r.try_get("actor_id").ok().flatten(),
+        kind: r.try_get("kind").unwrap_or_default(),
+        message: r.try_get("message").ok().flatten(),
+        attachments: r
+            .try_get::<serde_json::Value, _>("attachments")
+            .ok()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default(),
+        internal: r.try_get("internal").unwrap_or(false),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+async fn load_parties(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    for_update: bool,
+) -> Result<BookingParties, DisputeError> {
+    let sql = format!(
+        r#"
+        SELECT b.id, b.mentee_id, b.status, b.tokens_escrowed, o.mentor_id, o.topic_slug
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        {}
+        "#,
+        if for_update { "FOR UPDATE OF b" } else { "" }
+    );
+    let row = sqlx::query(&sql)
+        .bind(booking_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or(DisputeError::NotFound)?;
+
+    Ok(BookingParties {
+        booking_id: row.try_get("id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        status: row.try_get("status").unwrap_or_default(),
+        tokens_escrowed: row.try_get::<i32, _>("tokens_escrowed").unwrap_or(0) as i64,
+    })
+}
+
+async fn insert_event(
+    conn: &mut PgConnection,
+    dispute_id: &str,
+    actor_id: Option<&str>,
+    kind: &str,
+    message: Option<&str>,
+    attachments: &[String],
+    internal: bool,
+) -> Result<DisputeEvent, sqlx::Error> {
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_dispute_events
+            (dispute_id, actor_id, kind, message, attachments, internal)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(dispute_id)
+    .bind(actor_id)
+    .bind(kind)
+    .bind(message)
+    .bind(serde_json::json!(attachments))
+    .bind(internal)
+    .fetch_one(conn)
+    .await?;
+    Ok(event_from_row(&row))
+}
+
+/// Litige non résolu d'une réservation, verrouillé
+async fn open_dispute_for_booking(
+    conn: &mut PgConnection,
+    booking_id: &str,
+) -> Result<Dispute, DisputeError> {
+    sqlx::query(
+        "SELECT * FROM mentoring_disputes
+         WHERE booking_id = $1 AND status <> 'resolved' FOR UPDATE",
+    )
+    .bind(booking_id)
+    .fetch_optional(conn)
+    .await?
+    .map(|r| dispute_from_row(&r))
+    .ok_or(DisputeError::NotFound)
+}
+
+/// Ouvre un litige, dans la transaction de l'appelant : la réservation passe
+/// `disputed` et les propositions de report en attente expirent.
+pub async fn open(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    user_id: &str,
+    reason: &str,
+) -> Result<(Dispute, BookingParties), DisputeError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(DisputeError::Invalid("motif du litige requis".to_string()));
+    }
+
+    let parties = load_parties(&mut *conn, booking_id, true).await?;
+
+    let actor = Actor::participant(user_id, &parties.mentor_id, &parties.mentee_id)
+        .ok_or(DisputeError::Forbidden)?;
+    booking_state::apply(
+        &mut *conn,
+        booking_id,
+        BookingEvent::OpenDispute,
+        actor,
+        Some(user_id),
+        serde_json::json!({}),
+    )
+    .await?;
+    sqlx::query("UPDATE mentoring_bookings SET dispute_reason = $2 WHERE id = $1")
+        .bind(booking_id)
+        .bind(reason)
+        .execute(&mut *conn)
+        .await?;
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_disputes (booking_id, opened_by, reason, booking_status_before)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(reason)
+    .bind(&parties.status)
+    .fetch_one(&mut *conn)
+    .await?;
+    let dispute = dispute_from_row(&row);
+
+    insert_event(
+        &mut *conn,
+        &dispute.id,
+        Some(user_id),
+        "opened",
+        Some(reason),
+        &[],
+        false,
+    )
+    .await?;
+
+    info!(
+        "Dispute {} opened on booking {} by {}",
+        dispute.id, booking_id, user_id
+    );
+    Ok((dispute, parties))
+}
+
+/// Verse une preuve au dossier du litige en cours d'une réservation, dans la
+/// transaction de l'appelant
+pub async fn add_evidence(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    user_id: &str,
+    payload: &DisputeMessagePayload,
+) -> Result<(Dispute, BookingParties, DisputeEvent), DisputeError> {
+    payload.validate().map_err(DisputeError::Invalid)?;
+
+    let parties = load_parties(&mut *conn, booking_id, false).await?;
+    if !parties.is_participant(user_id) {
+        return Err(DisputeError::Forbidden);
+    }
+    let dispute = open_dispute_for_booking(&mut *conn, booking_id).await?;
+    let event = insert_event(
+        &mut *conn,
+        &dispute.id,
+        Some(user_id),
+        "evidence",
+        Some(payload.message.trim()),
+        &payload.attachments,
+        false,
+    )
+    .await?;
+    sqlx::query("UPDATE mentoring_disputes SET updated_at = NOW() WHERE id = $1")
+        .bind(&dispute.id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok((dispute, parties, event))
+}
+
+/// Note interne d'un admin, invisible des parties
+pub async fn add_note(
+    pool: &PgPool,
+    dispute_id: &str,
+    admin_id: &str,
+    payload: &DisputeMessagePayload,
+) -> Result<DisputeEvent, DisputeError> {
+    payload.validate().map_err(DisputeError::Invalid)?;
+
+    let mut conn = pool.acquire().await?;
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM mentoring_disputes WHERE id = $1)")
+            .bind(dispute_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if !exists {
+        return Err(DisputeError::NotFound);
+    }
+    Ok(insert_event(
+        &mut conn,
+        dispute_id,
+        Some(admin_id),
+        "note",
+        Some(payload.message.trim()),
+        &payload.attachments,
+        true,
+    )
+    .await?)
+}
+
+/// Assigne le litige à un admin (`assignee`) et le passe `under_review`
+pub async fn assign(
+    pool: &PgPool,
+    dispute_id: &str,
+    admin_id: &str,
+    assignee: &str,
+) -> Result<Dispute, DisputeError> {
+    let mut tx = pool.begin().await?;
+
+    let role: Option<String> = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+        .bind(assignee)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if !role.as_deref().is_some_and(is_admin_role) {
+        return Err(DisputeError::Invalid(
+            "le litige doit être assigné à un admin".to_string(),
+        ));
+    }
+
+    let row = sqlx::query(
+        r#"
+        UPDATE mentoring_disputes
+        SET assigned_to = $2, status = 'under_review', updated_at = NOW()
+        WHERE id = $1 AND status <> 'resolved'
+        RETURNING *
+        "#,
+    )
+    .bind(dispute_id)
+    .bind(assignee)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let dispute = match row {
+        Some(r) => dispute_from_row(&r),
+        None => {
+            let exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM mentoring_disputes WHERE id = $1)")
+                    .bind(dispute_id)
+                    .fetch_one(&mut *tx)
+                    .await?;
+            return Err(if exists {
+                DisputeError::InvalidState
+            } else {
+                DisputeError::NotFound
+            });
+        }
+    };
+
+    insert_event(
+        &mut tx,
+        dispute_id,
+        Some(admin_id),
+        "assigned",
+        Some(assignee),
+        &[],
+        true,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(dispute)
+}
+
+/// Litiges, les plus anciens d'abord, filtrés par statut
+pub async fn list(pool: &PgPool, status: Option<&str>) -> Result<Vec<Dispute>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM mentoring_disputes
+        WHERE ($1::text IS NULL OR status = $1)
+        ORDER BY created_at ASC
+        LIMIT 200
+        "#,
+    )
+    .bind(status)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(dispute_from_row).collect())
+}
+
+async fn case_from_dispute(
+    pool: &PgPool,
+    dispute: Dispute,
+    include_internal: bool,
+) -> Result<DisputeCase, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM mentoring_dispute_events
+        WHERE dispute_id = $1 AND ($2 OR NOT internal)
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(&dispute.id)
+    .bind(include_internal)
+    .fetch_all(pool)
+    .await?;
+    Ok(DisputeCase {
+        dispute,
+        timeline: rows.iter().map(event_from_row).collect(),
+    })
+}
+
+/// Dossier complet, notes internes comprises (vue admin)
+pub async fn get(pool: &PgPool, dispute_id: &str) -> Result<DisputeCase, DisputeError> {
+    let dispute = sqlx::query("SELECT * FROM mentoring_disputes WHERE id = $1")
+        .bind(dispute_id)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| dispute_from_row(&r))
+        .ok_or(DisputeError::NotFound)?;
+    Ok(case_from_dispute(pool, dispute, true).await?)
+}
+
+/// Dernier litige d'une réservation, vu par une partie (sans notes internes)
+pub async fn get_for_booking(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<DisputeCase, DisputeError> {
+    let mut conn = pool.acquire().await?;
+    let parties = load_parties(&mut conn, booking_id, false).await?;
+    if !parties.is_participant(user_id) {
+        return Err(DisputeError::Forbidden);
+    }
+    let dispute = sqlx::query(
+        "SELECT * FROM mentoring_disputes WHERE booking_id = $1
+         ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .map(|r| dispute_from_row(&r))
+    .ok_or(DisputeError::NotFound)?;
+    drop(conn);
+    Ok(case_from_dispute(pool, dispute, false).await?)
+}
+
+/// Tranche le litige dans la transaction de l'appelant : écritures du
+/// séquestre, statut de la réservation, clôture du dossier et action sur la
+/// preuve RGB. Les preuves d'apprentissage restent à enregistrer après
+/// validation quand une preuve est émise.
+pub async fn resolve(
+    conn: &mut PgConnection,
+    dispute_id: &str,
+    admin_id: &str,
+    payload: &ResolveDisputePayload,
+) -> Result<Resolution, DisputeError> {
+    let dispute = sqlx::query("SELECT * FROM mentoring_disputes WHERE id = $1 FOR UPDATE")
+        .bind(dispute_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(|r| dispute_from_row(&r))
+        .ok_or(DisputeError::NotFound)?;
+    if dispute.status == "resolved" {
+        return Err(DisputeError::InvalidState);
+    }
+
+    let parties = load_parties(&mut *conn, &dispute.booking_id, true).await?;
+    let allocation = payload
+        .allocation(parties.tokens_escrowed)
+        .map_err(DisputeError::Invalid)?;
+
+    let live_proof: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM mentoring_proofs WHERE request_id = $1 AND revoked_at IS NULL)",
+    )
+    .bind(&parties.booking_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    match payload.proof_action {
+        ProofAction::Issue if live_proof => {
+            return Err(DisputeError::Invalid(
+                "une preuve existe déjà pour cette session".to_string(),
+            ))
+        }
+        ProofAction::Revoke if !live_proof => {
+            return Err(DisputeError::Invalid(
+                "aucune preuve à révoquer pour cette session".to_string(),
+            ))
+        }
+        _ => {}
+    }
+
+    let topic = &parties.topic_slug;
+    if allocation.refund_tokens > 0 {
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_transactions
+                (id, user_id, action_type, tokens, description, metadata, impact_score)
+            VALUES (gen_random_uuid()::text, $1, 'service_refund', $2, $3, $4, 0.0)
+            "#,
+        )
+        .bind(&parties.mentee_id)
+        .bind(allocation.refund_tokens)
+        .bind(format!(
+            "Remboursement séquestre — arbitrage litige : {}",
+            topic
+        ))
+        .bind(serde_json::json!({
+            "booking_id": parties.booking_id,
+            "type": "escrow_refund",
+            "dispute_id": dispute.id,
+        }))
+        .execute(&mut *conn)
+        .await?;
+    }
+    if allocation.release_tokens > 0 {
+        // Versement total = libération ; partage = dédommagement d'annulation
+        let entry_type = match payload.outcome {
+            DisputeOutcome::FullRelease => "escrow_release",
+            _ => "escrow_compensation",
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_transactions
+                (id, user_id, action_type, tokens, description, metadata, impact_score)
+            VALUES (gen_random_uuid()::text, $1, 'mentoring', $2, $3, $4, 1.0)
+            "#,
+        )
+        .bind(&parties.mentor_id)
+        .bind(allocation.release_tokens)
+        .bind(format!("Séquestre versé — arbitrage litige : {}", topic))
+        .bind(serde_json::json!({
+            "booking_id": parties.booking_id,
+            "type": entry_type,
+            "dispute_id": dispute.id,
+        }))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let event = match payload.outcome {
+        DisputeOutcome::FullRefund => BookingEvent::ResolveRefund,
+        DisputeOutcome::FullRelease => BookingEvent::ResolveRelease,
+        DisputeOutcome::Split => BookingEvent::ResolveSplit,
+    };
+    let transition = booking_state::apply(
+        &mut *conn,
+        &parties.booking_id,
+        event,
+        Actor::Admin,
+        Some(admin_id),
+        serde_json::json!({ "dispute_id": dispute.id }),
+    )
+    .await?;
+
+    if transition.has(SideEffect::ReleaseEscrow) {
+        sqlx::query("UPDATE mentoring_bookings SET tokens_awarded_mentor = $2 WHERE id = $1")
+            .bind(&parties.booking_id)
+            .bind(allocation.release_tokens as i32)
+            .execute(&mut *conn)
+            .await?;
+    } else {
+        sqlx::query(
+            r#"
+            UPDATE mentoring_bookings
+            SET cancelled_by = $2, cancelled_at = NOW(), cancellation_reason = $3,
+                refund_tokens = $4, compensation_tokens = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(&parties.booking_id)
+        .bind(admin_id)
+        .bind(payload.note.trim())
+        .bind(allocation.refund_tokens as i32)
+        .bind(allocation.release_tokens as i32)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    let row = sqlx::query(
+        r#"
+        UPDATE mentoring_disputes
+        SET status = 'resolved', outcome = $2, refund_tokens = $3, release_tokens = $4,
+            proof_action = $5, resolution_note = $6, resolved_by = $7,
+            resolved_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(&dispute.id)
+    .bind(payload.outcome.to_string())
+    .bind(allocation.refund_tokens as i32)
+    .bind(allocation.release_tokens as i32)
+    .bind(payload.proof_action.to_string())
+    .bind(payload.note.trim())
+    .bind(admin_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let dispute = dispute_from_row(&row);
+
+    insert_event(
+        &mut *conn,
+        &dispute.id,
+        Some(admin_id),
+        "resolved",
+        Some(payload.note.trim()),
+        &[],
+        false,
+    )
+    .await?;
+
+    // Preuve RGB : émission inscrite dans l'outbox, révocation immédiate
+    let proof_queued = match payload.proof_action {
+        ProofAction::None => false,
+        ProofAction::Issue => {
+            queue_dispute_proof(&mut *conn, &dispute.id, admin_id, &parties).await?
+        }
+        ProofAction::Revoke => {
+            revoke_proof(
+                &mut *conn,
+                &dispute.id,
+                admin_id,
+                &parties.booking_id,
+                &payload.note,
+            )
+            .await?;
+            false
+        }
+    };
+
+    info!(
+        "Dispute {} resolved by {} ({}): {} T4G → mentee, {} T4G → mentor",
+        dispute.id, admin_id, payload.outcome, allocation.refund_tokens, allocation.release_tokens
+    );
+    Ok(Resolution {
+        dispute,
+        parties,
+        allocation,
+        proof_queued,
+    })
+}
+
+/// Inscrit l'émission de la preuve RGB dans l'outbox, avec la décision ;
+/// le contrat est rattaché à la réservation à la livraison.
+async fn queue_dispute_proof(
+    conn: &mut PgConnection,
+    dispute_id: &str,
+    admin_id: &str,
+    parties: &BookingParties,
+) -> Result<bool, DisputeError> {
+    let feedback =
+        sqlx::query("SELECT mentee_rating, mentee_comment FROM mentoring_bookings WHERE id = $1")
+            .bind(&parties.booking_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let rating: i32 = feedback
+        .as_ref()
+        .and_then(|r| r.try_get::<Option<i32>, _>("mentee_rating").ok().flatten())
+        .unwrap_or(5);
+    let comment: Option<String> = feedback
+        .as_ref()
+        .and_then(|r| r.try_get("mentee_comment").ok().flatten());
+
+    let proof = OutboxMessage::RgbProof {
+        booking_id: parties.booking_id.clone(),
+        mentor_id: parties.mentor_id.clone(),
+        mentee_id: parties.mentee_id.clone(),
+        rating,
+        comment,
+    };
+    let queued = outbox::enqueue(
+        &mut *conn,
+        &proof,
+        Some(&format!(
+            "rgb_proof:{}:dispute:{}",
+            parties.booking_id, dispute_id
+        )),
+    )
+    .await?;
+    insert_event(
+        conn,
+        dispute_id,
+        Some(admin_id),
+        "proof_issued",
+        None,
+        &[],
+        false,
+    )
+    .await?;
+    Ok(queued)
+}
+
+async fn revoke_proof(
+    conn: &mut PgConnection,
+    dispute_id: &str,
+    admin_id: &str,
+    booking_id: &str,
+    reason: &str,
+) -> Result<(), DisputeError> {
+    let revoked: Vec<String> = sqlx::query_scalar(
+        r#"
+        UPDATE mentoring_proofs
+        SET revoked_at = NOW(), revocation_reason = $2
+        WHERE request_id = $1 AND revoked_at IS NULL
+        RETURNING rgb_contract_id
+        "#,
+    )
+    .bind(booking_id)
+    .bind(reason.trim())
+    .fetch_all(&mut *conn)
+    .await?;
+    for contract_id in &revoked {
+        insert_event(
+            &mut *conn,
+            dispute_id,
+            Some(admin_id),
+            "proof_revoked",
+            Some(contract_id),
+            &[],
+            false,
+        )
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,962 @@
+//! Rapprochement des séquestres de mentoring avec le ledger T4G
+//!
+//! Pour chaque réservation, compare `tokens_escrowed` aux écritures
+//! `escrow_debit` / `escrow_refund` / `escrow_release` / `escrow_compensation`
+//! et classe les écarts.
+//! Les cas sûrs sont corrigés automatiquement :
+//! - séquestre jamais débité sur une réservation encore active (si solde suffisant)
+//! - réservation annulée jamais remboursée
+//! - séquestre débité plusieurs fois (remboursement de l'excédent)
+//!
+//! Les autres écarts (double libération, remboursement orphelin...) sont
+//! seulement signalés dans le rapport admin.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgPool, Row};
+use tracing::{error, info, warn};
+
+use crate::services::{mentoring_completion, token_ledger};
+
+// ── Constantes ─────────────────────────────────────────────────────────────
+
+/// Les réservations modifiées récemment sont ignorées : le débit et le
+/// remboursement sont écrits juste après le commit du changement de statut.
+const GRACE_MINUTES: i32 = 15;
+
+/// Verrou consultatif : une seule passe de rapprochement à la fois
+const RECONCILIATION_LOCK_KEY: i64 = 0x7434_675f_6573_6377; // "t4g_escw"
+
+const ACTIVE_STATUSES: [&str; 4] = ["pending", "confirmed", "pending_completion", "disputed"];
+const COMPLETED_STATUSES: [&str; 2] = ["completed", "auto_completed"];
+
+// ── Modèle ─────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct BookingEscrow {
+    pub booking_id: String,
+    pub mentee_id: String,
+    pub status: String,
+    pub tokens_escrowed: i64,
+    /// Part remboursable fixée à l'annulation par le mentee (politique de l'offre)
+    pub refund_due: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    Debit,
+    Refund,
+    Release,
+    /// Dédommagement du mentor sur une annulation tardive
+    Compensation,
+}
+
+impl EntryKind {
+    fn from_metadata(kind: &str) -> Option<Self> {
+        match kind {
+            "escrow_debit" => Some(Self::Debit),
+            "escrow_refund" => Some(Self::Refund),
+            "escrow_release" => Some(Self::Release),
+            "escrow_compensation" => Some(Self::Compensation),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EscrowEntry {
+    pub transaction_id: String,
+    pub booking_id: String,
+    pub user_id: String,
+    pub kind: EntryKind,
+    pub tokens: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchKind {
+    MissingDebit,
+    DebitAmountMismatch,
+    DoubleDebit,
+    MissingRelease,
+    DoubleRelease,
+    MissingRefund,
+    DoubleRefund,
+    OrphanRefund,
+    ReleaseAndRefund,
+    CompensationMismatch,
+    OrphanEntry,
+}
+
+impl std::fmt::Display for MismatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            MismatchKind::MissingDebit => "missing_debit",
+            MismatchKind::DebitAmountMismatch => "debit_amount_mismatch",
+            MismatchKind::DoubleDebit => "double_debit",
+            MismatchKind::MissingRelease => "missing_release",
+            MismatchKind::DoubleRelease => "double_release",
+            MismatchKind::MissingRefund => "missing_refund",
+            MismatchKind::DoubleRefund => "double_refund",
+            MismatchKind::OrphanRefund => "orphan_refund",
+            MismatchKind::ReleaseAndRefund => "release_and_refund",
+            MismatchKind::CompensationMismatch => "compensation_mismatch",
+            MismatchKind::OrphanEntry => "orphan_entry",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Correction automatique jugée sûre
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum AutoFix {
+    DebitMentee { mentee_id: String, amount: i64 },
+    RefundMentee { mentee_id: String, amount: i64 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub booking_id: String,
+    pub booking_status: Option<String>,
+    pub kind: MismatchKind,
+    pub expected_tokens: Option<i64>,
+    pub actual_tokens: Option<i64>,
+    pub transaction_ids: Vec<String>,
+    pub auto_fix: Option<AutoFix>,
+}
+
+/// Classe les écarts d'une réservation à partir de ses écritures séquestre.
+pub fn classify(booking: &BookingEscrow, entries: &[&EscrowEntry]) -> Vec<Mismatch> {
+    let of_kind = |k: EntryKind| -> Vec<&EscrowEntry> {
+        entries.iter().copied().filter(|e| e.kind == k).collect()
+    };
+    let debits = of_kind(EntryKind::Debit);
+    let refunds = of_kind(EntryKind::Refund);
+    let releases = of_kind(EntryKind::Release);
+    let compensations = of_kind(EntryKind::Compensation);
+
+    let debited: i64 = debits.iter().map(|e| -e.tokens).sum();
+    let refunded: i64 = refunds.iter().map(|e| e.tokens).sum();
+    let compensated: i64 = compensations.iter().map(|e| e.tokens).sum();
+    let ids = |es: &[&EscrowEntry]| es.iter().map(|e| e.transaction_id.clone()).collect();
+
+    let status = booking.status.as_str();
+    let is_active = ACTIVE_STATUSES.contains(&status);
+    let is_completed = COMPLETED_STATUSES.contains(&status);
+    let is_cancelled = status == "cancelled";
+    let escrow = booking.tokens_escrowed;
+
+    let mismatch = |kind, expected, actual, transaction_ids, auto_fix| Mismatch {
+        booking_id: booking.booking_id.clone(),
+        booking_status: Some(booking.status.clone()),
+        kind,
+        expected_tokens: expected,
+        actual_tokens: actual,
+        transaction_ids,
+        auto_fix,
+    };
+
+    let mut out = Vec::new();
+
+    // ── Débit ──
+    if escrow > 0 && debits.is_empty() {
+        if is_active {
+            out.push(mismatch(
+                MismatchKind::MissingDebit,
+                Some(escrow),
+                Some(0),
+                vec![],
+                Some(AutoFix::DebitMentee {
+                    mentee_id: booking.mentee_id.clone(),
+                    amount: escrow,
+                }),
+            ));
+        } else if is_completed {
+            out.push(mismatch(
+                MismatchKind::MissingDebit,
+                Some(escrow),
+                Some(0),
+                vec![],
+                None,
+            ));
+        }
+    } else if debits.len() > 1 {
+        let excess = debited - escrow;
+        out.push(mismatch(
+            MismatchKind::DoubleDebit,
+            Some(escrow),
+            Some(debited),
+            ids(&debits),
+            (excess > 0).then(|| AutoFix::RefundMentee {
+                mentee_id: booking.mentee_id.clone(),
+                amount: excess,
+            }),
+        ));
+    } else if debits.len() == 1 && debited != escrow {
+        out.push(mismatch(
+            MismatchKind::DebitAmountMismatch,
+            Some(escrow),
+            Some(debited),
+            ids(&debits),
+            None,
+        ));
+    }
+
+    // ── Remboursement sans débit ou sur réservation vivante ──
+    if !refunds.is_empty() && (debits.is_empty() || is_active) {
+        out.push(mismatch(
+            MismatchKind::OrphanRefund,
+            Some(0),
+            Some(refunded),
+            ids(&refunds),
+            None,
+        ));
+    }
+
+    // ── Libération ──
+    if releases.len() > 1 {
+        out.push(mismatch(
+            MismatchKind::DoubleRelease,
+            Some(releases[0].tokens),
+            Some(releases.iter().map(|e| e.tokens).sum()),
+            ids(&releases),
+            None,
+        ));
+    }
+    if !releases.is_empty() && !refunds.is_empty() {
+        let mut both = ids(&releases);
+        both.extend(ids(&refunds));
+        out.push(mismatch(
+            MismatchKind::ReleaseAndRefund,
+            None,
+            None,
+            both,
+            None,
+        ));
+    }
+    if is_completed && escrow > 0 && releases.is_empty() {
+        out.push(mismatch(
+            MismatchKind::MissingRelease,
+            Some(escrow),
+            Some(0),
+            vec![],
+            None,
+        ));
+    }
+
+    // ── Annulation ──
+    if is_cancelled && !debits.is_empty() {
+        if refunds.is_empty() && releases.is_empty() && compensations.is_empty() {
+            // Au plus le montant prévu : un double débit est traité à part
+            let owed = booking
+                .refund_due
+                .unwrap_or(escrow)
+                .min(debited)
+                .min(escrow.max(0));
+            out.push(mismatch(
+                MismatchKind::MissingRefund,
+                Some(owed),
+                Some(0),
+                ids(&debits),
+                (owed > 0).then(|| AutoFix::RefundMentee {
+                    mentee_id: booking.mentee_id.clone(),
+                    amount: owed,
+                }),
+            ));
+        } else if refunds.len() > 1 {
+            out.push(mismatch(
+                MismatchKind::DoubleRefund,
+                Some(debited),
+                Some(refunded),
+                ids(&refunds),
+                None,
+            ));
+        }
+    }
+
+    // ── Dédommagement : annulation seulement, une écriture, séquestre soldé ──
+    if !compensations.is_empty()
+        && (!is_cancelled
+            || compensations.len() > 1
+            || (debits.len() == 1 && refunded + compensated != debited))
+    {
+        let mut both = ids(&compensations);
+        both.extend(ids(&refunds));
+        out.push(mismatch(
+            MismatchKind::CompensationMismatch,
+            Some(debited),
+            Some(refunded + compensated),
+            both,
+            None,
+        ));
+    }
+
+    out
+}
+
+// ── Rapport ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationIssue {
+    pub id: String,
+    pub booking_id: String,
+    pub booking_status: Option<String>,
+    pub kind: String,
+    pub expected_tokens: Option<i32>,
+    pub actual_tokens: Option<i32>,
+    pub transaction_ids: Vec<String>,
+    pub auto_fixable: bool,
+    pub fixed: bool,
+    pub fix_transaction_id: Option<String>,
+    pub fix_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationRun {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub auto_fix: bool,
+    pub triggered_by: Option<String>,
+    pub bookings_checked: i32,
+    pub issues_found: i32,
+    pub issues_fixed: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReconciliationReport {
+    #[serde(flatten)]
+    pub run: ReconciliationRun,
+    /// Nombre d'écarts par type
+    pub by_kind: HashMap<String, usize>,
+    pub issues: Vec<ReconciliationIssue>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReconciliationError {
+    #[error("A reconciliation run is already in progress")]
+    AlreadyRunning,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Passe de rapprochement ─────────────────────────────────────────────────
+
+/// Exécute une passe complète et enregistre les écarts trouvés.
+pub async fn run_reconciliation(
+    pool: &PgPool,
+    auto_fix: bool,
+    triggered_by: Option<&str>,
+) -> Result<ReconciliationReport, ReconciliationError> {
+    // Verrou de session, tenu sur une connexion dédiée pendant toute la passe
+    let mut lock_conn = pool.acquire().await?;
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(RECONCILIATION_LOCK_KEY)
+        .fetch_one(&mut *lock_conn)
+        .await?;
+    if !locked {
+        return Err(ReconciliationError::AlreadyRunning);
+    }
+
+    let result = reconcile(pool, auto_fix, triggered_by).await;
+
+    if let Err(e) = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(RECONCILIATION_LOCK_KEY)
+        .execute(&mut *lock_conn)
+        .await
+    {
+        warn!("Failed to release reconciliation lock: {}", e);
+    }
+
+    result
+}
+
+async fn reconcile(
+    pool: &PgPool,
+    auto_fix: bool,
+    triggered_by: Option<&str>,
+) -> Result<ReconciliationReport, ReconciliationError> {
+    let run_id: String = sqlx::query_scalar(
+        "INSERT INTO escrow_reconciliation_runs (auto_fix, triggered_by) VALUES ($1, $2) RETURNING id",
+    )
+    .bind(auto_fix)
+    .bind(triggered_by)
+    .fetch_one(pool)
+    .await?;
+
+    let bookings: Vec<BookingEscrow> = sqlx::query(
+        r#"
+        SELECT id, mentee_id, status, tokens_escrowed, refund_tokens
+        FROM mentoring_bookings
+        WHERE updated_at < NOW() - make_interval(mins => $1)
+        "#,
+    )
+    .bind(GRACE_MINUTES)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|r| BookingEscrow {
+        booking_id: r.try_get("id").unwrap_or_default(),
+        mentee_id: r.try_get("mentee_id").unwrap_or_default(),
+        status: r.try_get("status").unwrap_or_default(),
+        tokens_escrowed: r.try_get::<i32, _>("tokens_escrowed").unwrap_or(0) as i64,
+        refund_due: r
+            .try_get::<Option<i32>, _>("refund_tokens")
+            .ok()
+            .flatten()
+            .map(i64::from),
+    })
+    .collect();
+
+    // Réservations récentes : ni contrôlées, ni considérées comme orphelines
+    let recent: HashSet<String> = sqlx::query_scalar(
+        "SELECT id FROM mentoring_bookings WHERE updated_at >= NOW() - make_interval(mins => $1)",
+    )
+    .bind(GRACE_MINUTES)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let entries: Vec<EscrowEntry> = sqlx::query(
+        r#"
+        SELECT id, user_id, tokens, metadata->>'booking_id' AS booking_id, metadata->>'type' AS kind
+        FROM t4g_token_transactions
+        WHERE metadata ? 'booking_id'
+          AND metadata->>'type' IN ('escrow_debit', 'escrow_refund', 'escrow_release',
+                                    'escrow_compensation')
+        "#,
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .filter_map(|r| {
+        let kind: String = r.try_get("kind").ok()?;
+        Some(EscrowEntry {
+            transaction_id: r.try_get("id").unwrap_or_default(),
+            booking_id: r.try_get("booking_id").unwrap_or_default(),
+            user_id: r.try_get("user_id").unwrap_or_default(),
+            kind: EntryKind::from_metadata(&kind)?,
+            tokens: r.try_get::<i32, _>("tokens").unwrap_or(0) as i64,
+        })
+    })
+    .collect();
+
+    let mut by_booking: HashMap<&str, Vec<&EscrowEntry>> = HashMap::new();
+    for e in &entries {
+        by_booking.entry(e.booking_id.as_str()).or_default().push(e);
+    }
+
+    let mut mismatches: Vec<Mismatch> = Vec::new();
+    for b in &bookings {
+        let es = by_booking.remove(b.booking_id.as_str()).unwrap_or_default();
+        mismatches.extend(classify(b, &es));
+    }
+
+    // Écritures rattachées à une réservation inexistante
+    for (booking_id, es) in by_booking {
+        if recent.contains(booking_id) {
+            continue;
+        }
+        let refunds_only = es.iter().all(|e| e.kind == EntryKind::Refund);
+        mismatches.push(Mismatch {
+            booking_id: booking_id.to_string(),
+            booking_status: None,
+            kind: if refunds_only {
+                MismatchKind::OrphanRefund
+            } else {
+                MismatchKind::OrphanEntry
+            },
+            expected_tokens: Some(0),
+            actual_tokens: Some(es.iter().map(|e| e.tokens).sum()),
+            transaction_ids: es.iter().map(|e| e.transaction_id.clone()).collect(),
+            auto_fix: None,
+        });
+    }
+
+    let mut fixed = 0i32;
+    for m in &mismatches {
+        let (fix_tx, fix_error) = match (&m.auto_fix, auto_fix) {
+            (Some(fix), true) => match apply_fix(pool, &run_id, &m.booking_id, fix).await {
+                Ok(tx_id) => {
+                    fixed += 1;
+                    (Some(tx_id), None)
+                }
+                Err(e) => {
+                    warn!(
+                        "Auto-fix failed for booking {} ({}): {}",
+                        m.booking_id, m.kind, e
+                    );
+                    (None, Some(e))
+                }
+            },
+            _ => (None, None),
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO escrow_reconciliation_issues
+                (run_id, booking_id, booking_status, kind, expected_tokens, actual_tokens,
+                 transaction_ids, auto_fixable, fixed, fix_transaction_id, fix_error)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+        )
+        .bind(&run_id)
+        .bind(&m.booking_id)
+        .bind(&m.booking_status)
+        .bind(m.kind.to_string())
+        .bind(m.expected_tokens.map(|v| v as i32))
+        .bind(m.actual_tokens.map(|v| v as i32))
+        .bind(&m.transaction_ids)
+        .bind(m.auto_fix.is_some())
+        .bind(fix_tx.is_some())
+        .bind(&fix_tx)
+        .bind(&fix_error)
+        .execute(pool)
+        .await?;
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE escrow_reconciliation_runs
+        SET finished_at = NOW(), bookings_checked = $1, issues_found = $2, issues_fixed = $3
+        WHERE id = $4
+        "#,
+    )
+    .bind(bookings.len() as i32)
+    .bind(mismatches.len() as i32)
+    .bind(fixed)
+    .bind(&run_id)
+    .execute(pool)
+    .await?;
+
+    info!(
+        "Escrow reconciliation {}: {} booking(s), {} issue(s), {} fixed",
+        run_id,
+        bookings.len(),
+        mismatches.len(),
+        fixed
+    );
+
+    get_report(pool, &run_id)
+        .await?
+        .ok_or(ReconciliationError::Database(sqlx::Error::RowNotFound))
+}
+
+/// Applique une correction sûre ; retourne l'id de l'écriture créée.
+async fn apply_fix(
+    pool: &PgPool,
+    run_id: &str,
+    booking_id: &str,
+    fix: &AutoFix,
+) -> Result<String, String> {
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    let (user_id, amount, kind, description) = match fix {
+        AutoFix::DebitMentee { mentee_id, amount } => {
+            token_ledger::lock_balance(&mut tx, mentee_id)
+                .await
+                .map_err(|e| e.to_string())?;
+            mentoring_completion::check_balance(&mut tx, mentee_id, *amount).await?;
+            (
+                mentee_id,
+                -*amount,
+                "escrow_debit",
+                "Séquestre session mentoring (régularisation)",
+            )
+        }
+        AutoFix::RefundMentee { mentee_id, amount } => (
+            mentee_id,
+            *amount,
+            "escrow_refund",
+            "Remboursement séquestre (régularisation)",
+        ),
+    };
+    let action_type = if amount < 0 {
+        "service_payment"
+    } else {
+        "service_refund"
+    };
+
+    let transaction_id: String = sqlx::query_scalar(
+        r#"
+        INSERT INTO t4g_token_transactions
+            (id, user_id, action_type, tokens, description, metadata, impact_score)
+        VALUES (gen_random_uuid()::text, $1, $2, $3, $4, $5, 0.0)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(action_type)
+    .bind(amount as i32)
+    .bind(description)
+    .bind(serde_json::json!({
+        "booking_id": booking_id,
+        "type": kind,
+        "reconciliation_run_id": run_id,
+    }))
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    if amount < 0 {
+        token_ledger::consume_lots(&mut tx, user_id, -amount)
+            .await
+            .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    info!(
+        "Reconciliation fix for booking {}: {:+} T4G to {}",
+        booking_id, amount, user_id
+    );
+    Ok(transaction_id)
+}
+
+/// Job périodique : passe de rapprochement, corrections selon
+/// `ESCROW_RECONCILIATION_AUTO_FIX` (activées par défaut).
+pub async fn run_scheduled_reconciliation(pool: &PgPool) -> u64 {
+    let auto_fix = std::env::var("ESCROW_RECONCILIATION_AUTO_FIX")
+        .map(|v| v != "false" && v != "0")
+        .unwrap_or(true);
+
+    match run_reconciliation(pool, auto_fix, None).await {
+        Ok(report) => report.run.issues_found as u64,
+        Err(ReconciliationError::AlreadyRunning) => 0,
+        Err(e) => {
+            error!("Escrow reconciliation failed: {}", e);
+            0
+        }
+    }
+}
+
+// ── Lecture des rapports ───────────────────────────────────────────────────
+
+fn run_from_row(r: &sqlx::postgres::PgRow) -> ReconciliationRun {
+    ReconciliationRun {
+        id: r.try_get("id").unwrap_or_default(),
+        started_at: r.try_get("started_at").unwrap_or_else(|_| Utc::now()),
+        finished_at: r.try_get("finished_at").ok().flatten(),
+        auto_fix: r.try_get("auto_fix").unwrap_or(false),
+        triggered_by: r.try_get("triggered_by").ok().flatten(),
+        bookings_checked: r.try_get("bookings_checked").unwrap_or(0),
+        issues_found: r.try_get("issues_found").unwrap_or(0),
+        issues_fixed: r.try_get("issues_fixed").unwrap_or(0),
+    }
+}
+
+pub async fn list_runs(pool: &PgPool, limit: i64) -> Result<Vec<ReconciliationRun>, sqlx::Error> {
+    let rows =
+        sqlx::query("SELECT * FROM escrow_reconciliation_runs ORDER BY started_at DESC LIMIT $1")
+            .bind(limit)
+            .fetch_all(pool)
+            .await?;
+    Ok(rows.iter().map(run_from_row).collect())
+}
+
+/// Rapport d'une passe (la plus récente terminée si `run_id` est vide).
+pub async fn get_report(
+    pool: &PgPool,
+    run_id: &str,
+) -> Result<Option<ReconciliationReport>, sqlx::Error> {
+    let run = sqlx::query(
+        r#"
+        SELECT * FROM escrow_reconciliation_runs
+        WHERE ($1 = '' AND finished_at IS NOT NULL) OR id = $1
+        ORDER BY started_at DESC LIMIT 1
+        "#,
+    )
+    .bind(run_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some(run) = run.as_ref().map(run_from_row) else {
+        return Ok(None);
+    };
+
+    let issues: Vec<ReconciliationIssue> = sqlx::query(
+        "SELECT * FROM escrow_reconciliation_issues WHERE run_id = $1 ORDER BY kind, booking_id",
+    )
+    .bind(&run.id)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|r| ReconciliationIssue {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        booking_status: r.try_get("booking_status").ok().flatten(),
+        kind: r.try_get("kind").unwrap_or_default(),
+        expected_tokens: r.try_get("expected_tokens").ok().flatten(),
+        actual_tokens: r.try_get("actual_tokens").ok().flatten(),
+        transaction_ids: r.try_get("transaction_ids").unwrap_or_default(),
+        auto_fixable: r.try_get("auto_fixable").unwrap_or(false),
+        fixed: r.try_get("fixed").unwrap_or(false),
+        fix_transaction_id: r.try_get("fix_transaction_id").ok().flatten(),
+        fix_error: r.try_get("fix_error").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    })
+    .collect();
+
+    let mut by_kind: HashMap<String, usize> = HashMap::new();
+    for i in &issues {
+        *by_kind.entry(i.kind.clone()).or_default() += 1;
+    }
+
+    Ok(Some(ReconciliationReport {
+        run,
+        by_kind,
+        issues,
+    }))
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn booking(status: &str, escrow: i64) -> BookingEscrow {
+        BookingEscrow {
+            booking_id: "b1".to_string(),
+            mentee_id: "mentee".to_string(),
+            status: status.to_string(),
+            tokens_escrowed: escrow,
+            refund_due: None,
+        }
+    }
+
+    fn entry(id: &str, kind: EntryKind, tokens: i64) -> EscrowEntry {
+        EscrowEntry {
+            transaction_id: id.to_string(),
+            booking_id: "b1".to_string(),
+            user_id: if matches!(kind, EntryKind::Release | EntryKind::Compensation) {
+                "mentor".to_string()
+            } else {
+                "mentee".to_string()
+            },
+            kind,
+            tokens,
+        }
+    }
+
+    fn kinds(b: &BookingEscrow, es: &[EscrowEntry]) -> Vec<MismatchKind> {
+        let refs: Vec<&EscrowEntry> = es.iter().collect();
+        classify(b, &refs).into_iter().map(|m| m.kind).collect()
+    }
+
+    #[test]
+    fn test_consistent_lifecycles() {
+        let debit = entry("d", EntryKind::Debit, -30);
+        assert!(kinds(&booking("confirmed", 30), std::slice::from_ref(&debit)).is_empty());
+        assert!(kinds(
+            &booking("completed", 30),
+            &[debit.clone(), entry("r", EntryKind::Release, 33)]
+        )
+        .is_empty());
+        assert!(kinds(
+            &booking("cancelled", 30),
+            &[debit, entry("f", EntryKind::Refund, 30)]
+        )
+        .is_empty());
+        // Réservation annulée dont le débit avait échoué : rien à rembourser
+        assert!(kinds(&booking("cancelled", 30), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_missing_debit_on_active_booking_is_fixable() {
+        let b = booking("pending", 30);
+        let m = classify(&b, &[]);
+        assert_eq!(m.len(), 1);
+        assert_eq!(m[0].kind, MismatchKind::MissingDebit);
+        assert_eq!(
+            m[0].auto_fix,
+            Some(AutoFix::DebitMentee {
+                mentee_id: "mentee".to_string(),
+                amount: 30
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_debit_on_completed_booking_is_manual() {
+        let es = [entry("r", EntryKind::Release, 30)];
+        let refs: Vec<&EscrowEntry> = es.iter().collect();
+        let m = classify(&booking("completed", 30), &refs);
+        assert_eq!(m[0].kind, MismatchKind::MissingDebit);
+        assert!(m[0].auto_fix.is_none());
+    }
+
+    #[test]
+    fn test_double_release_detected() {
+        let es = [
+            entry("d", EntryKind::Debit, -30),
+            entry("r1", EntryKind::Release, 30),
+            entry("r2", EntryKind::Release, 30),
+        ];
+        assert_eq!(
+            kinds(&booking("completed", 30), &es),
+            vec![MismatchKind::DoubleRelease]
+        );
+    }
+
+    #[test]
+    fn test_orphan_refund_without_debit() {
+        let es = [entry("f", EntryKind::Refund, 30)];
+        assert_eq!(
+            kinds(&booking("cancelled", 30), &es),
+            vec![MismatchKind::OrphanRefund]
+        );
+    }
+
+    #[test]
+    fn test_refund_on_live_booking_is_orphan() {
+        let es = [
+            entry("d", EntryKind::Debit, -30),
+            entry("f", EntryKind::Refund, 30),
+        ];
+        assert_eq!(
+            kinds(&booking("confirmed", 30), &es),
+            vec![MismatchKind::OrphanRefund]
+        );
+    }
+
+    #[test]
+    fn test_missing_refund_on_cancelled_booking_is_fixable() {
+        let es = [entry("d", EntryKind::Debit, -30)];
+        let refs: Vec<&EscrowEntry> = es.iter().collect();
+        let m = classify(&booking("cancelled", 30), &refs);
+        assert_eq!(m.len(), 1);
+        assert_eq!(m[0].kind, MismatchKind::MissingRefund);
+        assert_eq!(
+            m[0].auto_fix,
+            Some(AutoFix::RefundMentee {
+                mentee_id: "mentee".to_string(),
+                amount: 30
+            })
+        );
+    }
+
+    #[test]
+    fn test_double_debit_refunds_excess() {
+        let es = [
+            entry("d1", EntryKind::Debit, -30),
+            entry("d2", EntryKind::Debit, -30),
+        ];
+        let refs: Vec<&EscrowEntry> = es.iter().collect();
+        let m = classify(&booking("confirmed", 30), &refs);
+        assert_eq!(m[0].kind, MismatchKind::DoubleDebit);
+        assert_eq!(
+            m[0].auto_fix,
+            Some(AutoFix::RefundMentee {
+                mentee_id: "mentee".to_string(),
+                amount: 30
+            })
+        );
+    }
+
+    #[test]
+    fn test_double_refund_and_mixed_outcomes() {
+        let es = [
+            entry("d", EntryKind::Debit, -30),
+            entry("f1", EntryKind::Refund, 30),
+            entry("f2", EntryKind::Refund, 30),
+        ];
+        assert_eq!(
+            kinds(&booking("cancelled", 30), &es),
+            vec![MismatchKind::DoubleRefund]
+        );
+
+        let es = [
+            entry("d", EntryKind::Debit, -30),
+            entry("r", EntryKind::Release, 30),
+            entry("f", EntryKind::Refund, 30),
+        ];
+        assert_eq!(
+            kinds(&booking("completed", 30), &es),
+            vec![MismatchKind::ReleaseAndRefund]
+        );
+    }
+
+    #[test]
+    fn test_missing_refund_respects_cancellation_policy() {
+        let b = BookingEscrow {
+            refund_due: Some(15),
+            ..booking("cancelled", 30)
+        };
+        let es = [entry("d", EntryKind::Debit, -30)];
+        let refs: Vec<&EscrowEntry> = es.iter().collect();
+        let found = classify(&b, &refs);
+        assert_eq!(
+            found[0].auto_fix,
+            Some(AutoFix::RefundMentee {
+                mentee_id: "mentee".to_string(),
+                amount: 15
+            })
+        );
+    }
+
+    #[test]
+    fn test_cancellation_split_between_refund_and_compensation() {
+        let debit = entry("d", EntryKind::Debit, -30);
+        // Annulation tardive : 50 % remboursé, le reste au mentor
+        let split = [
+            debit.clone(),
+            entry("f", EntryKind::Refund, 15),
+            entry("c", EntryKind::Compensation, 15),
+        ];
+        assert!(kinds(&booking("cancelled", 30), &split).is_empty());
+        // Annulation de dernière minute : aucun remboursement
+        let none = [debit.clone(), entry("c", EntryKind::Compensation, 30)];
+        assert!(kinds(&booking("cancelled", 30), &none).is_empty());
+
+        let overpaid = [
+            debit.clone(),
+            entry("f", EntryKind::Refund, 30),
+            entry("c", EntryKind::Compensation, 15),
+        ];
+        assert_eq!(
+            kinds(&booking("cancelled", 30), &overpaid),
+            vec![MismatchKind::CompensationMismatch]
+        );
+        assert_eq!(
+            kinds(
+                &booking("completed", 30),
+                &[
+                    debit,
+                    entry("r", EntryKind::Release, 30),
+                    entry("c", EntryKind::Compensation, 5)
+                ]
+            ),
+            vec![MismatchKind::CompensationMismatch]
+        );
+    }
+
+    #[test]
+    fn test_every_kind_allowed_by_schema() {
+        // Contrainte CHECK de escrow_reconciliation_issues.kind
+        let migration = include_str!("../../migrations/018_booking_cancellation_policy.sql");
+        for kind in [
+            MismatchKind::MissingDebit,
+            MismatchKind::DebitAmountMismatch,
+            MismatchKind::DoubleDebit,
+            MismatchKind::MissingRelease,
+            MismatchKind::DoubleRelease,
+            MismatchKind::MissingRefund,
+            MismatchKind::DoubleRefund,
+            MismatchKind::OrphanRefund,
+            MismatchKind::ReleaseAndRefund,
+            MismatchKind::CompensationMismatch,
+            MismatchKind::OrphanEntry,
+        ] {
+            assert!(
+                migration.contains(&format!("'{}'", kind)),
+                "{} is not allowed by escrow_reconciliation_issues.kind",
+                kind
+            );
+        }
+    }
+}
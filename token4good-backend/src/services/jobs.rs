@@ -0,0 +1,235 @@
+//! Tâches de fond planifiées
+//!
+//! Déclarations passées au planificateur (`services::scheduler`). Les
+//! calendriers sont des expressions cron à 6 champs en UTC ; les tâches dont
+//! l'échec est remonté (ledger) sont relancées avec backoff, les autres
+//! journalisent leurs erreurs et reprennent à l'occurrence suivante.
+
+use sqlx::PgPool;
+
+use crate::services::{
+    attendance, chat, escrow_reconciliation, ledger_chain, mentoring_completion, offer_series,
+    outbox,
+    rgb::RGBService,
+    scheduler::{self, Job},
+    time_off, token_ledger, waitlist,
+};
+
+/// Durée de conservation de l'historique des exécutions
+const JOB_RUN_RETENTION_DAYS: i32 = 30;
+/// Durée de conservation des entrées d'outbox livrées
+const OUTBOX_RETENTION_DAYS: i32 = 14;
+
+pub fn registry(pool: PgPool, rgb: RGBService) -> Vec<Job> {
+    vec![
+        {
+            let pool = pool.clone();
+            Job::new(
+                "auto_completion",
+                "Auto-complétion des sessions en attente depuis 48h",
+                "0 0 * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = mentoring_completion::run_auto_completion(&pool).await;
+                        Ok(format!("{} booking(s) processed", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "session_reminders",
+                "Rappels de session J-1 et H-1",
+                "0 */15 * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = mentoring_completion::send_session_reminders(&pool).await;
+                        Ok(format!("{} notification(s) sent", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "no_show_detection",
+                "Constat des présences et absences",
+                "0 */5 * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = attendance::run_no_show_detection(&pool).await;
+                        Ok(format!("{} booking(s) settled", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "time_off_sync",
+                "Activité des mentors selon leurs absences",
+                "0 */15 * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = time_off::run_schedule_sync(&pool).await;
+                        Ok(format!("{} mentor(s) (de)activated", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "offer_series_generation",
+                "Génération des occurrences des séries récurrentes",
+                "0 0 */6 * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = offer_series::run_series_generation(&pool).await;
+                        Ok(format!("{} occurrence(s) generated", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "token_expiry",
+                "Expiration des lots de tokens et préavis J-7",
+                "0 0 3 * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let warned = token_ledger::send_expiry_warnings(&pool).await;
+                        let expired = token_ledger::run_token_expiry(&pool).await;
+                        Ok(format!(
+                            "{} lot(s) expired, {} warning(s) sent",
+                            expired, warned
+                        ))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "ledger_seal",
+                "Scellement du ledger dans la chaîne de hachage",
+                "0 * * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        ledger_chain::seal_pending(&pool)
+                            .await
+                            .map(|n| format!("{} row(s) sealed", n))
+                            .map_err(|e| e.to_string())
+                    }
+                },
+            )
+            .retries(2, 10)
+        },
+        {
+            let (pool, rgb) = (pool.clone(), rgb.clone());
+            Job::new(
+                "ledger_sign_head",
+                "Signature de la tête de chaîne avec la clé émetteur RGB",
+                "0 30 * * * *",
+                move || {
+                    let (pool, rgb) = (pool.clone(), rgb.clone());
+                    async move {
+                        ledger_chain::sign_chain_head(&pool, &rgb)
+                            .await
+                            .map(|_| "chain head signed".to_string())
+                            .map_err(|e| e.to_string())
+                    }
+                },
+            )
+            .retries(3, 60)
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "escrow_reconciliation",
+                "Rapprochement séquestres et ledger, corrections sûres",
+                "0 0 4 * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let issues =
+                            escrow_reconciliation::run_scheduled_reconciliation(&pool).await;
+                        Ok(format!("{} issue(s) found", issues))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "waitlist_sweep",
+                "Listes d'attente : expiration des options, relance des offres libres",
+                "0 */5 * * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = waitlist::run_waitlist_sweep(&pool).await;
+                        Ok(format!("{} hold(s) granted", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "chat_retention",
+                "Rétention des fils de discussion des réservations closes",
+                "0 0 5 * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        let n = chat::run_chat_retention(&pool).await;
+                        Ok(format!("{} message(s) purged", n))
+                    }
+                },
+            )
+        },
+        {
+            let pool = pool.clone();
+            Job::new(
+                "outbox_retention",
+                "Purge des entrées d'outbox livrées ou ignorées",
+                "0 30 5 * * *",
+                move || {
+                    let pool = pool.clone();
+                    async move {
+                        outbox::purge(&pool, OUTBOX_RETENTION_DAYS)
+                            .await
+                            .map(|n| format!("{} event(s) purged", n))
+                            .map_err(|e| e.to_string())
+                    }
+                },
+            )
+            .retries(1, 300)
+        },
+        Job::new(
+            "job_runs_retention",
+            "Purge de l'historique des exécutions planifiées",
+            "0 15 5 * * *",
+            move || {
+                let pool = pool.clone();
+                async move {
+                    scheduler::purge_runs(&pool, JOB_RUN_RETENTION_DAYS)
+                        .await
+                        .map(|n| format!("{} run(s) purged", n))
+                        .map_err(|e| e.to_string())
+                }
+            },
+        )
+        .retries(1, 300),
+    ]
+}
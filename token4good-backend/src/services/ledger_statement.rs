@@ -0,0 +1,566 @@
+//! Relevés du ledger T4G (par utilisateur ou plateforme)
+//!
+//! - Solde d'ouverture (avant `from`) et de clôture (fin de `to`)
+//! - Lignes avec solde courant et lien vers la réservation concernée
+//! - Totaux par `action_type`
+//! - Rendus JSON, CSV et PDF
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+
+use crate::services::pdf::{self, Font, PdfDocument, A4_HEIGHT, A4_WIDTH};
+
+// ── Modèle ─────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementLine {
+    pub transaction_id: String,
+    pub user_id: String,
+    pub date: DateTime<Utc>,
+    pub action_type: String,
+    pub description: String,
+    pub tokens: i64,
+    /// Solde après cette ligne (périmètre du relevé)
+    pub running_balance: i64,
+    pub booking_id: Option<String>,
+    pub booking_link: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ActionTypeTotal {
+    pub action_type: String,
+    pub count: u64,
+    pub credits: i64,
+    pub debits: i64,
+    pub net: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    /// `None` pour le relevé plateforme
+    pub user_id: Option<String>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub opening_balance: i64,
+    pub closing_balance: i64,
+    pub total_credits: i64,
+    pub total_debits: i64,
+    pub by_action_type: Vec<ActionTypeTotal>,
+    pub lines: Vec<StatementLine>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Statement {
+    /// Assemble un relevé à partir du solde d'ouverture et des lignes triées
+    /// chronologiquement (soldes courants et totaux recalculés).
+    pub fn build(
+        user_id: Option<String>,
+        from: NaiveDate,
+        to: NaiveDate,
+        opening_balance: i64,
+        mut lines: Vec<StatementLine>,
+    ) -> Self {
+        let mut balance = opening_balance;
+        let mut totals: BTreeMap<String, ActionTypeTotal> = BTreeMap::new();
+        let (mut credits, mut debits) = (0i64, 0i64);
+
+        for line in lines.iter_mut() {
+            balance += line.tokens;
+            line.running_balance = balance;
+
+            let t = totals
+                .entry(line.action_type.clone())
+                .or_insert_with(|| ActionTypeTotal {
+                    action_type: line.action_type.clone(),
+                    count: 0,
+                    credits: 0,
+                    debits: 0,
+                    net: 0,
+                });
+            t.count += 1;
+            if line.tokens >= 0 {
+                t.credits += line.tokens;
+                credits += line.tokens;
+            } else {
+                t.debits += -line.tokens;
+                debits += -line.tokens;
+            }
+            t.net += line.tokens;
+        }
+
+        Self {
+            user_id,
+            from,
+            to,
+            opening_balance,
+            closing_balance: balance,
+            total_credits: credits,
+            total_debits: debits,
+            by_action_type: totals.into_values().collect(),
+            lines,
+            generated_at: Utc::now(),
+        }
+    }
+
+    pub fn file_stem(&self) -> String {
+        format!(
+            "releve_t4g_{}_{}_{}",
+            self.user_id.as_deref().unwrap_or("plateforme"),
+            self.from,
+            self.to
+        )
+    }
+}
+
+/// Formats de sortie supportés
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatementFormat {
+    Json,
+    Csv,
+    Pdf,
+}
+
+impl StatementFormat {
+    pub fn parse(s: Option<&str>) -> Option<Self> {
+        match s.unwrap_or("json") {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Pdf => "application/pdf",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Pdf => "pdf",
+        }
+    }
+}
+
+/// Lien frontend vers une réservation de mentorat
+pub fn booking_link(booking_id: &str) -> String {
+    format!("/mentoring/session/{}", booking_id)
+}
+
+/// Paramètres de requête communs aux exports (dates incluses, format).
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub format: Option<String>,
+}
+
+impl StatementQuery {
+    /// Période demandée ; par défaut le mois en cours jusqu'à aujourd'hui.
+    pub fn period(&self) -> Result<(NaiveDate, NaiveDate), String> {
+        let today = Utc::now().date_naive();
+        let to = self.to.unwrap_or(today);
+        let from = self.from.unwrap_or_else(|| to.with_day(1).unwrap_or(to));
+        if from > to {
+            return Err("from doit précéder to".to_string());
+        }
+        if (to - from).num_days() > MAX_PERIOD_DAYS {
+            return Err(format!("période limitée à {} jours", MAX_PERIOD_DAYS));
+        }
+        Ok((from, to))
+    }
+}
+
+/// Période maximale d'un export (un peu plus d'un an)
+const MAX_PERIOD_DAYS: i64 = 370;
+
+// ── Lecture ────────────────────────────────────────────────────────────────
+
+/// Construit le relevé de `user_id` (ou de toute la plateforme si `None`)
+/// pour la période `[from, to]` incluse.
+pub async fn build_statement(
+    pool: &PgPool,
+    user_id: Option<&str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Statement, sqlx::Error> {
+    let start = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let end = (to + Duration::days(1))
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+
+    let opening: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(tokens), 0)::BIGINT FROM t4g_token_transactions
+        WHERE created_at < $1 AND ($2::text IS NULL OR user_id = $2)
+        "#,
+    )
+    .bind(start)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT id, user_id, action_type, tokens, description, created_at,
+               metadata->>'booking_id' AS booking_id
+        FROM t4g_token_transactions
+        WHERE created_at >= $1 AND created_at < $2
+          AND ($3::text IS NULL OR user_id = $3)
+        ORDER BY created_at ASC, id ASC
+        "#,
+    )
+    .bind(start)
+    .bind(end)
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let lines = rows
+        .iter()
+        .map(|r| {
+            let booking_id: Option<String> = r.try_get("booking_id").ok().flatten();
+            StatementLine {
+                transaction_id: r.try_get("id").unwrap_or_default(),
+                user_id: r.try_get("user_id").unwrap_or_default(),
+                date: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+                action_type: r.try_get("action_type").unwrap_or_default(),
+                description: r.try_get("description").unwrap_or_default(),
+                tokens: r.try_get::<i32, _>("tokens").unwrap_or(0) as i64,
+                running_balance: 0,
+                booking_link: booking_id.as_deref().map(booking_link),
+                booking_id,
+            }
+        })
+        .collect();
+
+    Ok(Statement::build(
+        user_id.map(str::to_string),
+        from,
+        to,
+        opening,
+        lines,
+    ))
+}
+
+// ── Rendus ─────────────────────────────────────────────────────────────────
+
+/// Champ CSV échappé. Un texte libre commençant par `=`, `+`, `-` ou `@`
+/// serait interprété comme une formule par un tableur : il est préfixé de `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Export CSV : en-tête de synthèse en commentaires puis une ligne par transaction.
+pub fn to_csv(statement: &Statement) -> String {
+    let mut out = String::new();
+    out.push_str(&format!(
+        "# periode,{},{}\n# solde_ouverture,{}\n# solde_cloture,{}\n",
+        statement.from, statement.to, statement.opening_balance, statement.closing_balance
+    ));
+    for t in &statement.by_action_type {
+        out.push_str(&format!(
+            "# total,{},{},{},{},{}\n",
+            csv_field(&t.action_type),
+            t.count,
+            t.credits,
+            t.debits,
+            t.net
+        ));
+    }
+    out.push_str(
+        "date,transaction_id,user_id,action_type,description,tokens,running_balance,booking_id,booking_link\n",
+    );
+    for l in &statement.lines {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{}\n",
+            l.date.to_rfc3339(),
+            csv_field(&l.transaction_id),
+            csv_field(&l.user_id),
+            csv_field(&l.action_type),
+            csv_field(&l.description),
+            l.tokens,
+            l.running_balance,
+            csv_field(l.booking_id.as_deref().unwrap_or_default()),
+            csv_field(l.booking_link.as_deref().unwrap_or_default()),
+        ));
+    }
+    out
+}
+
+/// Rendu dans le format demandé.
+pub fn render(statement: &Statement, format: StatementFormat) -> Vec<u8> {
+    match format {
+        StatementFormat::Json => serde_json::to_vec_pretty(statement).unwrap_or_default(),
+        StatementFormat::Csv => to_csv(statement).into_bytes(),
+        StatementFormat::Pdf => to_pdf(statement),
+    }
+}
+
+const MARGIN: f32 = 40.0;
+const ROW_HEIGHT: f32 = 14.0;
+
+/// Rendu PDF simple : synthèse, totaux par type puis détail paginé.
+pub fn to_pdf(statement: &Statement) -> Vec<u8> {
+    let mut doc = PdfDocument::new("Relevé de compte Token4Good");
+    let right = A4_WIDTH - MARGIN;
+    let mut y = A4_HEIGHT - MARGIN - 10.0;
+
+    doc.text(MARGIN, y, 18.0, Font::Bold, "Relevé de compte T4G");
+    y -= 22.0;
+    let scope = match &statement.user_id {
+        Some(u) => format!("Membre : {}", u),
+        None => "Périmètre : plateforme (tous les membres)".to_string(),
+    };
+    doc.text(MARGIN, y, 10.0, Font::Regular, &scope);
+    y -= 14.0;
+    doc.text(
+        MARGIN,
+        y,
+        10.0,
+        Font::Regular,
+        &format!(
+            "Période du {} au {}",
+            statement.from.format("%d/%m/%Y"),
+            statement.to.format("%d/%m/%Y")
+        ),
+    );
+    y -= 24.0;
+
+    for (label, value) in [
+        ("Solde d'ouverture", statement.opening_balance),
+        ("Total crédité", statement.total_credits),
+        ("Total débité", -statement.total_debits),
+        ("Solde de clôture", statement.closing_balance),
+    ] {
+        doc.text(MARGIN, y, 11.0, Font::Bold, label);
+        doc.text_right(220.0, y, 11.0, Font::Regular, &format!("{} T4G", value));
+        y -= ROW_HEIGHT + 2.0;
+    }
+    y -= 12.0;
+
+    doc.text(MARGIN, y, 12.0, Font::Bold, "Par type d'opération");
+    y -= 16.0;
+    for t in &statement.by_action_type {
+        doc.text(MARGIN, y, 9.0, Font::Regular, &t.action_type);
+        doc.text_right(260.0, y, 9.0, Font::Regular, &format!("{} op.", t.count));
+        doc.text_right(340.0, y, 9.0, Font::Regular, &format!("+{}", t.credits));
+        doc.text_right(420.0, y, 9.0, Font::Regular, &format!("-{}", t.debits));
+        doc.text_right(right, y, 9.0, Font::Bold, &format!("{}", t.net));
+        y -= ROW_HEIGHT;
+    }
+    y -= 16.0;
+
+    let header = |doc: &mut PdfDocument, y: f32| {
+        doc.fill_rect(
+            MARGIN,
+            y - 4.0,
+            right - MARGIN,
+            ROW_HEIGHT,
+            (0.92, 0.92, 0.95),
+        );
+        doc.text(MARGIN + 2.0, y, 8.0, Font::Bold, "Date");
+        doc.text(105.0, y, 8.0, Font::Bold, "Type");
+        doc.text(200.0, y, 8.0, Font::Bold, "Description");
+        doc.text_right(470.0, y, 8.0, Font::Bold, "Tokens");
+        doc.text_right(right - 2.0, y, 8.0, Font::Bold, "Solde");
+    };
+
+    doc.text(MARGIN, y, 12.0, Font::Bold, "Détail des opérations");
+    y -= 18.0;
+    header(&mut doc, y);
+    y -= ROW_HEIGHT;
+
+    for l in &statement.lines {
+        if y < MARGIN + ROW_HEIGHT {
+            doc.new_page();
+            y = A4_HEIGHT - MARGIN;
+            header(&mut doc, y);
+            y -= ROW_HEIGHT;
+        }
+        let description = match &l.booking_id {
+            Some(b) => format!("{} [réservation {}]", l.description, pdf::truncate(b, 8)),
+            None => l.description.clone(),
+        };
+        doc.text(
+            MARGIN + 2.0,
+            y,
+            8.0,
+            Font::Regular,
+            &l.date.format("%d/%m/%Y").to_string(),
+        );
+        doc.text(
+            105.0,
+            y,
+            8.0,
+            Font::Regular,
+            &pdf::truncate(&l.action_type, 18),
+        );
+        doc.text(
+            200.0,
+            y,
+            8.0,
+            Font::Regular,
+            &pdf::truncate(&description, 48),
+        );
+        doc.text_right(470.0, y, 8.0, Font::Regular, &format!("{:+}", l.tokens));
+        doc.text_right(
+            right - 2.0,
+            y,
+            8.0,
+            Font::Regular,
+            &l.running_balance.to_string(),
+        );
+        y -= ROW_HEIGHT;
+    }
+
+    if statement.lines.is_empty() {
+        doc.text(
+            MARGIN + 2.0,
+            y,
+            9.0,
+            Font::Regular,
+            "Aucune opération sur la période.",
+        );
+    }
+
+    let pages = doc.page_count();
+    doc.text(
+        MARGIN,
+        MARGIN / 2.0,
+        7.0,
+        Font::Regular,
+        &format!(
+            "Généré le {} — {} page(s)",
+            statement.generated_at.format("%d/%m/%Y %H:%M UTC"),
+            pages
+        ),
+    );
+
+    doc.to_bytes()
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(action_type: &str, tokens: i64, booking_id: Option<&str>) -> StatementLine {
+        StatementLine {
+            transaction_id: format!("tx_{}_{}", action_type, tokens),
+            user_id: "u1".to_string(),
+            date: Utc::now(),
+            action_type: action_type.to_string(),
+            description: "Session, \"Rust\"".to_string(),
+            tokens,
+            running_balance: 0,
+            booking_id: booking_id.map(str::to_string),
+            booking_link: booking_id.map(booking_link),
+        }
+    }
+
+    fn sample() -> Statement {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        Statement::build(
+            Some("u1".to_string()),
+            d,
+            NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+            100,
+            vec![
+                line("mentoring", 30, Some("b1")),
+                line("service_payment", -20, Some("b2")),
+                line("mentoring", 10, None),
+                line("service_refund", 20, Some("b2")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_balances_and_running_totals() {
+        let s = sample();
+        assert_eq!(s.opening_balance, 100);
+        assert_eq!(s.closing_balance, 140);
+        assert_eq!(s.total_credits, 60);
+        assert_eq!(s.total_debits, 20);
+        let running: Vec<i64> = s.lines.iter().map(|l| l.running_balance).collect();
+        assert_eq!(running, vec![130, 110, 120, 140]);
+    }
+
+    #[test]
+    fn test_grouping_by_action_type() {
+        let s = sample();
+        let mentoring = s
+            .by_action_type
+            .iter()
+            .find(|t| t.action_type == "mentoring")
+            .unwrap();
+        assert_eq!(mentoring.count, 2);
+        assert_eq!(mentoring.net, 40);
+        assert_eq!(s.by_action_type.len(), 3);
+    }
+
+    #[test]
+    fn test_csv_escapes_and_links_bookings() {
+        let csv = to_csv(&sample());
+        assert!(csv.contains("# solde_cloture,140"));
+        assert!(csv.contains("\"Session, \"\"Rust\"\"\""));
+        assert!(csv.contains("/mentoring/session/b1"));
+        assert_eq!(csv.lines().filter(|l| !l.starts_with('#')).count(), 5);
+    }
+
+    #[test]
+    fn test_csv_neutralizes_formulas() {
+        let mut s = sample();
+        s.lines[0].description = "=HYPERLINK(\"http://x\")".to_string();
+        s.lines[1].description = "@SUM(A1)".to_string();
+        s.lines[2].description = "-2+3".to_string();
+        let csv = to_csv(&s);
+        assert!(csv.contains(",\"'=HYPERLINK(\"\"http://x\"\")\","));
+        assert!(csv.contains(",'@SUM(A1),"));
+        assert!(csv.contains(",'-2+3,"));
+        // Les montants négatifs restent des nombres
+        assert!(csv.contains(",-20,"));
+    }
+
+    #[test]
+    fn test_pdf_paginates_long_statements() {
+        let d = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let lines = (0..200).map(|i| line("mentoring", i, None)).collect();
+        let s = Statement::build(None, d, d, 0, lines);
+        let bytes = to_pdf(&s);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(!text.contains("/Count 1 >>"));
+    }
+
+    #[test]
+    fn test_format_parse() {
+        assert_eq!(StatementFormat::parse(None), Some(StatementFormat::Json));
+        assert_eq!(
+            StatementFormat::parse(Some("pdf")),
+            Some(StatementFormat::Pdf)
+        );
+        assert_eq!(StatementFormat::parse(Some("xlsx")), None);
+    }
+}
@@ -0,0 +1,320 @@
+//! Mise en relation mentor–mentee : classement des offres ouvertes
+//!
+//! Le profil du mentee (sujets d'apprentissage, sessions passées, solde,
+//! habitudes horaires, historique avec chaque mentor) est confronté aux
+//! offres ouvertes ; le score pondère six signaux, minoré par le taux
+//! d'absence du mentor, et chaque suggestion porte ses raisons
+//! (`models::matching`).
+//!
+//! L'évaluation hors ligne rejoue les réservations complétées : pour chacune,
+//! le profil est reconstruit tel qu'il était à la réservation et les offres
+//! publiées dans les 90 jours précédents sont classées ; on mesure le rang de
+//! l'offre effectivement réservée. Le solde historique et l'agenda passé ne
+//! sont pas connus : les signaux prix et disponibilité y sont neutres, et les
+//! notes et taux d'absence des mentors sont les valeurs actuelles.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc};
+use sqlx::{PgPool, Row};
+
+use crate::models::attendance::smoothed_no_show_rate;
+use crate::models::matching::{
+    rank_offers, Level, MatchWeights, MatchingEvaluation, MenteeProfile, OfferCandidate, OfferMatch,
+};
+use crate::models::review::bayesian_average;
+use crate::models::time_slot::{bookable_slots, parse_availability};
+use crate::services::{attendance, availability, reviews, time_off, token_ledger};
+
+/// Horizon des créneaux pris en compte pour la disponibilité
+pub const RECOMMENDATION_HORIZON_DAYS: i64 = 14;
+/// Offres ouvertes examinées au plus par recommandation
+const MAX_CANDIDATES: i64 = 200;
+/// Fenêtre de publication des offres candidates lors de l'évaluation
+const EVALUATION_LOOKBACK_DAYS: i64 = 90;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MatchingError {
+    #[error("Mentee not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Profil du mentee, limité aux réservations antérieures à `as_of` si précisé
+pub async fn load_profile(
+    pool: &PgPool,
+    mentee_id: &str,
+    as_of: Option<DateTime<Utc>>,
+) -> Result<MenteeProfile, MatchingError> {
+    let learning_topics: Vec<String> = sqlx::query_scalar::<_, Option<Vec<String>>>(
+        "SELECT learning_topics FROM users WHERE id = $1",
+    )
+    .bind(mentee_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MatchingError::NotFound)?
+    .unwrap_or_default();
+
+    let learning_categories: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT c.slug
+        FROM learning_topics t
+        JOIN learning_categories c ON c.id = t.category_id
+        WHERE t.slug = ANY($1)
+        "#,
+    )
+    .bind(&learning_topics)
+    .fetch_all(pool)
+    .await?;
+
+    let past = sqlx::query(
+        r#"
+        SELECT o.mentor_id, o.topic_slug, b.status, b.scheduled_at, b.mentee_rating,
+               EXISTS(SELECT 1 FROM mentoring_disputes d WHERE d.booking_id = b.id) AS disputed
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.mentee_id = $1
+          AND b.status <> 'cancelled'
+          AND ($2::TIMESTAMPTZ IS NULL OR b.created_at < $2)
+        "#,
+    )
+    .bind(mentee_id)
+    .bind(as_of)
+    .fetch_all(pool)
+    .await?;
+
+    let mut profile = MenteeProfile {
+        mentee_id: mentee_id.to_string(),
+        learning_topics: learning_topics.into_iter().collect(),
+        learning_categories: learning_categories.into_iter().collect(),
+        ..Default::default()
+    };
+    let mut ratings: HashMap<String, (i64, i64)> = HashMap::new();
+    for row in &past {
+        let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+        let topic_slug: String = row.try_get("topic_slug").unwrap_or_default();
+        let status: String = row.try_get("status").unwrap_or_default();
+        let history = profile.mentor_history.entry(mentor_id.clone()).or_default();
+
+        if row.try_get("disputed").unwrap_or(false) {
+            history.disputed += 1;
+        }
+        if status == "completed" || status == "auto_completed" {
+            history.completed += 1;
+            *profile.topic_experience.entry(topic_slug).or_default() += 1;
+        }
+        if let Ok(Some(rating)) = row.try_get::<Option<i32>, _>("mentee_rating") {
+            let (sum, n) = ratings.entry(mentor_id).or_default();
+            *sum += rating as i64;
+            *n += 1;
+        }
+        if let Ok(at) = row.try_get::<DateTime<Utc>, _>("scheduled_at") {
+            profile
+                .habits
+                .insert((at.weekday().num_days_from_monday(), at.hour()));
+        }
+    }
+    for (mentor_id, (sum, n)) in ratings {
+        if let Some(h) = profile.mentor_history.get_mut(&mentor_id) {
+            h.average_rating = Some(sum as f64 / n as f64);
+        }
+    }
+
+    profile.balance = match as_of {
+        // Solde historique inconnu : signal prix neutre
+        Some(_) => i64::MAX,
+        None => {
+            let mut conn = pool.acquire().await?;
+            token_ledger::spendable_balance(&mut conn, mentee_id)
+                .await
+                .unwrap_or(0)
+        }
+    };
+    Ok(profile)
+}
+
+/// Offres avec leur sujet, les notes et les absences de leur mentor
+fn candidate_select() -> String {
+    format!(
+        r#"
+        SELECT o.id, o.mentor_id, o.topic_slug, o.target_level, o.token_cost,
+               o.duration_minutes, o.capacity, o.availability,
+               c.slug AS category_slug,
+               COALESCE(r.review_count, 0)         AS review_count,
+               COALESCE(r.rating_sum, 0)           AS rating_sum,
+               COALESCE(ns.attendance_sessions, 0) AS attendance_sessions,
+               COALESCE(ns.no_shows, 0)            AS no_shows
+        FROM mentoring_offers o
+        LEFT JOIN learning_topics t ON t.slug = o.topic_slug
+        LEFT JOIN learning_categories c ON c.id = t.category_id
+        LEFT JOIN {} r ON r.mentor_id = o.mentor_id
+        LEFT JOIN {} ns ON ns.mentor_id = o.mentor_id
+        "#,
+        reviews::mentor_ratings_subquery(),
+        attendance::mentor_no_show_subquery()
+    )
+}
+
+fn candidate_from_row(r: &sqlx::postgres::PgRow, prior: f64) -> OfferCandidate {
+    let review_count: i64 = r.try_get("review_count").unwrap_or(0);
+    let rating_sum: i64 = r.try_get("rating_sum").unwrap_or(0);
+    OfferCandidate {
+        offer_id: r.try_get("id").unwrap_or_default(),
+        mentor_id: r.try_get("mentor_id").unwrap_or_default(),
+        topic_slug: r.try_get("topic_slug").unwrap_or_default(),
+        category_slug: r.try_get("category_slug").ok().flatten(),
+        target_level: r
+            .try_get::<String, _>("target_level")
+            .ok()
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(Level::Beginner),
+        token_cost: r.try_get("token_cost").unwrap_or(0),
+        mentor_rating: bayesian_average(rating_sum, review_count, prior),
+        mentor_review_count: review_count,
+        mentor_no_show_rate: smoothed_no_show_rate(
+            r.try_get("no_shows").unwrap_or(0),
+            r.try_get("attendance_sessions").unwrap_or(0),
+        ),
+        upcoming_slots: None,
+    }
+}
+
+/// Offres ouvertes réservables par le mentee, avec leurs sessions à venir
+async fn live_candidates(
+    pool: &PgPool,
+    mentee_id: &str,
+    prior: f64,
+) -> Result<Vec<OfferCandidate>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        {}
+        WHERE o.status = 'open'
+          AND o.mentor_id <> $1
+          AND o.request_id IS NULL
+          AND (o.occurrence_at IS NULL OR o.occurrence_at > NOW())
+          AND NOT {}
+          AND NOT EXISTS (
+              SELECT 1 FROM mentoring_bookings b
+              WHERE b.offer_id = o.id AND b.mentee_id = $1 AND b.status = ANY($2)
+          )
+        ORDER BY o.created_at DESC
+        LIMIT $3
+        "#,
+        candidate_select(),
+        time_off::HIDDEN_BY_TIME_OFF
+    ))
+    .bind(mentee_id)
+    .bind(&availability::BLOCKING_STATUSES[..])
+    .bind(MAX_CANDIDATES)
+    .fetch_all(pool)
+    .await?;
+
+    let from = Utc::now();
+    let to = from + Duration::days(RECOMMENDATION_HORIZON_DAYS);
+    let mut conn = pool.acquire().await?;
+    let mut occupancy = HashMap::new();
+    let mut candidates = Vec::with_capacity(rows.len());
+    for row in &rows {
+        let mut candidate = candidate_from_row(row, prior);
+        let slots = parse_availability(row.try_get("availability").unwrap_or_default());
+        if !slots.is_empty() {
+            if !occupancy.contains_key(&candidate.mentor_id) {
+                let busy =
+                    availability::mentor_occupancy(&mut conn, &candidate.mentor_id, from, to, None)
+                        .await?;
+                occupancy.insert(candidate.mentor_id.clone(), busy);
+            }
+            let bookable = bookable_slots(
+                &candidate.offer_id,
+                &slots,
+                row.try_get("duration_minutes").unwrap_or(60),
+                row.try_get("capacity").unwrap_or(1),
+                from,
+                to,
+                &occupancy[&candidate.mentor_id],
+            );
+            candidate.upcoming_slots = Some(bookable.iter().map(|s| s.start).collect());
+        }
+        candidates.push(candidate);
+    }
+    Ok(candidates)
+}
+
+/// Meilleures offres ouvertes pour le mentee, avec leurs raisons
+pub async fn recommend(
+    pool: &PgPool,
+    mentee_id: &str,
+    limit: usize,
+) -> Result<Vec<OfferMatch>, MatchingError> {
+    let profile = load_profile(pool, mentee_id, None).await?;
+    let prior = reviews::prior_mean(pool).await?;
+    let candidates = live_candidates(pool, mentee_id, prior).await?;
+
+    let mut ranked = rank_offers(&profile, &candidates, &MatchWeights::default());
+    ranked.truncate(limit);
+    Ok(ranked)
+}
+
+/// Rejoue le classement sur les réservations complétées des `days` derniers jours
+pub async fn evaluate(
+    pool: &PgPool,
+    days: i64,
+    k: usize,
+) -> Result<MatchingEvaluation, MatchingError> {
+    let bookings = sqlx::query(
+        r#"
+        SELECT b.mentee_id, b.offer_id, b.created_at
+        FROM mentoring_bookings b
+        WHERE b.status IN ('completed', 'auto_completed')
+          AND b.created_at > NOW() - make_interval(days => $1)
+        ORDER BY b.created_at DESC
+        LIMIT 500
+        "#,
+    )
+    .bind(days as i32)
+    .fetch_all(pool)
+    .await?;
+
+    let prior = reviews::prior_mean(pool).await?;
+    let weights = MatchWeights::default();
+    let mut ranks = Vec::with_capacity(bookings.len());
+    for booking in &bookings {
+        let mentee_id: String = booking.try_get("mentee_id").unwrap_or_default();
+        let offer_id: String = booking.try_get("offer_id").unwrap_or_default();
+        let Ok(booked_at) = booking.try_get::<DateTime<Utc>, _>("created_at") else {
+            continue;
+        };
+
+        let profile = match load_profile(pool, &mentee_id, Some(booked_at)).await {
+            Ok(p) => p,
+            Err(MatchingError::NotFound) => continue,
+            Err(e) => return Err(e),
+        };
+        let rows = sqlx::query(&format!(
+            r#"
+            {}
+            WHERE o.mentor_id <> $1
+              AND (o.id = $2
+                   OR (o.created_at <= $3
+                       AND o.created_at > $3 - make_interval(days => $4)))
+            "#,
+            candidate_select()
+        ))
+        .bind(&mentee_id)
+        .bind(&offer_id)
+        .bind(booked_at)
+        .bind(EVALUATION_LOOKBACK_DAYS as i32)
+        .fetch_all(pool)
+        .await?;
+        let candidates: Vec<OfferCandidate> =
+            rows.iter().map(|r| candidate_from_row(r, prior)).collect();
+
+        let ranked = rank_offers(&profile, &candidates, &weights);
+        if let Some(pos) = ranked.iter().position(|m| m.offer_id == offer_id) {
+            ranks.push((pos + 1, ranked.len()));
+        }
+    }
+
+    Ok(MatchingEvaluation::from_ranks(&ranks, k))
+}
@@ -0,0 +1,460 @@
+//! Salles de visioconférence des sessions en ligne
+//!
+//! Le fournisseur est interchangeable (`MeetingProvider`), choisi par
+//! `MEETING_PROVIDER` ; la première implémentation est Jitsi : nom de salle
+//! déterministe (HMAC de l'offre et de l'horaire, partagé par les places d'une
+//! même occurrence de groupe) et jeton JWT de salle signé par participant.
+//!
+//! La salle est attribuée quand la réservation est confirmée ; l'URL de
+//! connexion n'est délivrée qu'aux participants, dans la fenêtre qui encadre
+//! `scheduled_at` (`models::meeting::join_window`). Les rappels de
+//! connexion/déconnexion du fournisseur sont enregistrés comme événements de
+//! présence.
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::{PgPool, Row};
+
+use crate::models::meeting::{
+    join_window, AttendanceEvent, MeetingCallbackPayload, MeetingRoom, RoomAccess,
+};
+
+/// Format d'offre qui reçoit une salle
+pub const ONLINE_FORMAT: &str = "video";
+
+#[derive(Debug, thiserror::Error)]
+pub enum MeetingError {
+    #[error("Booking or room not found")]
+    NotFound,
+    #[error("Not a participant of this booking")]
+    Forbidden,
+    #[error("Booking has no meeting room: {0}")]
+    NoRoom(String),
+    #[error("No meeting provider configured")]
+    Unavailable,
+    #[error("Room token signing failed: {0}")]
+    Token(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Participant pour lequel une URL de connexion est signée
+pub struct Participant<'a> {
+    pub user_id: &'a str,
+    pub display_name: &'a str,
+    /// Le mentor anime la salle
+    pub moderator: bool,
+}
+
+pub trait MeetingProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Nom de salle d'une occurrence — stable pour une offre et un horaire
+    fn room_name(&self, offer_id: &str, scheduled_at: DateTime<Utc>) -> String;
+    /// URL de connexion valable jusqu'à `expires_at`
+    fn join_url(
+        &self,
+        room_name: &str,
+        participant: &Participant,
+        not_before: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, MeetingError>;
+}
+
+/// Fournisseur configuré, `None` si la visioconférence est désactivée
+pub fn configured_provider() -> Option<Box<dyn MeetingProvider>> {
+    match std::env::var("MEETING_PROVIDER")
+        .unwrap_or_else(|_| "jitsi".to_string())
+        .as_str()
+    {
+        "jitsi" => JitsiProvider::from_env().map(|p| Box::new(p) as Box<dyn MeetingProvider>),
+        "none" => None,
+        other => {
+            tracing::warn!("Unknown MEETING_PROVIDER {}", other);
+            None
+        }
+    }
+}
+
+// ============================================================
+// Jitsi — salle déterministe et jeton JWT (authentification prosody)
+// ============================================================
+
+pub struct JitsiProvider {
+    base_url: String,
+    app_id: String,
+    app_secret: String,
+}
+
+#[derive(Debug, Serialize)]
+struct JitsiUser<'a> {
+    id: &'a str,
+    name: &'a str,
+    moderator: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct JitsiContext<'a> {
+    user: JitsiUser<'a>,
+}
+
+#[derive(Debug, Serialize)]
+struct JitsiClaims<'a> {
+    aud: &'a str,
+    iss: &'a str,
+    sub: &'a str,
+    room: &'a str,
+    nbf: i64,
+    exp: i64,
+    context: JitsiContext<'a>,
+}
+
+impl JitsiProvider {
+    pub fn new(base_url: &str, app_id: &str, app_secret: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            app_id: app_id.to_string(),
+            app_secret: app_secret.to_string(),
+        }
+    }
+
+    /// Configuré par `JITSI_APP_SECRET` (requis), `JITSI_APP_ID` et `JITSI_BASE_URL`
+    pub fn from_env() -> Option<Self> {
+        let app_secret = std::env::var("JITSI_APP_SECRET").ok()?;
+        let app_id = std::env::var("JITSI_APP_ID").unwrap_or_else(|_| "token4good".to_string());
+        let base_url =
+            std::env::var("JITSI_BASE_URL").unwrap_or_else(|_| "https://meet.jit.si".to_string());
+        Some(Self::new(&base_url, &app_id, &app_secret))
+    }
+
+    fn domain(&self) -> &str {
+        let host = self
+            .base_url
+            .split_once("://")
+            .map_or(self.base_url.as_str(), |(_, rest)| rest);
+        host.split('/').next().unwrap_or(host)
+    }
+}
+
+impl MeetingProvider for JitsiProvider {
+    fn name(&self) -> &'static str {
+        "jitsi"
+    }
+
+    fn room_name(&self, offer_id: &str, scheduled_at: DateTime<Utc>) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.app_secret.as_bytes())
+            .expect("HMAC accepts any key length");
+        mac.update(format!("room:{}:{}", offer_id, scheduled_at.timestamp()).as_bytes());
+        let digest = hex::encode(mac.finalize().into_bytes());
+        format!("t4g-{}", &digest[..24])
+    }
+
+    fn join_url(
+        &self,
+        room_name: &str,
+        participant: &Participant,
+        not_before: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<String, MeetingError> {
+        let claims = JitsiClaims {
+            aud: "jitsi",
+            iss: &self.app_id,
+            sub: self.domain(),
+            room: room_name,
+            nbf: not_before.timestamp(),
+            exp: expires_at.timestamp(),
+            context: JitsiContext {
+                user: JitsiUser {
+                    id: participant.user_id,
+                    name: participant.display_name,
+                    moderator: participant.moderator,
+                },
+            },
+        };
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(self.app_secret.as_bytes()),
+        )
+        .map_err(|e| MeetingError::Token(e.to_string()))?;
+        Ok(format!("{}/{}?jwt={}", self.base_url, room_name, token))
+    }
+}
+
+// ============================================================
+// Salles des réservations
+// ============================================================
+
+fn room_from_row(r: &sqlx::postgres::PgRow) -> MeetingRoom {
+    MeetingRoom {
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        provider: r.try_get("provider").unwrap_or_default(),
+        room_name: r.try_get("room_name").unwrap_or_default(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Attribue une salle à une réservation confirmée d'une offre en ligne.
+///
+/// Idempotent ; `None` si l'offre n'est pas en ligne, si la réservation n'est
+/// pas confirmée ou si aucun fournisseur n'est configuré.
+pub async fn provision(
+    pool: &PgPool,
+    booking_id: &str,
+) -> Result<Option<MeetingRoom>, MeetingError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.offer_id, b.scheduled_at, b.status, o.format
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MeetingError::NotFound)?;
+
+    let format: String = row.try_get("format").unwrap_or_default();
+    let status: String = row.try_get("status").unwrap_or_default();
+    if format != ONLINE_FORMAT || status != "confirmed" {
+        return Ok(None);
+    }
+    let Some(provider) = configured_provider() else {
+        tracing::debug!(
+            "No meeting provider configured, booking {} has no room",
+            booking_id
+        );
+        return Ok(None);
+    };
+
+    let offer_id: String = row.try_get("offer_id").unwrap_or_default();
+    let scheduled_at: DateTime<Utc> = row.try_get("scheduled_at").unwrap_or_else(|_| Utc::now());
+    sqlx::query(
+        r#"
+        INSERT INTO mentoring_meeting_rooms (booking_id, provider, room_name)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (booking_id) DO NOTHING
+        "#,
+    )
+    .bind(booking_id)
+    .bind(provider.name())
+    .bind(provider.room_name(&offer_id, scheduled_at))
+    .execute(pool)
+    .await?;
+
+    let room = sqlx::query("SELECT * FROM mentoring_meeting_rooms WHERE booking_id = $1")
+        .bind(booking_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(Some(room_from_row(&room)))
+}
+
+/// Accès de l'appelant à la salle de la réservation, avec une URL de
+/// connexion signée s'il se trouve dans la fenêtre de connexion.
+pub async fn access(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<RoomAccess, MeetingError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.mentee_id, b.scheduled_at, b.status, o.mentor_id, o.format, o.duration_minutes,
+               u.firstname, u.lastname
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        LEFT JOIN users u ON u.id = $2
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(MeetingError::NotFound)?;
+
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    if user_id != mentee_id && user_id != mentor_id {
+        return Err(MeetingError::Forbidden);
+    }
+    let format: String = row.try_get("format").unwrap_or_default();
+    if format != ONLINE_FORMAT {
+        return Err(MeetingError::NoRoom(format!("offer format is {}", format)));
+    }
+    let status: String = row.try_get("status").unwrap_or_default();
+    if status != "confirmed" {
+        return Err(MeetingError::NoRoom(format!("booking is {}", status)));
+    }
+    let provider = configured_provider().ok_or(MeetingError::Unavailable)?;
+
+    // Réservations confirmées avant la mise en place d'un fournisseur
+    let room = match sqlx::query("SELECT * FROM mentoring_meeting_rooms WHERE booking_id = $1")
+        .bind(booking_id)
+        .fetch_optional(pool)
+        .await?
+    {
+        Some(r) => room_from_row(&r),
+        None => provision(pool, booking_id)
+            .await?
+            .ok_or(MeetingError::Unavailable)?,
+    };
+
+    let scheduled_at: DateTime<Utc> = row.try_get("scheduled_at").unwrap_or_else(|_| Utc::now());
+    let (opens_at, closes_at) =
+        join_window(scheduled_at, row.try_get("duration_minutes").unwrap_or(60));
+    let now = Utc::now();
+    let join_url = if now >= opens_at && now <= closes_at {
+        let first: String = row.try_get("firstname").ok().flatten().unwrap_or_default();
+        let last: String = row.try_get("lastname").ok().flatten().unwrap_or_default();
+        let display_name = format!("{} {}", first, last).trim().to_string();
+        let participant = Participant {
+            user_id,
+            display_name: &display_name,
+            moderator: user_id == mentor_id,
+        };
+        Some(provider.join_url(&room.room_name, &participant, opens_at, closes_at)?)
+    } else {
+        None
+    };
+
+    Ok(RoomAccess {
+        booking_id: room.booking_id,
+        provider: room.provider,
+        room_name: room.room_name,
+        opens_at,
+        closes_at,
+        join_url,
+    })
+}
+
+// ============================================================
+// Présence
+// ============================================================
+
+/// Enregistre un rappel de connexion/déconnexion pour chaque réservation de
+/// la salle dont l'utilisateur est participant (le mentor d'une session de
+/// groupe participe à toutes les places). Retourne le nombre d'événements
+/// nouveaux ; les rappels rejoués sont ignorés.
+pub async fn record_callback(
+    pool: &PgPool,
+    payload: &MeetingCallbackPayload,
+    source: &str,
+) -> Result<u64, MeetingError> {
+    let bookings: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT r.booking_id
+        FROM mentoring_meeting_rooms r
+        JOIN mentoring_bookings b ON b.id = r.booking_id
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE r.room_name = $1 AND (b.mentee_id = $2 OR o.mentor_id = $2)
+        "#,
+    )
+    .bind(&payload.room_name)
+    .bind(&payload.user_id)
+    .fetch_all(pool)
+    .await?;
+    if bookings.is_empty() {
+        return Err(MeetingError::NotFound);
+    }
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO mentoring_attendance_events (booking_id, user_id, event, occurred_at, source)
+        SELECT booking_id, $2, $3, $4, $5 FROM UNNEST($1::VARCHAR[]) AS booking_id
+        ON CONFLICT (booking_id, user_id, event, occurred_at) DO NOTHING
+        "#,
+    )
+    .bind(&bookings)
+    .bind(&payload.user_id)
+    .bind(payload.event.as_str())
+    .bind(payload.occurred_at)
+    .bind(source)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    Ok(inserted)
+}
+
+/// Événements de présence d'une réservation, dans l'ordre chronologique
+pub async fn attendance(
+    pool: &PgPool,
+    booking_id: &str,
+) -> Result<Vec<AttendanceEvent>, MeetingError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT booking_id, user_id, event, occurred_at, source
+        FROM mentoring_attendance_events
+        WHERE booking_id = $1
+        ORDER BY occurred_at
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| AttendanceEvent {
+            booking_id: r.try_get("booking_id").unwrap_or_default(),
+            user_id: r.try_get("user_id").unwrap_or_default(),
+            event: r.try_get("event").unwrap_or_default(),
+            occurred_at: r.try_get("occurred_at").unwrap_or_else(|_| Utc::now()),
+            source: r.try_get("source").unwrap_or_default(),
+        })
+        .collect())
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use jsonwebtoken::{decode, DecodingKey, Validation};
+
+    #[test]
+    fn test_jitsi_room_is_deterministic_and_token_signed() {
+        let jitsi = JitsiProvider::new("https://meet.example.org/", "t4g", "secret");
+        let at = Utc::now();
+        let room = jitsi.room_name("offer-1", at);
+        assert_eq!(room, jitsi.room_name("offer-1", at));
+        assert_ne!(room, jitsi.room_name("offer-2", at));
+        assert_ne!(room, jitsi.room_name("offer-1", at + Duration::hours(1)));
+        assert!(room.starts_with("t4g-"));
+
+        let participant = Participant {
+            user_id: "u1",
+            display_name: "Alice Martin",
+            moderator: true,
+        };
+        let url = jitsi
+            .join_url(
+                &room,
+                &participant,
+                at - Duration::minutes(5),
+                at + Duration::hours(1),
+            )
+            .unwrap();
+        let prefix = format!("https://meet.example.org/{}?jwt=", room);
+        assert!(url.starts_with(&prefix));
+
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&["jitsi"]);
+        let claims = decode::<serde_json::Value>(
+            &url[prefix.len()..],
+            &DecodingKey::from_secret(b"secret"),
+            &validation,
+        )
+        .unwrap()
+        .claims;
+        assert_eq!(claims["room"], room.as_str());
+        assert_eq!(claims["sub"], "meet.example.org");
+        assert_eq!(claims["context"]["user"]["id"], "u1");
+        assert_eq!(claims["context"]["user"]["moderator"], true);
+    }
+}
@@ -5,15 +5,26 @@
 //!
 //! - Vérification solde T4G avant réservation
 //! - Débit séquestre à la réservation
+//! - Partage du séquestre à l'annulation (remboursement / dédommagement mentor)
 //! - Attribution tokens à la complétion (séquestre → mentor + bonus mentee)
 //! - Calcul du multiplicateur de niveau (Contributeur / Mentor / Expert)
-//! - Génération automatique de la preuve RGB
-//! - Auto-complétion 48h
+//! - Preuve RGB (engageant les notes de session), synchronisation Dazno et
+//!   e-mails de complétion inscrits dans l'outbox transactionnelle
+//! - Clôture des sessions de groupe (une preuve par mentee présent)
+//! - Auto-complétion 48h (après constat de présence des sessions suivies)
 
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 use tracing::{error, info, warn};
 
-use crate::services::rgb::RGBService;
+use crate::models::attendance::AttendanceOutcome;
+use crate::models::outbox::OutboxMessage;
+use crate::services::{
+    attendance,
+    booking_state::{self, Actor, BookingEvent, TransitionError},
+    outbox,
+    rgb::RGBService,
+    session_notes, skills, token_ledger,
+};
 
 // ── Constantes métier ──────────────────────────────────────────────────────
 
@@ -33,31 +44,21 @@ const MULTIPLIER_MENTOR: f64 = 1.1;
 pub struct CompletionResult {
     pub tokens_to_mentor: i64,
     pub tokens_to_mentee: i64,
-    pub rgb_contract_id: Option<String>,
-    pub rgb_signature: Option<String>,
 }
 
 // ── Vérification du solde ──────────────────────────────────────────────────
 
-/// Retourne le solde disponible du `user_id` ou une erreur si `< required`.
-pub async fn check_balance(pool: &PgPool, user_id: &str, required: i64) -> Result<i64, String> {
-    let earned: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(tokens), 0) FROM t4g_token_transactions WHERE user_id = $1 AND tokens > 0"
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await
-    .unwrap_or(0);
-
-    let spent: i64 = sqlx::query_scalar(
-        "SELECT COALESCE(ABS(SUM(tokens)), 0) FROM t4g_token_transactions WHERE user_id = $1 AND tokens < 0"
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await
-    .unwrap_or(0);
-
-    let balance = earned - spent;
+/// Retourne le solde dépensable du `user_id` ou une erreur si `< required`.
+///
+/// Les tokens non encore acquis (vesting) ou expirés ne sont pas comptés.
+pub async fn check_balance(
+    conn: &mut PgConnection,
+    user_id: &str,
+    required: i64,
+) -> Result<i64, String> {
+    let balance = token_ledger::spendable_balance(conn, user_id)
+        .await
+        .unwrap_or(0);
 
     if balance < required {
         return Err(format!(
@@ -72,8 +73,10 @@ pub async fn check_balance(pool: &PgPool, user_id: &str, required: i64) -> Resul
 // ── Débit séquestre à la réservation ──────────────────────────────────────
 
 /// Débite `amount` T4G du mentee (transaction négative = mise en séquestre).
+/// Les lots à expiration sont consommés en premier (FIFO). À appeler dans la
+/// transaction de la réservation, après `token_ledger::lock_balance`.
 pub async fn debit_escrow(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     mentee_id: &str,
     amount: i64,
     booking_id: &str,
@@ -90,9 +93,11 @@ pub async fn debit_escrow(
     .bind(-(amount))
     .bind(format!("Séquestre session mentoring : {}", offer_topic))
     .bind(serde_json::json!({ "booking_id": booking_id, "type": "escrow_debit" }))
-    .execute(pool)
+    .execute(&mut *conn)
     .await?;
 
+    token_ledger::consume_lots(conn, mentee_id, amount).await?;
+
     info!(
         "Escrow debit: {} T4G from {} for booking {}",
         amount, mentee_id, booking_id
@@ -100,9 +105,10 @@ pub async fn debit_escrow(
     Ok(())
 }
 
-/// Rembourse le séquestre au mentee suite à un refus ou annulation mentor
+/// Rembourse le séquestre au mentee suite à un refus ou annulation mentor,
+/// dans la transaction de la transition
 pub async fn refund_escrow(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     mentee_id: &str,
     amount: i64,
     booking_id: &str,
@@ -118,7 +124,7 @@ pub async fn refund_escrow(
     .bind(amount) // positif → crédit
     .bind("Remboursement séquestre — session refusée")
     .bind(serde_json::json!({ "booking_id": booking_id, "type": "escrow_refund" }))
-    .execute(pool)
+    .execute(conn)
     .await?;
 
     info!(
@@ -128,17 +134,69 @@ pub async fn refund_escrow(
     Ok(())
 }
 
+/// Règle le séquestre d'une réservation annulée par le mentee : `refund` T4G
+/// rendus au mentee, `compensation` T4G versés au mentor. Les deux écritures
+/// sont passées dans la transaction de la transition.
+pub async fn settle_cancellation(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    mentee_id: &str,
+    mentor_id: &str,
+    refund: i64,
+    compensation: i64,
+    offer_topic: &str,
+) -> Result<(), sqlx::Error> {
+    if refund > 0 {
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_transactions
+                (id, user_id, action_type, tokens, description, metadata, impact_score)
+            VALUES (gen_random_uuid()::text, $1, 'service_refund', $2, $3, $4, 0.0)
+            "#,
+        )
+        .bind(mentee_id)
+        .bind(refund)
+        .bind(format!("Remboursement séquestre — annulation : {}", offer_topic))
+        .bind(serde_json::json!({ "booking_id": booking_id, "type": "escrow_refund" }))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    if compensation > 0 {
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_transactions
+                (id, user_id, action_type, tokens, description, metadata, impact_score)
+            VALUES (gen_random_uuid()::text, $1, 'mentoring', $2, $3, $4, 0.0)
+            "#,
+        )
+        .bind(mentor_id)
+        .bind(compensation)
+        .bind(format!("Dédommagement annulation tardive : {}", offer_topic))
+        .bind(serde_json::json!({ "booking_id": booking_id, "type": "escrow_compensation" }))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    info!(
+        "Escrow settled for cancelled booking {}: {} T4G → mentee {}, {} T4G → mentor {}",
+        booking_id, refund, mentee_id, compensation, mentor_id
+    );
+    Ok(())
+}
+
 // ── Attribution des tokens à la complétion ────────────────────────────────
 
 /// Calcule les tokens, libère le séquestre vers le mentor, attribue le bonus
-/// au mentee, génère la proof RGB.
+/// au mentee et inscrit les effets de bord (preuve RGB, synchronisation
+/// Dazno, e-mails) dans l'outbox.
 ///
-/// Retourne toujours `Ok(_)` pour ne pas bloquer la réponse HTTP ;
-/// les erreurs partielles (RGB, DB) sont loguées.
+/// S'exécute dans la transaction de l'appelant, celle qui fait passer la
+/// réservation en `completed` : crédits et effets de bord sont validés ou
+/// annulés avec la transition.
 #[allow(clippy::too_many_arguments)]
 pub async fn complete_and_award(
-    pool: &PgPool,
-    rgb: &RGBService,
+    conn: &mut PgConnection,
     booking_id: &str,
     mentor_id: &str,
     mentee_id: &str,
@@ -147,7 +205,7 @@ pub async fn complete_and_award(
     duration_minutes: i32,
     rating: Option<i32>,
     comment: Option<String>,
-) -> CompletionResult {
+) -> Result<CompletionResult, sqlx::Error> {
     let rating_value = rating.unwrap_or(5).clamp(1, 5);
 
     // 1. Niveau du mentor (total gagné historique)
@@ -155,9 +213,8 @@ pub async fn complete_and_award(
         "SELECT COALESCE(SUM(tokens), 0) FROM t4g_token_transactions WHERE user_id = $1 AND tokens > 0"
     )
     .bind(mentor_id)
-    .fetch_one(pool)
-    .await
-    .unwrap_or(0);
+    .fetch_one(&mut *conn)
+    .await?;
 
     let level_multiplier = if mentor_total >= LEVEL_EXPERT_THRESHOLD {
         MULTIPLIER_EXPERT
@@ -172,7 +229,7 @@ pub async fn complete_and_award(
     let tokens_to_mentor = (escrow as f64 * impact_score).round() as i64;
 
     // 3. Crédit mentor
-    let award_result = sqlx::query(
+    sqlx::query(
         r#"
         INSERT INTO t4g_token_transactions
             (id, user_id, action_type, tokens, description, metadata, impact_score)
@@ -190,20 +247,11 @@ pub async fn complete_and_award(
         "level_multiplier": level_multiplier,
     }))
     .bind(impact_score)
-    .execute(pool)
-    .await;
-
-    if let Err(e) = award_result {
-        error!("Failed to award tokens to mentor {}: {}", mentor_id, e);
-    } else {
-        info!(
-            "{} T4G awarded to mentor {} (impact={:.2})",
-            tokens_to_mentor, mentor_id, impact_score
-        );
-    }
+    .execute(&mut *conn)
+    .await?;
 
     // 4. Bonus apprentissage → mentee
-    let bonus_result = sqlx::query(
+    sqlx::query(
         r#"
         INSERT INTO t4g_token_transactions
             (id, user_id, action_type, tokens, description, metadata, impact_score)
@@ -214,72 +262,329 @@ pub async fn complete_and_award(
     .bind(MENTEE_LEARNING_BONUS)
     .bind("Bonus apprentissage — session de mentoring complétée")
     .bind(serde_json::json!({ "booking_id": booking_id, "type": "learning_bonus" }))
-    .execute(pool)
-    .await;
+    .execute(&mut *conn)
+    .await?;
 
-    if let Err(e) = bonus_result {
-        error!(
-            "Failed to award learning bonus to mentee {}: {}",
-            mentee_id, e
-        );
-    } else {
-        info!(
-            "{} T4G learning bonus to mentee {}",
-            MENTEE_LEARNING_BONUS, mentee_id
-        );
+    sqlx::query(
+        "UPDATE mentoring_bookings SET tokens_awarded_mentor = $2, tokens_awarded_mentee = $3
+         WHERE id = $1",
+    )
+    .bind(booking_id)
+    .bind(tokens_to_mentor as i32)
+    .bind(MENTEE_LEARNING_BONUS as i32)
+    .execute(&mut *conn)
+    .await?;
+
+    // 5. Effets de bord, livrés par l'outbox (une preuve par réservation)
+    let side_effects = [
+        (
+            OutboxMessage::RgbProof {
+                booking_id: booking_id.to_string(),
+                mentor_id: mentor_id.to_string(),
+                mentee_id: mentee_id.to_string(),
+                rating: rating_value,
+                comment,
+            },
+            format!("rgb_proof:{}", booking_id),
+        ),
+        (
+            OutboxMessage::DaznoSync {
+                user_id: mentor_id.to_string(),
+                points: tokens_to_mentor.max(0) as u64,
+                action: "mentoring_given".to_string(),
+            },
+            format!("dazno_sync:{}:mentor", booking_id),
+        ),
+        (
+            OutboxMessage::DaznoSync {
+                user_id: mentee_id.to_string(),
+                points: MENTEE_LEARNING_BONUS as u64,
+                action: "mentoring_received".to_string(),
+            },
+            format!("dazno_sync:{}:mentee", booking_id),
+        ),
+        (
+            OutboxMessage::Email {
+                user_id: mentor_id.to_string(),
+                subject: "Session de mentoring complétée".to_string(),
+                body: format!(
+                    "Ta session sur « {} » est complétée : {} T4G ont été crédités \
+                     sur ton compte.",
+                    offer_topic, tokens_to_mentor
+                ),
+            },
+            format!("completion_email:{}:mentor", booking_id),
+        ),
+        (
+            OutboxMessage::Email {
+                user_id: mentee_id.to_string(),
+                subject: "Session de mentoring complétée".to_string(),
+                body: format!(
+                    "Ta session sur « {} » est complétée : {} T4G de bonus d'apprentissage \
+                     ont été crédités sur ton compte.",
+                    offer_topic, MENTEE_LEARNING_BONUS
+                ),
+            },
+            format!("completion_email:{}:mentee", booking_id),
+        ),
+    ];
+    for (message, dedup_key) in &side_effects {
+        outbox::enqueue(&mut *conn, message, Some(dedup_key)).await?;
+    }
+
+    info!(
+        "Booking {} awarded: {} T4G → mentor {} (impact={:.2}), {} T4G → mentee {}",
+        booking_id, tokens_to_mentor, mentor_id, impact_score, MENTEE_LEARNING_BONUS, mentee_id
+    );
+
+    Ok(CompletionResult {
+        tokens_to_mentor,
+        tokens_to_mentee: MENTEE_LEARNING_BONUS,
+    })
+}
+
+/// Preuves d'apprentissage d'une session complétée (non-bloquant)
+pub async fn record_skill_evidence(pool: &PgPool, booking_id: &str) {
+    if let Err(e) = skills::record_session_evidence(pool, booking_id).await {
+        error!("Failed to record skill evidence for booking {}: {}", booking_id, e);
     }
+}
 
-    // 5. Proof RGB (non-bloquant)
-    let (rgb_contract_id, rgb_signature) = match rgb
-        .create_proof_contract(
+/// Génère la preuve RGB d'une session, l'enregistre dans `mentoring_proofs`
+/// et la rattache à la réservation. La preuve engage l'empreinte des notes
+/// partagées de la session, s'il y en a.
+///
+/// Retourne `(contract_id, signature)` ; en cas d'échec RGB, l'erreur est
+/// loguée et retournée à l'appelant (l'outbox la retente).
+pub async fn issue_proof(
+    pool: &PgPool,
+    rgb: &RGBService,
+    booking_id: &str,
+    mentor_id: &str,
+    mentee_id: &str,
+    rating: i32,
+    comment: Option<String>,
+) -> Result<(String, String), String> {
+    let rating = rating.clamp(1, 5);
+    let notes_hash = session_notes::frozen_hash(pool, booking_id)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Session notes hash unavailable for {}: {}", booking_id, e);
+            None
+        });
+    let (contract_id, signature) = rgb
+        .create_proof_contract_with_notes(
             mentor_id,
             mentee_id,
             booking_id,
-            rating_value as u8,
+            rating as u8,
             comment,
+            notes_hash.clone(),
         )
         .await
-    {
-        Ok((contract_id, signature)) => {
-            info!("RGB proof generated: {}", contract_id);
-            let _ = sqlx::query(
-                r#"
-                INSERT INTO mentoring_proofs
-                    (id, request_id, mentor_id, mentee_id, rgb_contract_id, signature, rating)
-                VALUES (gen_random_uuid()::text, $1, $2, $3, $4, $5, $6)
-                ON CONFLICT DO NOTHING
-                "#,
-            )
-            .bind(booking_id)
-            .bind(mentor_id)
-            .bind(mentee_id)
-            .bind(&contract_id)
-            .bind(&signature)
-            .bind(rating_value)
-            .execute(pool)
-            .await;
-            (Some(contract_id), Some(signature))
+        .map_err(|e| {
+            warn!("RGB proof generation failed for {}: {}", booking_id, e);
+            e.to_string()
+        })?;
+    info!("RGB proof generated: {}", contract_id);
+
+    let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO mentoring_proofs
+            (id, request_id, mentor_id, mentee_id, rgb_contract_id, signature, rating,
+             notes_hash)
+        VALUES (gen_random_uuid()::text, $1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(booking_id)
+    .bind(mentor_id)
+    .bind(mentee_id)
+    .bind(&contract_id)
+    .bind(&signature)
+    .bind(rating)
+    .bind(&notes_hash)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    sqlx::query(
+        "UPDATE mentoring_bookings SET rgb_contract_id = $2, rgb_signature = $3 WHERE id = $1",
+    )
+    .bind(booking_id)
+    .bind(&contract_id)
+    .bind(&signature)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok((contract_id, signature))
+}
+
+// ── Sessions de groupe ─────────────────────────────────────────────────────
+
+#[derive(Debug, serde::Serialize)]
+pub struct SeatOutcome {
+    pub booking_id: String,
+    pub mentee_id: String,
+    pub tokens: i64,
+}
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct GroupCompletion {
+    pub attended: Vec<SeatOutcome>,
+    pub absent: Vec<SeatOutcome>,
+    pub tokens_to_mentor: i64,
+}
+
+/// Clôture d'une session de groupe par le mentor, dans la transaction de
+/// l'appelant.
+///
+/// Chaque place d'un mentee présent est complétée individuellement (séquestre
+/// libéré vers le mentor, bonus et preuve RGB propres au mentee) : le mentor
+/// est donc rémunéré au nombre de présents. Les places des absents sont
+/// annulées et leur séquestre partagé comme pour une absence du mentee en
+/// session individuelle. Une occurrence de série passe `completed`.
+#[allow(clippy::too_many_arguments)]
+pub async fn complete_group_session(
+    conn: &mut PgConnection,
+    offer_id: &str,
+    mentor_id: &str,
+    offer_topic: &str,
+    duration_minutes: i32,
+    scheduled_at: chrono::DateTime<chrono::Utc>,
+    attendees: &[String],
+) -> Result<GroupCompletion, sqlx::Error> {
+    use sqlx::Row;
+
+    let seats = sqlx::query(
+        r#"
+        SELECT id, mentee_id, tokens_escrowed, mentee_rating, mentee_comment
+        FROM mentoring_bookings
+        WHERE offer_id = $1 AND scheduled_at = $2
+          AND status IN ('confirmed', 'pending_completion')
+        "#,
+    )
+    .bind(offer_id)
+    .bind(scheduled_at)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut outcome = GroupCompletion::default();
+
+    for seat in &seats {
+        let booking_id: String = seat.try_get("id").unwrap_or_default();
+        let mentee_id: String = seat.try_get("mentee_id").unwrap_or_default();
+        let escrow: i32 = seat.try_get("tokens_escrowed").unwrap_or(0);
+        let attended = attendees.contains(&mentee_id);
+
+        // Transition gardée : une place déjà traitée n'est pas rejouée
+        let event = if attended {
+            BookingEvent::SessionAttended
+        } else {
+            BookingEvent::SessionMissed
+        };
+        match booking_state::apply(
+            &mut *conn,
+            &booking_id,
+            event,
+            Actor::Mentor,
+            Some(mentor_id),
+            serde_json::json!({}),
+        )
+        .await
+        {
+            Ok(_) => {}
+            Err(TransitionError::Database(e)) => return Err(sqlx::Error::Protocol(e)),
+            Err(_) => continue,
         }
-        Err(e) => {
-            warn!("RGB proof generation failed (non-critical): {}", e);
-            (None, None)
+        sqlx::query(
+            "UPDATE mentoring_bookings SET mentor_confirmed = $2,
+                attendance_outcome = CASE WHEN $2 THEN 'attended' ELSE 'mentee_no_show' END,
+                attendance_settled_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(&booking_id)
+        .bind(attended)
+        .execute(&mut *conn)
+        .await?;
+
+        if !attended {
+            let (refund, release) = AttendanceOutcome::MenteeNoShow
+                .escrow_split(escrow as i64)
+                .unwrap_or((escrow as i64, 0));
+            if escrow > 0 {
+                settle_cancellation(
+                    &mut *conn,
+                    &booking_id,
+                    &mentee_id,
+                    mentor_id,
+                    refund,
+                    release,
+                    offer_topic,
+                )
+                .await?;
+            }
+            outcome.tokens_to_mentor += release;
+            outcome.absent.push(SeatOutcome {
+                booking_id,
+                mentee_id,
+                tokens: refund,
+            });
+            continue;
         }
-    };
 
-    CompletionResult {
-        tokens_to_mentor,
-        tokens_to_mentee: MENTEE_LEARNING_BONUS,
-        rgb_contract_id,
-        rgb_signature,
+        let result = complete_and_award(
+            &mut *conn,
+            &booking_id,
+            mentor_id,
+            &mentee_id,
+            offer_topic,
+            escrow as i64,
+            duration_minutes,
+            seat.try_get("mentee_rating").ok(),
+            seat.try_get("mentee_comment").ok(),
+        )
+        .await?;
+
+        outcome.tokens_to_mentor += result.tokens_to_mentor;
+        outcome.attended.push(SeatOutcome {
+            booking_id,
+            mentee_id,
+            tokens: result.tokens_to_mentee,
+        });
     }
+
+    if !outcome.attended.is_empty() || !outcome.absent.is_empty() {
+        sqlx::query(
+            "UPDATE mentoring_offers SET status = 'completed'
+             WHERE id = $1 AND occurrence_at IS NOT NULL",
+        )
+        .bind(offer_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    info!(
+        "Group session {} @ {} closed: {} attended, {} absent, {} T4G → mentor {}",
+        offer_id,
+        scheduled_at,
+        outcome.attended.len(),
+        outcome.absent.len(),
+        outcome.tokens_to_mentor,
+        mentor_id
+    );
+    Ok(outcome)
 }
 
 // ── Auto-complétion 48h ────────────────────────────────────────────────────
 
 /// Cherche les réservations `pending_completion` depuis > 48h et les
-/// auto-complète (libère le séquestre sans nouvelle preuve RGB).
-pub async fn run_auto_completion(pool: &PgPool, rgb: &RGBService) -> u64 {
-    let rows = sqlx::query(
+/// auto-complète (libère le séquestre, preuve RGB via l'outbox).
+pub async fn run_auto_completion(pool: &PgPool) -> u64 {
+    // Une réservation suivie attend le constat de présence
+    let rows = sqlx::query(&format!(
         r#"
         SELECT b.id, b.mentee_id, b.tokens_escrowed, b.mentee_rating, b.mentee_comment,
                o.mentor_id, o.topic_slug, o.duration_minutes
@@ -287,8 +592,10 @@ pub async fn run_auto_completion(pool: &PgPool, rgb: &RGBService) -> u64 {
         JOIN mentoring_offers o ON o.id = b.offer_id
         WHERE b.status = 'pending_completion'
           AND b.updated_at < NOW() - INTERVAL '48 hours'
+          AND (b.attendance_outcome IS NOT NULL OR o.capacity > 1 OR NOT {})
         "#,
-    )
+        attendance::TRACKED_BOOKING
+    ))
     .fetch_all(pool)
     .await;
 
@@ -313,32 +620,48 @@ pub async fn run_auto_completion(pool: &PgPool, rgb: &RGBService) -> u64 {
         let rating: Option<i32> = row.try_get("mentee_rating").ok();
         let comment: Option<String> = row.try_get("mentee_comment").ok();
 
-        let update = sqlx::query(
-            "UPDATE mentoring_bookings SET status = 'auto_completed', mentor_confirmed = true WHERE id = $1"
-        )
-        .bind(&booking_id)
-        .execute(pool)
-        .await;
-
-        if let Err(e) = update {
-            error!("Auto-completion DB update failed for {}: {}", booking_id, e);
-            continue;
+        // Transition gardée : un litige ou une confirmation concurrente l'emporte
+        let update = async {
+            let mut tx = pool.begin().await?;
+            booking_state::apply(
+                &mut tx,
+                &booking_id,
+                BookingEvent::AutoComplete,
+                Actor::System,
+                None,
+                serde_json::json!({}),
+            )
+            .await?;
+            sqlx::query("UPDATE mentoring_bookings SET mentor_confirmed = true WHERE id = $1")
+                .bind(&booking_id)
+                .execute(&mut *tx)
+                .await?;
+            let result = complete_and_award(
+                &mut tx,
+                &booking_id,
+                &mentor_id,
+                &mentee_id,
+                &topic_slug,
+                escrow as i64,
+                duration,
+                rating,
+                comment,
+            )
+            .await?;
+            tx.commit().await?;
+            Ok::<_, TransitionError>(result)
         }
-
-        let result = complete_and_award(
-            pool,
-            rgb,
-            &booking_id,
-            &mentor_id,
-            &mentee_id,
-            &topic_slug,
-            escrow as i64,
-            duration,
-            rating,
-            comment,
-        )
         .await;
 
+        let result = match update {
+            Ok(result) => result,
+            Err(e) => {
+                error!("Auto-completion skipped for {}: {}", booking_id, e);
+                continue;
+            }
+        };
+        record_skill_evidence(pool, &booking_id).await;
+
         warn!(
             "Auto-completed booking {}: {} T4G → mentor, {} T4G → mentee",
             booking_id, result.tokens_to_mentor, result.tokens_to_mentee
@@ -352,7 +675,8 @@ pub async fn run_auto_completion(pool: &PgPool, rgb: &RGBService) -> u64 {
 
 /// Envoie des notifications de rappel J-1 (entre 23h et 25h avant la session)
 /// et H-1 (entre 50min et 70min avant la session) pour les sessions confirmées.
-/// Utilise la table `notifications` pour éviter les doublons.
+/// Utilise la table `notifications` pour éviter les doublons, par réservation
+/// et par horaire (une session reportée reçoit de nouveaux rappels).
 pub async fn send_session_reminders(pool: &PgPool) -> u64 {
     use sqlx::Row;
 
@@ -398,23 +722,26 @@ pub async fn send_session_reminders(pool: &PgPool) -> u64 {
         let minutes_until = (scheduled_at - now).num_minutes();
         let session_link = format!("/mentoring/session/{}", booking_id);
         let time_label = scheduled_at.format("%d/%m à %Hh%M").to_string();
+        // Un rappel est dû par horaire : un report relance J-1 / H-1
+        let scheduled_key = scheduled_at.to_rfc3339();
 
         // J-1 : entre 23h et 25h avant
-        if minutes_until >= 23 * 60 && minutes_until <= 25 * 60 {
+        if (23 * 60..=25 * 60).contains(&minutes_until) {
             let notif_type = "MENTORING_REMINDER_J1";
             // Vérifier si déjà envoyé
             let already_sent: bool = sqlx::query_scalar(
-                "SELECT EXISTS(SELECT 1 FROM notifications WHERE user_id = $1 AND type = $2 AND metadata->>'booking_id' = $3)"
+                "SELECT EXISTS(SELECT 1 FROM notifications WHERE user_id = $1 AND type = $2 AND metadata->>'booking_id' = $3 AND COALESCE(metadata->>'scheduled_at', $4) = $4)"
             )
             .bind(&mentee_id)
             .bind(notif_type)
             .bind(&booking_id)
+            .bind(&scheduled_key)
             .fetch_one(pool)
             .await
             .unwrap_or(false);
 
             if !already_sent {
-                let meta = serde_json::json!({ "booking_id": booking_id });
+                let meta = serde_json::json!({ "booking_id": booking_id, "scheduled_at": scheduled_key });
                 for (uid, msg) in [
                     (&mentee_id, format!("Rappel : ta session «{}» avec {} commence demain ({}).", topic_slug, mentor_firstname, time_label)),
                     (&mentor_id, format!("Rappel : ta session «{}» avec {} commence demain ({}).", topic_slug, mentee_firstname, time_label)),
@@ -437,20 +764,21 @@ pub async fn send_session_reminders(pool: &PgPool) -> u64 {
         }
 
         // H-1 : entre 50min et 70min avant
-        if minutes_until >= 50 && minutes_until <= 70 {
+        if (50..=70).contains(&minutes_until) {
             let notif_type = "MENTORING_REMINDER_H1";
             let already_sent: bool = sqlx::query_scalar(
-                "SELECT EXISTS(SELECT 1 FROM notifications WHERE user_id = $1 AND type = $2 AND metadata->>'booking_id' = $3)"
+                "SELECT EXISTS(SELECT 1 FROM notifications WHERE user_id = $1 AND type = $2 AND metadata->>'booking_id' = $3 AND COALESCE(metadata->>'scheduled_at', $4) = $4)"
             )
             .bind(&mentee_id)
             .bind(notif_type)
             .bind(&booking_id)
+            .bind(&scheduled_key)
             .fetch_one(pool)
             .await
             .unwrap_or(false);
 
             if !already_sent {
-                let meta = serde_json::json!({ "booking_id": booking_id });
+                let meta = serde_json::json!({ "booking_id": booking_id, "scheduled_at": scheduled_key });
                 for (uid, msg) in [
                     (&mentee_id, format!("Ta session «{}» avec {} commence dans 1 heure ({}) !", topic_slug, mentor_firstname, time_label)),
                     (&mentor_id, format!("Ta session «{}» avec {} commence dans 1 heure ({}) !", topic_slug, mentee_firstname, time_label)),
@@ -484,14 +812,14 @@ mod tests {
 
     #[test]
     fn test_level_thresholds() {
-        assert!(LEVEL_MENTOR_THRESHOLD < LEVEL_EXPERT_THRESHOLD);
-        assert!(MULTIPLIER_MENTOR > 1.0);
-        assert!(MULTIPLIER_EXPERT > MULTIPLIER_MENTOR);
+        const { assert!(LEVEL_MENTOR_THRESHOLD < LEVEL_EXPERT_THRESHOLD) };
+        const { assert!(MULTIPLIER_MENTOR > 1.0) };
+        const { assert!(MULTIPLIER_EXPERT > MULTIPLIER_MENTOR) };
     }
 
     #[test]
     fn test_learning_bonus() {
-        assert!(MENTEE_LEARNING_BONUS > 0);
+        const { assert!(MENTEE_LEARNING_BONUS > 0) };
     }
 
     #[test]
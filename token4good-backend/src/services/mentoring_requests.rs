@@ -0,0 +1,475 @@
+//! Demandes de mentoring
+//!
+//! Un mentee publie une demande (« j'ai besoin d'aide sur X »). Les mentors
+//! y répondent par une offre privée (`mentoring_offers.request_id`),
+//! absente du catalogue et réservable par le seul mentee. La réservation
+//! passe par le parcours normal (solde, séquestre, machine à états) ; elle
+//! attribue la demande et retire les autres réponses. `follow_booking`,
+//! appelé par `booking_state::apply`, clôt la demande à la complétion et la
+//! rouvre si la réservation est annulée.
+
+use sqlx::{postgres::PgRow, PgConnection, PgExecutor, PgPool, Row};
+use tracing::info;
+
+use crate::models::mentoring::{
+    CreateRequestPayload, MentoringRequest, MentoringRequestDetail, RequestStatus, RequestsQuery,
+    RespondToRequestPayload,
+};
+use crate::models::mentoring_offer::{BookingStatus, CancellationPolicy, MentoringOffer};
+use crate::models::time_slot::parse_availability;
+
+/// Mentors prévenus d'une nouvelle demande sur un sujet
+const MAX_NOTIFIED_MENTORS: i64 = 20;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RequestError {
+    #[error("Request or offer not found")]
+    NotFound,
+    #[error("Not allowed on this request")]
+    Forbidden,
+    #[error("Request is no longer open")]
+    Closed,
+    #[error("Mentor already has an open offer on this request")]
+    AlreadyResponded,
+    #[error("Invalid request: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+pub fn request_from_row(row: &PgRow) -> MentoringRequest {
+    MentoringRequest {
+        id: row.try_get("id").unwrap_or_default(),
+        title: row.try_get("title").unwrap_or_default(),
+        description: row.try_get("description").unwrap_or_default(),
+        created_at: row
+            .try_get("created_at")
+            .unwrap_or_else(|_| chrono::Utc::now()),
+        status: row
+            .try_get::<String, _>("status")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(RequestStatus::Open),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").ok().flatten(),
+        tags: row.try_get("tags").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").ok().flatten(),
+        max_tokens: row.try_get("max_tokens").ok().flatten(),
+        booking_id: row.try_get("booking_id").ok().flatten(),
+    }
+}
+
+fn offer_from_row(row: &PgRow) -> MentoringOffer {
+    MentoringOffer {
+        id: row.try_get("id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        target_level: row.try_get("target_level").unwrap_or_default(),
+        description: row.try_get("description").ok(),
+        duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
+        format: row.try_get("format").unwrap_or_default(),
+        token_cost: row.try_get("token_cost").unwrap_or(0),
+        availability: parse_availability(row.try_get("availability").unwrap_or_default()),
+        status: row.try_get("status").unwrap_or_else(|_| "open".to_string()),
+        capacity: row.try_get("capacity").unwrap_or(1),
+        series_id: row.try_get("series_id").ok().flatten(),
+        occurrence_at: row.try_get("occurrence_at").ok().flatten(),
+        cancellation_policy: CancellationPolicy::from_value(
+            row.try_get("cancellation_policy").unwrap_or_default(),
+        ),
+        request_id: row.try_get("request_id").ok().flatten(),
+        created_at: row
+            .try_get("created_at")
+            .unwrap_or_else(|_| chrono::Utc::now()),
+        updated_at: row
+            .try_get("updated_at")
+            .unwrap_or_else(|_| chrono::Utc::now()),
+    }
+}
+
+async fn fetch<'e, E: PgExecutor<'e>>(
+    executor: E,
+    request_id: &str,
+) -> Result<MentoringRequest, RequestError> {
+    sqlx::query("SELECT * FROM mentoring_requests WHERE id = $1")
+        .bind(request_id)
+        .fetch_optional(executor)
+        .await?
+        .map(|r| request_from_row(&r))
+        .ok_or(RequestError::NotFound)
+}
+
+pub async fn create(
+    conn: &mut PgConnection,
+    mentee_id: &str,
+    payload: &CreateRequestPayload,
+) -> Result<MentoringRequest, RequestError> {
+    payload.validate().map_err(RequestError::Invalid)?;
+    if let Some(slug) = &payload.topic_slug {
+        let known: bool =
+            sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM learning_topics WHERE slug = $1)")
+                .bind(slug)
+                .fetch_one(&mut *conn)
+                .await?;
+        if !known {
+            return Err(RequestError::Invalid(format!("unknown topic: {}", slug)));
+        }
+    }
+    let tags: Vec<String> = payload
+        .tags
+        .iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_requests
+            (mentee_id, topic_slug, title, description, tags, max_tokens)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(mentee_id)
+    .bind(&payload.topic_slug)
+    .bind(payload.title.trim())
+    .bind(payload.description.trim())
+    .bind(&tags)
+    .bind(payload.max_tokens)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let request = request_from_row(&row);
+    info!(
+        "Mentee {} opened mentoring request {}",
+        mentee_id, request.id
+    );
+    Ok(request)
+}
+
+/// Demandes ouvertes des autres utilisateurs, plus récentes d'abord
+pub async fn list_open(
+    pool: &PgPool,
+    viewer_id: &str,
+    query: &RequestsQuery,
+) -> Result<Vec<MentoringRequest>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM mentoring_requests
+        WHERE status = 'open' AND mentee_id <> $1
+          AND ($2::text IS NULL OR topic_slug = $2)
+          AND ($3::text IS NULL OR $3 = ANY(tags))
+        ORDER BY created_at DESC
+        LIMIT $4 OFFSET $5
+        "#,
+    )
+    .bind(viewer_id)
+    .bind(&query.topic_slug)
+    .bind(query.tag.as_ref().map(|t| t.trim().to_lowercase()))
+    .bind(query.limit.unwrap_or(50).clamp(1, 100))
+    .bind(query.offset.unwrap_or(0).max(0))
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(request_from_row).collect())
+}
+
+/// Demandes d'un mentee, plus récentes d'abord
+pub async fn list_for_mentee(
+    pool: &PgPool,
+    mentee_id: &str,
+) -> Result<Vec<MentoringRequest>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT * FROM mentoring_requests WHERE mentee_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(mentee_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(request_from_row).collect())
+}
+
+/// Demande et réponses : toutes pour le mentee, les siennes pour un mentor.
+/// Une demande qui n'est plus ouverte reste visible du mentee et des
+/// mentors qui y ont répondu.
+pub async fn detail(
+    pool: &PgPool,
+    request_id: &str,
+    viewer_id: &str,
+) -> Result<MentoringRequestDetail, RequestError> {
+    let request = fetch(pool, request_id).await?;
+    let is_mentee = request.mentee_id == viewer_id;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM mentoring_offers
+        WHERE request_id = $1 AND ($2 OR mentor_id = $3)
+        ORDER BY created_at
+        "#,
+    )
+    .bind(request_id)
+    .bind(is_mentee)
+    .bind(viewer_id)
+    .fetch_all(pool)
+    .await?;
+
+    if !is_mentee && request.status != RequestStatus::Open && rows.is_empty() {
+        return Err(RequestError::Forbidden);
+    }
+    Ok(MentoringRequestDetail {
+        request,
+        offers: rows.iter().map(offer_from_row).collect(),
+    })
+}
+
+/// Mentors proposant une offre publique ouverte sur le sujet de la demande
+pub async fn interested_mentors<'e, E: PgExecutor<'e>>(
+    executor: E,
+    request: &MentoringRequest,
+) -> Result<Vec<String>, sqlx::Error> {
+    let Some(topic_slug) = &request.topic_slug else {
+        return Ok(vec![]);
+    };
+    sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT mentor_id FROM mentoring_offers
+        WHERE topic_slug = $1 AND status = 'open' AND request_id IS NULL
+          AND mentor_id <> $2
+        LIMIT $3
+        "#,
+    )
+    .bind(topic_slug)
+    .bind(&request.mentee_id)
+    .bind(MAX_NOTIFIED_MENTORS)
+    .fetch_all(executor)
+    .await
+}
+
+/// Offre privée d'un mentor en réponse à une demande ouverte (une seule
+/// réponse ouverte par mentor), dans la transaction de l'appelant
+pub async fn respond(
+    conn: &mut PgConnection,
+    request_id: &str,
+    mentor_id: &str,
+    payload: &RespondToRequestPayload,
+) -> Result<(MentoringRequest, MentoringOffer), RequestError> {
+    let request = fetch(&mut *conn, request_id).await?;
+    if request.mentee_id == mentor_id {
+        return Err(RequestError::Forbidden);
+    }
+    if request.status != RequestStatus::Open {
+        return Err(RequestError::Closed);
+    }
+    payload
+        .validate(request.max_tokens)
+        .map_err(RequestError::Invalid)?;
+    let topic_slug = payload
+        .topic_slug
+        .clone()
+        .or_else(|| request.topic_slug.clone())
+        .ok_or_else(|| RequestError::Invalid("topic_slug est requis".to_string()))?;
+
+    // Verrou de la demande : pas de réponse concurrente ni de clôture entre-temps
+    let status: String =
+        sqlx::query_scalar("SELECT status FROM mentoring_requests WHERE id = $1 FOR UPDATE")
+            .bind(request_id)
+            .fetch_one(&mut *conn)
+            .await?;
+    if status != RequestStatus::Open.to_string() {
+        return Err(RequestError::Closed);
+    }
+    let already: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM mentoring_offers
+            WHERE request_id = $1 AND mentor_id = $2 AND status = 'open'
+        )
+        "#,
+    )
+    .bind(request_id)
+    .bind(mentor_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if already {
+        return Err(RequestError::AlreadyResponded);
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_offers
+            (mentor_id, topic_slug, target_level, description, duration_minutes,
+             format, token_cost, availability, status, capacity, cancellation_policy,
+             request_id)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'open', 1, $9, $10)
+        RETURNING *
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(&topic_slug)
+    .bind(payload.target_level.as_deref().unwrap_or("beginner"))
+    .bind(&payload.description)
+    .bind(payload.duration_minutes)
+    .bind(&payload.format)
+    .bind(payload.token_cost)
+    .bind(serde_json::to_value(&payload.availability).unwrap_or_default())
+    .bind(
+        serde_json::to_value(payload.cancellation_policy.clone().unwrap_or_default())
+            .unwrap_or_default(),
+    )
+    .bind(request_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            RequestError::Invalid(format!("unknown topic: {}", topic_slug))
+        }
+        e => RequestError::Database(e),
+    })?;
+
+    let offer = offer_from_row(&row);
+    info!(
+        "Mentor {} answered request {} with private offer {}",
+        mentor_id, request_id, offer.id
+    );
+    Ok((request, offer))
+}
+
+/// Retrait d'une demande ouverte par son mentee, dans la transaction de
+/// l'appelant ; les réponses ouvertes sont annulées. Renvoie les mentors
+/// concernés.
+pub async fn cancel(
+    conn: &mut PgConnection,
+    request_id: &str,
+    mentee_id: &str,
+) -> Result<(MentoringRequest, Vec<String>), RequestError> {
+    let request = fetch(&mut *conn, request_id).await?;
+    if request.mentee_id != mentee_id {
+        return Err(RequestError::Forbidden);
+    }
+
+    let row = sqlx::query(
+        r#"
+        UPDATE mentoring_requests SET status = 'cancelled'
+        WHERE id = $1 AND status = 'open'
+        RETURNING *
+        "#,
+    )
+    .bind(request_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(RequestError::Closed)?;
+    let mentors: Vec<String> = sqlx::query_scalar(
+        r#"
+        UPDATE mentoring_offers SET status = 'cancelled', updated_at = NOW()
+        WHERE request_id = $1 AND status = 'open'
+        RETURNING mentor_id
+        "#,
+    )
+    .bind(request_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    info!(
+        "Mentee {} cancelled mentoring request {}",
+        mentee_id, request_id
+    );
+    Ok((request_from_row(&row), mentors))
+}
+
+// ============================================================
+// Réservation d'une offre privée
+// ============================================================
+
+/// Vérifie, dans la transaction de réservation, qu'une offre privée est
+/// réservée par le mentee de sa demande, encore ouverte. Sans effet pour
+/// une offre du catalogue.
+pub async fn check_bookable(
+    conn: &mut PgConnection,
+    offer_id: &str,
+    mentee_id: &str,
+) -> Result<(), RequestError> {
+    let row = sqlx::query(
+        r#"
+        SELECT r.mentee_id, r.status
+        FROM mentoring_offers o
+        JOIN mentoring_requests r ON r.id = o.request_id
+        WHERE o.id = $1
+        FOR UPDATE OF r
+        "#,
+    )
+    .bind(offer_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(row) = row else {
+        return Ok(());
+    };
+
+    let owner: String = row.try_get("mentee_id").unwrap_or_default();
+    let status: String = row.try_get("status").unwrap_or_default();
+    if owner != mentee_id {
+        return Err(RequestError::Forbidden);
+    }
+    if status != RequestStatus::Open.to_string() {
+        return Err(RequestError::Closed);
+    }
+    Ok(())
+}
+
+/// Attribue la demande au mentor de l'offre réservée et annule les autres
+/// réponses ouvertes. Renvoie l'id de la demande pour une offre privée.
+pub async fn mark_booked(
+    conn: &mut PgConnection,
+    offer_id: &str,
+    booking_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let request_id: Option<String> = sqlx::query_scalar(
+        r#"
+        UPDATE mentoring_requests r
+        SET status = 'assigned', mentor_id = o.mentor_id, booking_id = $2
+        FROM mentoring_offers o
+        WHERE o.id = $1 AND r.id = o.request_id
+        RETURNING r.id
+        "#,
+    )
+    .bind(offer_id)
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(request_id) = &request_id {
+        sqlx::query(
+            r#"
+            UPDATE mentoring_offers SET status = 'cancelled', updated_at = NOW()
+            WHERE request_id = $1 AND id <> $2 AND status = 'open'
+            "#,
+        )
+        .bind(request_id)
+        .bind(offer_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(request_id)
+}
+
+/// Répercute le nouveau statut d'une réservation sur la demande d'origine :
+/// complétée avec la session, rouverte si la réservation est annulée.
+pub async fn follow_booking(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    to: BookingStatus,
+) -> Result<(), sqlx::Error> {
+    let sql = match to {
+        BookingStatus::Completed | BookingStatus::AutoCompleted => {
+            "UPDATE mentoring_requests SET status = 'completed'
+             WHERE booking_id = $1 AND status = 'assigned'"
+        }
+        BookingStatus::Cancelled => {
+            "UPDATE mentoring_requests SET status = 'open', mentor_id = NULL, booking_id = NULL
+             WHERE booking_id = $1 AND status = 'assigned'"
+        }
+        _ => return Ok(()),
+    };
+    sqlx::query(sql)
+        .bind(booking_id)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
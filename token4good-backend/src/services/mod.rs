@@ -1,8 +1,34 @@
+pub mod attendance;
+pub mod availability;
+pub mod booking_state;
+pub mod calendar;
+pub mod certificate;
+pub mod chat;
 pub mod database_services;
 pub mod database_simplified;
 pub mod dazno;
+pub mod disputes;
+pub mod escrow_reconciliation;
+pub mod jobs;
+pub mod ledger_chain;
+pub mod ledger_statement;
+pub mod matching;
+pub mod meeting;
 pub mod mentoring_completion;
+pub mod mentoring_requests;
+pub mod offer_series;
+pub mod outbox;
+pub mod pdf;
+pub mod rescheduling;
+pub mod reviews;
 pub mod rgb;
 pub mod rgb_native;
+pub mod scheduler;
+pub mod session_notes;
+pub mod skills;
+pub mod time_off;
+pub mod token_adjustment;
+pub mod token_ledger;
+pub mod waitlist;
 
 pub use database_simplified as database;
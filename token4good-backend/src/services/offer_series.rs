@@ -0,0 +1,267 @@
+//! Séries d'offres récurrentes
+//!
+//! Chaque série génère, sur un horizon glissant, une offre `mentoring_offers`
+//! par occurrence (créneau unique, capacité de la série). La génération est
+//! idempotente grâce à l'index unique (series_id, occurrence_at).
+
+use chrono::{DateTime, Duration, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use sqlx::{PgPool, Row};
+use tracing::{error, info, warn};
+
+use crate::models::offer_series::{CreateSeriesPayload, OfferSeries, SeriesRule};
+use crate::models::time_slot::{OneOffSlot, TimeSlot};
+
+/// Horizon de génération des occurrences
+const SERIES_HORIZON_DAYS: i64 = 28;
+
+fn series_from_row(r: &sqlx::postgres::PgRow) -> Option<OfferSeries> {
+    let weekday: i16 = r.try_get("weekday").ok()?;
+    let timezone: String = r.try_get("timezone").ok()?;
+    Some(OfferSeries {
+        id: r.try_get("id").ok()?,
+        mentor_id: r.try_get("mentor_id").unwrap_or_default(),
+        topic_slug: r.try_get("topic_slug").unwrap_or_default(),
+        target_level: r.try_get("target_level").unwrap_or_default(),
+        description: r.try_get("description").ok().flatten(),
+        duration_minutes: r.try_get("duration_minutes").unwrap_or(60),
+        format: r.try_get("format").unwrap_or_default(),
+        token_cost: r.try_get("token_cost").unwrap_or(0),
+        capacity: r.try_get("capacity").unwrap_or(1),
+        rule: SeriesRule {
+            weekday: Weekday::try_from(weekday as u8).ok()?,
+            start_time: r.try_get::<NaiveTime, _>("start_time").ok()?,
+            timezone: timezone.parse::<Tz>().ok()?,
+            interval_weeks: r.try_get("interval_weeks").unwrap_or(1),
+            starts_on: r.try_get("starts_on").ok()?,
+            ends_on: r.try_get("ends_on").ok().flatten(),
+            max_occurrences: r.try_get("max_occurrences").ok().flatten(),
+        },
+        status: r.try_get("status").unwrap_or_default(),
+        generated_until: r.try_get("generated_until").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        updated_at: r.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+pub async fn create_series(
+    pool: &PgPool,
+    mentor_id: &str,
+    payload: &CreateSeriesPayload,
+) -> Result<OfferSeries, sqlx::Error> {
+    let rule = &payload.rule;
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_offer_series
+            (mentor_id, topic_slug, target_level, description, duration_minutes, format,
+             token_cost, capacity, weekday, start_time, timezone, interval_weeks,
+             starts_on, ends_on, max_occurrences)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+        RETURNING *
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(&payload.topic_slug)
+    .bind(&payload.target_level)
+    .bind(&payload.description)
+    .bind(payload.duration_minutes)
+    .bind(&payload.format)
+    .bind(payload.token_cost)
+    .bind(payload.capacity)
+    .bind(rule.weekday.num_days_from_monday() as i16)
+    .bind(rule.start_time)
+    .bind(rule.timezone.name())
+    .bind(rule.interval_weeks)
+    .bind(rule.starts_on)
+    .bind(rule.ends_on)
+    .bind(rule.max_occurrences)
+    .fetch_one(pool)
+    .await?;
+
+    let series = series_from_row(&row).ok_or(sqlx::Error::RowNotFound)?;
+    info!("Mentor {} created offer series {}", mentor_id, series.id);
+    generate_occurrences(pool, &series).await?;
+    Ok(series)
+}
+
+pub async fn get_series(
+    pool: &PgPool,
+    series_id: &str,
+) -> Result<Option<OfferSeries>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM mentoring_offer_series WHERE id = $1")
+        .bind(series_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(row.as_ref().and_then(series_from_row))
+}
+
+pub async fn list_mentor_series(
+    pool: &PgPool,
+    mentor_id: &str,
+) -> Result<Vec<OfferSeries>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT * FROM mentoring_offer_series WHERE mentor_id = $1 ORDER BY created_at DESC",
+    )
+    .bind(mentor_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().filter_map(series_from_row).collect())
+}
+
+/// Crée les offres des occurrences à venir jusqu'à l'horizon ; retourne le
+/// nombre d'offres créées. La série passe en `ended` après sa dernière occurrence.
+pub async fn generate_occurrences(pool: &PgPool, series: &OfferSeries) -> Result<u64, sqlx::Error> {
+    let now = Utc::now();
+    let from = series.generated_until.unwrap_or(now).max(now);
+    let to = now + Duration::days(SERIES_HORIZON_DAYS);
+
+    let mut created = 0u64;
+    for start in series.rule.occurrences(from, to) {
+        let slot = TimeSlot::OneOff(OneOffSlot {
+            date: start.with_timezone(&series.rule.timezone).fixed_offset(),
+            duration_minutes: series.duration_minutes,
+            seats: None,
+        });
+        let result = sqlx::query(
+            r#"
+            INSERT INTO mentoring_offers
+                (mentor_id, topic_slug, target_level, description, duration_minutes,
+                 format, token_cost, availability, status, capacity, series_id, occurrence_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, 'open', $9, $10, $11)
+            ON CONFLICT (series_id, occurrence_at) WHERE series_id IS NOT NULL DO NOTHING
+            "#,
+        )
+        .bind(&series.mentor_id)
+        .bind(&series.topic_slug)
+        .bind(&series.target_level)
+        .bind(&series.description)
+        .bind(series.duration_minutes)
+        .bind(&series.format)
+        .bind(series.token_cost)
+        .bind(serde_json::json!([slot]))
+        .bind(series.capacity)
+        .bind(&series.id)
+        .bind(start)
+        .execute(pool)
+        .await?;
+        created += result.rows_affected();
+    }
+
+    let status = if series.rule.has_occurrences_after(to) {
+        "active"
+    } else {
+        "ended"
+    };
+    sqlx::query(
+        "UPDATE mentoring_offer_series SET generated_until = $1, status = $2 WHERE id = $3 AND status = 'active'",
+    )
+    .bind(to)
+    .bind(status)
+    .bind(&series.id)
+    .execute(pool)
+    .await?;
+
+    if created > 0 {
+        info!("Series {}: {} occurrence(s) generated", series.id, created);
+    }
+    Ok(created)
+}
+
+/// Job périodique : étend toutes les séries actives jusqu'à l'horizon.
+pub async fn run_series_generation(pool: &PgPool) -> u64 {
+    let rows = match sqlx::query("SELECT * FROM mentoring_offer_series WHERE status = 'active'")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            error!("Series generation query failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut created = 0u64;
+    for row in &rows {
+        let Some(series) = series_from_row(row) else {
+            warn!("Skipping unreadable offer series row");
+            continue;
+        };
+        match generate_occurrences(pool, &series).await {
+            Ok(n) => created += n,
+            Err(e) => error!(
+                "Occurrence generation failed for series {}: {}",
+                series.id, e
+            ),
+        }
+    }
+    created
+}
+
+/// Annule la série et ses occurrences futures sans réservation active ;
+/// les occurrences déjà réservées restent maintenues. Retourne le nombre
+/// d'offres annulées.
+pub async fn cancel_series(pool: &PgPool, series_id: &str) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE mentoring_offer_series SET status = 'cancelled' WHERE id = $1")
+        .bind(series_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE mentoring_offers o SET status = 'cancelled'
+        WHERE o.series_id = $1
+          AND o.status = 'open'
+          AND o.occurrence_at > NOW()
+          AND NOT EXISTS (
+              SELECT 1 FROM mentoring_bookings b
+              WHERE b.offer_id = o.id
+                AND b.status IN ('pending', 'confirmed', 'pending_completion', 'disputed')
+          )
+        "#,
+    )
+    .bind(series_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// Occurrences générées d'une série, avec leur remplissage.
+pub async fn list_occurrences(
+    pool: &PgPool,
+    series_id: &str,
+) -> Result<Vec<serde_json::Value>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT o.id, o.occurrence_at, o.status, o.capacity,
+               COUNT(b.id) FILTER (
+                   WHERE b.status IN ('pending', 'confirmed', 'pending_completion', 'disputed',
+                                      'completed', 'auto_completed')
+               ) AS seats_taken
+        FROM mentoring_offers o
+        LEFT JOIN mentoring_bookings b ON b.offer_id = o.id
+        WHERE o.series_id = $1
+        GROUP BY o.id
+        ORDER BY o.occurrence_at
+        "#,
+    )
+    .bind(series_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "offer_id":      r.try_get::<String, _>("id").unwrap_or_default(),
+                "occurrence_at": r.try_get::<Option<DateTime<Utc>>, _>("occurrence_at").unwrap_or(None),
+                "status":        r.try_get::<String, _>("status").unwrap_or_default(),
+                "capacity":      r.try_get::<i32, _>("capacity").unwrap_or(1),
+                "seats_taken":   r.try_get::<i64, _>("seats_taken").unwrap_or(0),
+            })
+        })
+        .collect())
+}
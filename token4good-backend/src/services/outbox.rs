@@ -0,0 +1,424 @@
+//! Outbox transactionnelle des effets de bord
+//!
+//! Un changement métier écrit ses effets de bord (notification in-app,
+//! e-mail, synchronisation gamification Dazno, preuve RGB) dans
+//! `outbox_events` au sein de sa propre transaction : ils ne sont produits que
+//! si le changement est validé, et ne sont plus perdus si le service externe
+//! est indisponible.
+//!
+//! Le répartiteur tourne sur chaque instance : il réserve les entrées dues
+//! (`FOR UPDATE SKIP LOCKED`, bail `locked_until`), les livre à leur
+//! gestionnaire et consigne le résultat. Un échec est retenté avec backoff
+//! exponentiel jusqu'à `max_attempts`, puis l'entrée passe en `dead` ; un
+//! admin peut alors la rejouer. La livraison est « au moins une fois » : les
+//! gestionnaires non idempotents s'appuient sur `dedup_key` ou vérifient
+//! l'effet déjà produit (preuve RGB).
+
+use chrono::Utc;
+use sqlx::{postgres::PgRow, PgExecutor, PgPool, Row};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::models::outbox::{
+    retry_delay, Delivery, OutboxEvent, OutboxMessage, OutboxQuery, OutboxStatus,
+};
+use crate::services::{dazno::DaznoService, mentoring_completion, rgb::RGBService};
+
+/// Intervalle de scrutation quand aucune entrée n'est due
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+/// Entrées réservées par passage
+const BATCH_SIZE: i64 = 20;
+/// Bail d'une entrée réservée ; au-delà, une autre instance la reprend
+const LEASE_SECONDS: f64 = 300.0;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    #[error("Outbox event not found")]
+    NotFound,
+    #[error("Outbox event is {0} and cannot be replayed")]
+    NotReplayable(OutboxStatus),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Écriture ─────────────────────────────────────────────────────────────────
+
+/// Inscrit un effet de bord, dans la transaction de l'appelant le cas échéant.
+/// Retourne `false` si une entrée de même `dedup_key` existe déjà.
+pub async fn enqueue<'e, E: PgExecutor<'e>>(
+    executor: E,
+    message: &OutboxMessage,
+    dedup_key: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        "INSERT INTO outbox_events (kind, payload, dedup_key) VALUES ($1, $2, $3)
+         ON CONFLICT (dedup_key) DO NOTHING",
+    )
+    .bind(message.kind())
+    .bind(message.payload())
+    .bind(dedup_key)
+    .execute(executor)
+    .await?
+    .rows_affected();
+    Ok(inserted > 0)
+}
+
+// ── Répartition ──────────────────────────────────────────────────────────────
+
+/// Livre les entrées de l'outbox à leurs gestionnaires
+#[derive(Clone)]
+pub struct Dispatcher {
+    pool: PgPool,
+    rgb: RGBService,
+    dazno: DaznoService,
+    http: reqwest::Client,
+    /// Relais HTTP d'envoi des e-mails ; sans relais, les e-mails sont ignorés
+    email_relay_url: Option<String>,
+}
+
+impl Dispatcher {
+    pub fn new(pool: PgPool, rgb: RGBService, dazno: DaznoService) -> Self {
+        Dispatcher {
+            pool,
+            rgb,
+            dazno,
+            http: reqwest::Client::new(),
+            email_relay_url: std::env::var("EMAIL_RELAY_URL")
+                .ok()
+                .filter(|u| !u.is_empty()),
+        }
+    }
+
+    /// Lance la boucle de répartition de cette instance
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            info!("Outbox dispatcher started");
+            loop {
+                match self.dispatch_due().await {
+                    Ok(n) if n as i64 >= BATCH_SIZE => continue,
+                    Ok(_) => {}
+                    Err(e) => error!("Outbox dispatch failed: {}", e),
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        })
+    }
+
+    /// Réserve et livre un lot d'entrées dues ; retourne leur nombre
+    pub async fn dispatch_due(&self) -> Result<usize, sqlx::Error> {
+        let claimed = sqlx::query(
+            r#"
+            UPDATE outbox_events SET locked_until = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM outbox_events
+                WHERE status = 'pending' AND next_attempt_at <= NOW()
+                  AND (locked_until IS NULL OR locked_until < NOW())
+                ORDER BY next_attempt_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, kind, payload, attempts, max_attempts
+            "#,
+        )
+        .bind(BATCH_SIZE)
+        .bind(LEASE_SECONDS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = claimed.len();
+        futures::future::join_all(claimed.iter().map(|row| self.process(row))).await;
+        Ok(count)
+    }
+
+    async fn process(&self, row: &PgRow) {
+        let id: String = row.try_get("id").unwrap_or_default();
+        let kind: String = row.try_get("kind").unwrap_or_default();
+        let payload: serde_json::Value = row.try_get("payload").unwrap_or_default();
+        let attempts: i32 = row.try_get::<i32, _>("attempts").unwrap_or(0) + 1;
+        let max_attempts: i32 = row.try_get("max_attempts").unwrap_or(1);
+
+        let outcome = match OutboxMessage::from_parts(&kind, payload) {
+            Ok(message) => self.deliver(message).await,
+            // Illisible : aucune relance n'y changera rien
+            Err(e) => {
+                self.record_failure(&id, attempts, attempts, &e).await;
+                return;
+            }
+        };
+
+        match outcome {
+            Ok(delivery) => {
+                let (status, result) = match delivery {
+                    Delivery::Delivered(r) => (OutboxStatus::Delivered, r),
+                    Delivery::Skipped(r) => (OutboxStatus::Skipped, r),
+                };
+                if let Err(e) = sqlx::query(
+                    "UPDATE outbox_events SET status = $2, result = $3, attempts = $4,
+                        last_error = NULL, locked_until = NULL, delivered_at = NOW()
+                     WHERE id = $1",
+                )
+                .bind(&id)
+                .bind(status.to_string())
+                .bind(&result)
+                .bind(attempts)
+                .execute(&self.pool)
+                .await
+                {
+                    error!("Failed to record outbox delivery {}: {}", id, e);
+                }
+            }
+            Err(e) => self.record_failure(&id, attempts, max_attempts, &e).await,
+        }
+    }
+
+    /// Planifie la tentative suivante, ou abandonne l'entrée après
+    /// `max_attempts` échecs
+    async fn record_failure(&self, id: &str, attempts: i32, max_attempts: i32, reason: &str) {
+        let dead = attempts >= max_attempts;
+        if dead {
+            error!(
+                "Outbox event {} dead after {} attempt(s): {}",
+                id, attempts, reason
+            );
+        } else {
+            warn!(
+                "Outbox event {} failed (attempt {}/{}): {}",
+                id, attempts, max_attempts, reason
+            );
+        }
+        let status = if dead {
+            OutboxStatus::Dead
+        } else {
+            OutboxStatus::Pending
+        };
+        if let Err(e) = sqlx::query(
+            "UPDATE outbox_events SET status = $2, attempts = $3, last_error = $4,
+                next_attempt_at = $5, locked_until = NULL
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(status.to_string())
+        .bind(attempts)
+        .bind(reason)
+        .bind(Utc::now() + retry_delay(attempts))
+        .execute(&self.pool)
+        .await
+        {
+            error!("Failed to record outbox failure {}: {}", id, e);
+        }
+    }
+
+    async fn deliver(&self, message: OutboxMessage) -> Result<Delivery, String> {
+        match message {
+            OutboxMessage::Notification {
+                user_id,
+                title,
+                message,
+                notif_type,
+                link,
+                metadata,
+            } => {
+                sqlx::query(
+                    "INSERT INTO notifications (user_id, title, message, type, link, metadata)
+                     VALUES ($1, $2, $3, $4, $5, $6)",
+                )
+                .bind(&user_id)
+                .bind(&title)
+                .bind(&message)
+                .bind(&notif_type)
+                .bind(&link)
+                .bind(&metadata)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+                Ok(Delivery::Delivered(format!(
+                    "notification sent to {}",
+                    user_id
+                )))
+            }
+            OutboxMessage::Email {
+                user_id,
+                subject,
+                body,
+            } => self.send_email(&user_id, &subject, &body).await,
+            OutboxMessage::DaznoSync {
+                user_id,
+                points,
+                action,
+            } => {
+                self.dazno
+                    .sync_gamification(&user_id, points, &action)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(Delivery::Delivered(format!(
+                    "{} point(s) synced for {}",
+                    points, user_id
+                )))
+            }
+            OutboxMessage::RgbProof {
+                booking_id,
+                mentor_id,
+                mentee_id,
+                rating,
+                comment,
+            } => {
+                let existing: Option<String> = sqlx::query_scalar(
+                    "SELECT rgb_contract_id FROM mentoring_proofs
+                     WHERE request_id = $1 AND revoked_at IS NULL LIMIT 1",
+                )
+                .bind(&booking_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| e.to_string())?;
+                if let Some(contract_id) = existing {
+                    return Ok(Delivery::Skipped(format!(
+                        "proof already issued: {}",
+                        contract_id
+                    )));
+                }
+                let (contract_id, _) = mentoring_completion::issue_proof(
+                    &self.pool,
+                    &self.rgb,
+                    &booking_id,
+                    &mentor_id,
+                    &mentee_id,
+                    rating,
+                    comment,
+                )
+                .await?;
+                Ok(Delivery::Delivered(contract_id))
+            }
+        }
+    }
+
+    async fn send_email(
+        &self,
+        user_id: &str,
+        subject: &str,
+        body: &str,
+    ) -> Result<Delivery, String> {
+        let Some(relay) = &self.email_relay_url else {
+            return Ok(Delivery::Skipped("email relay not configured".to_string()));
+        };
+        let email: Option<String> = sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|e: &String| !e.is_empty());
+        let Some(email) = email else {
+            return Ok(Delivery::Skipped(format!(
+                "no email address for {}",
+                user_id
+            )));
+        };
+
+        let response = self
+            .http
+            .post(relay)
+            .json(&serde_json::json!({ "to": email, "subject": subject, "body": body }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("email relay responded {}", response.status()));
+        }
+        Ok(Delivery::Delivered(format!("email sent to {}", user_id)))
+    }
+}
+
+// ── Administration ───────────────────────────────────────────────────────────
+
+fn event_from_row(row: &PgRow) -> OutboxEvent {
+    OutboxEvent {
+        id: row.try_get("id").unwrap_or_default(),
+        kind: row.try_get("kind").unwrap_or_default(),
+        payload: row.try_get("payload").unwrap_or_default(),
+        dedup_key: row.try_get("dedup_key").ok().flatten(),
+        status: row
+            .try_get::<String, _>("status")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(OutboxStatus::Pending),
+        attempts: row.try_get("attempts").unwrap_or(0),
+        max_attempts: row.try_get("max_attempts").unwrap_or(0),
+        next_attempt_at: row
+            .try_get("next_attempt_at")
+            .unwrap_or_else(|_| Utc::now()),
+        last_error: row.try_get("last_error").ok().flatten(),
+        result: row.try_get("result").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        delivered_at: row.try_get("delivered_at").ok().flatten(),
+    }
+}
+
+/// Entrées filtrées par statut et type, les plus récentes d'abord
+pub async fn list(pool: &PgPool, query: &OutboxQuery) -> Result<Vec<OutboxEvent>, sqlx::Error> {
+    Ok(sqlx::query(
+        "SELECT * FROM outbox_events
+         WHERE ($1::TEXT IS NULL OR status = $1) AND ($2::TEXT IS NULL OR kind = $2)
+         ORDER BY created_at DESC LIMIT $3",
+    )
+    .bind(&query.status)
+    .bind(&query.kind)
+    .bind(query.limit.unwrap_or(50).clamp(1, 500))
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(event_from_row)
+    .collect())
+}
+
+/// Remet une entrée abandonnée (ou ignorée) en file, compteur remis à zéro
+pub async fn replay(pool: &PgPool, id: &str) -> Result<OutboxEvent, OutboxError> {
+    let status: String = sqlx::query_scalar("SELECT status FROM outbox_events WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(OutboxError::NotFound)?;
+    let status: OutboxStatus = status.parse().unwrap_or(OutboxStatus::Pending);
+    if !matches!(status, OutboxStatus::Dead | OutboxStatus::Skipped) {
+        return Err(OutboxError::NotReplayable(status));
+    }
+
+    let row = sqlx::query(
+        "UPDATE outbox_events SET status = 'pending', attempts = 0, next_attempt_at = NOW(),
+            locked_until = NULL, delivered_at = NULL
+         WHERE id = $1 AND status IN ('dead', 'skipped')
+         RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(OutboxError::NotReplayable(status))?;
+    info!("Outbox event {} replayed", id);
+    Ok(event_from_row(&row))
+}
+
+/// Remet en file toutes les entrées abandonnées, d'un type donné ou non
+pub async fn replay_dead(pool: &PgPool, kind: Option<&str>) -> Result<u64, sqlx::Error> {
+    let n = sqlx::query(
+        "UPDATE outbox_events SET status = 'pending', attempts = 0, next_attempt_at = NOW(),
+            locked_until = NULL
+         WHERE status = 'dead' AND ($1::TEXT IS NULL OR kind = $1)",
+    )
+    .bind(kind)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    info!("{} dead outbox event(s) replayed", n);
+    Ok(n)
+}
+
+/// Purge les entrées livrées ou ignorées plus anciennes que `days` jours ;
+/// les entrées abandonnées sont conservées pour rejeu.
+pub async fn purge(pool: &PgPool, days: i32) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query(
+        "DELETE FROM outbox_events
+         WHERE status IN ('delivered', 'skipped') AND created_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected())
+}
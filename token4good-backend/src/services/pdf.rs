@@ -0,0 +1,320 @@
+//! Générateur PDF minimal, sans dépendance
+//!
+//! Suffisant pour des documents simples rendus côté serveur (relevés,
+//! attestations) : pages A4, texte Helvetica / Helvetica-Bold en
+//! WinAnsiEncoding (accents français), traits, rectangles pleins ou en
+//! contour.
+//! Coordonnées en points, origine en bas à gauche.
+
+use std::fmt::Write;
+
+pub const A4_WIDTH: f32 = 595.0;
+pub const A4_HEIGHT: f32 = 842.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Document PDF en cours de construction.
+#[derive(Debug, Default)]
+pub struct PdfDocument {
+    pages: Vec<String>,
+    title: String,
+}
+
+impl PdfDocument {
+    pub fn new(title: &str) -> Self {
+        Self {
+            pages: vec![String::new()],
+            title: title.to_string(),
+        }
+    }
+
+    /// Démarre une nouvelle page ; les appels suivants y dessinent.
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    fn current(&mut self) -> &mut String {
+        if self.pages.is_empty() {
+            self.pages.push(String::new());
+        }
+        self.pages.last_mut().expect("au moins une page")
+    }
+
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let escaped = escape_text(text);
+        let _ = writeln!(
+            self.current(),
+            "BT /{} {:.1} Tf {:.2} {:.2} Td ({}) Tj ET",
+            font.resource(),
+            size,
+            x,
+            y,
+            escaped
+        );
+    }
+
+    /// Texte aligné à droite sur `right_x` (largeur estimée).
+    pub fn text_right(&mut self, right_x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let x = right_x - text_width(text, size);
+        self.text(x, y, size, font, text);
+    }
+
+    /// Texte centré sur `center_x` (largeur estimée).
+    pub fn text_centered(&mut self, center_x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let x = center_x - text_width(text, size) / 2.0;
+        self.text(x, y, size, font, text);
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        let _ = writeln!(
+            self.current(),
+            "{:.2} w {:.2} {:.2} m {:.2} {:.2} l S",
+            width,
+            x1,
+            y1,
+            x2,
+            y2
+        );
+    }
+
+    /// Rectangle plein, couleur RGB en composantes 0.0–1.0.
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, rgb: (f32, f32, f32)) {
+        let _ = writeln!(
+            self.current(),
+            "q {:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f Q",
+            rgb.0,
+            rgb.1,
+            rgb.2,
+            x,
+            y,
+            w,
+            h
+        );
+    }
+
+    /// Texte en couleur RGB (composantes 0.0–1.0).
+    pub fn text_colored(
+        &mut self,
+        x: f32,
+        y: f32,
+        size: f32,
+        font: Font,
+        text: &str,
+        rgb: (f32, f32, f32),
+    ) {
+        let _ = writeln!(
+            self.current(),
+            "q {:.3} {:.3} {:.3} rg",
+            rgb.0,
+            rgb.1,
+            rgb.2
+        );
+        self.text(x, y, size, font, text);
+        let _ = writeln!(self.current(), "Q");
+    }
+
+    /// Contour de rectangle, couleur RGB en composantes 0.0–1.0.
+    pub fn stroke_rect(
+        &mut self,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        width: f32,
+        rgb: (f32, f32, f32),
+    ) {
+        let _ = writeln!(
+            self.current(),
+            "q {:.3} {:.3} {:.3} RG {:.2} w {:.2} {:.2} {:.2} {:.2} re S Q",
+            rgb.0,
+            rgb.1,
+            rgb.2,
+            width,
+            x,
+            y,
+            w,
+            h
+        );
+    }
+
+    /// Sérialise le document (PDF 1.4).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut objects: Vec<Vec<u8>> = Vec::new();
+        let n_pages = self.pages.len().max(1);
+
+        // 1: catalogue, 2: arbre des pages, 3-4: polices, 5: infos,
+        // puis pour chaque page : objet page + flux de contenu
+        let first_page_obj = 6;
+        let kids: Vec<String> = (0..n_pages)
+            .map(|i| format!("{} 0 R", first_page_obj + i * 2))
+            .collect();
+
+        objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+        objects.push(
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                n_pages
+            )
+            .into_bytes(),
+        );
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+        objects.push(
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>"
+                .to_vec(),
+        );
+        let mut info = b"<< /Producer (Token4Good) /Title (".to_vec();
+        info.extend(encode_latin1(&escape_text(&self.title)));
+        info.extend(b") >>");
+        objects.push(info);
+
+        let empty = String::new();
+        for i in 0..n_pages {
+            let content = self.pages.get(i).unwrap_or(&empty);
+            let content_obj = first_page_obj + i * 2 + 1;
+            objects.push(
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                     /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                    A4_WIDTH, A4_HEIGHT, content_obj
+                )
+                .into_bytes(),
+            );
+            let stream = encode_latin1(content);
+            let mut obj = format!("<< /Length {} >>\nstream\n", stream.len()).into_bytes();
+            obj.extend(stream);
+            obj.extend(b"\nendstream");
+            objects.push(obj);
+        }
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (i, obj) in objects.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).into_bytes());
+            out.extend(obj);
+            out.extend(b"\nendobj\n");
+        }
+
+        let xref_offset = out.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for off in offsets {
+            let _ = writeln!(xref, "{:010} 00000 n ", off);
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        );
+        out.extend(xref.into_bytes());
+        out
+    }
+}
+
+/// Largeur approximative d'un texte Helvetica (0.5 em par caractère).
+pub fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().count() as f32 * size * 0.5
+}
+
+/// Tronque un texte à `max` caractères avec une ellipse.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut s: String = text.chars().take(max.saturating_sub(3)).collect();
+    s.push_str("...");
+    s
+}
+
+fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' | '\r' | '\t' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Encode en Latin-1 (compatible WinAnsi pour les caractères usuels) ;
+/// les caractères hors plage sont remplacés par `?`.
+fn encode_latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '’' => b'\'',
+            '–' | '—' => b'-',
+            '€' => 0x80,
+            c if (c as u32) < 256 => c as u32 as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_structure() {
+        let mut doc = PdfDocument::new("Relevé");
+        doc.text(50.0, 800.0, 12.0, Font::Bold, "Relevé de compte (T4G)");
+        doc.new_page();
+        doc.text(50.0, 800.0, 10.0, Font::Regular, "Page 2");
+
+        let bytes = doc.to_bytes();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(bytes.starts_with(b"%PDF-1.4"));
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("startxref"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        // Parenthèses échappées, accents en Latin-1
+        assert!(text.contains("Relev\u{FFFD} de compte \\(T4G\\)"));
+    }
+
+    #[test]
+    fn test_xref_offsets_point_to_objects() {
+        let doc = PdfDocument::new("x");
+        let bytes = doc.to_bytes();
+        let xref = bytes.windows(5).position(|w| w == b"xref\n").unwrap();
+        let entries = String::from_utf8(bytes[xref..].to_vec()).unwrap();
+        let first_entry = entries.lines().nth(3).unwrap();
+        let offset: usize = first_entry[..10].parse().unwrap();
+        assert!(bytes[offset..].starts_with(b"1 0 obj"));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("court", 10), "court");
+        assert_eq!(truncate("une description trop longue", 10), "une des...");
+    }
+}
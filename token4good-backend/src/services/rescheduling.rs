@@ -0,0 +1,459 @@
+//! Report des réservations confirmées
+//!
+//! Une partie (mentor ou mentee) propose un nouvel horaire pris dans les
+//! disponibilités de l'offre ; l'autre accepte, refuse ou contre-propose.
+//! Une nouvelle proposition remplace celle en attente : `countered` si elle
+//! vient de l'autre partie, `withdrawn` sinon. L'acceptation revérifie le
+//! créneau sous le verrou d'agenda du mentor avant de déplacer la réservation.
+//! Un report hors de l'absence du mentor lève le signalement de conflit.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::info;
+
+use crate::models::mentoring_offer::BookingReschedule;
+use crate::models::time_slot::{
+    offered_seats, parse_availability, seats_taken, SlotWindow, TimeSlot,
+};
+use crate::services::availability;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RescheduleError {
+    #[error("Booking or proposal not found")]
+    NotFound,
+    #[error("Not a participant of this booking")]
+    Forbidden,
+    #[error("Booking cannot be rescheduled in its current state")]
+    InvalidState,
+    #[error("Proposed time is not offered")]
+    SlotNotOffered,
+    #[error("Proposed time is no longer available")]
+    SlotTaken,
+    #[error("Proposal is no longer valid")]
+    Expired,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Réservation et offre nécessaires aux vérifications de report
+#[derive(Debug, Clone)]
+pub struct BookingContext {
+    pub booking_id: String,
+    pub offer_id: String,
+    pub mentor_id: String,
+    pub mentee_id: String,
+    pub topic_slug: String,
+    pub status: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub duration_minutes: i32,
+    pub capacity: i32,
+    pub slots: Vec<TimeSlot>,
+}
+
+impl BookingContext {
+    /// Autre partie de la réservation, ou `Forbidden` si `user_id` n'en est pas.
+    pub fn counterpart(&self, user_id: &str) -> Result<&str, RescheduleError> {
+        if user_id == self.mentor_id {
+            Ok(&self.mentee_id)
+        } else if user_id == self.mentee_id {
+            Ok(&self.mentor_id)
+        } else {
+            Err(RescheduleError::Forbidden)
+        }
+    }
+}
+
+/// Vérifications sans base : réservation confirmée et à venir, nouvel horaire
+/// futur, différent et proposé par l'offre. Retourne le nombre de places du
+/// créneau.
+pub fn check_proposal(
+    ctx: &BookingContext,
+    proposed: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<i32, RescheduleError> {
+    if ctx.status != "confirmed" || ctx.scheduled_at <= now {
+        return Err(RescheduleError::InvalidState);
+    }
+    if proposed <= now || proposed == ctx.scheduled_at {
+        return Err(RescheduleError::SlotNotOffered);
+    }
+    offered_seats(&ctx.slots, ctx.duration_minutes, ctx.capacity, proposed)
+        .ok_or(RescheduleError::SlotNotOffered)
+}
+
+fn reschedule_from_row(r: &sqlx::postgres::PgRow) -> BookingReschedule {
+    BookingReschedule {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        proposed_by: r.try_get("proposed_by").unwrap_or_default(),
+        previous_scheduled_at: r
+            .try_get("previous_scheduled_at")
+            .unwrap_or_else(|_| Utc::now()),
+        proposed_scheduled_at: r
+            .try_get("proposed_scheduled_at")
+            .unwrap_or_else(|_| Utc::now()),
+        message: r.try_get("message").ok().flatten(),
+        status: r.try_get("status").unwrap_or_default(),
+        counter_of: r.try_get("counter_of").ok().flatten(),
+        responded_by: r.try_get("responded_by").ok().flatten(),
+        responded_at: r.try_get("responded_at").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+async fn load_context(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    for_update: bool,
+) -> Result<BookingContext, RescheduleError> {
+    let sql = format!(
+        r#"
+        SELECT b.id, b.offer_id, b.mentee_id, b.status, b.scheduled_at,
+               o.mentor_id, o.topic_slug, o.duration_minutes, o.capacity, o.availability
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        {}
+        "#,
+        if for_update { "FOR UPDATE OF b" } else { "" }
+    );
+    let row = sqlx::query(&sql)
+        .bind(booking_id)
+        .fetch_optional(conn)
+        .await?
+        .ok_or(RescheduleError::NotFound)?;
+
+    Ok(BookingContext {
+        booking_id: row.try_get("id").unwrap_or_default(),
+        offer_id: row.try_get("offer_id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        status: row.try_get("status").unwrap_or_default(),
+        scheduled_at: row.try_get("scheduled_at").unwrap_or_else(|_| Utc::now()),
+        duration_minutes: row.try_get("duration_minutes").unwrap_or(60),
+        capacity: row.try_get("capacity").unwrap_or(1),
+        slots: parse_availability(row.try_get("availability").unwrap_or_default()),
+    })
+}
+
+/// Le créneau est libre dans l'agenda du mentor (hors cette réservation) et
+/// le mentee n'y occupe pas déjà une place.
+async fn ensure_slot_free(
+    conn: &mut PgConnection,
+    ctx: &BookingContext,
+    proposed: DateTime<Utc>,
+    seats: i32,
+) -> Result<(), RescheduleError> {
+    let window = SlotWindow::new(proposed, ctx.duration_minutes);
+    let occupancy = availability::mentor_occupancy(
+        &mut *conn,
+        &ctx.mentor_id,
+        window.start,
+        window.end,
+        Some(&ctx.booking_id),
+    )
+    .await?;
+    seats_taken(&ctx.offer_id, &window, &occupancy)
+        .filter(|taken| *taken < seats)
+        .ok_or(RescheduleError::SlotTaken)?;
+
+    let already_seated: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM mentoring_bookings
+            WHERE offer_id = $1 AND scheduled_at = $2 AND mentee_id = $3
+              AND status = ANY($4) AND id <> $5
+        )
+        "#,
+    )
+    .bind(&ctx.offer_id)
+    .bind(proposed)
+    .bind(&ctx.mentee_id)
+    .bind(&availability::BLOCKING_STATUSES[..])
+    .bind(&ctx.booking_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if already_seated {
+        return Err(RescheduleError::SlotTaken);
+    }
+    Ok(())
+}
+
+/// Propose un nouvel horaire, dans la transaction de l'appelant. Retourne la
+/// proposition et le contexte de la réservation (pour notifier l'autre partie).
+pub async fn propose(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    user_id: &str,
+    proposed: DateTime<Utc>,
+    message: Option<&str>,
+) -> Result<(BookingReschedule, BookingContext), RescheduleError> {
+    let ctx = load_context(&mut *conn, booking_id, true).await?;
+    ctx.counterpart(user_id)?;
+    let seats = check_proposal(&ctx, proposed, Utc::now())?;
+    ensure_slot_free(&mut *conn, &ctx, proposed, seats).await?;
+
+    // La proposition en attente est remplacée
+    let previous = sqlx::query(
+        r#"
+        UPDATE mentoring_booking_reschedules
+        SET status = CASE WHEN proposed_by = $2 THEN 'withdrawn' ELSE 'countered' END,
+            responded_by = $2, responded_at = NOW()
+        WHERE booking_id = $1 AND status = 'pending'
+        RETURNING id, status
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+    let counter_of: Option<String> = previous
+        .filter(|r| r.try_get::<String, _>("status").ok().as_deref() == Some("countered"))
+        .and_then(|r| r.try_get("id").ok());
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_booking_reschedules
+            (booking_id, proposed_by, previous_scheduled_at, proposed_scheduled_at,
+             message, counter_of)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(ctx.scheduled_at)
+    .bind(proposed)
+    .bind(message)
+    .bind(&counter_of)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let proposal = reschedule_from_row(&row);
+    info!(
+        "Reschedule {} proposed by {} for booking {}: {} → {}",
+        proposal.id, user_id, booking_id, ctx.scheduled_at, proposed
+    );
+    Ok((proposal, ctx))
+}
+
+/// Marque la proposition comme expirée (réservation modifiée entre-temps).
+async fn expire(conn: &mut PgConnection, proposal_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE mentoring_booking_reschedules SET status = 'expired', responded_at = NOW()
+         WHERE id = $1 AND status = 'pending'",
+    )
+    .bind(proposal_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Répond à une proposition en attente, dans la transaction de l'appelant.
+/// L'autre partie accepte ou refuse ; l'auteur peut seulement la retirer
+/// (`accept = false`). Sur `Expired`, la proposition est marquée expirée :
+/// l'appelant valide la transaction pour le consigner.
+pub async fn respond(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    proposal_id: &str,
+    user_id: &str,
+    accept: bool,
+) -> Result<(BookingReschedule, BookingContext), RescheduleError> {
+    // Mentor connu avant tout verrou de ligne, pour respecter l'ordre
+    // verrou d'agenda → réservation utilisé à la création
+    let mentor_id: String = sqlx::query_scalar(
+        "SELECT o.mentor_id FROM mentoring_bookings b
+         JOIN mentoring_offers o ON o.id = b.offer_id WHERE b.id = $1",
+    )
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(RescheduleError::NotFound)?;
+    availability::lock_mentor_schedule(&mut *conn, &mentor_id).await?;
+
+    let ctx = load_context(&mut *conn, booking_id, true).await?;
+    ctx.counterpart(user_id)?;
+
+    let proposal = sqlx::query(
+        "SELECT * FROM mentoring_booking_reschedules
+         WHERE id = $1 AND booking_id = $2 FOR UPDATE",
+    )
+    .bind(proposal_id)
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .as_ref()
+    .map(reschedule_from_row)
+    .ok_or(RescheduleError::NotFound)?;
+    if proposal.status != "pending" {
+        return Err(RescheduleError::InvalidState);
+    }
+
+    let by_author = proposal.proposed_by == user_id;
+    if accept && by_author {
+        return Err(RescheduleError::Forbidden);
+    }
+
+    if !accept {
+        let status = if by_author { "withdrawn" } else { "declined" };
+        let row = sqlx::query(
+            "UPDATE mentoring_booking_reschedules
+             SET status = $2, responded_by = $3, responded_at = NOW()
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(proposal_id)
+        .bind(status)
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        info!("Reschedule {} {} by {}", proposal_id, status, user_id);
+        return Ok((reschedule_from_row(&row), ctx));
+    }
+
+    // La réservation doit être telle qu'au moment de la proposition
+    let proposed = proposal.proposed_scheduled_at;
+    let still_valid = ctx.scheduled_at == proposal.previous_scheduled_at;
+    let seats = match check_proposal(&ctx, proposed, Utc::now()) {
+        Ok(seats) if still_valid => seats,
+        Ok(_) | Err(RescheduleError::InvalidState) | Err(RescheduleError::SlotNotOffered) => {
+            expire(conn, proposal_id).await?;
+            return Err(RescheduleError::Expired);
+        }
+        Err(e) => return Err(e),
+    };
+    ensure_slot_free(&mut *conn, &ctx, proposed, seats).await?;
+
+    sqlx::query(
+        "UPDATE mentoring_bookings SET scheduled_at = $2, time_off_id = NULL, updated_at = NOW()
+         WHERE id = $1",
+    )
+    .bind(booking_id)
+    .bind(proposed)
+    .execute(&mut *conn)
+    .await?;
+
+    let row = sqlx::query(
+        "UPDATE mentoring_booking_reschedules
+         SET status = 'accepted', responded_by = $2, responded_at = NOW()
+         WHERE id = $1 RETURNING *",
+    )
+    .bind(proposal_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    info!(
+        "Booking {} rescheduled from {} to {} (proposal {})",
+        booking_id, ctx.scheduled_at, proposed, proposal_id
+    );
+    Ok((reschedule_from_row(&row), ctx))
+}
+
+/// Historique des propositions de report d'une réservation (plus ancienne d'abord).
+pub async fn history(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<Vec<BookingReschedule>, RescheduleError> {
+    let mut conn = pool.acquire().await?;
+    let ctx = load_context(&mut conn, booking_id, false).await?;
+    ctx.counterpart(user_id)?;
+
+    let rows = sqlx::query(
+        "SELECT * FROM mentoring_booking_reschedules WHERE booking_id = $1 ORDER BY created_at, id",
+    )
+    .bind(booking_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    Ok(rows.iter().map(reschedule_from_row).collect())
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn ctx(slots: serde_json::Value) -> BookingContext {
+        BookingContext {
+            booking_id: "b1".to_string(),
+            offer_id: "o1".to_string(),
+            mentor_id: "mentor".to_string(),
+            mentee_id: "mentee".to_string(),
+            topic_slug: "rust".to_string(),
+            status: "confirmed".to_string(),
+            scheduled_at: utc("2026-06-10T09:00:00Z"),
+            duration_minutes: 60,
+            capacity: 1,
+            slots: parse_availability(slots),
+        }
+    }
+
+    #[test]
+    fn test_counterpart() {
+        let c = ctx(serde_json::json!([]));
+        assert_eq!(c.counterpart("mentor").unwrap(), "mentee");
+        assert_eq!(c.counterpart("mentee").unwrap(), "mentor");
+        assert!(matches!(
+            c.counterpart("someone"),
+            Err(RescheduleError::Forbidden)
+        ));
+    }
+
+    #[test]
+    fn test_check_proposal_requires_offered_future_slot() {
+        let now = utc("2026-06-01T00:00:00Z");
+        let c = ctx(serde_json::json!([
+            { "date": "2026-06-12T14:00:00+02:00", "duration_minutes": 120 }
+        ]));
+
+        assert_eq!(
+            check_proposal(&c, utc("2026-06-12T13:00:00Z"), now).unwrap(),
+            1
+        );
+        // Hors disponibilités, identique à l'horaire actuel, ou passé
+        assert!(matches!(
+            check_proposal(&c, utc("2026-06-12T15:30:00Z"), now),
+            Err(RescheduleError::SlotNotOffered)
+        ));
+        assert!(matches!(
+            check_proposal(&c, c.scheduled_at, now),
+            Err(RescheduleError::SlotNotOffered)
+        ));
+        assert!(matches!(
+            check_proposal(&c, utc("2026-05-30T12:00:00Z"), now),
+            Err(RescheduleError::SlotNotOffered)
+        ));
+    }
+
+    #[test]
+    fn test_check_proposal_requires_upcoming_confirmed_booking() {
+        let now = utc("2026-06-01T00:00:00Z");
+        let pending = BookingContext {
+            status: "pending".to_string(),
+            ..ctx(serde_json::json!([]))
+        };
+        assert!(matches!(
+            check_proposal(&pending, utc("2026-06-20T10:00:00Z"), now),
+            Err(RescheduleError::InvalidState)
+        ));
+
+        let started = ctx(serde_json::json!([]));
+        assert!(matches!(
+            check_proposal(
+                &started,
+                utc("2026-06-20T10:00:00Z"),
+                utc("2026-06-10T09:30:00Z")
+            ),
+            Err(RescheduleError::InvalidState)
+        ));
+    }
+}
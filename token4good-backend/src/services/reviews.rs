@@ -0,0 +1,359 @@
+//! Avis sur les mentors : listes, agrégats, réponses et modération
+//!
+//! Un avis est la note (`mentee_rating`) et le commentaire laissés par le
+//! mentee sur une session complétée. Les agrégats utilisent une moyenne
+//! bayésienne tirée vers la moyenne de la plateforme, pour qu'un mentor avec
+//! un seul avis ne dépasse pas un mentor régulier. Un avis masqué par la
+//! modération sort des listes publiques et des agrégats.
+
+use sqlx::{PgConnection, PgExecutor, PgPool, Row};
+use tracing::info;
+
+use crate::models::review::{
+    ModerateReviewPayload, ModerationAction, ModerationItem, RatingSummary, ReportReviewPayload,
+    Review, ReviewReplyPayload, ReviewReport, ReviewsPage, DEFAULT_PRIOR_MEAN,
+};
+
+/// Condition SQL d'un avis publié, sur l'alias de réservation `b`
+pub const VISIBLE_REVIEW: &str = "b.mentee_rating IS NOT NULL \
+     AND b.review_hidden_at IS NULL \
+     AND b.status IN ('completed', 'auto_completed')";
+
+/// Sous-requête des agrégats par mentor (`mentor_id`, `review_count`, `rating_sum`)
+pub fn mentor_ratings_subquery() -> String {
+    format!(
+        r#"(
+            SELECT ro.mentor_id, COUNT(*) AS review_count, SUM(b.mentee_rating) AS rating_sum
+            FROM mentoring_bookings b
+            JOIN mentoring_offers ro ON ro.id = b.offer_id
+            WHERE {}
+            GROUP BY ro.mentor_id
+        )"#,
+        VISIBLE_REVIEW
+    )
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReviewError {
+    #[error("Review not found")]
+    NotFound,
+    #[error("Not allowed to act on this review")]
+    Forbidden,
+    #[error("Review already answered or reported")]
+    Conflict,
+    #[error("Invalid review request: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Filtre des listes d'avis
+#[derive(Debug, Default)]
+pub struct ReviewQuery<'a> {
+    pub mentor_id: Option<&'a str>,
+    pub topic_slug: Option<&'a str>,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+const REVIEW_SELECT: &str = r#"
+    SELECT b.id AS booking_id, o.mentor_id, b.mentee_id, u.firstname AS mentee_firstname,
+           o.topic_slug, b.mentee_rating, b.mentee_comment, b.mentee_rated_at,
+           b.mentor_reply, b.mentor_replied_at, b.review_hidden_at
+    FROM mentoring_bookings b
+    JOIN mentoring_offers o ON o.id = b.offer_id
+    LEFT JOIN users u ON u.id = b.mentee_id
+"#;
+
+fn review_from_row(r: &sqlx::postgres::PgRow) -> Review {
+    Review {
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        mentor_id: r.try_get("mentor_id").unwrap_or_default(),
+        mentee_id: r.try_get("mentee_id").unwrap_or_default(),
+        mentee_firstname: r.try_get("mentee_firstname").ok().flatten(),
+        topic_slug: r.try_get("topic_slug").unwrap_or_default(),
+        rating: r.try_get("mentee_rating").unwrap_or(0),
+        comment: r.try_get("mentee_comment").ok().flatten(),
+        rated_at: r.try_get("mentee_rated_at").ok().flatten(),
+        mentor_reply: r.try_get("mentor_reply").ok().flatten(),
+        mentor_replied_at: r.try_get("mentor_replied_at").ok().flatten(),
+    }
+}
+
+fn report_from_row(r: &sqlx::postgres::PgRow) -> ReviewReport {
+    ReviewReport {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        reporter_id: r.try_get("reporter_id").unwrap_or_default(),
+        reason: r.try_get("reason").unwrap_or_default(),
+        status: r.try_get("status").unwrap_or_default(),
+        resolved_by: r.try_get("resolved_by").ok().flatten(),
+        resolution_note: r.try_get("resolution_note").ok().flatten(),
+        resolved_at: r.try_get("resolved_at").ok().flatten(),
+        created_at: r
+            .try_get("created_at")
+            .unwrap_or_else(|_| chrono::Utc::now()),
+    }
+}
+
+/// Moyenne des avis publiés sur toute la plateforme, a priori des agrégats
+pub async fn prior_mean(pool: &PgPool) -> Result<f64, sqlx::Error> {
+    let mean: Option<f64> = sqlx::query_scalar(&format!(
+        "SELECT AVG(b.mentee_rating)::FLOAT8 FROM mentoring_bookings b WHERE {}",
+        VISIBLE_REVIEW
+    ))
+    .fetch_one(pool)
+    .await?;
+    Ok(mean.unwrap_or(DEFAULT_PRIOR_MEAN))
+}
+
+/// Agrégats des avis publiés, filtrés par mentor et/ou sujet
+pub async fn summary(
+    pool: &PgPool,
+    mentor_id: Option<&str>,
+    topic_slug: Option<&str>,
+) -> Result<RatingSummary, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT b.mentee_rating AS rating, COUNT(*) AS n
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE {}
+          AND ($1::VARCHAR IS NULL OR o.mentor_id = $1)
+          AND ($2::VARCHAR IS NULL OR o.topic_slug = $2)
+        GROUP BY b.mentee_rating
+        "#,
+        VISIBLE_REVIEW
+    ))
+    .bind(mentor_id)
+    .bind(topic_slug)
+    .fetch_all(pool)
+    .await?;
+
+    let mut histogram = [0i64; 5];
+    for row in rows {
+        let rating: i32 = row.try_get("rating").unwrap_or(0);
+        if (1..=5).contains(&rating) {
+            histogram[rating as usize - 1] = row.try_get("n").unwrap_or(0);
+        }
+    }
+    Ok(RatingSummary::from_histogram(
+        histogram,
+        prior_mean(pool).await?,
+    ))
+}
+
+/// Avis publiés, les plus récents d'abord, avec leurs agrégats
+pub async fn list(pool: &PgPool, query: &ReviewQuery<'_>) -> Result<ReviewsPage, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        r#"
+        {}
+        WHERE {}
+          AND ($1::VARCHAR IS NULL OR o.mentor_id = $1)
+          AND ($2::VARCHAR IS NULL OR o.topic_slug = $2)
+        ORDER BY b.mentee_rated_at DESC NULLS LAST, b.updated_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        REVIEW_SELECT, VISIBLE_REVIEW
+    ))
+    .bind(query.mentor_id)
+    .bind(query.topic_slug)
+    .bind(query.limit)
+    .bind(query.offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ReviewsPage {
+        summary: summary(pool, query.mentor_id, query.topic_slug).await?,
+        reviews: rows.iter().map(review_from_row).collect(),
+    })
+}
+
+/// Avis publié d'une réservation (les avis masqués ne se répondent ni ne se signalent)
+async fn get_review<'e, E: PgExecutor<'e>>(
+    executor: E,
+    booking_id: &str,
+) -> Result<Review, ReviewError> {
+    let row = sqlx::query(&format!(
+        "{} WHERE b.id = $1 AND {}",
+        REVIEW_SELECT, VISIBLE_REVIEW
+    ))
+    .bind(booking_id)
+    .fetch_optional(executor)
+    .await?
+    .ok_or(ReviewError::NotFound)?;
+    Ok(review_from_row(&row))
+}
+
+/// Réponse publique du mentor, une seule par avis, dans la transaction de
+/// l'appelant
+pub async fn reply(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    mentor_id: &str,
+    payload: &ReviewReplyPayload,
+) -> Result<Review, ReviewError> {
+    payload.validate().map_err(ReviewError::Invalid)?;
+    let review = get_review(&mut *conn, booking_id).await?;
+    if review.mentor_id != mentor_id {
+        return Err(ReviewError::Forbidden);
+    }
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE mentoring_bookings
+        SET mentor_reply = $2, mentor_replied_at = NOW(), updated_at = NOW()
+        WHERE id = $1 AND mentor_reply IS NULL
+        "#,
+    )
+    .bind(booking_id)
+    .bind(payload.reply.trim())
+    .execute(&mut *conn)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(ReviewError::Conflict);
+    }
+
+    info!(
+        "Mentor {} replied to review on booking {}",
+        mentor_id, booking_id
+    );
+    get_review(&mut *conn, booking_id).await
+}
+
+/// Signale un avis abusif ; l'auteur de l'avis ne peut pas se signaler.
+pub async fn report(
+    pool: &PgPool,
+    booking_id: &str,
+    reporter_id: &str,
+    payload: &ReportReviewPayload,
+) -> Result<ReviewReport, ReviewError> {
+    payload.validate().map_err(ReviewError::Invalid)?;
+    let review = get_review(pool, booking_id).await?;
+    if review.mentee_id == reporter_id {
+        return Err(ReviewError::Forbidden);
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_review_reports (booking_id, reporter_id, reason)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(reporter_id)
+    .bind(payload.reason.trim())
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => ReviewError::Conflict,
+        _ => ReviewError::Database(e),
+    })?;
+
+    info!(
+        "Review on booking {} reported by {}",
+        booking_id, reporter_id
+    );
+    Ok(report_from_row(&row))
+}
+
+async fn moderation_item(
+    conn: &mut PgConnection,
+    booking_id: &str,
+) -> Result<ModerationItem, ReviewError> {
+    let row = sqlx::query(&format!(
+        "{} WHERE b.id = $1 AND b.mentee_rating IS NOT NULL",
+        REVIEW_SELECT
+    ))
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(ReviewError::NotFound)?;
+    let hidden = row
+        .try_get::<Option<chrono::DateTime<chrono::Utc>>, _>("review_hidden_at")
+        .ok()
+        .flatten()
+        .is_some();
+
+    let reports = sqlx::query(
+        "SELECT * FROM mentoring_review_reports WHERE booking_id = $1 ORDER BY created_at",
+    )
+    .bind(booking_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(ModerationItem {
+        review: review_from_row(&row),
+        hidden,
+        reports: reports.iter().map(report_from_row).collect(),
+    })
+}
+
+/// File de modération : avis ayant des signalements en attente, plus ancien
+/// signalement d'abord.
+pub async fn moderation_queue(pool: &PgPool) -> Result<Vec<ModerationItem>, ReviewError> {
+    let booking_ids: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT booking_id FROM mentoring_review_reports
+        WHERE status = 'pending'
+        GROUP BY booking_id
+        ORDER BY MIN(created_at)
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut conn = pool.acquire().await?;
+    let mut queue = Vec::with_capacity(booking_ids.len());
+    for booking_id in booking_ids {
+        queue.push(moderation_item(&mut conn, &booking_id).await?);
+    }
+    Ok(queue)
+}
+
+/// Tranche les signalements en attente d'un avis : masquage ou classement,
+/// dans la transaction de l'appelant.
+pub async fn moderate(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    admin_id: &str,
+    payload: &ModerateReviewPayload,
+) -> Result<ModerationItem, ReviewError> {
+    let resolved = sqlx::query(
+        r#"
+        UPDATE mentoring_review_reports
+        SET status = $2, resolved_by = $3, resolution_note = $4, resolved_at = NOW()
+        WHERE booking_id = $1 AND status = 'pending'
+        "#,
+    )
+    .bind(booking_id)
+    .bind(payload.action.report_status())
+    .bind(admin_id)
+    .bind(payload.note.as_deref().map(str::trim))
+    .execute(&mut *conn)
+    .await?;
+    if resolved.rows_affected() == 0 {
+        return Err(ReviewError::NotFound);
+    }
+
+    if payload.action == ModerationAction::Hide {
+        sqlx::query(
+            r#"
+            UPDATE mentoring_bookings
+            SET review_hidden_at = NOW(), review_hidden_by = $2, updated_at = NOW()
+            WHERE id = $1 AND review_hidden_at IS NULL
+            "#,
+        )
+        .bind(booking_id)
+        .bind(admin_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    info!(
+        "Review on booking {} moderated by {}: {:?}",
+        booking_id, admin_id, payload.action
+    );
+    moderation_item(conn, booking_id).await
+}
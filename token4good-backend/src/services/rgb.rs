@@ -72,10 +72,17 @@ struct ProofMetadata {
     rating: u8,
     comment: String,
     timestamp: u64,
+    /// Empreinte des notes partagées de la session (absente des contrats
+    /// antérieurs)
+    #[serde(default)]
+    notes_hash: Option<String>,
 }
 
 impl ProofMetadata {
     /// Hash du contenu des métadonnées = SHA256(tous les champs)
+    ///
+    /// L'empreinte des notes n'est ajoutée que si elle existe : les contrats
+    /// sans notes gardent le même hash.
     fn content_hash(&self) -> [u8; 32] {
         let mut h = Sha256::new();
         h.update(self.mentor_id.as_bytes());
@@ -89,6 +96,10 @@ impl ProofMetadata {
         h.update(self.comment.as_bytes());
         h.update(b":");
         h.update(self.timestamp.to_le_bytes());
+        if let Some(notes_hash) = &self.notes_hash {
+            h.update(b":");
+            h.update(notes_hash.as_bytes());
+        }
         h.finalize().into()
     }
 }
@@ -144,6 +155,9 @@ pub struct ProofDetails {
     pub comment: String,
     pub contract_id: String,
     pub signature: String,
+    /// Empreinte des notes partagées engagée dans la preuve
+    #[serde(default)]
+    pub notes_hash: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -355,6 +369,22 @@ impl RGBService {
         .await
     }
 
+    /// Variante engageant l'empreinte des notes de session dans le contrat.
+    pub async fn create_proof_contract_with_notes(
+        &self,
+        mentor_id: &str,
+        mentee_id: &str,
+        request_id: &str,
+        rating: u8,
+        comment: Option<String>,
+        notes_hash: Option<String>,
+    ) -> Result<(String, String), RGBError> {
+        self.issue_contract(
+            mentor_id, mentee_id, request_id, rating, comment, None, notes_hash,
+        )
+        .await
+    }
+
     /// Variante avec UTXO seal explicite.
     pub async fn create_proof_contract_with_seal(
         &self,
@@ -364,6 +394,23 @@ impl RGBService {
         rating: u8,
         comment: Option<String>,
         utxo_seal: Option<&str>,
+    ) -> Result<(String, String), RGBError> {
+        self.issue_contract(
+            mentor_id, mentee_id, request_id, rating, comment, utxo_seal, None,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn issue_contract(
+        &self,
+        mentor_id: &str,
+        mentee_id: &str,
+        request_id: &str,
+        rating: u8,
+        comment: Option<String>,
+        utxo_seal: Option<&str>,
+        notes_hash: Option<String>,
     ) -> Result<(String, String), RGBError> {
         if mentor_id.is_empty() || mentee_id.is_empty() || request_id.is_empty() {
             return Err(RGBError::ContractCreation(
@@ -384,6 +431,7 @@ impl RGBService {
             rating,
             comment: comment.unwrap_or_default(),
             timestamp,
+            notes_hash,
         };
 
         let content_hash = metadata.content_hash();
@@ -485,6 +533,7 @@ impl RGBService {
                 comment: c.metadata.comment.clone(),
                 contract_id: contract_id.to_string(),
                 signature: c.genesis.issuer_sig.clone(),
+                notes_hash: c.metadata.notes_hash.clone(),
             })
             .ok_or_else(|| RGBError::Storage("Contrat introuvable".to_string()))
     }
@@ -606,6 +655,7 @@ impl RGBService {
                 comment: c.metadata.comment.clone(),
                 contract_id: id.clone(),
                 signature: c.genesis.issuer_sig.clone(),
+                notes_hash: c.metadata.notes_hash.clone(),
             })
             .collect())
     }
@@ -664,6 +714,35 @@ impl RGBService {
     pub fn network(&self) -> &str {
         &self.network
     }
+
+    /// Signe un message hors contrat (ex: tête de chaîne du ledger) avec la clé émetteur.
+    pub fn sign_message(&self, data: &[u8]) -> Result<String, RGBError> {
+        self.sign_bytes(data)
+    }
+
+    /// Vérifie une signature produite par `sign_message`. Ne nécessite pas la
+    /// clé privée : utilisable hors serveur (CLI de vérification).
+    pub fn verify_message(data: &[u8], sig_hex: &str, pubkey_hex: &str) -> bool {
+        let Ok(sig_bytes) = hex::decode(sig_hex) else {
+            return false;
+        };
+        let Ok(pk_bytes) = hex::decode(pubkey_hex) else {
+            return false;
+        };
+        let Ok(sig) = Signature::from_compact(&sig_bytes) else {
+            return false;
+        };
+        let Ok(pk) = bitcoin::secp256k1::PublicKey::from_slice(&pk_bytes) else {
+            return false;
+        };
+        let hash: [u8; 32] = Sha256::digest(data).into();
+        let Ok(msg) = Message::from_slice(&hash) else {
+            return false;
+        };
+        Secp256k1::verification_only()
+            .verify_ecdsa(&msg, &sig, &pk)
+            .is_ok()
+    }
 }
 
 // ─── Tests ────────────────────────────────────────────────────────────────────
@@ -743,6 +822,23 @@ mod tests {
         assert!(!valid, "Un contract_id altéré doit être rejeté");
     }
 
+    #[tokio::test]
+    async fn test_sign_message_roundtrip() {
+        let svc = make_service();
+        let sig = svc.sign_message(b"42:abcdef").unwrap();
+
+        assert!(RGBService::verify_message(
+            b"42:abcdef",
+            &sig,
+            svc.issuer_pubkey()
+        ));
+        assert!(!RGBService::verify_message(
+            b"43:abcdef",
+            &sig,
+            svc.issuer_pubkey()
+        ));
+    }
+
     #[tokio::test]
     async fn test_invalid_rating_rejected() {
         let svc = make_service();
@@ -766,6 +862,30 @@ mod tests {
             128,
             "signature ECDSA = 128 chars hex"
         );
+        assert!(details.notes_hash.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_proof_commits_to_notes_hash() {
+        let svc = make_service();
+        let notes_hash = "ab".repeat(32);
+        let (contract_id, signature) = svc
+            .create_proof_contract_with_notes("m1", "m2", "r1", 4, None, Some(notes_hash.clone()))
+            .await
+            .unwrap();
+        assert!(svc.verify_proof(&contract_id, &signature).await.unwrap());
+        let details = svc.get_proof_details(&contract_id).await.unwrap();
+        assert_eq!(details.notes_hash, Some(notes_hash));
+
+        // Notes altérées dans le stash : le contract_id ne correspond plus
+        svc.contracts
+            .write()
+            .await
+            .get_mut(&contract_id)
+            .unwrap()
+            .metadata
+            .notes_hash = Some("cd".repeat(32));
+        assert!(!svc.verify_proof(&contract_id, &signature).await.unwrap());
     }
 
     #[tokio::test]
@@ -0,0 +1,593 @@
+//! Planificateur persistant des tâches de fond
+//!
+//! Les tâches sont déclarées dans le code (`services::jobs`) avec une
+//! expression cron et une politique de relance, puis synchronisées dans
+//! `scheduled_jobs`. Chaque instance se porte candidate via un verrou
+//! consultatif Postgres tenu sur une connexion dédiée : seule l'instance qui
+//! le détient exécute les tâches. Si elle tombe, sa connexion se ferme, le
+//! verrou est libéré et une autre instance prend la main ; les exécutions
+//! qu'elle laissait `running` sont alors marquées `interrupted` et relancées.
+//!
+//! Chaque exécution est consignée dans `scheduled_job_runs` (durée, résultat,
+//! erreur). Une tâche en échec est relancée avec un backoff exponentiel
+//! jusqu'à `max_retries`, puis reprend son calendrier. Les admins peuvent
+//! suspendre une tâche ou demander une exécution immédiate : la demande est
+//! écrite en base et prise en charge par l'instance élue.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Utc;
+use futures::FutureExt;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::models::scheduled_job::{
+    next_occurrence, parse_schedule, retry_delay, JobRun, JobRunStatus, JobTrigger, ScheduledJob,
+    SchedulerLeader, SchedulerOverview,
+};
+
+/// Verrou consultatif de l'élection
+const SCHEDULER_LOCK_KEY: i64 = 0x7434_675f_7363_6864; // "t4g_schd"
+/// Intervalle entre deux candidatures d'une instance non élue
+const ELECTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// Intervalle de scrutation des tâches dues par l'instance élue
+const TICK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum SchedulerError {
+    #[error("Job not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Définition des tâches ────────────────────────────────────────────────────
+
+/// Résultat d'une exécution : résumé en cas de succès, message d'erreur sinon
+pub type JobResult = Result<String, String>;
+type JobFn = dyn Fn() -> Pin<Box<dyn Future<Output = JobResult> + Send>> + Send + Sync;
+
+#[derive(Clone)]
+pub struct Job {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Expression cron à 6 champs, UTC
+    pub schedule: &'static str,
+    pub max_retries: i32,
+    pub retry_backoff_seconds: i32,
+    run: Arc<JobFn>,
+}
+
+impl Job {
+    /// Tâche sans relance ; voir `retries`
+    pub fn new<F, Fut>(
+        name: &'static str,
+        description: &'static str,
+        schedule: &'static str,
+        run: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = JobResult> + Send + 'static,
+    {
+        Job {
+            name,
+            description,
+            schedule,
+            max_retries: 0,
+            retry_backoff_seconds: 60,
+            run: Arc::new(move || Box::pin(run())),
+        }
+    }
+
+    pub fn retries(mut self, max_retries: i32, backoff_seconds: i32) -> Self {
+        self.max_retries = max_retries;
+        self.retry_backoff_seconds = backoff_seconds;
+        self
+    }
+}
+
+// ── Élection et boucle de l'instance élue ────────────────────────────────────
+
+pub struct Scheduler {
+    pool: PgPool,
+    jobs: Arc<HashMap<&'static str, Job>>,
+    instance_id: String,
+}
+
+impl Scheduler {
+    pub fn new(pool: PgPool, jobs: Vec<Job>) -> Self {
+        for job in &jobs {
+            if let Err(e) = parse_schedule(job.schedule) {
+                panic!("Job {} has an invalid schedule: {}", job.name, e);
+            }
+        }
+        let host = std::env::var("RAILWAY_REPLICA_ID")
+            .or_else(|_| std::env::var("HOSTNAME"))
+            .unwrap_or_else(|_| "local".to_string());
+        let suffix = uuid::Uuid::new_v4().simple().to_string();
+        Scheduler {
+            pool,
+            jobs: Arc::new(jobs.into_iter().map(|j| (j.name, j)).collect()),
+            instance_id: format!("{}-{}", host, &suffix[..8]),
+        }
+    }
+
+    /// Lance la candidature permanente de l'instance
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            if let Err(e) = sync_definitions(&self.pool, &self.jobs).await {
+                error!("Scheduler: job definitions sync failed: {}", e);
+            }
+            loop {
+                match try_acquire(&self.pool).await {
+                    Ok(Some(lock_conn)) => {
+                        info!("Scheduler: instance {} elected leader", self.instance_id);
+                        self.lead(lock_conn).await;
+                        warn!("Scheduler: instance {} lost leadership", self.instance_id);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Scheduler: election failed: {}", e),
+                }
+                tokio::time::sleep(ELECTION_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Exécute les tâches dues tant que la connexion du verrou répond
+    async fn lead(&self, mut lock_conn: PgConnection) {
+        if let Err(e) = self.take_over().await {
+            error!("Scheduler: take over failed: {}", e);
+        }
+
+        let mut running: HashMap<String, JoinHandle<()>> = HashMap::new();
+        let mut tick = tokio::time::interval(TICK_INTERVAL);
+        loop {
+            tick.tick().await;
+
+            // La perte de la connexion libère le verrou : on arrête tout
+            if let Err(e) = sqlx::query("SELECT 1").execute(&mut lock_conn).await {
+                error!("Scheduler: lock connection lost: {}", e);
+                for (_, handle) in running.drain() {
+                    handle.abort();
+                }
+                return;
+            }
+            let _ = sqlx::query(
+                "UPDATE scheduler_leader SET heartbeat_at = NOW() WHERE instance_id = $1",
+            )
+            .bind(&self.instance_id)
+            .execute(&self.pool)
+            .await;
+
+            running.retain(|_, handle| !handle.is_finished());
+            let due = match due_jobs(&self.pool).await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Scheduler: due jobs query failed: {}", e);
+                    continue;
+                }
+            };
+            for (name, trigger, attempt) in due {
+                if running.contains_key(&name) {
+                    continue;
+                }
+                let Some(job) = self.jobs.get(name.as_str()).cloned() else {
+                    continue;
+                };
+                let pool = self.pool.clone();
+                let instance_id = self.instance_id.clone();
+                running.insert(
+                    name,
+                    tokio::spawn(async move {
+                        execute(&pool, &job, trigger, attempt, &instance_id).await;
+                    }),
+                );
+            }
+        }
+    }
+
+    /// Prise de fonction : enregistre l'instance élue, clôt les exécutions
+    /// laissées en cours par la précédente et relance les tâches concernées.
+    async fn take_over(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO scheduler_leader (id, instance_id, acquired_at, heartbeat_at)
+            VALUES (TRUE, $1, NOW(), NOW())
+            ON CONFLICT (id) DO UPDATE SET
+                instance_id = EXCLUDED.instance_id,
+                acquired_at = EXCLUDED.acquired_at,
+                heartbeat_at = EXCLUDED.heartbeat_at
+            "#,
+        )
+        .bind(&self.instance_id)
+        .execute(&self.pool)
+        .await?;
+
+        let interrupted: Vec<String> = sqlx::query_scalar(
+            r#"
+            UPDATE scheduled_job_runs SET
+                status = 'interrupted',
+                finished_at = NOW(),
+                duration_ms = (EXTRACT(EPOCH FROM NOW() - started_at) * 1000)::BIGINT,
+                error = 'instance lost during run'
+            WHERE status = 'running'
+            RETURNING job_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        if !interrupted.is_empty() {
+            warn!("Scheduler: re-running interrupted job(s) {:?}", interrupted);
+            sqlx::query("UPDATE scheduled_jobs SET next_run_at = NOW() WHERE name = ANY($1)")
+                .bind(&interrupted)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Tente de prendre le verrou d'élection sur une connexion détachée du pool,
+/// conservée tant que l'instance est élue.
+async fn try_acquire(pool: &PgPool) -> Result<Option<PgConnection>, sqlx::Error> {
+    let mut conn = pool.acquire().await?.detach();
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(SCHEDULER_LOCK_KEY)
+        .fetch_one(&mut conn)
+        .await?;
+    Ok(locked.then_some(conn))
+}
+
+/// Enregistre les tâches déclarées ; un changement de calendrier recalcule
+/// la prochaine exécution, la suspension décidée par un admin est conservée.
+async fn sync_definitions(
+    pool: &PgPool,
+    jobs: &HashMap<&'static str, Job>,
+) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    for job in jobs.values() {
+        let next_run_at = parse_schedule(job.schedule)
+            .ok()
+            .and_then(|s| next_occurrence(&s, now));
+        sqlx::query(
+            r#"
+            INSERT INTO scheduled_jobs
+                (name, description, schedule, max_retries, retry_backoff_seconds, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (name) DO UPDATE SET
+                description = EXCLUDED.description,
+                max_retries = EXCLUDED.max_retries,
+                retry_backoff_seconds = EXCLUDED.retry_backoff_seconds,
+                next_run_at = CASE
+                    WHEN scheduled_jobs.schedule <> EXCLUDED.schedule
+                        OR scheduled_jobs.next_run_at IS NULL
+                    THEN EXCLUDED.next_run_at
+                    ELSE scheduled_jobs.next_run_at
+                END,
+                schedule = EXCLUDED.schedule
+            "#,
+        )
+        .bind(job.name)
+        .bind(job.description)
+        .bind(job.schedule)
+        .bind(job.max_retries)
+        .bind(job.retry_backoff_seconds)
+        .bind(next_run_at)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Tâches à lancer : exécution manuelle demandée, ou échéance atteinte hors
+/// suspension
+async fn due_jobs(pool: &PgPool) -> Result<Vec<(String, JobTrigger, i32)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT name, attempt, trigger_requested_at IS NOT NULL AS manual
+        FROM scheduled_jobs
+        WHERE trigger_requested_at IS NOT NULL
+           OR (NOT paused AND next_run_at <= NOW())
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let attempt: i32 = r.try_get("attempt").unwrap_or(0);
+            let trigger = if r.try_get("manual").unwrap_or(false) {
+                JobTrigger::Manual
+            } else if attempt > 0 {
+                JobTrigger::Retry
+            } else {
+                JobTrigger::Schedule
+            };
+            (r.try_get("name").unwrap_or_default(), trigger, attempt)
+        })
+        .collect())
+}
+
+/// Exécute une tâche et consigne le résultat ; une panique compte comme un
+/// échec.
+async fn execute(pool: &PgPool, job: &Job, trigger: JobTrigger, attempt: i32, instance_id: &str) {
+    let run_id: Result<String, sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE scheduled_jobs SET trigger_requested_at = NULL WHERE name = $1")
+            .bind(job.name)
+            .execute(&mut *tx)
+            .await?;
+        let id = sqlx::query_scalar(
+            "INSERT INTO scheduled_job_runs (job_name, trigger, attempt, instance_id)
+             VALUES ($1, $2, $3, $4) RETURNING id",
+        )
+        .bind(job.name)
+        .bind(trigger.to_string())
+        .bind(attempt)
+        .bind(instance_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+    .await;
+    let run_id = match run_id {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Scheduler: could not start job {}: {}", job.name, e);
+            return;
+        }
+    };
+
+    let started = std::time::Instant::now();
+    // Exécutée dans la tâche courante : la perte de la main l'interrompt
+    let outcome = match AssertUnwindSafe((job.run)()).catch_unwind().await {
+        Ok(outcome) => outcome,
+        Err(_) => Err("job panicked".to_string()),
+    };
+    let duration_ms = started.elapsed().as_millis() as i64;
+
+    let now = Utc::now();
+    let next_scheduled = parse_schedule(job.schedule)
+        .ok()
+        .and_then(|s| next_occurrence(&s, now));
+    let (status, next_attempt, next_run_at) = match &outcome {
+        Ok(summary) => {
+            info!(
+                "Job {} succeeded in {} ms: {}",
+                job.name, duration_ms, summary
+            );
+            (JobRunStatus::Succeeded, 0, next_scheduled)
+        }
+        Err(e) if attempt < job.max_retries => {
+            let delay = retry_delay(job.retry_backoff_seconds, attempt + 1);
+            warn!(
+                "Job {} failed (attempt {}), retrying in {}s: {}",
+                job.name,
+                attempt + 1,
+                delay.num_seconds(),
+                e
+            );
+            (JobRunStatus::Failed, attempt + 1, Some(now + delay))
+        }
+        Err(e) => {
+            error!("Job {} failed: {}", job.name, e);
+            (JobRunStatus::Failed, 0, next_scheduled)
+        }
+    };
+
+    let recorded: Result<(), sqlx::Error> = async {
+        let mut tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE scheduled_job_runs SET
+                status = $2, result = $3, error = $4, finished_at = NOW(), duration_ms = $5
+            WHERE id = $1
+            "#,
+        )
+        .bind(&run_id)
+        .bind(status.to_string())
+        .bind(outcome.as_ref().ok())
+        .bind(outcome.as_ref().err())
+        .bind(duration_ms)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE scheduled_jobs SET attempt = $2, next_run_at = $3 WHERE name = $1")
+            .bind(job.name)
+            .bind(next_attempt)
+            .bind(next_run_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await
+    }
+    .await;
+    if let Err(e) = recorded {
+        error!("Scheduler: could not record run of {}: {}", job.name, e);
+    }
+}
+
+// ── Administration ───────────────────────────────────────────────────────────
+
+fn run_from_row(r: &PgRow) -> JobRun {
+    JobRun {
+        id: r.try_get("id").unwrap_or_default(),
+        job_name: r.try_get("job_name").unwrap_or_default(),
+        trigger: r
+            .try_get::<String, _>("trigger")
+            .unwrap_or_default()
+            .parse()
+            .unwrap_or(JobTrigger::Schedule),
+        attempt: r.try_get("attempt").unwrap_or(0),
+        instance_id: r.try_get("instance_id").unwrap_or_default(),
+        status: r
+            .try_get::<String, _>("status")
+            .unwrap_or_default()
+            .parse()
+            .unwrap_or(JobRunStatus::Failed),
+        result: r.try_get("result").ok().flatten(),
+        error: r.try_get("error").ok().flatten(),
+        started_at: r.try_get("started_at").unwrap_or_else(|_| Utc::now()),
+        finished_at: r.try_get("finished_at").ok().flatten(),
+        duration_ms: r.try_get("duration_ms").ok().flatten(),
+    }
+}
+
+fn job_from_row(r: &PgRow) -> ScheduledJob {
+    let last_run = r
+        .try_get::<Option<String>, _>("run_id")
+        .ok()
+        .flatten()
+        .map(|id| JobRun {
+            id,
+            ..run_from_row(r)
+        });
+    ScheduledJob {
+        name: r.try_get("name").unwrap_or_default(),
+        description: r.try_get("description").unwrap_or_default(),
+        schedule: r.try_get("schedule").unwrap_or_default(),
+        paused: r.try_get("paused").unwrap_or(false),
+        max_retries: r.try_get("max_retries").unwrap_or(0),
+        retry_backoff_seconds: r.try_get("retry_backoff_seconds").unwrap_or(60),
+        attempt: r.try_get("job_attempt").unwrap_or(0),
+        next_run_at: r.try_get("next_run_at").ok().flatten(),
+        trigger_requested_at: r.try_get("trigger_requested_at").ok().flatten(),
+        last_run,
+    }
+}
+
+const JOB_SELECT: &str = r#"
+    SELECT j.name, j.description, j.schedule, j.paused, j.max_retries,
+           j.retry_backoff_seconds, j.attempt AS job_attempt, j.next_run_at,
+           j.trigger_requested_at,
+           r.id AS run_id, r.job_name, r.trigger, r.attempt, r.instance_id, r.status,
+           r.result, r.error, r.started_at, r.finished_at, r.duration_ms
+    FROM scheduled_jobs j
+    LEFT JOIN LATERAL (
+        SELECT * FROM scheduled_job_runs
+        WHERE job_name = j.name
+        ORDER BY started_at DESC
+        LIMIT 1
+    ) r ON TRUE
+"#;
+
+/// Tâches, dernière exécution de chacune et instance élue
+pub async fn overview(pool: &PgPool) -> Result<SchedulerOverview, sqlx::Error> {
+    let jobs = sqlx::query(&format!("{} ORDER BY j.name", JOB_SELECT))
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(job_from_row)
+        .collect();
+    let leader = sqlx::query("SELECT instance_id, acquired_at, heartbeat_at FROM scheduler_leader")
+        .fetch_optional(pool)
+        .await?
+        .map(|r| SchedulerLeader {
+            instance_id: r.try_get("instance_id").unwrap_or_default(),
+            acquired_at: r.try_get("acquired_at").unwrap_or_else(|_| Utc::now()),
+            heartbeat_at: r.try_get("heartbeat_at").unwrap_or_else(|_| Utc::now()),
+        });
+    Ok(SchedulerOverview { leader, jobs })
+}
+
+async fn get_job(pool: &PgPool, name: &str) -> Result<ScheduledJob, SchedulerError> {
+    sqlx::query(&format!("{} WHERE j.name = $1", JOB_SELECT))
+        .bind(name)
+        .fetch_optional(pool)
+        .await?
+        .map(|r| job_from_row(&r))
+        .ok_or(SchedulerError::NotFound)
+}
+
+/// Historique des exécutions d'une tâche, les plus récentes d'abord
+pub async fn list_runs(
+    pool: &PgPool,
+    name: &str,
+    limit: i64,
+) -> Result<Vec<JobRun>, SchedulerError> {
+    get_job(pool, name).await?;
+    Ok(sqlx::query(
+        "SELECT * FROM scheduled_job_runs WHERE job_name = $1 ORDER BY started_at DESC LIMIT $2",
+    )
+    .bind(name)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(run_from_row)
+    .collect())
+}
+
+/// Suspend ou reprend une tâche ; à la reprise, la prochaine exécution suit
+/// le calendrier.
+pub async fn set_paused(
+    pool: &PgPool,
+    name: &str,
+    paused: bool,
+) -> Result<ScheduledJob, SchedulerError> {
+    let schedule: String =
+        sqlx::query_scalar("SELECT schedule FROM scheduled_jobs WHERE name = $1")
+            .bind(name)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(SchedulerError::NotFound)?;
+    let next_run_at = parse_schedule(&schedule)
+        .ok()
+        .and_then(|s| next_occurrence(&s, Utc::now()));
+    sqlx::query(
+        r#"
+        UPDATE scheduled_jobs SET
+            paused = $2,
+            attempt = CASE WHEN $2 THEN attempt ELSE 0 END,
+            next_run_at = CASE WHEN $2 THEN next_run_at ELSE $3 END
+        WHERE name = $1
+        "#,
+    )
+    .bind(name)
+    .bind(paused)
+    .bind(next_run_at)
+    .execute(pool)
+    .await?;
+    info!("Job {} {}", name, if paused { "paused" } else { "resumed" });
+    get_job(pool, name).await
+}
+
+/// Demande une exécution immédiate, y compris d'une tâche suspendue ;
+/// l'instance élue la lance à sa prochaine scrutation.
+pub async fn request_trigger(
+    pool: &PgPool,
+    name: &str,
+    admin_id: &str,
+) -> Result<ScheduledJob, SchedulerError> {
+    let updated = sqlx::query(
+        "UPDATE scheduled_jobs SET trigger_requested_at = NOW(), triggered_by = $2
+         WHERE name = $1",
+    )
+    .bind(name)
+    .bind(admin_id)
+    .execute(pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        return Err(SchedulerError::NotFound);
+    }
+    info!("Job {} triggered manually by {}", name, admin_id);
+    get_job(pool, name).await
+}
+
+/// Purge l'historique des exécutions terminées depuis plus de `days` jours
+pub async fn purge_runs(pool: &PgPool, days: i32) -> Result<u64, sqlx::Error> {
+    Ok(sqlx::query(
+        "DELETE FROM scheduled_job_runs
+         WHERE status <> 'running' AND started_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(days)
+    .execute(pool)
+    .await?
+    .rows_affected())
+}
@@ -0,0 +1,318 @@
+//! Notes de session et pièces jointes par réservation
+//!
+//! Mentor et mentee tiennent chacun une fiche : résumé, actions à mener et
+//! liens sont partagés, les notes privées restent visibles de leur seul
+//! auteur. Les pièces jointes sont des références vers des documents
+//! hébergés ailleurs. Tout est modifiable jusqu'à la clôture de la
+//! réservation ; `freeze`, appelé par `booking_state::apply`, fige alors
+//! notes et pièces jointes, dont l'empreinte (`frozen_hash`) est engagée dans
+//! la preuve RGB de la session.
+
+use chrono::Utc;
+use sqlx::{postgres::PgRow, PgConnection, PgPool, Row};
+
+use crate::models::mentoring_offer::BookingStatus;
+use crate::models::session_notes::{
+    is_editable, notes_hash, AddAttachmentPayload, BookingNotes, NoteLink, SessionAttachment,
+    SessionNotes, UpsertNotesPayload, MAX_ATTACHMENTS_PER_BOOKING,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum NotesError {
+    #[error("Booking or attachment not found")]
+    NotFound,
+    #[error("Not a participant of this booking")]
+    Forbidden,
+    #[error("Session notes are frozen")]
+    Frozen,
+    #[error("Invalid notes: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Accès ────────────────────────────────────────────────────────────────────
+
+/// Rôle de l'appelant et statut de la réservation ; `lock` verrouille la
+/// réservation le temps de l'écriture (une clôture concurrente attend).
+async fn participant(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    user_id: &str,
+    lock: bool,
+) -> Result<(&'static str, BookingStatus), NotesError> {
+    let row = sqlx::query(&format!(
+        r#"
+        SELECT b.mentee_id, o.mentor_id, b.status
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        {}
+        "#,
+        if lock { "FOR SHARE OF b" } else { "" }
+    ))
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(NotesError::NotFound)?;
+
+    let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+    let mentor_id: String = row.try_get("mentor_id").unwrap_or_default();
+    let role = if user_id == mentee_id {
+        "mentee"
+    } else if user_id == mentor_id {
+        "mentor"
+    } else {
+        return Err(NotesError::Forbidden);
+    };
+    let status: BookingStatus = row
+        .try_get::<String, _>("status")
+        .unwrap_or_default()
+        .parse()
+        .map_err(|_| NotesError::NotFound)?;
+    Ok((role, status))
+}
+
+// ── Lecture ──────────────────────────────────────────────────────────────────
+
+fn notes_from_row(r: &PgRow, viewer_id: Option<&str>) -> SessionNotes {
+    let author_id: String = r.try_get("author_id").unwrap_or_default();
+    let private_notes = viewer_id
+        .filter(|viewer| *viewer == author_id)
+        .map(|_| r.try_get("private_notes").unwrap_or_default());
+    SessionNotes {
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        author_role: r.try_get("author_role").unwrap_or_default(),
+        summary: r.try_get("summary").unwrap_or_default(),
+        action_items: r
+            .try_get::<serde_json::Value, _>("action_items")
+            .ok()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default(),
+        links: r
+            .try_get::<serde_json::Value, _>("links")
+            .ok()
+            .and_then(|v| serde_json::from_value::<Vec<NoteLink>>(v).ok())
+            .unwrap_or_default(),
+        private_notes,
+        frozen_at: r.try_get("frozen_at").ok().flatten(),
+        updated_at: r.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+        author_id,
+    }
+}
+
+fn attachment_from_row(r: &PgRow) -> SessionAttachment {
+    SessionAttachment {
+        id: r.try_get("id").unwrap_or_default(),
+        booking_id: r.try_get("booking_id").unwrap_or_default(),
+        uploader_id: r.try_get("uploader_id").ok().flatten(),
+        uploader_role: r.try_get("uploader_role").unwrap_or_default(),
+        name: r.try_get("name").unwrap_or_default(),
+        url: r.try_get("url").unwrap_or_default(),
+        content_type: r.try_get("content_type").ok().flatten(),
+        size_bytes: r.try_get("size_bytes").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+async fn load(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    viewer_id: Option<&str>,
+) -> Result<(Vec<SessionNotes>, Vec<SessionAttachment>), sqlx::Error> {
+    let notes = sqlx::query(
+        "SELECT * FROM mentoring_session_notes WHERE booking_id = $1 ORDER BY author_role",
+    )
+    .bind(booking_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(|r| notes_from_row(r, viewer_id))
+    .collect();
+    let attachments = sqlx::query(
+        "SELECT * FROM mentoring_session_attachments WHERE booking_id = $1
+         ORDER BY created_at, id",
+    )
+    .bind(booking_id)
+    .fetch_all(&mut *conn)
+    .await?
+    .iter()
+    .map(attachment_from_row)
+    .collect();
+    Ok((notes, attachments))
+}
+
+/// Notes et pièces jointes d'une réservation, vues par un participant
+pub async fn get(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+) -> Result<BookingNotes, NotesError> {
+    let mut conn = pool.acquire().await?;
+    let (_, status) = participant(&mut conn, booking_id, user_id, false).await?;
+    let (notes, attachments) = load(&mut conn, booking_id, Some(user_id)).await?;
+    Ok(BookingNotes {
+        booking_id: booking_id.to_string(),
+        notes_hash: notes_hash(booking_id, &notes, &attachments),
+        editable: is_editable(status),
+        notes,
+        attachments,
+    })
+}
+
+/// Empreinte des notes partagées d'une réservation, pour la preuve RGB
+pub async fn frozen_hash(pool: &PgPool, booking_id: &str) -> Result<Option<String>, sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    let (notes, attachments) = load(&mut conn, booking_id, None).await?;
+    Ok(notes_hash(booking_id, &notes, &attachments))
+}
+
+// ── Écriture ─────────────────────────────────────────────────────────────────
+
+/// Crée ou remplace la fiche de l'appelant
+pub async fn upsert(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+    payload: &UpsertNotesPayload,
+) -> Result<SessionNotes, NotesError> {
+    payload.validate().map_err(NotesError::Invalid)?;
+    let mut tx = pool.begin().await?;
+    let (role, status) = participant(&mut tx, booking_id, user_id, true).await?;
+    if !is_editable(status) {
+        return Err(NotesError::Frozen);
+    }
+
+    let action_items: Vec<&str> = payload.action_items.iter().map(|i| i.trim()).collect();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_session_notes
+            (booking_id, author_id, author_role, summary, action_items, links, private_notes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (booking_id, author_id) DO UPDATE SET
+            summary = EXCLUDED.summary,
+            action_items = EXCLUDED.action_items,
+            links = EXCLUDED.links,
+            private_notes = EXCLUDED.private_notes
+        WHERE mentoring_session_notes.frozen_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(role)
+    .bind(payload.summary.trim())
+    .bind(serde_json::json!(action_items))
+    .bind(serde_json::json!(payload.links))
+    .bind(&payload.private_notes)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(NotesError::Frozen)?;
+    tx.commit().await?;
+
+    Ok(notes_from_row(&row, Some(user_id)))
+}
+
+/// Ajoute une référence de pièce jointe à la réservation
+pub async fn add_attachment(
+    pool: &PgPool,
+    booking_id: &str,
+    user_id: &str,
+    payload: &AddAttachmentPayload,
+) -> Result<SessionAttachment, NotesError> {
+    payload.validate().map_err(NotesError::Invalid)?;
+    let mut tx = pool.begin().await?;
+    let (role, status) = participant(&mut tx, booking_id, user_id, true).await?;
+    if !is_editable(status) {
+        return Err(NotesError::Frozen);
+    }
+
+    let count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM mentoring_session_attachments WHERE booking_id = $1",
+    )
+    .bind(booking_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if count >= MAX_ATTACHMENTS_PER_BOOKING {
+        return Err(NotesError::Invalid(format!(
+            "{} pièces jointes maximum par réservation",
+            MAX_ATTACHMENTS_PER_BOOKING
+        )));
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_session_attachments
+            (booking_id, uploader_id, uploader_role, name, url, content_type, size_bytes)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(booking_id)
+    .bind(user_id)
+    .bind(role)
+    .bind(payload.name.trim())
+    .bind(&payload.url)
+    .bind(&payload.content_type)
+    .bind(payload.size_bytes)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(attachment_from_row(&row))
+}
+
+/// Retire une pièce jointe (par celui qui l'a déposée)
+pub async fn remove_attachment(
+    pool: &PgPool,
+    booking_id: &str,
+    attachment_id: &str,
+    user_id: &str,
+) -> Result<(), NotesError> {
+    let mut tx = pool.begin().await?;
+    let (_, status) = participant(&mut tx, booking_id, user_id, true).await?;
+    if !is_editable(status) {
+        return Err(NotesError::Frozen);
+    }
+
+    let uploader: Option<String> = sqlx::query_scalar(
+        "SELECT uploader_id FROM mentoring_session_attachments WHERE id = $1 AND booking_id = $2",
+    )
+    .bind(attachment_id)
+    .bind(booking_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(NotesError::NotFound)?;
+    if uploader.as_deref() != Some(user_id) {
+        return Err(NotesError::Forbidden);
+    }
+
+    sqlx::query("DELETE FROM mentoring_session_attachments WHERE id = $1 AND frozen_at IS NULL")
+        .bind(attachment_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Fige notes et pièces jointes à la clôture de la réservation (appelé dans
+/// la transaction de la transition).
+pub async fn freeze(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    to: BookingStatus,
+) -> Result<(), sqlx::Error> {
+    if is_editable(to) {
+        return Ok(());
+    }
+    for table in ["mentoring_session_notes", "mentoring_session_attachments"] {
+        sqlx::query(&format!(
+            "UPDATE {} SET frozen_at = NOW() WHERE booking_id = $1 AND frozen_at IS NULL",
+            table
+        ))
+        .bind(booking_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,409 @@
+//! Progression des compétences à partir des sessions complétées
+//!
+//! Chaque session complétée laisse une preuve d'apprentissage
+//! (`user_skill_evidence`) pour le sujet de l'offre et pour chaque
+//! compétence déclarée à la confirmation qui correspond à un sujet du
+//! référentiel. Le niveau d'un utilisateur sur un sujet est dérivé du nombre
+//! de sessions, relevé par les recommandations de ses mentors ; la preuve RGB
+//! de chaque session est retrouvée par la réservation.
+//!
+//! Les prochains sujets sont suggérés d'après les prérequis explicites
+//! (`learning_topic_prerequisites`) ou, à défaut, l'ordre des niveaux de la
+//! catégorie (`models::skills::suggest_topics`).
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::info;
+
+use crate::models::matching::Level;
+use crate::models::skills::{
+    creates_cycle, skill_level, suggest_topics, EndorseSkillPayload, LearningPath,
+    SkillEndorsement, SkillEvidence, SkillProgress, TopicNode,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SkillError {
+    #[error("Not found")]
+    NotFound,
+    #[error("Only the session mentor can endorse this skill")]
+    Forbidden,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Enregistre les preuves d'apprentissage d'une session complétée : sujet de
+/// l'offre et compétences déclarées reconnues. Idempotent.
+pub async fn record_session_evidence(pool: &PgPool, booking_id: &str) -> Result<u64, sqlx::Error> {
+    let from_offer = sqlx::query(
+        r#"
+        INSERT INTO user_skill_evidence (user_id, topic_slug, booking_id, label, learned_at)
+        SELECT b.mentee_id, t.slug, b.id, NULL, NOW()
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        JOIN learning_topics t ON t.slug = o.topic_slug
+        WHERE b.id = $1
+        ON CONFLICT (user_id, topic_slug, booking_id) DO NOTHING
+        "#,
+    )
+    .bind(booking_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let from_skills = sqlx::query(
+        r#"
+        INSERT INTO user_skill_evidence (user_id, topic_slug, booking_id, label, learned_at)
+        SELECT DISTINCT ON (t.slug) b.mentee_id, t.slug, b.id, s.label, NOW()
+        FROM mentoring_bookings b
+        CROSS JOIN LATERAL unnest(b.learned_skills) AS s(label)
+        JOIN learning_topics t
+          ON t.slug = lower(trim(s.label)) OR lower(t.name) = lower(trim(s.label))
+        WHERE b.id = $1
+        ORDER BY t.slug
+        ON CONFLICT (user_id, topic_slug, booking_id) DO NOTHING
+        "#,
+    )
+    .bind(booking_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let recorded = from_offer + from_skills;
+    if recorded > 0 {
+        info!(
+            "{} skill evidence(s) recorded for booking {}",
+            recorded, booking_id
+        );
+    }
+    Ok(recorded)
+}
+
+fn endorsement_from_row(row: &sqlx::postgres::PgRow) -> SkillEndorsement {
+    SkillEndorsement {
+        id: row.try_get("id").unwrap_or_default(),
+        user_id: row.try_get("user_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        booking_id: row.try_get("booking_id").unwrap_or_default(),
+        level: row
+            .try_get::<String, _>("level")
+            .ok()
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(Level::Beginner),
+        comment: row.try_get("comment").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        updated_at: row.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+fn empty_progress(row: &sqlx::postgres::PgRow) -> SkillProgress {
+    SkillProgress {
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        topic_name: row.try_get("topic_name").unwrap_or_default(),
+        category_slug: row.try_get("category_slug").ok().flatten(),
+        level: Level::Beginner,
+        sessions: 0,
+        first_learned_at: None,
+        last_learned_at: None,
+        evidence: vec![],
+        endorsements: vec![],
+    }
+}
+
+/// Compétences d'un utilisateur, dernières travaillées d'abord
+pub async fn progress(pool: &PgPool, user_id: &str) -> Result<Vec<SkillProgress>, sqlx::Error> {
+    let evidence = sqlx::query(
+        r#"
+        SELECT e.topic_slug, t.name AS topic_name, c.slug AS category_slug,
+               e.booking_id, e.label, e.learned_at, o.mentor_id, p.rgb_contract_id
+        FROM user_skill_evidence e
+        JOIN learning_topics t ON t.slug = e.topic_slug
+        LEFT JOIN learning_categories c ON c.id = t.category_id
+        JOIN mentoring_bookings b ON b.id = e.booking_id
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        LEFT JOIN LATERAL (
+            SELECT mp.rgb_contract_id FROM mentoring_proofs mp
+            WHERE mp.request_id = e.booking_id AND mp.revoked_at IS NULL
+            ORDER BY mp.created_at DESC
+            LIMIT 1
+        ) p ON true
+        WHERE e.user_id = $1
+        ORDER BY e.learned_at ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let endorsements = sqlx::query(
+        r#"
+        SELECT se.*, t.name AS topic_name, c.slug AS category_slug
+        FROM skill_endorsements se
+        JOIN learning_topics t ON t.slug = se.topic_slug
+        LEFT JOIN learning_categories c ON c.id = t.category_id
+        WHERE se.user_id = $1
+        ORDER BY se.updated_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut skills: BTreeMap<String, SkillProgress> = BTreeMap::new();
+    for row in &evidence {
+        let slug: String = row.try_get("topic_slug").unwrap_or_default();
+        let skill = skills.entry(slug).or_insert_with(|| empty_progress(row));
+        let learned_at: DateTime<Utc> = row.try_get("learned_at").unwrap_or_else(|_| Utc::now());
+        skill.sessions += 1;
+        skill.first_learned_at.get_or_insert(learned_at);
+        skill.last_learned_at = Some(learned_at);
+        skill.evidence.push(SkillEvidence {
+            booking_id: row.try_get("booking_id").unwrap_or_default(),
+            mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+            label: row.try_get("label").ok().flatten(),
+            rgb_contract_id: row.try_get("rgb_contract_id").ok().flatten(),
+            learned_at,
+        });
+    }
+    for row in &endorsements {
+        let slug: String = row.try_get("topic_slug").unwrap_or_default();
+        skills
+            .entry(slug)
+            .or_insert_with(|| empty_progress(row))
+            .endorsements
+            .push(endorsement_from_row(row));
+    }
+
+    let mut skills: Vec<SkillProgress> = skills
+        .into_values()
+        .map(|mut s| {
+            let endorsed = s.endorsements.iter().map(|e| e.level).max();
+            s.level = skill_level(s.sessions, endorsed);
+            s
+        })
+        .collect();
+    skills.sort_by_key(|s| std::cmp::Reverse(s.last_learned_at));
+    Ok(skills)
+}
+
+async fn prerequisite_map(pool: &PgPool) -> Result<HashMap<String, Vec<String>>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT topic_slug, prerequisite_slug FROM learning_topic_prerequisites
+         ORDER BY topic_slug, prerequisite_slug",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut map: HashMap<String, Vec<String>> = HashMap::new();
+    for row in rows {
+        map.entry(row.try_get("topic_slug").unwrap_or_default())
+            .or_default()
+            .push(row.try_get("prerequisite_slug").unwrap_or_default());
+    }
+    Ok(map)
+}
+
+/// Parcours d'apprentissage : compétences acquises et prochains sujets
+pub async fn learning_path(pool: &PgPool, user_id: &str) -> Result<LearningPath, sqlx::Error> {
+    let skills = progress(pool, user_id).await?;
+
+    let topics: Vec<TopicNode> = sqlx::query(
+        r#"
+        SELECT t.slug, t.name, t.level, c.slug AS category_slug
+        FROM learning_topics t
+        LEFT JOIN learning_categories c ON c.id = t.category_id
+        WHERE t.is_active = true
+        ORDER BY t.sort_order ASC, t.name ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?
+    .iter()
+    .map(|row| TopicNode {
+        slug: row.try_get("slug").unwrap_or_default(),
+        name: row.try_get("name").unwrap_or_default(),
+        category_slug: row.try_get("category_slug").ok().flatten(),
+        level: row
+            .try_get::<Option<String>, _>("level")
+            .ok()
+            .flatten()
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(Level::Beginner),
+    })
+    .collect();
+
+    let goals: HashSet<String> = sqlx::query_scalar::<_, Option<Vec<String>>>(
+        "SELECT learning_topics FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .flatten()
+    .unwrap_or_default()
+    .into_iter()
+    .collect();
+
+    let acquired: HashMap<String, Level> = skills
+        .iter()
+        .filter(|s| s.sessions > 0)
+        .map(|s| (s.topic_slug.clone(), s.level))
+        .collect();
+    let suggestions = suggest_topics(&topics, &prerequisite_map(pool).await?, &acquired, &goals);
+
+    Ok(LearningPath {
+        user_id: user_id.to_string(),
+        skills,
+        suggestions,
+    })
+}
+
+/// Recommandation d'une compétence par le mentor d'une session complétée.
+/// Le sujet doit être celui de l'offre ou une compétence attestée par la
+/// session ; une nouvelle recommandation remplace la précédente du mentor.
+pub async fn endorse(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    mentor_id: &str,
+    payload: &EndorseSkillPayload,
+) -> Result<SkillEndorsement, SkillError> {
+    let level = payload.validate().map_err(SkillError::Invalid)?;
+
+    let booking = sqlx::query(
+        r#"
+        SELECT b.mentee_id, b.status, o.mentor_id, o.topic_slug
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(SkillError::NotFound)?;
+
+    let owner: String = booking.try_get("mentor_id").unwrap_or_default();
+    if owner != mentor_id {
+        return Err(SkillError::Forbidden);
+    }
+    let status: String = booking.try_get("status").unwrap_or_default();
+    if status != "completed" && status != "auto_completed" {
+        return Err(SkillError::Invalid(
+            "la session doit être complétée pour recommander une compétence".to_string(),
+        ));
+    }
+    let mentee_id: String = booking.try_get("mentee_id").unwrap_or_default();
+    let offer_topic: String = booking.try_get("topic_slug").unwrap_or_default();
+    let topic_slug = payload
+        .topic_slug
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .unwrap_or(&offer_topic)
+        .to_string();
+
+    let attested: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(SELECT 1 FROM learning_topics WHERE slug = $2)
+           AND ($2 = $3 OR EXISTS(
+                SELECT 1 FROM user_skill_evidence
+                WHERE booking_id = $1 AND topic_slug = $2))
+        "#,
+    )
+    .bind(booking_id)
+    .bind(&topic_slug)
+    .bind(&offer_topic)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !attested {
+        return Err(SkillError::Invalid(format!(
+            "«{}» n'a pas été travaillé pendant cette session",
+            topic_slug
+        )));
+    }
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO skill_endorsements (user_id, topic_slug, mentor_id, booking_id, level, comment)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (user_id, topic_slug, mentor_id) DO UPDATE SET
+            booking_id = EXCLUDED.booking_id,
+            level      = EXCLUDED.level,
+            comment    = EXCLUDED.comment,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(&mentee_id)
+    .bind(&topic_slug)
+    .bind(mentor_id)
+    .bind(booking_id)
+    .bind(level.to_string())
+    .bind(
+        payload
+            .comment
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty()),
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    info!(
+        "Skill {} endorsed at {} for {} by mentor {}",
+        topic_slug, level, mentee_id, mentor_id
+    );
+    Ok(endorsement_from_row(&row))
+}
+
+/// Remplace les prérequis d'un sujet ; refuse les sujets inconnus et les cycles
+pub async fn set_prerequisites(
+    pool: &PgPool,
+    topic_slug: &str,
+    prerequisites: &[String],
+) -> Result<Vec<String>, SkillError> {
+    let mut wanted: Vec<String> = prerequisites.iter().map(|p| p.trim().to_string()).collect();
+    wanted.sort();
+    wanted.dedup();
+
+    let known: Vec<String> =
+        sqlx::query_scalar("SELECT slug FROM learning_topics WHERE slug = $1 OR slug = ANY($2)")
+            .bind(topic_slug)
+            .bind(&wanted)
+            .fetch_all(pool)
+            .await?;
+    if !known.iter().any(|s| s == topic_slug) {
+        return Err(SkillError::NotFound);
+    }
+    if let Some(unknown) = wanted.iter().find(|p| !known.contains(p)) {
+        return Err(SkillError::Invalid(format!("sujet inconnu : {}", unknown)));
+    }
+
+    let mut existing = prerequisite_map(pool).await?;
+    existing.remove(topic_slug);
+    if creates_cycle(topic_slug, &wanted, &existing) {
+        return Err(SkillError::Invalid(
+            "ces prérequis créeraient un cycle".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM learning_topic_prerequisites WHERE topic_slug = $1")
+        .bind(topic_slug)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        "INSERT INTO learning_topic_prerequisites (topic_slug, prerequisite_slug)
+         SELECT $1, unnest($2::VARCHAR[])",
+    )
+    .bind(topic_slug)
+    .bind(&wanted)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    Ok(wanted)
+}
@@ -0,0 +1,361 @@
+//! Absences des mentors
+//!
+//! Une absence bloque l'agenda du mentor sur sa période
+//! (`availability::mentor_occupancy`) : aucune session n'y est réservable ni
+//! reportable. Pendant l'absence, les offres ouvertes du mentor sont masquées
+//! des listes et des recommandations, ainsi que les sessions datées qui
+//! tombent dans une absence à venir.
+//!
+//! À la déclaration, les réservations en attente ou confirmées qui recoupent
+//! la période sont signalées (`time_off_id`) et leurs mentees prévenus. Le
+//! mentor refuse les demandes en attente, et pour chaque session confirmée
+//! propose un report ou se retire (`withdraw`, séquestre intégralement
+//! remboursé).
+//!
+//! `run_schedule_sync` aligne `is_mentor_active` sur le calendrier : un
+//! mentor actif est désactivé pendant son absence et réactivé à son terme.
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgExecutor, PgPool, Row};
+use tracing::info;
+
+use crate::models::outbox::OutboxMessage;
+use crate::models::time_off::{
+    conflict_actions, ConflictAction, CreateTimeOffPayload, TimeOff, TimeOffConflict, TimeOffDetail,
+};
+use crate::services::{
+    booking_state::{self, Actor, BookingEvent, Transition, TransitionError},
+    outbox,
+};
+
+/// Offre masquée par une absence (alias `o`) : mentor absent en ce moment,
+/// ou session datée tombant dans une absence
+pub const HIDDEN_BY_TIME_OFF: &str = "EXISTS (SELECT 1 FROM mentor_time_off mto \
+     WHERE mto.mentor_id = o.mentor_id \
+       AND ((mto.starts_at <= NOW() AND mto.ends_at > NOW()) \
+         OR (o.occurrence_at >= mto.starts_at AND o.occurrence_at < mto.ends_at)))";
+
+/// Statuts de réservation signalés lorsqu'une absence les recoupe
+const CONFLICTING_STATUSES: [&str; 2] = ["pending", "confirmed"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum TimeOffError {
+    #[error("Time off or booking not found")]
+    NotFound,
+    #[error("Not allowed")]
+    Forbidden,
+    #[error("{0}")]
+    Invalid(String),
+    #[error("Booking does not overlap a time off")]
+    NotInConflict,
+    #[error(transparent)]
+    Transition(#[from] TransitionError),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+fn time_off_from_row(row: &sqlx::postgres::PgRow) -> TimeOff {
+    TimeOff {
+        id: row.try_get("id").unwrap_or_default(),
+        mentor_id: row.try_get("mentor_id").unwrap_or_default(),
+        starts_at: row.try_get("starts_at").unwrap_or_else(|_| Utc::now()),
+        ends_at: row.try_get("ends_at").unwrap_or_else(|_| Utc::now()),
+        reason: row.try_get("reason").ok().flatten(),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Réservations signalées pour une absence, en attente de décision
+async fn conflicts<'e, E: PgExecutor<'e>>(
+    executor: E,
+    time_off_id: &str,
+) -> Result<Vec<TimeOffConflict>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT b.id, b.offer_id, b.mentee_id, b.scheduled_at, b.status, o.topic_slug,
+               EXISTS(
+                   SELECT 1 FROM mentoring_booking_reschedules r
+                   WHERE r.booking_id = b.id AND r.status = 'pending'
+               ) AS reschedule_pending
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.time_off_id = $1 AND b.status = ANY($2)
+        ORDER BY b.scheduled_at ASC
+        "#,
+    )
+    .bind(time_off_id)
+    .bind(&CONFLICTING_STATUSES[..])
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rows
+        .iter()
+        .map(|r| {
+            let status: String = r.try_get("status").unwrap_or_default();
+            let reschedule_pending: bool = r.try_get("reschedule_pending").unwrap_or(false);
+            TimeOffConflict {
+                booking_id: r.try_get("id").unwrap_or_default(),
+                offer_id: r.try_get("offer_id").unwrap_or_default(),
+                topic_slug: r.try_get("topic_slug").unwrap_or_default(),
+                mentee_id: r.try_get("mentee_id").unwrap_or_default(),
+                scheduled_at: r.try_get("scheduled_at").unwrap_or_else(|_| Utc::now()),
+                actions: conflict_actions(&status, reschedule_pending),
+                status,
+                reschedule_pending,
+            }
+        })
+        .collect())
+}
+
+/// Déclare une absence et signale les réservations qui la recoupent
+pub async fn create(
+    pool: &PgPool,
+    mentor_id: &str,
+    payload: &CreateTimeOffPayload,
+) -> Result<TimeOffDetail, TimeOffError> {
+    payload
+        .validate(Utc::now())
+        .map_err(TimeOffError::Invalid)?;
+
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentor_time_off (mentor_id, starts_at, ends_at, reason)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(payload.starts_at)
+    .bind(payload.ends_at)
+    .bind(
+        payload
+            .reason
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty()),
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+    let time_off = time_off_from_row(&row);
+
+    let flagged = sqlx::query(
+        r#"
+        UPDATE mentoring_bookings b SET time_off_id = $2
+        FROM mentoring_offers o
+        WHERE o.id = b.offer_id
+          AND o.mentor_id = $1
+          AND b.status = ANY($5)
+          AND b.scheduled_at < $4
+          AND b.scheduled_at + make_interval(mins => o.duration_minutes) > $3
+        "#,
+    )
+    .bind(mentor_id)
+    .bind(&time_off.id)
+    .bind(time_off.starts_at)
+    .bind(time_off.ends_at)
+    .bind(&CONFLICTING_STATUSES[..])
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    // Les mentees des sessions confirmées sont prévenus : un report ou une
+    // annulation remboursée va leur être proposé
+    let conflicts = conflicts(&mut *tx, &time_off.id).await?;
+    for conflict in conflicts
+        .iter()
+        .filter(|c| c.actions.contains(&ConflictAction::Reschedule))
+    {
+        let notification = OutboxMessage::Notification {
+            user_id: conflict.mentee_id.clone(),
+            title: "Ton mentor sera absent".to_string(),
+            message: format!(
+                "Ton mentor sera indisponible pour la session «{}» du {}. \
+                 Il va te proposer un nouvel horaire ou l'annuler avec remboursement.",
+                conflict.topic_slug,
+                conflict.scheduled_at.format("%d/%m/%Y")
+            ),
+            notif_type: "MENTORING_MENTOR_TIME_OFF".to_string(),
+            link: Some(format!("/mentoring/session/{}", conflict.booking_id)),
+            metadata: serde_json::json!({ "amount": 0 }),
+        };
+        outbox::enqueue(&mut *tx, &notification, None).await?;
+    }
+    tx.commit().await?;
+
+    sync_mentor(pool, Some(mentor_id)).await?;
+    info!(
+        "Time off {} declared by mentor {} ({} → {}), {} booking(s) flagged",
+        time_off.id, mentor_id, time_off.starts_at, time_off.ends_at, flagged
+    );
+
+    Ok(TimeOffDetail {
+        time_off,
+        conflicts,
+    })
+}
+
+/// Absences en cours et à venir du mentor
+pub async fn list(pool: &PgPool, mentor_id: &str) -> Result<Vec<TimeOff>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT * FROM mentor_time_off WHERE mentor_id = $1 AND ends_at > NOW()
+         ORDER BY starts_at ASC",
+    )
+    .bind(mentor_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(time_off_from_row).collect())
+}
+
+async fn load(pool: &PgPool, mentor_id: &str, id: &str) -> Result<TimeOff, TimeOffError> {
+    let time_off = sqlx::query("SELECT * FROM mentor_time_off WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(time_off_from_row)
+        .ok_or(TimeOffError::NotFound)?;
+    if time_off.mentor_id != mentor_id {
+        return Err(TimeOffError::Forbidden);
+    }
+    Ok(time_off)
+}
+
+/// Absence avec les réservations restant à traiter
+pub async fn detail(
+    pool: &PgPool,
+    mentor_id: &str,
+    id: &str,
+) -> Result<TimeOffDetail, TimeOffError> {
+    let time_off = load(pool, mentor_id, id).await?;
+    let conflicts = conflicts(pool, &time_off.id).await?;
+    Ok(TimeOffDetail {
+        time_off,
+        conflicts,
+    })
+}
+
+/// Supprime une absence ; ses signalements sont levés par la base
+pub async fn delete(pool: &PgPool, mentor_id: &str, id: &str) -> Result<(), TimeOffError> {
+    load(pool, mentor_id, id).await?;
+    sqlx::query("DELETE FROM mentor_time_off WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    sync_mentor(pool, Some(mentor_id)).await?;
+    info!("Time off {} removed by mentor {}", id, mentor_id);
+    Ok(())
+}
+
+/// Réservation dont le mentor se retire
+#[derive(Debug)]
+pub struct Withdrawal {
+    pub transition: Transition,
+    pub offer_id: String,
+    pub mentee_id: String,
+    pub topic_slug: String,
+    pub scheduled_at: DateTime<Utc>,
+    pub tokens_escrowed: i32,
+}
+
+/// Le mentor se retire d'une session confirmée signalée par son absence, dans
+/// la transaction de l'appelant, qui y ajoute le remboursement et la
+/// notification du mentee.
+pub async fn withdraw(
+    conn: &mut PgConnection,
+    booking_id: &str,
+    mentor_id: &str,
+    reason: Option<&str>,
+) -> Result<Withdrawal, TimeOffError> {
+    let row = sqlx::query(
+        r#"
+        SELECT b.offer_id, b.mentee_id, b.scheduled_at, b.tokens_escrowed, b.time_off_id,
+               o.mentor_id, o.topic_slug
+        FROM mentoring_bookings b
+        JOIN mentoring_offers o ON o.id = b.offer_id
+        WHERE b.id = $1
+        "#,
+    )
+    .bind(booking_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(TimeOffError::NotFound)?;
+
+    let owner: String = row.try_get("mentor_id").unwrap_or_default();
+    if owner != mentor_id {
+        return Err(TimeOffError::Forbidden);
+    }
+    let time_off_id: Option<String> = row.try_get("time_off_id").ok().flatten();
+    if time_off_id.is_none() {
+        return Err(TimeOffError::NotInConflict);
+    }
+
+    let transition = booking_state::apply(
+        conn,
+        booking_id,
+        BookingEvent::Withdraw,
+        Actor::Mentor,
+        Some(mentor_id),
+        serde_json::json!({ "time_off_id": time_off_id, "reason": reason }),
+    )
+    .await?;
+
+    info!(
+        "Mentor {} withdrew from booking {} (time off {})",
+        mentor_id,
+        booking_id,
+        time_off_id.unwrap_or_default()
+    );
+    Ok(Withdrawal {
+        transition,
+        offer_id: row.try_get("offer_id").unwrap_or_default(),
+        mentee_id: row.try_get("mentee_id").unwrap_or_default(),
+        topic_slug: row.try_get("topic_slug").unwrap_or_default(),
+        scheduled_at: row.try_get("scheduled_at").unwrap_or_else(|_| Utc::now()),
+        tokens_escrowed: row.try_get("tokens_escrowed").unwrap_or(0),
+    })
+}
+
+/// Aligne `is_mentor_active` sur les absences, pour un mentor ou pour tous.
+async fn sync_mentor(pool: &PgPool, mentor_id: Option<&str>) -> Result<u64, sqlx::Error> {
+    let current = "EXISTS (SELECT 1 FROM mentor_time_off t \
+         WHERE t.mentor_id = users.id AND t.starts_at <= NOW() AND t.ends_at > NOW())";
+
+    let paused = sqlx::query(&format!(
+        "UPDATE users SET is_mentor_active = false, mentor_paused_by_time_off = true,
+                updated_at = NOW()
+         WHERE is_mentor_active = true AND ($1::text IS NULL OR id = $1) AND {}",
+        current
+    ))
+    .bind(mentor_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let resumed = sqlx::query(&format!(
+        "UPDATE users SET is_mentor_active = true, mentor_paused_by_time_off = false,
+                updated_at = NOW()
+         WHERE mentor_paused_by_time_off = true AND ($1::text IS NULL OR id = $1)
+           AND NOT {}",
+        current
+    ))
+    .bind(mentor_id)
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok(paused + resumed)
+}
+
+/// Tâche périodique : désactive les mentors dont l'absence commence et
+/// réactive ceux dont l'absence est terminée. Retourne le nombre de mentors
+/// mis à jour.
+pub async fn run_schedule_sync(pool: &PgPool) -> u64 {
+    match sync_mentor(pool, None).await {
+        Ok(n) => n,
+        Err(e) => {
+            tracing::error!("Mentor availability sync failed: {}", e);
+            0
+        }
+    }
+}
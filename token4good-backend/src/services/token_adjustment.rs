@@ -0,0 +1,351 @@
+//! Ajustements manuels de solde T4G par les admins
+//!
+//! - Crédit ou débit avec code motif et justification obligatoires
+//! - Au-delà de `ADMIN_ADJUSTMENT_APPROVAL_THRESHOLD` T4G (valeur absolue),
+//!   approbation par un second admin avant écriture au ledger
+//! - Piste d'audit append-only : demandes, approbations, rejets, applications
+
+use sqlx::{PgConnection, PgPool};
+use tracing::info;
+
+use crate::models::token_adjustment::{
+    AdjustmentEvent, AdjustmentStatus, CreateAdjustmentPayload, TokenAdjustment,
+};
+use crate::services::token_ledger;
+
+// ── Configuration ──────────────────────────────────────────────────────────
+
+/// Seuil par défaut au-delà duquel un second admin doit approuver
+const DEFAULT_APPROVAL_THRESHOLD: i64 = 100;
+
+pub fn approval_threshold() -> i64 {
+    std::env::var("ADMIN_ADJUSTMENT_APPROVAL_THRESHOLD")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_APPROVAL_THRESHOLD)
+}
+
+// ── Erreurs ────────────────────────────────────────────────────────────────
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdjustmentError {
+    #[error("Adjustment not found")]
+    NotFound,
+    #[error("Invalid adjustment: {0}")]
+    Validation(String),
+    #[error("Adjustment is {0}, expected pending_approval")]
+    InvalidState(String),
+    #[error("An admin cannot review their own adjustment")]
+    SelfReview,
+    #[error("Insufficient balance for debit")]
+    InsufficientBalance,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+// ── Demande ────────────────────────────────────────────────────────────────
+
+/// Enregistre une demande d'ajustement. Sous le seuil, elle est appliquée
+/// immédiatement ; au-delà, elle attend l'approbation d'un autre admin.
+pub async fn request_adjustment(
+    pool: &PgPool,
+    admin_id: &str,
+    payload: &CreateAdjustmentPayload,
+) -> Result<TokenAdjustment, AdjustmentError> {
+    payload.validate().map_err(AdjustmentError::Validation)?;
+    let requires_approval = payload.requires_approval(approval_threshold());
+
+    let mut tx = pool.begin().await?;
+
+    let user_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+        .bind(&payload.user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if !user_exists {
+        return Err(AdjustmentError::Validation(
+            "utilisateur inconnu".to_string(),
+        ));
+    }
+
+    let adjustment = sqlx::query_as::<_, TokenAdjustment>(
+        r#"
+        INSERT INTO t4g_token_adjustments
+            (user_id, amount, reason_code, justification, requires_approval, requested_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(&payload.user_id)
+    .bind(payload.amount)
+    .bind(payload.reason_code.to_string())
+    .bind(payload.justification.trim())
+    .bind(requires_approval)
+    .bind(admin_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_event(&mut tx, &adjustment.id, admin_id, "requested", None).await?;
+
+    let adjustment = if requires_approval {
+        adjustment
+    } else {
+        apply(&mut tx, &adjustment, admin_id).await?
+    };
+
+    tx.commit().await?;
+
+    info!(
+        "Admin {} requested adjustment {} ({:+} T4G for {}, {})",
+        admin_id, adjustment.id, adjustment.amount, adjustment.user_id, adjustment.status
+    );
+    Ok(adjustment)
+}
+
+// ── Revue quatre yeux ──────────────────────────────────────────────────────
+
+/// Approuve un ajustement en attente et l'écrit au ledger.
+pub async fn approve_adjustment(
+    pool: &PgPool,
+    adjustment_id: &str,
+    admin_id: &str,
+    comment: Option<&str>,
+) -> Result<TokenAdjustment, AdjustmentError> {
+    let mut tx = pool.begin().await?;
+    let adjustment = lock_pending(&mut tx, adjustment_id, admin_id).await?;
+
+    sqlx::query(
+        r#"
+        UPDATE t4g_token_adjustments
+        SET reviewed_by = $1, review_comment = $2, reviewed_at = NOW()
+        WHERE id = $3
+        "#,
+    )
+    .bind(admin_id)
+    .bind(comment)
+    .bind(adjustment_id)
+    .execute(&mut *tx)
+    .await?;
+    record_event(&mut tx, adjustment_id, admin_id, "approved", comment).await?;
+
+    let adjustment = apply(&mut tx, &adjustment, admin_id).await?;
+    tx.commit().await?;
+
+    info!(
+        "Admin {} approved adjustment {} requested by {}",
+        admin_id, adjustment.id, adjustment.requested_by
+    );
+    Ok(adjustment)
+}
+
+/// Rejette un ajustement en attente ; rien n'est écrit au ledger.
+pub async fn reject_adjustment(
+    pool: &PgPool,
+    adjustment_id: &str,
+    admin_id: &str,
+    comment: Option<&str>,
+) -> Result<TokenAdjustment, AdjustmentError> {
+    let mut tx = pool.begin().await?;
+    lock_pending(&mut tx, adjustment_id, admin_id).await?;
+
+    let adjustment = sqlx::query_as::<_, TokenAdjustment>(
+        r#"
+        UPDATE t4g_token_adjustments
+        SET status = 'rejected', reviewed_by = $1, review_comment = $2, reviewed_at = NOW()
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(admin_id)
+    .bind(comment)
+    .bind(adjustment_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    record_event(&mut tx, adjustment_id, admin_id, "rejected", comment).await?;
+
+    tx.commit().await?;
+
+    info!("Admin {} rejected adjustment {}", admin_id, adjustment_id);
+    Ok(adjustment)
+}
+
+async fn lock_pending(
+    conn: &mut PgConnection,
+    adjustment_id: &str,
+    admin_id: &str,
+) -> Result<TokenAdjustment, AdjustmentError> {
+    let adjustment = sqlx::query_as::<_, TokenAdjustment>(
+        "SELECT * FROM t4g_token_adjustments WHERE id = $1 FOR UPDATE",
+    )
+    .bind(adjustment_id)
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or(AdjustmentError::NotFound)?;
+
+    if adjustment.status != AdjustmentStatus::PendingApproval.to_string() {
+        return Err(AdjustmentError::InvalidState(adjustment.status));
+    }
+    if adjustment.requested_by == admin_id {
+        return Err(AdjustmentError::SelfReview);
+    }
+    Ok(adjustment)
+}
+
+// ── Écriture au ledger ─────────────────────────────────────────────────────
+
+async fn apply(
+    conn: &mut PgConnection,
+    adjustment: &TokenAdjustment,
+    actor_id: &str,
+) -> Result<TokenAdjustment, AdjustmentError> {
+    let amount = adjustment.amount as i64;
+
+    if amount < 0 {
+        // Même verrou que les réservations : un débit admin ne peut pas
+        // croiser un débit de séquestre
+        token_ledger::lock_balance(&mut *conn, &adjustment.user_id).await?;
+        let balance = token_ledger::spendable_balance(&mut *conn, &adjustment.user_id).await?;
+        if balance < -amount {
+            return Err(AdjustmentError::InsufficientBalance);
+        }
+    }
+
+    let transaction_id: String = sqlx::query_scalar(
+        r#"
+        INSERT INTO t4g_token_transactions
+            (id, user_id, action_type, tokens, description, metadata, impact_score)
+        VALUES (gen_random_uuid()::text, $1, 'admin_adjustment', $2, $3, $4, 0.0)
+        RETURNING id
+        "#,
+    )
+    .bind(&adjustment.user_id)
+    .bind(adjustment.amount)
+    .bind(format!("Ajustement manuel ({})", adjustment.reason_code))
+    .bind(serde_json::json!({
+        "adjustment_id": adjustment.id,
+        "type": "admin_adjustment",
+        "reason_code": adjustment.reason_code,
+        "requested_by": adjustment.requested_by,
+        "approved_by": adjustment.requires_approval.then_some(actor_id),
+    }))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if amount < 0 {
+        token_ledger::consume_lots(&mut *conn, &adjustment.user_id, -amount).await?;
+    }
+
+    let applied = sqlx::query_as::<_, TokenAdjustment>(
+        r#"
+        UPDATE t4g_token_adjustments
+        SET status = 'applied', transaction_id = $1, applied_at = NOW()
+        WHERE id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(&transaction_id)
+    .bind(&adjustment.id)
+    .fetch_one(&mut *conn)
+    .await?;
+    record_event(&mut *conn, &adjustment.id, actor_id, "applied", None).await?;
+
+    sqlx::query(
+        "INSERT INTO notifications (user_id, title, message, type, link, metadata) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&adjustment.user_id)
+    .bind("Ajustement de solde")
+    .bind(format!(
+        "Ton solde a été ajusté de {:+} T4G par l'équipe support.",
+        adjustment.amount
+    ))
+    .bind("TOKEN_ADJUSTMENT")
+    .bind("/wallet")
+    .bind(serde_json::json!({
+        "adjustment_id": adjustment.id,
+        "amount": adjustment.amount,
+        "reason_code": adjustment.reason_code,
+    }))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(applied)
+}
+
+async fn record_event(
+    conn: &mut PgConnection,
+    adjustment_id: &str,
+    actor_id: &str,
+    event: &str,
+    comment: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO t4g_token_adjustment_events (adjustment_id, actor_id, event, comment)
+        VALUES ($1, $2, $3, $4)
+        "#,
+    )
+    .bind(adjustment_id)
+    .bind(actor_id)
+    .bind(event)
+    .bind(comment)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+// ── Lecture ────────────────────────────────────────────────────────────────
+
+pub async fn list_adjustments(
+    pool: &PgPool,
+    status: Option<&str>,
+    user_id: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<TokenAdjustment>, sqlx::Error> {
+    sqlx::query_as::<_, TokenAdjustment>(
+        r#"
+        SELECT * FROM t4g_token_adjustments
+        WHERE ($1::text IS NULL OR status = $1)
+          AND ($2::text IS NULL OR user_id = $2)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(status)
+    .bind(user_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_adjustment(
+    pool: &PgPool,
+    adjustment_id: &str,
+) -> Result<Option<TokenAdjustment>, sqlx::Error> {
+    sqlx::query_as::<_, TokenAdjustment>("SELECT * FROM t4g_token_adjustments WHERE id = $1")
+        .bind(adjustment_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Piste d'audit, globale ou limitée à un ajustement.
+pub async fn list_events(
+    pool: &PgPool,
+    adjustment_id: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AdjustmentEvent>, sqlx::Error> {
+    sqlx::query_as::<_, AdjustmentEvent>(
+        r#"
+        SELECT * FROM t4g_token_adjustment_events
+        WHERE ($1::text IS NULL OR adjustment_id = $1)
+        ORDER BY created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(adjustment_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
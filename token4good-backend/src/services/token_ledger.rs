@@ -0,0 +1,384 @@
+//! Lots de tokens T4G : expiration, vesting et consommation FIFO
+//!
+//! Fonctions libres sur `PgPool`, sur le modèle de `mentoring_completion`.
+//!
+//! - Crédit d'un grant avec conditions (vesting linéaire, cliff, expiration)
+//! - Solde dépensable = solde ledger − tokens non acquis ou expirés
+//! - Consommation FIFO des lots lors d'un débit
+//! - Job quotidien : expiration des lots échus (retour à la trésorerie)
+//!   et préavis J-7 aux utilisateurs
+
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::{error, info, warn};
+
+use crate::models::token_lot::{allocate_fifo, GrantTerms, TokenLot};
+
+// ── Constantes métier ──────────────────────────────────────────────────────
+
+/// Préavis avant expiration d'un lot (jours)
+const EXPIRY_NOTICE_DAYS: i64 = 7;
+
+/// Espace de clés des verrous de solde (première moitié du verrou à deux entiers)
+const BALANCE_LOCK_NAMESPACE: i32 = 0x7434_6261; // "t4ba"
+
+// ── Crédit avec conditions ─────────────────────────────────────────────────
+
+/// Crédite `amount` T4G à `user_id` et crée le lot associé si des conditions
+/// de vesting ou d'expiration sont fournies. Retourne l'id de la transaction.
+#[allow(clippy::too_many_arguments)]
+pub async fn grant_tokens(
+    pool: &PgPool,
+    user_id: &str,
+    action_type: &str,
+    amount: i64,
+    description: &str,
+    metadata: Option<serde_json::Value>,
+    impact_score: f64,
+    terms: &GrantTerms,
+) -> Result<String, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let transaction_id: String = sqlx::query_scalar(
+        r#"
+        INSERT INTO t4g_token_transactions
+            (id, user_id, action_type, tokens, description, metadata, impact_score)
+        VALUES (gen_random_uuid()::text, $1, $2, $3, $4, $5, $6)
+        RETURNING id
+        "#,
+    )
+    .bind(user_id)
+    .bind(action_type)
+    .bind(amount)
+    .bind(description)
+    .bind(metadata.unwrap_or_else(|| serde_json::json!({})))
+    .bind(impact_score)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if amount > 0 && !terms.is_unrestricted() {
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_token_lots
+                (user_id, transaction_id, amount, vesting_start, vesting_end, expires_at)
+            VALUES ($1, $2, $3, COALESCE($4, NOW()), $5, $6)
+            "#,
+        )
+        .bind(user_id)
+        .bind(&transaction_id)
+        .bind(amount as i32)
+        .bind(terms.vesting_start)
+        .bind(terms.vesting_end)
+        .bind(terms.expires_at)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    info!(
+        "Grant {} T4G to {} ({}, expires: {})",
+        amount,
+        user_id,
+        action_type,
+        terms
+            .expires_at
+            .map(|d| d.to_rfc3339())
+            .unwrap_or_else(|| "never".to_string())
+    );
+    Ok(transaction_id)
+}
+
+// ── Lecture des lots ───────────────────────────────────────────────────────
+
+fn lot_from_row(row: &sqlx::postgres::PgRow) -> TokenLot {
+    TokenLot {
+        id: row.try_get("id").unwrap_or_default(),
+        user_id: row.try_get("user_id").unwrap_or_default(),
+        transaction_id: row.try_get("transaction_id").unwrap_or_default(),
+        amount: row.try_get("amount").unwrap_or(0),
+        consumed: row.try_get("consumed").unwrap_or(0),
+        vesting_start: row.try_get("vesting_start").unwrap_or_else(|_| Utc::now()),
+        vesting_end: row.try_get("vesting_end").ok().flatten(),
+        expires_at: row.try_get("expires_at").ok().flatten(),
+        expiry_notified_at: row.try_get("expiry_notified_at").ok().flatten(),
+        expired_at: row.try_get("expired_at").ok().flatten(),
+        expired_amount: row.try_get("expired_amount").unwrap_or(0),
+        created_at: row.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Lots non expirés et non épuisés d'un utilisateur.
+pub async fn active_lots(
+    conn: &mut PgConnection,
+    user_id: &str,
+) -> Result<Vec<TokenLot>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM t4g_token_lots
+        WHERE user_id = $1 AND expired_at IS NULL AND consumed < amount
+        ORDER BY created_at ASC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    Ok(rows.iter().map(lot_from_row).collect())
+}
+
+/// Somme des tokens crédités mais pas encore dépensables (vesting en cours, lots échus).
+pub async fn locked_balance(conn: &mut PgConnection, user_id: &str) -> Result<i64, sqlx::Error> {
+    let now = Utc::now();
+    Ok(active_lots(conn, user_id)
+        .await?
+        .iter()
+        .map(|l| l.locked_at(now))
+        .sum())
+}
+
+/// Solde dépensable : solde du ledger moins la part verrouillée des lots.
+pub async fn spendable_balance(conn: &mut PgConnection, user_id: &str) -> Result<i64, sqlx::Error> {
+    let ledger: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(tokens), 0)::BIGINT FROM t4g_token_transactions WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let locked = locked_balance(conn, user_id).await?;
+    Ok((ledger - locked).max(0))
+}
+
+/// Verrouille le solde de l'utilisateur jusqu'à la fin de la transaction
+/// courante : le contrôle du solde dépensable et le débit qui le suit ne
+/// peuvent pas s'entrelacer avec ceux d'une autre réservation.
+pub async fn lock_balance(conn: &mut PgConnection, user_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock($1, hashtext($2))")
+        .bind(BALANCE_LOCK_NAMESPACE)
+        .bind(user_id)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+// ── Consommation FIFO ──────────────────────────────────────────────────────
+
+/// Impute un débit de `amount` T4G sur les lots de l'utilisateur, du plus
+/// ancien au plus récent. À appeler dans la même transaction que le débit.
+pub async fn consume_lots(
+    conn: &mut PgConnection,
+    user_id: &str,
+    amount: i64,
+) -> Result<(), sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM t4g_token_lots
+        WHERE user_id = $1 AND expired_at IS NULL AND consumed < amount
+        ORDER BY created_at ASC
+        FOR UPDATE
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let lots: Vec<TokenLot> = rows.iter().map(lot_from_row).collect();
+    for (lot_id, take) in allocate_fifo(&lots, amount, Utc::now()) {
+        sqlx::query("UPDATE t4g_token_lots SET consumed = consumed + $1 WHERE id = $2")
+            .bind(take as i32)
+            .bind(&lot_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+// ── Job d'expiration ───────────────────────────────────────────────────────
+
+/// Expire les lots échus : la part restante est débitée de l'utilisateur
+/// (`token_expiry`) et reversée à la trésorerie. Retourne le nombre de lots expirés.
+pub async fn run_token_expiry(pool: &PgPool) -> u64 {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM t4g_token_lots
+        WHERE expired_at IS NULL AND expires_at IS NOT NULL AND expires_at <= NOW()
+        ORDER BY expires_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await;
+
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Token expiry query failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut expired = 0u64;
+    for lot in rows.iter().map(lot_from_row) {
+        match expire_lot(pool, &lot).await {
+            Ok(amount) => {
+                expired += 1;
+                info!(
+                    "Lot {} expired: {} T4G from {} returned to treasury",
+                    lot.id, amount, lot.user_id
+                );
+            }
+            Err(e) => error!("Failed to expire lot {}: {}", lot.id, e),
+        }
+    }
+    expired
+}
+
+async fn expire_lot(pool: &PgPool, lot: &TokenLot) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Relire le lot verrouillé : un débit concurrent a pu le consommer
+    let Some(row) =
+        sqlx::query("SELECT * FROM t4g_token_lots WHERE id = $1 AND expired_at IS NULL FOR UPDATE")
+            .bind(&lot.id)
+            .fetch_optional(&mut *tx)
+            .await?
+    else {
+        return Ok(0);
+    };
+    let lot = lot_from_row(&row);
+
+    // Ne jamais rendre le solde négatif si des débits ont contourné les lots
+    let ledger: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(tokens), 0)::BIGINT FROM t4g_token_transactions WHERE user_id = $1",
+    )
+    .bind(&lot.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    let amount = lot.remaining().min(ledger.max(0));
+
+    if amount > 0 {
+        let transaction_id: String = sqlx::query_scalar(
+            r#"
+                INSERT INTO t4g_token_transactions
+                    (id, user_id, action_type, tokens, description, metadata, impact_score)
+                VALUES (gen_random_uuid()::text, $1, 'token_expiry', $2, $3, $4, 0.0)
+                RETURNING id
+                "#,
+        )
+        .bind(&lot.user_id)
+        .bind(-amount)
+        .bind("Expiration de tokens non utilisés")
+        .bind(serde_json::json!({
+            "lot_id": lot.id,
+            "type": "token_expiry",
+            "source_transaction_id": lot.transaction_id,
+        }))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO t4g_treasury_movements (amount, reason, user_id, lot_id, transaction_id)
+            VALUES ($1, 'token_expiry', $2, $3, $4)
+            "#,
+        )
+        .bind(amount as i32)
+        .bind(&lot.user_id)
+        .bind(&lot.id)
+        .bind(&transaction_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query("UPDATE t4g_token_lots SET expired_at = NOW(), expired_amount = $1 WHERE id = $2")
+        .bind(amount as i32)
+        .bind(&lot.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(amount)
+}
+
+// ── Préavis d'expiration ───────────────────────────────────────────────────
+
+/// Notifie une seule fois chaque utilisateur dont un lot expire dans moins de
+/// `EXPIRY_NOTICE_DAYS` jours et contient encore des tokens.
+pub async fn send_expiry_warnings(pool: &PgPool) -> u64 {
+    let rows = sqlx::query(
+        r#"
+        SELECT * FROM t4g_token_lots
+        WHERE expired_at IS NULL
+          AND expiry_notified_at IS NULL
+          AND expires_at IS NOT NULL
+          AND expires_at > NOW()
+          AND expires_at <= NOW() + make_interval(days => $1)
+          AND consumed < amount
+        "#,
+    )
+    .bind(EXPIRY_NOTICE_DAYS as i32)
+    .fetch_all(pool)
+    .await;
+
+    let rows = match rows {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Token expiry warnings query failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut sent = 0u64;
+    for lot in rows.iter().map(lot_from_row) {
+        let Some(expires_at) = lot.expires_at else {
+            continue;
+        };
+        let amount = lot.remaining();
+        if amount <= 0 {
+            continue;
+        }
+
+        if let Err(e) = notify_expiry(pool, &lot, amount, expires_at).await {
+            warn!("Failed to send expiry warning for lot {}: {}", lot.id, e);
+            continue;
+        }
+        sent += 1;
+    }
+    sent
+}
+
+async fn notify_expiry(
+    pool: &PgPool,
+    lot: &TokenLot,
+    amount: i64,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "INSERT INTO notifications (user_id, title, message, type, link, metadata) VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(&lot.user_id)
+    .bind("Tokens bientôt expirés")
+    .bind(format!(
+        "{} T4G expireront le {}. Utilise-les avant cette date !",
+        amount,
+        expires_at.format("%d/%m/%Y")
+    ))
+    .bind("TOKEN_EXPIRY_WARNING")
+    .bind("/wallet")
+    .bind(serde_json::json!({
+        "lot_id": lot.id,
+        "amount": amount,
+        "expires_at": expires_at,
+    }))
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query("UPDATE t4g_token_lots SET expiry_notified_at = NOW() WHERE id = $1")
+        .bind(&lot.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
@@ -0,0 +1,475 @@
+//! Listes d'attente des offres de mentoring
+//!
+//! Un mentee s'inscrit sur une offre ou sur un couple mentor/sujet. Quand une
+//! offre redevient disponible (refus, annulation, nouveau créneau, nouvelle
+//! offre), elle est proposée aux inscrits dans l'ordre d'inscription : le
+//! premier dont le budget et le solde couvrent le coût reçoit une option
+//! exclusive de `HOLD_DURATION_MINUTES`. Pendant l'option, seule sa
+//! réservation est acceptée sur une offre individuelle ; la réservation passe
+//! par le parcours normal (vérification du solde, débit du séquestre). Une
+//! option expirée ou abandonnée passe à l'inscrit suivant. Les offres
+//! privées (réponses à une demande mentee) n'entrent pas dans ce circuit.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgConnection, PgPool, Row};
+use tracing::{error, info};
+
+use crate::models::outbox::OutboxMessage;
+use crate::models::waitlist::{JoinWaitlistPayload, WaitlistEntry, WaitlistTarget};
+use crate::services::{mentoring_completion, outbox};
+
+/// Durée de l'option de réservation proposée à un inscrit
+pub const HOLD_DURATION_MINUTES: i64 = 120;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WaitlistError {
+    #[error("Waitlist entry, offer or mentor not found")]
+    NotFound,
+    #[error("Not the owner of this waitlist entry")]
+    Forbidden,
+    #[error("Already waiting for this target")]
+    AlreadyWaiting,
+    #[error("Invalid waitlist request: {0}")]
+    Invalid(String),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Un inscrit peut recevoir l'option si son budget et son solde couvrent le coût.
+pub fn is_eligible(max_tokens: Option<i32>, token_cost: i32, balance: i64) -> bool {
+    max_tokens.is_none_or(|max| max >= token_cost) && balance >= token_cost as i64
+}
+
+fn entry_from_row(r: &sqlx::postgres::PgRow) -> WaitlistEntry {
+    WaitlistEntry {
+        id: r.try_get("id").unwrap_or_default(),
+        mentee_id: r.try_get("mentee_id").unwrap_or_default(),
+        offer_id: r.try_get("offer_id").ok().flatten(),
+        mentor_id: r.try_get("mentor_id").ok().flatten(),
+        topic_slug: r.try_get("topic_slug").ok().flatten(),
+        max_tokens: r.try_get("max_tokens").ok().flatten(),
+        status: r.try_get("status").unwrap_or_default(),
+        hold_offer_id: r.try_get("hold_offer_id").ok().flatten(),
+        hold_expires_at: r.try_get("hold_expires_at").ok().flatten(),
+        position: r.try_get("position").ok().flatten(),
+        created_at: r.try_get("created_at").unwrap_or_else(|_| Utc::now()),
+        updated_at: r.try_get("updated_at").unwrap_or_else(|_| Utc::now()),
+    }
+}
+
+/// Avis de liste d'attente, inscrit dans l'outbox avec le changement d'option
+async fn notify(
+    conn: &mut PgConnection,
+    user_id: &str,
+    title: &str,
+    message: &str,
+    kind: &str,
+    link: &str,
+) -> Result<(), sqlx::Error> {
+    let notification = OutboxMessage::Notification {
+        user_id: user_id.to_string(),
+        title: title.to_string(),
+        message: message.to_string(),
+        notif_type: kind.to_string(),
+        link: Some(link.to_string()),
+        metadata: serde_json::json!({}),
+    };
+    outbox::enqueue(conn, &notification, None).await?;
+    Ok(())
+}
+
+/// Inscrit `mentee_id` ; si l'offre visée est déjà disponible, l'option lui
+/// est proposée immédiatement.
+pub async fn join(
+    pool: &PgPool,
+    mentee_id: &str,
+    payload: &JoinWaitlistPayload,
+) -> Result<WaitlistEntry, WaitlistError> {
+    let target = payload.target().map_err(WaitlistError::Invalid)?;
+
+    let (offer_id, mentor_id, topic_slug) = match &target {
+        WaitlistTarget::Offer(offer_id) => {
+            let owner: Option<String> =
+                sqlx::query_scalar("SELECT mentor_id FROM mentoring_offers WHERE id = $1")
+                    .bind(offer_id)
+                    .fetch_optional(pool)
+                    .await?;
+            if owner.ok_or(WaitlistError::NotFound)? == mentee_id {
+                return Err(WaitlistError::Invalid(
+                    "impossible de s'inscrire sur sa propre offre".to_string(),
+                ));
+            }
+            (Some(offer_id.as_str()), None, None)
+        }
+        WaitlistTarget::MentorTopic {
+            mentor_id,
+            topic_slug,
+        } => {
+            if mentor_id == mentee_id {
+                return Err(WaitlistError::Invalid(
+                    "impossible de s'inscrire sur ses propres offres".to_string(),
+                ));
+            }
+            let exists: bool =
+                sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE id = $1)")
+                    .bind(mentor_id)
+                    .fetch_one(pool)
+                    .await?;
+            if !exists {
+                return Err(WaitlistError::NotFound);
+            }
+            (None, Some(mentor_id.as_str()), Some(topic_slug.as_str()))
+        }
+    };
+
+    let row = sqlx::query(
+        r#"
+        INSERT INTO mentoring_waitlist_entries (mentee_id, offer_id, mentor_id, topic_slug, max_tokens)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(mentee_id)
+    .bind(offer_id)
+    .bind(mentor_id)
+    .bind(topic_slug)
+    .bind(payload.max_tokens)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match &e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => WaitlistError::AlreadyWaiting,
+        _ => WaitlistError::Database(e),
+    })?;
+    let entry = entry_from_row(&row);
+    info!("Mentee {} joined waitlist {}", mentee_id, entry.id);
+
+    // Offres déjà disponibles pour cette cible
+    let open_offers: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT o.id FROM mentoring_offers o
+        WHERE o.status = 'open' AND o.request_id IS NULL
+          AND (o.occurrence_at IS NULL OR o.occurrence_at > NOW())
+          AND (o.id = $1 OR (o.mentor_id = $2 AND o.topic_slug = $3))
+        ORDER BY o.created_at
+        "#,
+    )
+    .bind(offer_id)
+    .bind(mentor_id)
+    .bind(topic_slug)
+    .fetch_all(pool)
+    .await?;
+    for open_offer in open_offers {
+        if offer_available(pool, &open_offer).await?.is_some() {
+            break;
+        }
+    }
+
+    get_entry(pool, &entry.id).await
+}
+
+async fn get_entry(pool: &PgPool, entry_id: &str) -> Result<WaitlistEntry, WaitlistError> {
+    let rows = sqlx::query(&format!("{} WHERE w.id = $1", ENTRY_SELECT))
+        .bind(entry_id)
+        .fetch_optional(pool)
+        .await?;
+    rows.map(|r| entry_from_row(&r))
+        .ok_or(WaitlistError::NotFound)
+}
+
+/// Inscriptions avec leur rang parmi les inscrits en attente sur la même cible
+const ENTRY_SELECT: &str = r#"
+    SELECT w.*,
+           CASE WHEN w.status = 'waiting' THEN (
+               SELECT COUNT(*) FROM mentoring_waitlist_entries x
+               WHERE x.status = 'waiting'
+                 AND x.created_at <= w.created_at
+                 AND x.offer_id IS NOT DISTINCT FROM w.offer_id
+                 AND x.mentor_id IS NOT DISTINCT FROM w.mentor_id
+                 AND x.topic_slug IS NOT DISTINCT FROM w.topic_slug
+           ) END AS position
+    FROM mentoring_waitlist_entries w
+"#;
+
+/// Inscriptions actives et récentes d'un mentee
+pub async fn list_for_mentee(
+    pool: &PgPool,
+    mentee_id: &str,
+) -> Result<Vec<WaitlistEntry>, sqlx::Error> {
+    let rows = sqlx::query(&format!(
+        "{} WHERE w.mentee_id = $1 AND (w.status IN ('waiting', 'offered') OR w.updated_at > NOW() - INTERVAL '30 days')
+         ORDER BY w.created_at DESC",
+        ENTRY_SELECT
+    ))
+    .bind(mentee_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.iter().map(entry_from_row).collect())
+}
+
+/// Quitte la liste ; une option en cours passe à l'inscrit suivant.
+pub async fn leave(pool: &PgPool, entry_id: &str, mentee_id: &str) -> Result<(), WaitlistError> {
+    let row = sqlx::query(
+        "SELECT mentee_id, status, hold_offer_id FROM mentoring_waitlist_entries WHERE id = $1",
+    )
+    .bind(entry_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(WaitlistError::NotFound)?;
+    let owner: String = row.try_get("mentee_id").unwrap_or_default();
+    if owner != mentee_id {
+        return Err(WaitlistError::Forbidden);
+    }
+
+    let released: Option<Option<String>> = sqlx::query_scalar(
+        r#"
+        UPDATE mentoring_waitlist_entries w
+        SET status = 'left', updated_at = NOW()
+        FROM (SELECT id, status AS prev_status FROM mentoring_waitlist_entries WHERE id = $1) p
+        WHERE w.id = p.id AND w.status IN ('waiting', 'offered')
+        RETURNING CASE WHEN p.prev_status = 'offered' THEN w.hold_offer_id END
+        "#,
+    )
+    .bind(entry_id)
+    .fetch_optional(pool)
+    .await?;
+
+    if let Some(Some(offer_id)) = released {
+        offer_available(pool, &offer_id).await?;
+    }
+    Ok(())
+}
+
+/// Inscrit détenant l'option en cours sur une offre individuelle
+pub async fn active_hold(
+    conn: &mut PgConnection,
+    offer_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        SELECT w.mentee_id FROM mentoring_waitlist_entries w
+        JOIN mentoring_offers o ON o.id = w.hold_offer_id
+        WHERE w.hold_offer_id = $1 AND w.status = 'offered'
+          AND w.hold_expires_at > NOW() AND o.capacity = 1
+        "#,
+    )
+    .bind(offer_id)
+    .fetch_optional(conn)
+    .await
+}
+
+/// Clôt les inscriptions du mentee satisfaites par sa réservation sur l'offre
+pub async fn mark_claimed(
+    conn: &mut PgConnection,
+    offer_id: &str,
+    mentee_id: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE mentoring_waitlist_entries
+        SET status = 'claimed', updated_at = NOW()
+        WHERE mentee_id = $2
+          AND ((status = 'offered' AND hold_offer_id = $1)
+               OR (status = 'waiting' AND offer_id = $1))
+        "#,
+    )
+    .bind(offer_id)
+    .bind(mentee_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// L'offre est disponible : propose une option au premier inscrit éligible.
+/// Retourne le mentee retenu, `None` si l'offre n'est pas ouverte, déjà sous
+/// option, ou si aucun inscrit n'est éligible.
+pub async fn offer_available(pool: &PgPool, offer_id: &str) -> Result<Option<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let Some(offer) = sqlx::query(
+        r#"
+        SELECT mentor_id, topic_slug, token_cost
+        FROM mentoring_offers
+        WHERE id = $1 AND status = 'open' AND request_id IS NULL
+          AND (occurrence_at IS NULL OR occurrence_at > NOW())
+        FOR UPDATE
+        "#,
+    )
+    .bind(offer_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let mentor_id: String = offer.try_get("mentor_id").unwrap_or_default();
+    let topic_slug: String = offer.try_get("topic_slug").unwrap_or_default();
+    let token_cost: i32 = offer.try_get("token_cost").unwrap_or(0);
+
+    let held: bool = sqlx::query_scalar(
+        "SELECT EXISTS(SELECT 1 FROM mentoring_waitlist_entries WHERE hold_offer_id = $1 AND status = 'offered')",
+    )
+    .bind(offer_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if held {
+        return Ok(None);
+    }
+
+    let candidates = sqlx::query(
+        r#"
+        SELECT id, mentee_id, max_tokens FROM mentoring_waitlist_entries
+        WHERE status = 'waiting'
+          AND mentee_id <> $2
+          AND (offer_id = $1 OR (offer_id IS NULL AND mentor_id = $2 AND topic_slug = $3))
+        ORDER BY created_at
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(offer_id)
+    .bind(&mentor_id)
+    .bind(&topic_slug)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for candidate in candidates {
+        let entry_id: String = candidate.try_get("id").unwrap_or_default();
+        let mentee_id: String = candidate.try_get("mentee_id").unwrap_or_default();
+        let max_tokens: Option<i32> = candidate.try_get("max_tokens").ok().flatten();
+
+        let balance = mentoring_completion::check_balance(&mut tx, &mentee_id, 0)
+            .await
+            .unwrap_or(0);
+        if !is_eligible(max_tokens, token_cost, balance) {
+            continue;
+        }
+
+        let expires_at: DateTime<Utc> = Utc::now() + Duration::minutes(HOLD_DURATION_MINUTES);
+        sqlx::query(
+            r#"
+            UPDATE mentoring_waitlist_entries
+            SET status = 'offered', hold_offer_id = $2, hold_expires_at = $3, updated_at = NOW()
+            WHERE id = $1
+            "#,
+        )
+        .bind(&entry_id)
+        .bind(offer_id)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        notify(
+            &mut tx,
+            &mentee_id,
+            "Une place s'est libérée",
+            &format!(
+                "Une session «{}» est disponible pour toi pendant {}h. Réserve-la avant qu'elle ne passe au suivant.",
+                topic_slug,
+                HOLD_DURATION_MINUTES / 60
+            ),
+            "MENTORING_WAITLIST_OFFERED",
+            &format!("/mentoring/offers/{}", offer_id),
+        )
+        .await?;
+        tx.commit().await?;
+        info!(
+            "Waitlist hold on offer {} granted to mentee {} until {}",
+            offer_id, mentee_id, expires_at
+        );
+        return Ok(Some(mentee_id));
+    }
+
+    Ok(None)
+}
+
+/// Expire les options échues et propose les offres disponibles aux inscrits.
+/// Retourne le nombre d'options accordées.
+pub async fn run_waitlist_sweep(pool: &PgPool) -> u64 {
+    // Options échues et avis aux inscrits, dans une même transaction
+    let expire = async {
+        let mut tx = pool.begin().await?;
+        let expired = sqlx::query(
+            r#"
+            UPDATE mentoring_waitlist_entries
+            SET status = 'expired', updated_at = NOW()
+            WHERE status = 'offered' AND hold_expires_at <= NOW()
+            RETURNING mentee_id, hold_offer_id
+            "#,
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        for row in &expired {
+            let mentee_id: String = row.try_get("mentee_id").unwrap_or_default();
+            let offer_id: Option<String> = row.try_get("hold_offer_id").ok().flatten();
+            notify(
+                &mut tx,
+                &mentee_id,
+                "Option de réservation expirée",
+                "La session qui t'était réservée est passée à la personne suivante.",
+                "MENTORING_WAITLIST_EXPIRED",
+                &offer_id
+                    .map(|id| format!("/mentoring/offers/{}", id))
+                    .unwrap_or_else(|| "/mentoring/find".to_string()),
+            )
+            .await?;
+        }
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>(())
+    }
+    .await;
+    if let Err(e) = expire {
+        error!("Waitlist hold expiry failed: {}", e);
+        return 0;
+    }
+
+    // Offres ouvertes sans option en cours, avec au moins un inscrit en attente
+    let offers: Vec<String> = match sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT o.id FROM mentoring_offers o
+        JOIN mentoring_waitlist_entries w
+          ON w.status = 'waiting'
+         AND (w.offer_id = o.id
+              OR (w.offer_id IS NULL AND w.mentor_id = o.mentor_id AND w.topic_slug = o.topic_slug))
+        WHERE o.status = 'open' AND o.request_id IS NULL
+          AND (o.occurrence_at IS NULL OR o.occurrence_at > NOW())
+          AND NOT EXISTS (
+              SELECT 1 FROM mentoring_waitlist_entries h
+              WHERE h.hold_offer_id = o.id AND h.status = 'offered'
+          )
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    {
+        Ok(ids) => ids,
+        Err(e) => {
+            error!("Waitlist sweep query failed: {}", e);
+            return 0;
+        }
+    };
+
+    let mut granted = 0;
+    for offer_id in offers {
+        match offer_available(pool, &offer_id).await {
+            Ok(Some(_)) => granted += 1,
+            Ok(None) => {}
+            Err(e) => error!("Waitlist hold on offer {} failed: {}", offer_id, e),
+        }
+    }
+    granted
+}
+
+// ============================================================
+// Tests
+// ============================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eligibility_respects_budget_and_balance() {
+        assert!(is_eligible(None, 30, 30));
+        assert!(is_eligible(Some(40), 30, 100));
+        assert!(!is_eligible(Some(20), 30, 100));
+        assert!(!is_eligible(None, 30, 29));
+        assert!(is_eligible(Some(0), 0, 0));
+    }
+}
@@ -1,4 +1,3 @@
-use reqwest;
 use serde_json::json;
 use std::time::Duration;
 use token4good_backend::services::rgb_native::RGBNativeService;
@@ -13,7 +12,7 @@ async fn test_health_check() {
     sleep(Duration::from_secs(2)).await;
 
     let client = reqwest::Client::new();
-    let response = client.get(&format!("{}/health", BASE_URL)).send().await;
+    let response = client.get(format!("{}/health", BASE_URL)).send().await;
 
     // Le endpoint health n'existe pas encore, mais on teste la connectivité
     assert!(response.is_ok());
@@ -30,7 +29,7 @@ async fn test_dazeno_auth_flow() {
     });
 
     let response = client
-        .post(&format!("{}/api/auth/dazeno/verify", BASE_URL))
+        .post(format!("{}/api/auth/dazeno/verify", BASE_URL))
         .json(&verify_payload)
         .send()
         .await
@@ -47,7 +46,7 @@ async fn test_lightning_node_info() {
     let client = reqwest::Client::new();
 
     let response = client
-        .get(&format!("{}/api/lightning/node/info", BASE_URL))
+        .get(format!("{}/api/lightning/node/info", BASE_URL))
         .send()
         .await
         .expect("Failed to send request");
@@ -71,7 +70,7 @@ async fn test_user_creation_flow() {
     });
 
     let response = client
-        .post(&format!("{}/api/users", BASE_URL))
+        .post(format!("{}/api/users", BASE_URL))
         .json(&user_payload)
         .send()
         .await
@@ -86,7 +85,7 @@ async fn test_user_creation_flow() {
         // Récupérer l'utilisateur créé
         let user_id = user["id"].as_str().unwrap();
         let get_response = client
-            .get(&format!("{}/api/users/{}", BASE_URL, user_id))
+            .get(format!("{}/api/users/{}", BASE_URL, user_id))
             .send()
             .await
             .expect("Failed to get user");
@@ -109,7 +108,7 @@ async fn test_mentoring_proof_creation() {
     });
 
     let response = client
-        .post(&format!("{}/api/mentoring/requests", BASE_URL))
+        .post(format!("{}/api/mentoring/requests", BASE_URL))
         .json(&request_payload)
         .send()
         .await
@@ -131,7 +130,7 @@ async fn test_mentoring_proof_creation() {
         });
 
         let proof_response = client
-            .post(&format!("{}/api/proofs", BASE_URL))
+            .post(format!("{}/api/proofs", BASE_URL))
             .json(&proof_payload)
             .send()
             .await
@@ -146,7 +145,7 @@ async fn test_mentoring_proof_creation() {
             // Vérifier la preuve
             let proof_id = proof["proof"]["id"].as_str().unwrap();
             let verify_response = client
-                .get(&format!("{}/api/proofs/{}/verify", BASE_URL, proof_id))
+                .get(format!("{}/api/proofs/{}/verify", BASE_URL, proof_id))
                 .send()
                 .await
                 .expect("Failed to verify proof");
@@ -168,7 +167,7 @@ async fn test_lightning_invoice_creation() {
     });
 
     let response = client
-        .post(&format!("{}/api/lightning/invoice", BASE_URL))
+        .post(format!("{}/api/lightning/invoice", BASE_URL))
         .json(&invoice_payload)
         .send()
         .await
@@ -190,7 +189,7 @@ async fn test_rgb_contract_integration() {
 
     // Essayer de lister les preuves (qui utilise le service RGB en interne)
     let response = client
-        .get(&format!("{}/api/proofs", BASE_URL))
+        .get(format!("{}/api/proofs", BASE_URL))
         .send()
         .await
         .expect("Failed to send request");
@@ -205,7 +204,7 @@ async fn test_cors_headers() {
     let client = reqwest::Client::new();
 
     let response = client
-        .request(reqwest::Method::OPTIONS, &format!("{}/api/users", BASE_URL))
+        .request(reqwest::Method::OPTIONS, format!("{}/api/users", BASE_URL))
         .header("Origin", "http://localhost:3001")
         .header("Access-Control-Request-Method", "POST")
         .send()